@@ -460,6 +460,11 @@ impl DoraIntegration {
                 }
             }
 
+            // Reconnect bridges whose dynamic node dropped out
+            if let Some(ref mut disp) = dispatcher {
+                disp.supervise();
+            }
+
             // Poll for events via SharedDoraState
             if let Some(ref disp) = dispatcher {
                 let shared_state = disp.shared_state();
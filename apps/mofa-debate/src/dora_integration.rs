@@ -5,7 +5,8 @@
 
 use crossbeam_channel::{bounded, Receiver, Sender};
 use mofa_dora_bridge::{
-    controller::DataflowController, dispatcher::DynamicNodeDispatcher, SharedDoraState,
+    controller::DataflowController, dispatcher::DynamicNodeDispatcher, ControlMessage,
+    SharedDoraState,
 };
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -30,7 +31,7 @@ pub enum DoraCommand {
     /// Send a prompt to LLM
     SendPrompt { message: String },
     /// Send a control command
-    SendControl { command: String },
+    SendControl { command: ControlMessage },
     /// Update buffer status
     UpdateBufferStatus { fill_percentage: f64 },
 }
@@ -156,11 +157,9 @@ impl DoraIntegration {
         })
     }
 
-    /// Send a control command (e.g., "reset", "cancel")
-    pub fn send_control(&self, command: impl Into<String>) -> bool {
-        self.send_command(DoraCommand::SendControl {
-            command: command.into(),
-        })
+    /// Send a control command (e.g., reset, cancel)
+    pub fn send_control(&self, command: ControlMessage) -> bool {
+        self.send_command(DoraCommand::SendControl { command })
     }
 
     /// Poll for events (non-blocking)
@@ -357,11 +356,10 @@ impl DoraIntegration {
                                 .or_else(|| disp.get_bridge("mofa-prompt-input"))
                             {
                                 log::info!("Sending control command: {}", command);
-                                let ctrl = mofa_dora_bridge::ControlCommand::new(&command);
                                 if let Err(e) = send_with_retry(
                                     bridge,
                                     "control",
-                                    mofa_dora_bridge::DoraData::Control(ctrl),
+                                    mofa_dora_bridge::DoraData::Control(command),
                                 ) {
                                     log::error!("Failed to send control: {}", e);
                                 }
@@ -423,6 +421,11 @@ impl DoraIntegration {
                 }
             }
 
+            // Reconnect bridges whose dynamic node dropped out
+            if let Some(ref mut disp) = dispatcher {
+                disp.supervise();
+            }
+
             // Check SharedDoraState for critical errors (UI polls everything else directly)
             if let Some(status) = shared_state_for_dispatcher.status.read_if_dirty() {
                 if let Some(error) = status.last_error {
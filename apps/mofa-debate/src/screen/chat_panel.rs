@@ -3,6 +3,7 @@
 //! Handles chat display, prompt input, and message formatting.
 
 use makepad_widgets::*;
+use mofa_dora_bridge::ControlMessage;
 
 use super::{ChatMessageEntry, MoFaDebateScreen};
 
@@ -97,7 +98,7 @@ impl MoFaDebateScreen {
         // Send reset command to conference controller via dora
         if let Some(ref dora) = self.dora_integration {
             if dora.is_running() {
-                dora.send_control("reset");
+                dora.send_control(ControlMessage::Reset);
                 self.add_log(
                     cx,
                     "[INFO] [App] Sent reset command to conference controller",
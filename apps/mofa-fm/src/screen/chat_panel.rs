@@ -3,6 +3,7 @@
 //! Handles chat display, prompt input, and message formatting.
 
 use makepad_widgets::*;
+use mofa_dora_bridge::ControlMessage;
 
 use super::{MoFaFMScreen, ChatMessageEntry};
 
@@ -53,7 +54,7 @@ impl MoFaFMScreen {
         // Send reset command to conference controller via dora
         if let Some(ref dora) = self.dora_integration {
             if dora.is_running() {
-                dora.send_control("reset");
+                dora.send_control(ControlMessage::Reset);
                 self.add_log(cx, "[INFO] [App] Sent reset command to conference controller");
             } else {
                 self.add_log(cx, "[WARN] [App] Dataflow not running - reset not sent");
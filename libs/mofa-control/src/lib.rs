@@ -0,0 +1,354 @@
+//! # MoFA Control Protocol
+//!
+//! Typed messages for the `control` inputs of MoFA dora nodes (maas-client,
+//! conference-controller, conference-bridge) and the MoFA Studio bridges that
+//! send them.
+//!
+//! ## Wire Format
+//!
+//! Messages are encoded as versioned JSON objects tagged by `command`:
+//!
+//! ```text
+//! {"version":1,"command":"reset"}
+//! {"version":1,"command":"prompt","prompt":"What is dora?"}
+//! ```
+//!
+//! [`ControlMessage::parse`] also accepts the formats older senders still use:
+//!
+//! - Plain text commands (`reset`, `Cancel`, ...), matched case-insensitively,
+//!   and `load:<session_id>`
+//! - JSON without a `version` field
+//! - `{"prompt": "..."}` without a `command` field
+//!
+//! ## Usage Example
+//!
+//! ```
+//! use mofa_control::ControlMessage;
+//!
+//! let msg = ControlMessage::parse("reset").unwrap();
+//! assert_eq!(msg, ControlMessage::Reset);
+//!
+//! let wire = ControlMessage::prompt("hello").encode();
+//! assert_eq!(ControlMessage::parse(&wire).unwrap(), ControlMessage::prompt("hello"));
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Protocol version written by [`ControlMessage::encode`]
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Errors from parsing a control message
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ControlError {
+    #[error("Empty control message")]
+    Empty,
+
+    #[error("Unknown control command: {0}")]
+    UnknownCommand(String),
+
+    #[error("Unsupported control protocol version {0} (supported: {PROTOCOL_VERSION})")]
+    UnsupportedVersion(u64),
+
+    #[error("Invalid control message: {0}")]
+    Invalid(String),
+}
+
+/// A command sent on a node's `control` input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlMessage {
+    /// Start processing
+    Start,
+    /// Stop processing
+    Stop,
+    /// Cancel streaming and clear conversation state
+    Reset,
+    /// Cancel streaming but keep conversation state
+    Cancel,
+    /// Let the next participant speak (conference-bridge)
+    Resume,
+    /// Ask the node to report `ready` on its status output
+    Ready,
+    /// Ask the node to report its statistics on its status output
+    Stats,
+    /// Drop the current session
+    Exit,
+    /// Send a user prompt
+    Prompt { prompt: String },
+    /// Persist the current session (maas-client)
+    Save,
+    /// Restore a persisted session into the current one (maas-client)
+    Load { session_id: String },
+    /// Ask the node to report its persisted sessions on its status output
+    List,
+    /// Re-read the node's configuration file (maas-client)
+    ReloadConfig,
+}
+
+impl ControlMessage {
+    /// Command names, as used on the wire
+    pub const COMMANDS: [&'static str; 13] = [
+        "start",
+        "stop",
+        "reset",
+        "cancel",
+        "resume",
+        "ready",
+        "stats",
+        "exit",
+        "prompt",
+        "save",
+        "load",
+        "list",
+        "reload_config",
+    ];
+
+    /// Create a prompt message
+    pub fn prompt(prompt: impl Into<String>) -> Self {
+        ControlMessage::Prompt {
+            prompt: prompt.into(),
+        }
+    }
+
+    /// Wire name of the command
+    pub fn command(&self) -> &'static str {
+        match self {
+            ControlMessage::Start => "start",
+            ControlMessage::Stop => "stop",
+            ControlMessage::Reset => "reset",
+            ControlMessage::Cancel => "cancel",
+            ControlMessage::Resume => "resume",
+            ControlMessage::Ready => "ready",
+            ControlMessage::Stats => "stats",
+            ControlMessage::Exit => "exit",
+            ControlMessage::Prompt { .. } => "prompt",
+            ControlMessage::Save => "save",
+            ControlMessage::Load { .. } => "load",
+            ControlMessage::List => "list",
+            ControlMessage::ReloadConfig => "reload_config",
+        }
+    }
+
+    /// Encode as versioned JSON
+    pub fn encode(&self) -> String {
+        let mut value = serde_json::to_value(self).expect("control message serializes");
+        if let Value::Object(map) = &mut value {
+            map.insert("version".to_string(), Value::from(PROTOCOL_VERSION));
+        }
+        value.to_string()
+    }
+
+    /// Parse a control message from JSON or plain text
+    pub fn parse(text: &str) -> Result<Self, ControlError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(ControlError::Empty);
+        }
+        if text.starts_with('{') {
+            let value: Value =
+                serde_json::from_str(text).map_err(|e| ControlError::Invalid(e.to_string()))?;
+            return Self::from_json(value);
+        }
+
+        if let Some((command, session_id)) = text.split_once(':') {
+            if command.trim().eq_ignore_ascii_case("load") {
+                let session_id = session_id.trim();
+                if session_id.is_empty() {
+                    return Err(ControlError::Invalid("load needs a session ID".to_string()));
+                }
+                return Ok(ControlMessage::Load {
+                    session_id: session_id.to_string(),
+                });
+            }
+        }
+
+        let command = text.to_ascii_lowercase();
+        match command.as_str() {
+            "prompt" => Err(ControlError::Invalid(
+                "prompt needs a JSON payload".to_string(),
+            )),
+            "load" => Err(ControlError::Invalid(
+                "load needs a session ID (load:<id>)".to_string(),
+            )),
+            _ if Self::COMMANDS.contains(&command.as_str()) => {
+                Self::from_json(serde_json::json!({ "command": command }))
+            }
+            _ => Err(ControlError::UnknownCommand(text.to_string())),
+        }
+    }
+
+    fn from_json(value: Value) -> Result<Self, ControlError> {
+        let Value::Object(mut map) = value else {
+            return Err(ControlError::Invalid("expected a JSON object".to_string()));
+        };
+
+        // Unversioned JSON is the legacy format and stays accepted
+        if let Some(version) = map.remove("version") {
+            match version.as_u64() {
+                Some(v) if v <= PROTOCOL_VERSION as u64 => {}
+                Some(v) => return Err(ControlError::UnsupportedVersion(v)),
+                None => return Err(ControlError::Invalid(format!("bad version: {}", version))),
+            }
+        }
+
+        let command = match map.get("command") {
+            Some(Value::String(command)) => command.to_ascii_lowercase(),
+            Some(other) => return Err(ControlError::Invalid(format!("bad command: {}", other))),
+            // Legacy `{"prompt": "..."}` from prompt inputs
+            None if map.contains_key("prompt") => "prompt".to_string(),
+            None => return Err(ControlError::Invalid("missing command".to_string())),
+        };
+        if !Self::COMMANDS.contains(&command.as_str()) {
+            return Err(ControlError::UnknownCommand(command));
+        }
+        map.insert("command".to_string(), Value::String(command));
+
+        serde_json::from_value(Value::Object(map)).map_err(|e| ControlError::Invalid(e.to_string()))
+    }
+}
+
+impl FromStr for ControlMessage {
+    type Err = ControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for ControlMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.command())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_messages() -> Vec<ControlMessage> {
+        vec![
+            ControlMessage::Start,
+            ControlMessage::Stop,
+            ControlMessage::Reset,
+            ControlMessage::Cancel,
+            ControlMessage::Resume,
+            ControlMessage::Ready,
+            ControlMessage::Stats,
+            ControlMessage::Exit,
+            ControlMessage::prompt("What is \"dora\"?"),
+            ControlMessage::Save,
+            ControlMessage::Load {
+                session_id: "study-42".to_string(),
+            },
+            ControlMessage::List,
+            ControlMessage::ReloadConfig,
+        ]
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for msg in all_messages() {
+            let wire = msg.encode();
+            let value: Value = serde_json::from_str(&wire).unwrap();
+            assert_eq!(value["version"], PROTOCOL_VERSION);
+            assert_eq!(value["command"], msg.command());
+            assert_eq!(ControlMessage::parse(&wire), Ok(msg));
+        }
+    }
+
+    #[test]
+    fn test_commands_match_variants() {
+        let names: Vec<_> = all_messages().iter().map(|m| m.command()).collect();
+        assert_eq!(names, ControlMessage::COMMANDS);
+    }
+
+    #[test]
+    fn test_plain_text_fallback() {
+        assert_eq!(ControlMessage::parse("reset"), Ok(ControlMessage::Reset));
+        assert_eq!(
+            ControlMessage::parse(" Cancel\n"),
+            Ok(ControlMessage::Cancel)
+        );
+        assert_eq!("READY".parse(), Ok(ControlMessage::Ready));
+        assert_eq!(ControlMessage::Resume.to_string(), "resume");
+        assert_eq!(
+            ControlMessage::parse("Reload_Config"),
+            Ok(ControlMessage::ReloadConfig)
+        );
+        assert!(matches!(
+            ControlMessage::parse("prompt"),
+            Err(ControlError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_shorthand() {
+        let load = ControlMessage::Load {
+            session_id: "study-42".to_string(),
+        };
+        assert_eq!(ControlMessage::parse("load:study-42"), Ok(load.clone()));
+        assert_eq!(ControlMessage::parse(" LOAD: study-42 "), Ok(load.clone()));
+        assert_eq!(
+            ControlMessage::parse(r#"{"command": "load", "session_id": "study-42"}"#),
+            Ok(load)
+        );
+        assert!(matches!(
+            ControlMessage::parse("load"),
+            Err(ControlError::Invalid(_))
+        ));
+        assert!(matches!(
+            ControlMessage::parse("load:"),
+            Err(ControlError::Invalid(_))
+        ));
+        assert_eq!(
+            ControlMessage::parse("save:now"),
+            Err(ControlError::UnknownCommand("save:now".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_legacy_json() {
+        assert_eq!(
+            ControlMessage::parse(r#"{"command": "RESET"}"#),
+            Ok(ControlMessage::Reset)
+        );
+        assert_eq!(
+            ControlMessage::parse(r#"{"prompt": "hi"}"#),
+            Ok(ControlMessage::prompt("hi"))
+        );
+        // Extra fields from older senders are ignored
+        assert_eq!(
+            ControlMessage::parse(r#"{"command": "stats", "params": {}}"#),
+            Ok(ControlMessage::Stats)
+        );
+    }
+
+    #[test]
+    fn test_rejects_bad_messages() {
+        assert_eq!(ControlMessage::parse("  "), Err(ControlError::Empty));
+        assert_eq!(
+            ControlMessage::parse("resum"),
+            Err(ControlError::UnknownCommand("resum".to_string()))
+        );
+        assert_eq!(
+            ControlMessage::parse(r#"{"command": "pause"}"#),
+            Err(ControlError::UnknownCommand("pause".to_string()))
+        );
+        assert_eq!(
+            ControlMessage::parse(r#"{"version": 2, "command": "reset"}"#),
+            Err(ControlError::UnsupportedVersion(2))
+        );
+        assert!(matches!(
+            ControlMessage::parse(r#"{"command": "prompt"}"#),
+            Err(ControlError::Invalid(_))
+        ));
+        assert!(matches!(
+            ControlMessage::parse("{not json"),
+            Err(ControlError::Invalid(_))
+        ));
+    }
+}
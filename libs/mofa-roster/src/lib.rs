@@ -0,0 +1,507 @@
+//! # MoFA Participant Roster
+//!
+//! One TOML file describing the participants of a conference dataflow, read
+//! by the conference controller and every conference bridge so a panel can
+//! be resized or renamed without touching Rust code.
+//!
+//! ## File Format
+//!
+//! ```toml
+//! [[participant]]
+//! id = "tutor"                    # dora node / controller input port
+//! display_name = "Tutor"          # used in logs and error messages
+//! control_output = "control_judge"  # controller output feeding its bridge
+//! bridge_port = "tutor"           # input port carrying its text on bridges
+//! voice = "Luo Xiang"             # TTS voice
+//! priority = "*"                  # "*" speaks first, a number is a ratio weight
+//! aliases = ["judge"]             # other names it is known by
+//! ```
+//!
+//! Only `id` is required:
+//!
+//! | Field | Default |
+//! |-------|---------|
+//! | `display_name` | `id` |
+//! | `control_output` | `control_<id>` |
+//! | `bridge_port` | `id` |
+//! | `voice` | none |
+//! | `priority` | none (ratio weight 1 when others set one) |
+//! | `aliases` | none |
+//!
+//! [`Roster::pattern`] turns the roster into a controller policy pattern,
+//! so the speaking order can live in the roster as well.
+//!
+//! ## Usage Example
+//!
+//! ```
+//! use mofa_roster::Roster;
+//!
+//! let roster: Roster = r#"
+//!     [[participant]]
+//!     id = "host"
+//!     priority = "*"
+//!
+//!     [[participant]]
+//!     id = "guest"
+//!     display_name = "Guest Speaker"
+//!     aliases = ["llm1"]
+//! "#
+//! .parse()
+//! .unwrap();
+//!
+//! assert_eq!(roster.resolve("llm1"), Some("guest"));
+//! assert_eq!(roster.get("guest").unwrap().control_output, "control_guest");
+//! assert_eq!(roster.pattern(), "[(host, *), (guest, 1)]");
+//! ```
+
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Environment variable naming the roster file
+pub const ROSTER_ENV: &str = "DORA_ROSTER";
+
+/// Errors from loading or validating a roster
+#[derive(Error, Debug)]
+pub enum RosterError {
+    #[error("Failed to read roster {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid roster: {0}")]
+    Parse(String),
+
+    #[error("Roster has no participants")]
+    Empty,
+
+    #[error("Invalid participant ID '{0}': no spaces or pattern characters allowed")]
+    InvalidId(String),
+
+    #[error("'{0}' is used by more than one participant")]
+    Duplicate(String),
+
+    #[error("Participant '{0}' is not in the roster")]
+    UnknownParticipant(String),
+}
+
+/// How a participant takes part in the speaking order
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "RawPriority")]
+pub enum Priority {
+    /// Speaks first whenever it has not spoken yet (`*` in patterns)
+    First,
+    /// Share of speaking time relative to the others
+    Weight(f64),
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Priority::First => f.write_str("*"),
+            Priority::Weight(weight) => weight.fmt(f),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawPriority {
+    Number(f64),
+    Text(String),
+}
+
+impl TryFrom<RawPriority> for Priority {
+    type Error = String;
+
+    fn try_from(raw: RawPriority) -> Result<Self, Self::Error> {
+        let weight = match raw {
+            RawPriority::Text(text) if text.trim() == "*" => return Ok(Priority::First),
+            RawPriority::Text(text) => text
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("priority must be \"*\" or a number, got '{}'", text))?,
+            RawPriority::Number(weight) => weight,
+        };
+        if weight.is_finite() && weight > 0.0 {
+            Ok(Priority::Weight(weight))
+        } else {
+            Err(format!("priority weight must be positive, got {}", weight))
+        }
+    }
+}
+
+/// One participant of the conference
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Participant {
+    pub id: String,
+    #[serde(default)]
+    pub display_name: String,
+    #[serde(default)]
+    pub control_output: String,
+    #[serde(default)]
+    pub bridge_port: String,
+    #[serde(default)]
+    pub voice: Option<String>,
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+impl Participant {
+    /// A participant with every optional field at its default
+    pub fn new(id: impl Into<String>) -> Self {
+        let mut participant = Self {
+            id: id.into(),
+            display_name: String::new(),
+            control_output: String::new(),
+            bridge_port: String::new(),
+            voice: None,
+            priority: None,
+            aliases: Vec::new(),
+        };
+        participant.fill_defaults();
+        participant
+    }
+
+    pub fn with_display_name(mut self, display_name: impl Into<String>) -> Self {
+        self.display_name = display_name.into();
+        self
+    }
+
+    pub fn with_control_output(mut self, control_output: impl Into<String>) -> Self {
+        self.control_output = control_output.into();
+        self
+    }
+
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn with_alias(mut self, alias: impl Into<String>) -> Self {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    /// Whether `name` is this participant's ID or one of its aliases
+    pub fn is_named(&self, name: &str) -> bool {
+        self.id == name || self.aliases.iter().any(|alias| alias == name)
+    }
+
+    fn fill_defaults(&mut self) {
+        self.id = self.id.trim().to_string();
+        if self.display_name.trim().is_empty() {
+            self.display_name = self.id.clone();
+        }
+        if self.control_output.trim().is_empty() {
+            self.control_output = format!("control_{}", self.id);
+        }
+        if self.bridge_port.trim().is_empty() {
+            self.bridge_port = self.id.clone();
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RosterFile {
+    #[serde(default)]
+    participant: Vec<Participant>,
+}
+
+/// The participants of a conference, in speaking-pattern order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Roster {
+    participants: Vec<Participant>,
+}
+
+impl Roster {
+    /// Build a roster, filling in defaults and rejecting ambiguous names
+    pub fn new(participants: Vec<Participant>) -> Result<Self, RosterError> {
+        if participants.is_empty() {
+            return Err(RosterError::Empty);
+        }
+
+        let mut participants = participants;
+        let mut names = HashSet::new();
+        let mut ports = HashSet::new();
+        for participant in &mut participants {
+            participant.fill_defaults();
+            for name in std::iter::once(&participant.id).chain(&participant.aliases) {
+                if !is_valid_id(name) {
+                    return Err(RosterError::InvalidId(name.clone()));
+                }
+                if !names.insert(name.clone()) {
+                    return Err(RosterError::Duplicate(name.clone()));
+                }
+            }
+            if !ports.insert(participant.bridge_port.clone()) {
+                return Err(RosterError::Duplicate(participant.bridge_port.clone()));
+            }
+        }
+
+        Ok(Self { participants })
+    }
+
+    /// Read a roster file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RosterError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|source| RosterError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        content.parse()
+    }
+
+    /// Read the roster file named by [`ROSTER_ENV`], if it is set
+    pub fn from_env() -> Result<Option<Self>, RosterError> {
+        match std::env::var(ROSTER_ENV) {
+            Ok(path) if !path.trim().is_empty() => Self::load(path.trim()).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// The built-in roster for dataflows without a roster file
+    ///
+    /// Maps the debate and study roles onto the controller outputs those
+    /// dataflows were wired with: `llm1`/`student1` → `control_llm1`,
+    /// `llm2`/`student2` → `control_llm2`, `judge`/`tutor` → `control_judge`.
+    /// Other IDs are matched by substring, falling back to `control_llm1`.
+    pub fn legacy<S: AsRef<str>>(ids: &[S]) -> Result<Self, RosterError> {
+        let ids: Vec<&str> = ids.iter().map(|id| id.as_ref().trim()).collect();
+        let participants = ids
+            .iter()
+            .map(|&id| {
+                let (control_output, alias) = match id {
+                    "llm1" => ("control_llm1", Some("student1")),
+                    "student1" => ("control_llm1", Some("llm1")),
+                    "llm2" => ("control_llm2", Some("student2")),
+                    "student2" => ("control_llm2", Some("llm2")),
+                    "judge" => ("control_judge", Some("tutor")),
+                    "tutor" => ("control_judge", Some("judge")),
+                    _ if id.contains("judge") || id.contains("tutor") => ("control_judge", None),
+                    _ if id.contains("llm2") || id.contains("student2") => ("control_llm2", None),
+                    _ => ("control_llm1", None),
+                };
+                let participant = Participant::new(id).with_control_output(control_output);
+                match alias {
+                    // Both role names in one pattern keep their own entries
+                    Some(alias) if !ids.contains(&alias) => participant.with_alias(alias),
+                    _ => participant,
+                }
+            })
+            .collect();
+        Self::new(participants)
+    }
+
+    pub fn participants(&self) -> &[Participant] {
+        &self.participants
+    }
+
+    pub fn ids(&self) -> Vec<&str> {
+        self.participants.iter().map(|p| p.id.as_str()).collect()
+    }
+
+    /// Look up a participant by ID or alias
+    pub fn get(&self, name: &str) -> Option<&Participant> {
+        let name = name.trim();
+        self.participants.iter().find(|p| p.is_named(name))
+    }
+
+    /// The ID of the participant known as `name`
+    pub fn resolve(&self, name: &str) -> Option<&str> {
+        self.get(name).map(|p| p.id.as_str())
+    }
+
+    /// Look up the participant whose text arrives on bridge input `port`
+    pub fn by_port(&self, port: &str) -> Option<&Participant> {
+        self.participants.iter().find(|p| p.bridge_port == port)
+    }
+
+    /// Every distinct control output, in roster order
+    pub fn control_outputs(&self) -> Vec<&str> {
+        let mut outputs: Vec<&str> = Vec::new();
+        for participant in &self.participants {
+            if !outputs.contains(&participant.control_output.as_str()) {
+                outputs.push(&participant.control_output);
+            }
+        }
+        outputs
+    }
+
+    /// Fail unless every name resolves to a participant
+    pub fn check_covers<S: AsRef<str>>(&self, names: &[S]) -> Result<(), RosterError> {
+        match names.iter().find(|name| self.get(name.as_ref()).is_none()) {
+            Some(name) => Err(RosterError::UnknownParticipant(name.as_ref().to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// The controller policy pattern for this roster
+    ///
+    /// Sequential (`[a → b]`) when no participant has a priority, otherwise
+    /// ratio/priority (`[(a, *), (b, 1)]`) with unset priorities weighted 1.
+    pub fn pattern(&self) -> String {
+        if self.participants.iter().all(|p| p.priority.is_none()) {
+            return format!("[{}]", self.ids().join(" → "));
+        }
+        let entries: Vec<String> = self
+            .participants
+            .iter()
+            .map(|p| {
+                let priority = p.priority.unwrap_or(Priority::Weight(1.0));
+                format!("({}, {})", p.id, priority)
+            })
+            .collect();
+        format!("[{}]", entries.join(", "))
+    }
+}
+
+impl FromStr for Roster {
+    type Err = RosterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let file: RosterFile = toml::from_str(s).map_err(|e| RosterError::Parse(e.to_string()))?;
+        Self::new(file.participant)
+    }
+}
+
+/// IDs end up in dora port names and policy patterns
+fn is_valid_id(name: &str) -> bool {
+    !name.is_empty()
+        && !name
+            .chars()
+            .any(|c| c.is_whitespace() || ",()[]→*".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PANEL: &str = r#"
+        [[participant]]
+        id = "host"
+        display_name = "Host Alex"
+        voice = "Luo Xiang"
+        priority = "*"
+
+        [[participant]]
+        id = "guest1"
+        priority = 2
+
+        [[participant]]
+        id = "guest2"
+        control_output = "control_guests"
+        bridge_port = "g2"
+
+        [[participant]]
+        id = "guest3"
+        control_output = "control_guests"
+        priority = "1.5"
+        aliases = ["llm3"]
+    "#;
+
+    #[test]
+    fn test_parse_fills_defaults() {
+        let roster: Roster = PANEL.parse().unwrap();
+        assert_eq!(roster.ids(), ["host", "guest1", "guest2", "guest3"]);
+
+        let host = roster.get("host").unwrap();
+        assert_eq!(host.display_name, "Host Alex");
+        assert_eq!(host.control_output, "control_host");
+        assert_eq!(host.bridge_port, "host");
+        assert_eq!(host.voice.as_deref(), Some("Luo Xiang"));
+        assert_eq!(host.priority, Some(Priority::First));
+
+        let guest1 = roster.get("guest1").unwrap();
+        assert_eq!(guest1.display_name, "guest1");
+        assert_eq!(guest1.priority, Some(Priority::Weight(2.0)));
+        assert_eq!(guest1.voice, None);
+    }
+
+    #[test]
+    fn test_lookups() {
+        let roster: Roster = PANEL.parse().unwrap();
+        assert_eq!(roster.resolve("llm3"), Some("guest3"));
+        assert_eq!(roster.resolve(" guest1 "), Some("guest1"));
+        assert_eq!(roster.resolve("nobody"), None);
+        assert_eq!(roster.by_port("g2").unwrap().id, "guest2");
+        assert!(roster.by_port("guest2").is_none());
+        assert_eq!(
+            roster.control_outputs(),
+            ["control_host", "control_guest1", "control_guests"]
+        );
+        assert!(roster.check_covers(&["host", "llm3"]).is_ok());
+        assert!(matches!(
+            roster.check_covers(&["host", "judge"]),
+            Err(RosterError::UnknownParticipant(name)) if name == "judge"
+        ));
+    }
+
+    #[test]
+    fn test_pattern() {
+        let roster: Roster = PANEL.parse().unwrap();
+        assert_eq!(
+            roster.pattern(),
+            "[(host, *), (guest1, 2), (guest2, 1), (guest3, 1.5)]"
+        );
+
+        let sequential = Roster::new(vec![Participant::new("a"), Participant::new("b")]).unwrap();
+        assert_eq!(sequential.pattern(), "[a → b]");
+    }
+
+    #[test]
+    fn test_rejects_invalid_rosters() {
+        assert!(matches!("".parse::<Roster>(), Err(RosterError::Empty)));
+        assert!(matches!(
+            "[[participant]]\nid = \"a\"\nvoic = \"x\"".parse::<Roster>(),
+            Err(RosterError::Parse(_))
+        ));
+        assert!(matches!(
+            "[[participant]]\nid = \"a\"\npriority = 0".parse::<Roster>(),
+            Err(RosterError::Parse(_))
+        ));
+        assert!(matches!(
+            "[[participant]]\nid = \"a\"\npriority = \"first\"".parse::<Roster>(),
+            Err(RosterError::Parse(_))
+        ));
+        assert!(matches!(
+            Roster::new(vec![Participant::new("a, b")]),
+            Err(RosterError::InvalidId(_))
+        ));
+        assert!(matches!(
+            Roster::new(vec![Participant::new("a"), Participant::new("b").with_alias("a")]),
+            Err(RosterError::Duplicate(name)) if name == "a"
+        ));
+    }
+
+    #[test]
+    fn test_legacy_roster_matches_old_aliases() {
+        let roster = Roster::legacy(&["tutor", "student2", "student1"]).unwrap();
+        assert_eq!(roster.get("judge").unwrap().control_output, "control_judge");
+        assert_eq!(roster.get("llm2").unwrap().control_output, "control_llm2");
+        assert_eq!(roster.resolve("llm1"), Some("student1"));
+
+        let roster = Roster::legacy(&["Judge", "prosecution_llm2", "defense"]).unwrap();
+        assert_eq!(roster.get("Judge").unwrap().control_output, "control_llm1");
+        assert_eq!(
+            roster.get("prosecution_llm2").unwrap().control_output,
+            "control_llm2"
+        );
+        assert_eq!(
+            roster.get("defense").unwrap().control_output,
+            "control_llm1"
+        );
+
+        // Both names of a role in one pattern stay separate participants
+        let roster = Roster::legacy(&["llm1", "student1"]).unwrap();
+        assert_eq!(roster.resolve("student1"), Some("student1"));
+    }
+}
@@ -0,0 +1,197 @@
+//! # MoFA Node Simulation
+//!
+//! A dora node whose logic lives in a [`Node`] takes one [`Input`] at a time
+//! and queues [`Action`]s: outputs to send and log lines. The node binary
+//! only converts dora events into inputs and actions into `send_output`
+//! calls, so the same logic can be driven without dora.
+//!
+//! [`Scenario`] replays a scripted timeline of inputs from YAML against a
+//! node and checks the outputs each step sends. Time is part of the script:
+//! every step carries its offset from the start, and nodes read the clock
+//! only from the `now` they are handed, so a run is fully deterministic.
+//!
+//! ## Usage Example
+//!
+//! ```
+//! use mofa_sim::{Action, Input, Node, Output, Scenario};
+//! use std::time::Instant;
+//!
+//! /// Echoes every input back on `echo`
+//! #[derive(Default)]
+//! struct Echo {
+//!     actions: Vec<Action>,
+//! }
+//!
+//! impl Node for Echo {
+//!     fn handle(&mut self, input: &Input, _now: Instant) {
+//!         self.actions.push(Action::Send(Output::new("echo", input.text.clone())));
+//!     }
+//!
+//!     fn take_actions(&mut self) -> Vec<Action> {
+//!         std::mem::take(&mut self.actions)
+//!     }
+//! }
+//!
+//! let scenario: Scenario = r#"
+//!     name: echo
+//!     steps:
+//!       - input: text
+//!         data: hello
+//!         expect:
+//!           - { output: echo, data: hello }
+//! "#
+//! .parse()
+//! .unwrap();
+//!
+//! let trace = scenario.run(&mut Echo::default(), Instant::now()).unwrap();
+//! assert_eq!(trace.sent("echo").count(), 1);
+//! ```
+
+pub mod scenario;
+
+pub use scenario::{Expect, Scenario, ScenarioError, Step, Trace};
+
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// Metadata key of the turn ID (same as `mofa_stream::keys::QUESTION_ID`)
+pub const QUESTION_ID: &str = "question_id";
+
+/// Severity of a log action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error = 0,
+    Warn = 1,
+    Info = 2,
+    Debug = 3,
+}
+
+impl LogLevel {
+    /// Parse a `LOG_LEVEL` value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "error" => Some(LogLevel::Error),
+            "warn" | "warning" => Some(LogLevel::Warn),
+            "info" => Some(LogLevel::Info),
+            "debug" => Some(LogLevel::Debug),
+            _ => None,
+        }
+    }
+
+    /// Whether a node configured with this level logs `other`
+    pub fn allows(self, other: LogLevel) -> bool {
+        other <= self
+    }
+}
+
+/// Text received on an input port, with its metadata as strings
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Input {
+    pub id: String,
+    pub text: String,
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl Input {
+    pub fn new(id: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            text: text.into(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+}
+
+/// Text to send on an output port
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Output {
+    pub id: String,
+    pub data: String,
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl Output {
+    pub fn new(id: impl Into<String>, data: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            data: data.into(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn question_id(&self) -> Option<&str> {
+        self.metadata.get(QUESTION_ID).map(String::as_str)
+    }
+}
+
+/// Something a node wants done
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Send(Output),
+    /// A log line; the node binary filters it by its configured level
+    Log {
+        level: LogLevel,
+        message: String,
+    },
+}
+
+impl Action {
+    pub fn log(level: LogLevel, message: impl Into<String>) -> Self {
+        Action::Log {
+            level,
+            message: message.into(),
+        }
+    }
+
+    pub fn as_output(&self) -> Option<&Output> {
+        match self {
+            Action::Send(output) => Some(output),
+            Action::Log { .. } => None,
+        }
+    }
+}
+
+/// Node logic driven one input at a time
+pub trait Node {
+    /// React to `input`, received at `now`
+    fn handle(&mut self, input: &Input, now: Instant);
+
+    /// Take the actions queued since the last call, oldest first
+    ///
+    /// Actions queued while the node was created (e.g. startup logs or an
+    /// initial status) are returned by the first call.
+    fn take_actions(&mut self) -> Vec<Action>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_levels() {
+        assert_eq!(LogLevel::parse("WARNING"), Some(LogLevel::Warn));
+        assert_eq!(LogLevel::parse("verbose"), None);
+        assert!(LogLevel::Info.allows(LogLevel::Warn));
+        assert!(LogLevel::Info.allows(LogLevel::Info));
+        assert!(!LogLevel::Info.allows(LogLevel::Debug));
+    }
+
+    #[test]
+    fn test_output_question_id() {
+        let output = Output::new("control_llm1", "resume").with_metadata(QUESTION_ID, "t1:5:1:0:a");
+        assert_eq!(output.question_id(), Some("t1:5:1:0:a"));
+        assert_eq!(Output::new("status", "waiting").question_id(), None);
+        assert_eq!(Action::Send(output.clone()).as_output(), Some(&output));
+        assert_eq!(Action::log(LogLevel::Info, "hi").as_output(), None);
+    }
+}
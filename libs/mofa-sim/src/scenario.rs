@@ -0,0 +1,397 @@
+//! # Scenarios
+//!
+//! A scenario is a YAML timeline of inputs, each with the outputs it must
+//! (and must not) cause:
+//!
+//! ```yaml
+//! name: cold start
+//! setup:                      # node specific, see the node's tests
+//!   pattern: "[tutor → student1]"
+//! steps:
+//!   - at: 0.0                 # seconds since the start (default: previous step)
+//!     input: tutor            # input port
+//!     data: "Welcome!"        # text on the port
+//!     metadata: { status: ended }
+//!     expect:                 # sent in this order (other outputs may come between)
+//!       - output: control_llm1
+//!         data: resume
+//!         question_id: "t1:1000:1:0:student1"
+//!     silent: [control_judge] # outputs this step must not send
+//! ```
+//!
+//! An expectation matches an output with the same port whose `data`,
+//! `contains` (substring of the data), `question_id` and `metadata` entries
+//! all match where given. Metadata values may be any YAML scalar.
+
+use crate::{Action, Input, Node, Output, QUESTION_ID};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Errors from loading or running a scenario
+#[derive(Error, Debug)]
+pub enum ScenarioError {
+    #[error("Failed to read scenario {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid scenario: {0}")]
+    Parse(#[from] serde_yaml::Error),
+
+    #[error("{scenario}: step {step} ({input}) at {at}s is earlier than the previous step")]
+    TimeGoesBack {
+        scenario: String,
+        step: usize,
+        input: String,
+        at: f64,
+    },
+
+    #[error("{scenario}: step {step} ({input}) {problem}\nsent:\n{sent}")]
+    Failed {
+        scenario: String,
+        step: usize,
+        input: String,
+        problem: String,
+        sent: String,
+    },
+}
+
+/// A scripted timeline; `S` is the node-specific `setup` section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(bound(deserialize = "S: Deserialize<'de> + Default"))]
+pub struct Scenario<S = ()> {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub setup: S,
+    pub steps: Vec<Step>,
+}
+
+/// One input and what it must cause
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    /// Seconds since the start of the scenario
+    pub at: Option<f64>,
+    pub input: String,
+    #[serde(default)]
+    pub data: String,
+    #[serde(default, deserialize_with = "scalar_map")]
+    pub metadata: BTreeMap<String, String>,
+    #[serde(default)]
+    pub expect: Vec<Expect>,
+    #[serde(default)]
+    pub silent: Vec<String>,
+}
+
+impl Step {
+    pub fn to_input(&self) -> Input {
+        Input {
+            id: self.input.clone(),
+            text: self.data.clone(),
+            metadata: self.metadata.clone(),
+        }
+    }
+}
+
+/// An output a step must send
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expect {
+    pub output: String,
+    pub data: Option<String>,
+    pub contains: Option<String>,
+    pub question_id: Option<String>,
+    #[serde(default, deserialize_with = "scalar_map")]
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl Expect {
+    pub fn matches(&self, output: &Output) -> bool {
+        output.id == self.output
+            && self.data.iter().all(|data| &output.data == data)
+            && self
+                .contains
+                .iter()
+                .all(|part| output.data.contains(part.as_str()))
+            && self
+                .question_id
+                .iter()
+                .all(|qid| output.question_id() == Some(qid.as_str()))
+            && self
+                .metadata
+                .iter()
+                .all(|(key, value)| output.metadata.get(key) == Some(value))
+    }
+}
+
+impl fmt::Display for Expect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.output)?;
+        if let Some(data) = &self.data {
+            write!(f, " {:?}", data)?;
+        }
+        if let Some(part) = &self.contains {
+            write!(f, " containing {:?}", part)?;
+        }
+        if let Some(qid) = &self.question_id {
+            write!(f, " {}={}", QUESTION_ID, qid)?;
+        }
+        for (key, value) in &self.metadata {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything a scenario run did
+#[derive(Debug, Clone, Default)]
+pub struct Trace {
+    /// Actions queued before the first step
+    pub startup: Vec<Action>,
+    /// Actions of each step
+    pub steps: Vec<Vec<Action>>,
+}
+
+impl Trace {
+    /// Outputs sent on `output` over the whole run, in order
+    pub fn sent<'a>(&'a self, output: &'a str) -> impl Iterator<Item = &'a Output> + 'a {
+        self.startup
+            .iter()
+            .chain(self.steps.iter().flatten())
+            .filter_map(Action::as_output)
+            .filter(move |sent| sent.id == output)
+    }
+}
+
+impl<S: DeserializeOwned + Default> Scenario<S> {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| ScenarioError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        text.parse()
+    }
+}
+
+impl<S: DeserializeOwned + Default> FromStr for Scenario<S> {
+    type Err = ScenarioError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(serde_yaml::from_str(s)?)
+    }
+}
+
+impl<S> Scenario<S> {
+    /// Replay the steps against `node`, created at `start`
+    ///
+    /// Stops at the first step that does not send what it should.
+    pub fn run(&self, node: &mut impl Node, start: Instant) -> Result<Trace, ScenarioError> {
+        let mut trace = Trace {
+            startup: node.take_actions(),
+            steps: Vec::with_capacity(self.steps.len()),
+        };
+        let mut at = 0.0;
+
+        for (index, step) in self.steps.iter().enumerate() {
+            let number = index + 1;
+            let step_at = step.at.unwrap_or(at);
+            if step_at < at || !step_at.is_finite() {
+                return Err(ScenarioError::TimeGoesBack {
+                    scenario: self.name.clone(),
+                    step: number,
+                    input: step.input.clone(),
+                    at: step_at,
+                });
+            }
+            at = step_at;
+
+            node.handle(&step.to_input(), start + Duration::from_secs_f64(at));
+            let actions = node.take_actions();
+            if let Some(problem) = check(step, &actions) {
+                return Err(ScenarioError::Failed {
+                    scenario: self.name.clone(),
+                    step: number,
+                    input: step.input.clone(),
+                    problem,
+                    sent: describe(&actions),
+                });
+            }
+            trace.steps.push(actions);
+        }
+
+        Ok(trace)
+    }
+}
+
+/// What is wrong with the outputs of a step, if anything
+fn check(step: &Step, actions: &[Action]) -> Option<String> {
+    let mut sent = actions.iter().filter_map(Action::as_output);
+    for expect in &step.expect {
+        if !sent.any(|output| expect.matches(output)) {
+            return Some(format!("did not send {}", expect));
+        }
+    }
+
+    actions
+        .iter()
+        .filter_map(Action::as_output)
+        .find(|output| step.silent.contains(&output.id))
+        .map(|output| format!("sent {} {:?} but should not have", output.id, output.data))
+}
+
+fn describe(actions: &[Action]) -> String {
+    let mut text = String::new();
+    for output in actions.iter().filter_map(Action::as_output) {
+        let _ = write!(text, "  {} {:?}", output.id, output.data);
+        for (key, value) in &output.metadata {
+            let _ = write!(text, " {}={}", key, value);
+        }
+        text.push('\n');
+    }
+    if text.is_empty() {
+        text.push_str("  (nothing)\n");
+    }
+    text
+}
+
+/// A map of YAML scalars, read as strings
+fn scalar_map<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, String>, D::Error> {
+    let map = BTreeMap::<String, serde_yaml::Value>::deserialize(deserializer)?;
+    map.into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                serde_yaml::Value::String(s) => s,
+                serde_yaml::Value::Number(n) => n.to_string(),
+                serde_yaml::Value::Bool(b) => b.to_string(),
+                other => {
+                    return Err(serde::de::Error::custom(format!(
+                        "metadata {} must be a scalar, got {:?}",
+                        key, other
+                    )))
+                }
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LogLevel;
+
+    /// Counts inputs and resumes on every `go`
+    #[derive(Default)]
+    struct Counter {
+        count: u32,
+        actions: Vec<Action>,
+    }
+
+    impl Node for Counter {
+        fn handle(&mut self, input: &Input, _now: Instant) {
+            self.count += 1;
+            self.actions
+                .push(Action::log(LogLevel::Debug, format!("input {}", input.id)));
+            if input.id == "go" {
+                self.actions.push(Action::Send(
+                    Output::new("control", "resume")
+                        .with_metadata(QUESTION_ID, self.count.to_string())
+                        .with_metadata("seconds", input.metadata["seconds"].clone()),
+                ));
+            }
+        }
+
+        fn take_actions(&mut self) -> Vec<Action> {
+            std::mem::take(&mut self.actions)
+        }
+    }
+
+    #[test]
+    fn test_scenario_passes() {
+        let scenario: Scenario = r#"
+            name: counter
+            steps:
+              - { input: wait, at: 1 }
+              - input: go
+                metadata: { seconds: 2.5 }
+                expect:
+                  - { output: control, data: resume, question_id: "2", metadata: { seconds: 2.5 } }
+              - { input: wait, silent: [control] }
+        "#
+        .parse()
+        .unwrap();
+
+        let trace = scenario
+            .run(&mut Counter::default(), Instant::now())
+            .unwrap();
+        assert_eq!(trace.steps.len(), 3);
+        assert_eq!(trace.sent("control").count(), 1);
+    }
+
+    #[test]
+    fn test_scenario_failures() {
+        let run = |yaml: &str| {
+            yaml.parse::<Scenario>()
+                .unwrap()
+                .run(&mut Counter::default(), Instant::now())
+                .unwrap_err()
+                .to_string()
+        };
+
+        let missing = run(r#"
+            name: missing
+            steps:
+              - input: go
+                metadata: { seconds: 1 }
+                expect: [{ output: control, question_id: "7" }]
+        "#);
+        assert!(
+            missing.contains("step 1 (go) did not send control question_id=7"),
+            "{missing}"
+        );
+        assert!(
+            missing.contains("control \"resume\" question_id=1 seconds=1"),
+            "{missing}"
+        );
+
+        let unexpected = run(r#"
+            name: unexpected
+            steps:
+              - { input: go, metadata: { seconds: 1 }, silent: [control] }
+        "#);
+        assert!(
+            unexpected.contains("sent control \"resume\" but should not have"),
+            "{unexpected}"
+        );
+
+        let backwards = run(r#"
+            name: backwards
+            steps:
+              - { input: wait, at: 2 }
+              - { input: wait, at: 1 }
+        "#);
+        assert!(
+            backwards.contains("earlier than the previous step"),
+            "{backwards}"
+        );
+    }
+
+    #[test]
+    fn test_scenario_rejects_unknown_fields() {
+        let result = "name: x\nsteps:\n  - { input: a, expects: [] }\n".parse::<Scenario>();
+        assert!(matches!(result, Err(ScenarioError::Parse(_))));
+    }
+}
@@ -0,0 +1,714 @@
+//! # MoFA Stream Metadata
+//!
+//! Typed metadata attached to every chunk of a streamed `text` output
+//! (maas-client), so downstream nodes can tell chunks, responses and their
+//! outcome apart without guessing from free-form status strings.
+//!
+//! ## Fields
+//!
+//! | Key | Type | Description |
+//! |-----|------|-------------|
+//! | `session_id` | string | Conversation the response belongs to |
+//! | `request_id` | string | One model response; all its chunks share it |
+//! | `seq` | integer | Chunk number within the response, from 0, including the final chunk |
+//! | `status` | string | `started`, `ongoing`, `ended`, `error`, `cancelled` or `reset` |
+//! | `question_id` | string | Copied from the input that triggered the response, when it had one (see [`turn`]) |
+//! | `finish_reason` | string | Final chunk only: `stop`, `length`, `tool_calls`, `content_filter`, `timeout`, `error`, `cancelled` or `reset` |
+//! | `stream_version` | integer | [`META_VERSION`] |
+//!
+//! The first chunk of a response is `started`, later ones `ongoing`, and
+//! exactly one terminal chunk (`ended`, `error`, `cancelled` or `reset`)
+//! closes it. Terminal chunks may carry text, e.g. an error message.
+//!
+//! Producers also write the legacy `session_status` (same value as `status`)
+//! and `segment_index` keys for consumers that predate this contract, and
+//! [`status`] falls back to them when reading metadata from older producers.
+//!
+//! ## Usage Example
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//! use mofa_stream::{StreamAssembler, StreamMeta, StreamStatus, StreamWriter};
+//!
+//! let mut writer = StreamWriter::new("debate", "req-1");
+//! let mut assembler = StreamAssembler::new();
+//!
+//! for (meta, text) in [
+//!     (writer.chunk(), "Hello, "),
+//!     (writer.chunk(), "world."),
+//!     (writer.finish(StreamStatus::Ended, Some("stop")), ""),
+//! ] {
+//!     // Producer side: write into the output metadata
+//!     let mut parameters = BTreeMap::new();
+//!     meta.write_to(&mut parameters);
+//!
+//!     // Consumer side: read it back and reassemble
+//!     let meta = StreamMeta::read(&parameters).unwrap();
+//!     if let Some(response) = assembler.push(&meta, text) {
+//!         assert_eq!(response.text, "Hello, world.");
+//!         assert!(response.is_intact());
+//!     }
+//! }
+//! ```
+
+pub mod turn;
+
+pub use turn::{LegacyQuestionId, QuestionId, TurnId};
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Contract version written by [`StreamMeta::write_to`]
+pub const META_VERSION: u32 = 1;
+
+/// Metadata keys of the contract
+pub mod keys {
+    pub const SESSION_ID: &str = "session_id";
+    pub const REQUEST_ID: &str = "request_id";
+    pub const SEQ: &str = "seq";
+    pub const STATUS: &str = "status";
+    pub const QUESTION_ID: &str = "question_id";
+    pub const FINISH_REASON: &str = "finish_reason";
+    pub const VERSION: &str = "stream_version";
+
+    /// Legacy status key, written alongside [`STATUS`]
+    pub const SESSION_STATUS: &str = "session_status";
+    /// Legacy chunk counter (string), written alongside [`SEQ`]
+    pub const SEGMENT_INDEX: &str = "segment_index";
+    /// Legacy completion flag
+    pub const IS_COMPLETE: &str = "is_complete";
+}
+
+/// Errors from reading stream metadata
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MetaError {
+    #[error("Missing stream metadata field: {0}")]
+    Missing(&'static str),
+
+    #[error("Invalid stream metadata field {key}: {value}")]
+    Invalid { key: &'static str, value: String },
+
+    #[error("Unsupported stream metadata version {0} (supported: {META_VERSION})")]
+    UnsupportedVersion(u64),
+}
+
+/// Stage of a streamed response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamStatus {
+    /// First chunk
+    Started,
+    /// Any later non-final chunk
+    Ongoing,
+    /// Completed normally
+    Ended,
+    /// Failed (provider error, timeout, ...)
+    Error,
+    /// Cancelled; conversation history is kept
+    Cancelled,
+    /// Cancelled and conversation history cleared
+    Reset,
+}
+
+impl StreamStatus {
+    /// Wire name of the status
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StreamStatus::Started => "started",
+            StreamStatus::Ongoing => "ongoing",
+            StreamStatus::Ended => "ended",
+            StreamStatus::Error => "error",
+            StreamStatus::Cancelled => "cancelled",
+            StreamStatus::Reset => "reset",
+        }
+    }
+
+    /// Whether this status closes the response
+    pub fn is_terminal(self) -> bool {
+        !matches!(self, StreamStatus::Started | StreamStatus::Ongoing)
+    }
+
+    /// Whether the response was cut short (error, cancel or reset)
+    pub fn is_interrupted(self) -> bool {
+        matches!(
+            self,
+            StreamStatus::Error | StreamStatus::Cancelled | StreamStatus::Reset
+        )
+    }
+
+    /// Map a legacy `session_status` value, including the aliases older
+    /// producers used (`complete`, `timeout`)
+    fn from_legacy(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "complete" => Some(StreamStatus::Ended),
+            "timeout" => Some(StreamStatus::Error),
+            other => other.parse().ok(),
+        }
+    }
+}
+
+impl FromStr for StreamStatus {
+    type Err = MetaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "started" => Ok(StreamStatus::Started),
+            "ongoing" => Ok(StreamStatus::Ongoing),
+            "ended" => Ok(StreamStatus::Ended),
+            "error" => Ok(StreamStatus::Error),
+            "cancelled" => Ok(StreamStatus::Cancelled),
+            "reset" => Ok(StreamStatus::Reset),
+            _ => Err(MetaError::Invalid {
+                key: keys::STATUS,
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for StreamStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A metadata value as written by [`StreamMeta::write_to`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetaValue {
+    String(String),
+    Integer(i64),
+    Bool(bool),
+}
+
+/// Metadata maps stream metadata can be read from
+///
+/// Implemented for dora metadata parameters (with the `dora` feature) and
+/// for string maps like the ones MoFA Studio bridges hand to widgets.
+pub trait MetadataRead {
+    /// Value of `key` as text; numbers and booleans are formatted
+    fn get_str(&self, key: &str) -> Option<Cow<'_, str>>;
+}
+
+/// Metadata maps stream metadata can be written to
+pub trait MetadataWrite {
+    fn set(&mut self, key: &str, value: MetaValue);
+    fn unset(&mut self, key: &str);
+}
+
+impl MetadataRead for BTreeMap<String, String> {
+    fn get_str(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.get(key).map(|v| Cow::Borrowed(v.as_str()))
+    }
+}
+
+impl MetadataRead for HashMap<String, String> {
+    fn get_str(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.get(key).map(|v| Cow::Borrowed(v.as_str()))
+    }
+}
+
+impl MetadataWrite for BTreeMap<String, String> {
+    fn set(&mut self, key: &str, value: MetaValue) {
+        let value = match value {
+            MetaValue::String(s) => s,
+            MetaValue::Integer(i) => i.to_string(),
+            MetaValue::Bool(b) => b.to_string(),
+        };
+        self.insert(key.to_string(), value);
+    }
+
+    fn unset(&mut self, key: &str) {
+        self.remove(key);
+    }
+}
+
+#[cfg(feature = "dora")]
+mod dora {
+    use super::*;
+    use dora_node_api::Parameter;
+
+    impl MetadataRead for BTreeMap<String, Parameter> {
+        fn get_str(&self, key: &str) -> Option<Cow<'_, str>> {
+            match self.get(key)? {
+                Parameter::String(s) => Some(Cow::Borrowed(s.as_str())),
+                Parameter::Integer(i) => Some(Cow::Owned(i.to_string())),
+                Parameter::Bool(b) => Some(Cow::Owned(b.to_string())),
+                Parameter::Float(f) => Some(Cow::Owned(f.to_string())),
+                _ => None,
+            }
+        }
+    }
+
+    impl MetadataWrite for BTreeMap<String, Parameter> {
+        fn set(&mut self, key: &str, value: MetaValue) {
+            let value = match value {
+                MetaValue::String(s) => Parameter::String(s),
+                MetaValue::Integer(i) => Parameter::Integer(i),
+                MetaValue::Bool(b) => Parameter::Bool(b),
+            };
+            self.insert(key.to_string(), value);
+        }
+
+        fn unset(&mut self, key: &str) {
+            self.remove(key);
+        }
+    }
+}
+
+/// Stream metadata of one `text` chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamMeta {
+    pub session_id: String,
+    pub request_id: String,
+    pub seq: u64,
+    pub status: StreamStatus,
+    pub question_id: Option<String>,
+    pub finish_reason: Option<String>,
+}
+
+impl StreamMeta {
+    /// Read the contract fields; fails on metadata from producers that
+    /// don't implement it (use [`status`] for a lenient check)
+    pub fn read(metadata: &impl MetadataRead) -> Result<Self, MetaError> {
+        if let Some(version) = metadata.get_str(keys::VERSION) {
+            let version = parse_u64(keys::VERSION, &version)?;
+            if version > META_VERSION as u64 {
+                return Err(MetaError::UnsupportedVersion(version));
+            }
+        }
+        let required = |key: &'static str| {
+            metadata
+                .get_str(key)
+                .map(Cow::into_owned)
+                .ok_or(MetaError::Missing(key))
+        };
+
+        Ok(Self {
+            session_id: required(keys::SESSION_ID)?,
+            request_id: required(keys::REQUEST_ID)?,
+            seq: parse_u64(keys::SEQ, &required(keys::SEQ)?)?,
+            status: required(keys::STATUS)?.parse()?,
+            question_id: metadata.get_str(keys::QUESTION_ID).map(Cow::into_owned),
+            finish_reason: metadata.get_str(keys::FINISH_REASON).map(Cow::into_owned),
+        })
+    }
+
+    /// Write the contract fields (and the legacy aliases), replacing any
+    /// stream metadata already present, e.g. passed through from an input
+    pub fn write_to(&self, metadata: &mut impl MetadataWrite) {
+        let string = |s: &str| MetaValue::String(s.to_string());
+        metadata.set(keys::SESSION_ID, string(&self.session_id));
+        metadata.set(keys::REQUEST_ID, string(&self.request_id));
+        metadata.set(keys::SEQ, MetaValue::Integer(self.seq as i64));
+        metadata.set(keys::STATUS, string(self.status.as_str()));
+        metadata.set(keys::VERSION, MetaValue::Integer(META_VERSION as i64));
+        match &self.question_id {
+            Some(question_id) => metadata.set(keys::QUESTION_ID, string(question_id)),
+            None => metadata.unset(keys::QUESTION_ID),
+        }
+        match &self.finish_reason {
+            Some(reason) => metadata.set(keys::FINISH_REASON, string(reason)),
+            None => metadata.unset(keys::FINISH_REASON),
+        }
+
+        metadata.set(keys::SESSION_STATUS, string(self.status.as_str()));
+        metadata.set(keys::SEGMENT_INDEX, MetaValue::String(self.seq.to_string()));
+    }
+}
+
+fn parse_u64(key: &'static str, value: &str) -> Result<u64, MetaError> {
+    value.trim().parse().map_err(|_| MetaError::Invalid {
+        key,
+        value: value.to_string(),
+    })
+}
+
+/// Status of a chunk from any producer.
+///
+/// Reads `status`, then the legacy `session_status`, then treats
+/// `is_complete = true` as [`StreamStatus::Ended`]. `None` when the metadata
+/// carries no status at all (e.g. a non-streaming input).
+pub fn status(metadata: &impl MetadataRead) -> Option<StreamStatus> {
+    if let Some(status) = metadata.get_str(keys::STATUS) {
+        if let Ok(status) = status.parse() {
+            return Some(status);
+        }
+    }
+    if let Some(status) = metadata
+        .get_str(keys::SESSION_STATUS)
+        .and_then(|s| StreamStatus::from_legacy(&s))
+    {
+        return Some(status);
+    }
+    match metadata.get_str(keys::IS_COMPLETE).as_deref() {
+        Some("true") => Some(StreamStatus::Ended),
+        _ => None,
+    }
+}
+
+/// Whether a chunk closes its response (any terminal [`status`])
+pub fn is_complete(metadata: &impl MetadataRead) -> bool {
+    status(metadata).is_some_and(StreamStatus::is_terminal)
+}
+
+/// Produces the metadata of one response's chunks in order
+#[derive(Debug, Clone)]
+pub struct StreamWriter {
+    session_id: String,
+    request_id: String,
+    question_id: Option<String>,
+    next_seq: u64,
+    finished: bool,
+}
+
+impl StreamWriter {
+    pub fn new(session_id: impl Into<String>, request_id: impl Into<String>) -> Self {
+        Self {
+            session_id: session_id.into(),
+            request_id: request_id.into(),
+            question_id: None,
+            next_seq: 0,
+            finished: false,
+        }
+    }
+
+    /// Tag every chunk with the question ID of the triggering input
+    pub fn with_question_id(mut self, question_id: Option<String>) -> Self {
+        self.question_id = question_id;
+        self
+    }
+
+    pub fn request_id(&self) -> &str {
+        &self.request_id
+    }
+
+    /// Whether any chunk has been produced
+    pub fn has_started(&self) -> bool {
+        self.next_seq > 0
+    }
+
+    /// Whether the terminal chunk has been produced
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Metadata of the next content chunk (`started`, then `ongoing`)
+    pub fn chunk(&mut self) -> StreamMeta {
+        let status = if self.has_started() {
+            StreamStatus::Ongoing
+        } else {
+            StreamStatus::Started
+        };
+        self.next(status, None)
+    }
+
+    /// Metadata of the terminal chunk
+    pub fn finish(&mut self, status: StreamStatus, finish_reason: Option<&str>) -> StreamMeta {
+        debug_assert!(status.is_terminal(), "{} is not a terminal status", status);
+        self.finished = true;
+        self.next(status, finish_reason.map(str::to_string))
+    }
+
+    fn next(&mut self, status: StreamStatus, finish_reason: Option<String>) -> StreamMeta {
+        let meta = StreamMeta {
+            session_id: self.session_id.clone(),
+            request_id: self.request_id.clone(),
+            seq: self.next_seq,
+            status,
+            question_id: self.question_id.clone(),
+            finish_reason,
+        };
+        self.next_seq += 1;
+        meta
+    }
+}
+
+/// A response rebuilt by [`StreamAssembler`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssembledStream {
+    pub session_id: String,
+    pub request_id: String,
+    pub question_id: Option<String>,
+    /// Status of the terminal chunk
+    pub status: StreamStatus,
+    pub finish_reason: Option<String>,
+    /// Text of all received chunks in `seq` order
+    pub text: String,
+    /// Sequence numbers before the terminal chunk that never arrived
+    pub missing: Vec<u64>,
+    /// Chunks that arrived ahead of an earlier one
+    pub out_of_order: usize,
+    /// Chunks received more than once (ignored)
+    pub duplicates: usize,
+}
+
+impl AssembledStream {
+    /// Whether every chunk arrived
+    pub fn is_intact(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+#[derive(Debug, Default)]
+struct PartialStream {
+    session_id: String,
+    chunks: BTreeMap<u64, String>,
+    highest_seq: Option<u64>,
+    out_of_order: usize,
+    duplicates: usize,
+}
+
+/// Number of completed request IDs remembered to drop late chunks
+const FINISHED_CAPACITY: usize = 64;
+
+/// Rebuilds responses from chunks, in `seq` order
+///
+/// Chunks are buffered per `request_id` until the terminal chunk arrives;
+/// the response is then returned with any gaps listed in
+/// [`AssembledStream::missing`]. Chunks of a response that already completed
+/// are dropped.
+#[derive(Debug, Default)]
+pub struct StreamAssembler {
+    streams: HashMap<String, PartialStream>,
+    finished: VecDeque<String>,
+}
+
+impl StreamAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a chunk; returns the response once its terminal chunk arrives
+    pub fn push(&mut self, meta: &StreamMeta, text: &str) -> Option<AssembledStream> {
+        if self.finished.contains(&meta.request_id) {
+            return None;
+        }
+        let stream = self
+            .streams
+            .entry(meta.request_id.clone())
+            .or_insert_with(|| PartialStream {
+                session_id: meta.session_id.clone(),
+                ..Default::default()
+            });
+
+        if stream.chunks.contains_key(&meta.seq) {
+            stream.duplicates += 1;
+            return None;
+        }
+        if stream.highest_seq.is_some_and(|highest| meta.seq < highest) {
+            stream.out_of_order += 1;
+        }
+        stream.highest_seq = stream.highest_seq.max(Some(meta.seq));
+        stream.chunks.insert(meta.seq, text.to_string());
+
+        if !meta.status.is_terminal() {
+            return None;
+        }
+
+        let stream = self.streams.remove(&meta.request_id)?;
+        if self.finished.len() == FINISHED_CAPACITY {
+            self.finished.pop_front();
+        }
+        self.finished.push_back(meta.request_id.clone());
+
+        let missing = (0..meta.seq)
+            .filter(|seq| !stream.chunks.contains_key(seq))
+            .collect();
+        Some(AssembledStream {
+            session_id: meta.session_id.clone(),
+            request_id: meta.request_id.clone(),
+            question_id: meta.question_id.clone(),
+            status: meta.status,
+            finish_reason: meta.finish_reason.clone(),
+            text: stream
+                .chunks
+                .range(..=meta.seq)
+                .map(|(_, text)| text.as_str())
+                .collect(),
+            missing,
+            out_of_order: stream.out_of_order,
+            duplicates: stream.duplicates,
+        })
+    }
+
+    /// Number of responses still waiting for their terminal chunk
+    pub fn in_progress(&self) -> usize {
+        self.streams.len()
+    }
+
+    /// Drop partial responses of a session, e.g. after a reset
+    pub fn discard_session(&mut self, session_id: &str) {
+        self.streams
+            .retain(|_, stream| stream.session_id != session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(meta: &StreamMeta) -> BTreeMap<String, String> {
+        let mut metadata = BTreeMap::new();
+        meta.write_to(&mut metadata);
+        metadata
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut writer =
+            StreamWriter::new("debate", "req-1").with_question_id(Some("259".to_string()));
+        let first = writer.chunk();
+        let second = writer.chunk();
+        let last = writer.finish(StreamStatus::Ended, Some("stop"));
+
+        assert_eq!(first.status, StreamStatus::Started);
+        assert_eq!(second.status, StreamStatus::Ongoing);
+        assert_eq!((first.seq, second.seq, last.seq), (0, 1, 2));
+        assert!(writer.is_finished());
+
+        for meta in [first, second, last] {
+            let metadata = write(&meta);
+            assert_eq!(metadata["session_status"], meta.status.as_str());
+            assert_eq!(metadata["segment_index"], meta.seq.to_string());
+            assert_eq!(StreamMeta::read(&metadata), Ok(meta));
+        }
+    }
+
+    #[test]
+    fn test_write_replaces_passed_through_fields() {
+        let mut metadata = BTreeMap::from([
+            ("session_id".to_string(), "old".to_string()),
+            ("finish_reason".to_string(), "stop".to_string()),
+            ("question_id".to_string(), "7".to_string()),
+            ("participant".to_string(), "tutor".to_string()),
+        ]);
+        StreamWriter::new("debate", "req-2")
+            .chunk()
+            .write_to(&mut metadata);
+
+        assert_eq!(metadata["session_id"], "debate");
+        assert!(!metadata.contains_key("finish_reason"));
+        assert!(!metadata.contains_key("question_id"));
+        assert_eq!(metadata["participant"], "tutor");
+    }
+
+    #[test]
+    fn test_read_rejects_incomplete_metadata() {
+        let mut metadata = write(&StreamWriter::new("debate", "req-1").chunk());
+        metadata.remove("request_id");
+        assert_eq!(
+            StreamMeta::read(&metadata),
+            Err(MetaError::Missing("request_id"))
+        );
+
+        let mut metadata = write(&StreamWriter::new("debate", "req-1").chunk());
+        metadata.insert("seq".to_string(), "first".to_string());
+        assert!(matches!(
+            StreamMeta::read(&metadata),
+            Err(MetaError::Invalid { key: "seq", .. })
+        ));
+
+        let mut metadata = write(&StreamWriter::new("debate", "req-1").chunk());
+        metadata.insert("stream_version".to_string(), "2".to_string());
+        assert_eq!(
+            StreamMeta::read(&metadata),
+            Err(MetaError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn test_status_falls_back_to_legacy_fields() {
+        let legacy =
+            |key: &str, value: &str| BTreeMap::from([(key.to_string(), value.to_string())]);
+
+        assert_eq!(
+            status(&legacy("session_status", "ongoing")),
+            Some(StreamStatus::Ongoing)
+        );
+        assert_eq!(
+            status(&legacy("session_status", "complete")),
+            Some(StreamStatus::Ended)
+        );
+        assert_eq!(
+            status(&legacy("session_status", "timeout")),
+            Some(StreamStatus::Error)
+        );
+        assert_eq!(
+            status(&legacy("is_complete", "true")),
+            Some(StreamStatus::Ended)
+        );
+        assert_eq!(status(&legacy("session_status", "paused")), None);
+        assert_eq!(status(&BTreeMap::<String, String>::new()), None);
+
+        assert!(is_complete(&legacy("session_status", "reset")));
+        assert!(!is_complete(&legacy("session_status", "started")));
+        assert!(StreamStatus::Cancelled.is_interrupted());
+        assert!(!StreamStatus::Ended.is_interrupted());
+    }
+
+    #[test]
+    fn test_assembler_reorders_and_reports_gaps() {
+        let mut writer = StreamWriter::new("debate", "req-1");
+        let chunks: Vec<_> = ["The ", "quick ", "brown ", "fox."]
+            .into_iter()
+            .map(|text| (writer.chunk(), text))
+            .collect();
+        let end = writer.finish(StreamStatus::Ended, Some("stop"));
+
+        // In order
+        let mut assembler = StreamAssembler::new();
+        for (meta, text) in &chunks {
+            assert_eq!(assembler.push(meta, text), None);
+        }
+        let response = assembler.push(&end, "").unwrap();
+        assert_eq!(response.text, "The quick brown fox.");
+        assert!(response.is_intact());
+        assert_eq!(response.finish_reason.as_deref(), Some("stop"));
+        assert_eq!(assembler.in_progress(), 0);
+
+        // Shuffled, with a duplicate and a lost chunk
+        let mut assembler = StreamAssembler::new();
+        for index in [2, 0, 2, 3] {
+            let (meta, text) = &chunks[index];
+            assert_eq!(assembler.push(meta, text), None);
+        }
+        let response = assembler.push(&end, "").unwrap();
+        assert_eq!(response.text, "The brown fox.");
+        assert_eq!(response.missing, vec![1]);
+        assert_eq!(response.out_of_order, 1);
+        assert_eq!(response.duplicates, 1);
+
+        // Late chunk of a completed response
+        assert_eq!(assembler.push(&chunks[1].0, chunks[1].1), None);
+        assert_eq!(assembler.in_progress(), 0);
+    }
+
+    #[test]
+    fn test_assembler_keeps_responses_apart() {
+        let mut tutor = StreamWriter::new("study", "req-tutor");
+        let mut student = StreamWriter::new("study", "req-student");
+        let mut assembler = StreamAssembler::new();
+
+        assembler.push(&tutor.chunk(), "Why?");
+        assembler.push(&student.chunk(), "Because");
+        let response = assembler
+            .push(&student.finish(StreamStatus::Error, Some("timeout")), "")
+            .unwrap();
+        assert_eq!(response.text, "Because");
+        assert_eq!(response.status, StreamStatus::Error);
+        assert_eq!(assembler.in_progress(), 1);
+
+        let response = assembler
+            .push(&tutor.finish(StreamStatus::Ended, Some("stop")), "")
+            .unwrap();
+        assert_eq!(response.request_id, "req-tutor");
+        assert_eq!(response.text, "Why?");
+
+        assembler.push(&StreamWriter::new("study", "req-3").chunk(), "So");
+        assembler.push(&StreamWriter::new("other", "req-4").chunk(), "Hi");
+        assembler.discard_session("study");
+        assert_eq!(assembler.in_progress(), 1);
+    }
+}
@@ -0,0 +1,333 @@
+//! # Turn Identifiers
+//!
+//! The `question_id` metadata value that ties a speaking turn together
+//! across the controller, LLM clients, bridges, text segmenter and audio
+//! player.
+//!
+//! A [`TurnId`] carries:
+//!
+//! | Field | Description |
+//! |-------|-------------|
+//! | `epoch` | Conversation session; bumped by a full reset (starts at the controller's start time in seconds) |
+//! | `round` | Turn counter within the epoch, monotonic |
+//! | `generation` | Interrupt generation; bumped when a human interrupts |
+//! | `speaker` | Participant the turn was handed to (empty before one is chosen) |
+//!
+//! It serializes as `t1:<epoch>:<round>:<generation>:<speaker>`, e.g.
+//! `t1:1760000000:12:3:tutor`. The speaker comes last so it may contain `:`.
+//!
+//! Older controllers sent a 16-bit number packing round, participant count
+//! and participant index (8-4-4 bits). [`QuestionId::parse`] still reads
+//! those as [`LegacyQuestionId`], and any other string as
+//! [`QuestionId::Other`].
+//!
+//! Stale-audio filtering after a reset compares `(epoch, generation)`
+//! instead of matching strings: everything from an older generation is
+//! stale, everything from the reset's generation onwards is current,
+//! whatever its round or speaker. See [`is_stale`].
+
+use crate::{keys, MetaError, MetadataRead};
+use std::fmt;
+use std::str::FromStr;
+
+/// Prefix (and format version) of serialized turn IDs
+pub const TURN_ID_PREFIX: &str = "t1";
+
+/// Structured turn identifier
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TurnId {
+    pub epoch: u64,
+    pub round: u64,
+    pub generation: u32,
+    pub speaker: String,
+}
+
+impl TurnId {
+    /// First turn of a session, before a speaker is chosen
+    pub fn new(epoch: u64) -> Self {
+        Self {
+            epoch,
+            ..Default::default()
+        }
+    }
+
+    /// The same turn handed to `speaker`
+    pub fn with_speaker(&self, speaker: impl Into<String>) -> Self {
+        Self {
+            speaker: speaker.into(),
+            ..self.clone()
+        }
+    }
+
+    /// The following turn, handed to `speaker`
+    pub fn next_round(&self, speaker: impl Into<String>) -> Self {
+        Self {
+            round: self.round + 1,
+            speaker: speaker.into(),
+            ..self.clone()
+        }
+    }
+
+    /// The turn after an interrupt: new generation, next round, no speaker
+    pub fn interrupted(&self) -> Self {
+        Self {
+            round: self.round + 1,
+            generation: self.generation + 1,
+            speaker: String::new(),
+            ..self.clone()
+        }
+    }
+
+    /// First turn of a new session
+    ///
+    /// The epoch becomes `min_epoch` (e.g. the current time) or the next
+    /// epoch, whichever is larger, so it never goes backwards.
+    pub fn next_epoch(&self, min_epoch: u64) -> Self {
+        Self::new(min_epoch.max(self.epoch + 1))
+    }
+
+    /// Whether this turn belongs to an earlier epoch or interrupt generation
+    /// than `reset`
+    pub fn is_stale_for(&self, reset: &TurnId) -> bool {
+        (self.epoch, self.generation) < (reset.epoch, reset.generation)
+    }
+}
+
+impl fmt::Display for TurnId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}:{}",
+            TURN_ID_PREFIX, self.epoch, self.round, self.generation, self.speaker
+        )
+    }
+}
+
+impl FromStr for TurnId {
+    type Err = MetaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || MetaError::Invalid {
+            key: keys::QUESTION_ID,
+            value: s.to_string(),
+        };
+        let mut parts = s.trim().splitn(5, ':');
+        if parts.next() != Some(TURN_ID_PREFIX) {
+            return Err(invalid());
+        }
+        let mut number = || parts.next().and_then(|p| p.parse::<u64>().ok());
+        let epoch = number().ok_or_else(invalid)?;
+        let round = number().ok_or_else(invalid)?;
+        let generation = number()
+            .and_then(|g| u32::try_from(g).ok())
+            .ok_or_else(invalid)?;
+        let speaker = parts.next().ok_or_else(invalid)?.to_string();
+        Ok(Self {
+            epoch,
+            round,
+            generation,
+            speaker,
+        })
+    }
+}
+
+/// The 16-bit question ID of older controllers
+///
+/// Bits 15-8: round, bits 7-4: participant count - 1, bits 3-0: participant
+/// index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LegacyQuestionId {
+    pub round: u8,
+    pub participant: u8,
+    pub total_participants: u8,
+}
+
+impl LegacyQuestionId {
+    pub fn decode(value: u16) -> Self {
+        Self {
+            round: (value >> 8) as u8,
+            participant: (value & 0xF) as u8,
+            total_participants: ((value >> 4) & 0xF) as u8 + 1,
+        }
+    }
+
+    pub fn encode(&self) -> u16 {
+        let total = self.total_participants.clamp(1, 16) - 1;
+        (self.round as u16) << 8 | (total as u16) << 4 | (self.participant & 0xF) as u16
+    }
+
+    /// Whether the participant is the last of the round
+    pub fn is_last_participant(&self) -> bool {
+        self.participant + 1 == self.total_participants
+    }
+}
+
+/// A `question_id` as found in metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuestionId {
+    Turn(TurnId),
+    Legacy(LegacyQuestionId),
+    /// Any other value, e.g. IDs generated by ASR nodes; compared as text
+    Other(String),
+}
+
+impl QuestionId {
+    /// Parse a `question_id`; never fails, unknown formats become `Other`
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if let Ok(turn) = value.parse() {
+            return QuestionId::Turn(turn);
+        }
+        if let Ok(legacy) = value.parse::<u16>() {
+            return QuestionId::Legacy(LegacyQuestionId::decode(legacy));
+        }
+        QuestionId::Other(value.to_string())
+    }
+
+    /// Read the `question_id` key, if present
+    pub fn read(metadata: &impl MetadataRead) -> Option<Self> {
+        metadata
+            .get_str(keys::QUESTION_ID)
+            .map(|value| Self::parse(&value))
+    }
+
+    pub fn as_turn(&self) -> Option<&TurnId> {
+        match self {
+            QuestionId::Turn(turn) => Some(turn),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for QuestionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuestionId::Turn(turn) => turn.fmt(f),
+            QuestionId::Legacy(legacy) => legacy.encode().fmt(f),
+            QuestionId::Other(other) => f.write_str(other),
+        }
+    }
+}
+
+/// Whether content tagged `incoming` predates a reset tagged `reset`
+///
+/// Turn IDs compare by epoch and generation. Anything else falls back to
+/// the old rule: only an identical `question_id` is current.
+pub fn is_stale(incoming: &str, reset: &str) -> bool {
+    match (QuestionId::parse(incoming), QuestionId::parse(reset)) {
+        (QuestionId::Turn(incoming), QuestionId::Turn(reset)) => incoming.is_stale_for(&reset),
+        _ => incoming.trim() != reset.trim(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_turn_id_round_trip() {
+        let turn = TurnId::new(1_760_000_000).next_round("tutor");
+        assert_eq!(turn.to_string(), "t1:1760000000:1:0:tutor");
+        assert_eq!(turn.to_string().parse::<TurnId>(), Ok(turn));
+
+        // Speakers may contain the separator
+        let turn = TurnId::new(3).with_speaker("panel:guest");
+        assert_eq!(turn.to_string().parse::<TurnId>(), Ok(turn));
+
+        let unassigned = TurnId::new(3);
+        assert_eq!(unassigned.to_string(), "t1:3:0:0:");
+        assert_eq!(unassigned.to_string().parse::<TurnId>(), Ok(unassigned));
+    }
+
+    #[test]
+    fn test_turn_id_rejects_malformed() {
+        for value in [
+            "",
+            "259",
+            "t1:1:2",
+            "t2:1:2:3:tutor",
+            "t1:x:2:3:tutor",
+            "t1:1:2:99999999999:a",
+        ] {
+            assert!(value.parse::<TurnId>().is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_no_limits_on_rounds_or_participants() {
+        let mut turn = TurnId::new(1);
+        for i in 0..300 {
+            turn = turn.next_round(format!("participant{}", i));
+        }
+        assert_eq!(turn.round, 300);
+        assert_eq!(turn.speaker, "participant299");
+    }
+
+    #[test]
+    fn test_interrupt_and_epoch() {
+        let turn = TurnId::new(10).next_round("tutor");
+        let interrupted = turn.interrupted();
+        assert_eq!((interrupted.round, interrupted.generation), (2, 1));
+        assert!(interrupted.speaker.is_empty());
+
+        assert_eq!(turn.next_epoch(0), TurnId::new(11));
+        assert_eq!(turn.next_epoch(500), TurnId::new(500));
+    }
+
+    #[test]
+    fn test_staleness_compares_generations() {
+        let turn = TurnId::new(10).next_round("tutor");
+        let reset = turn.interrupted();
+
+        assert!(turn.is_stale_for(&reset));
+        assert!(turn.next_round("student1").is_stale_for(&reset));
+        assert!(!reset.is_stale_for(&reset));
+        // Later turns of the same generation, with any speaker, are current
+        assert!(!reset.with_speaker("tutor").is_stale_for(&reset));
+        assert!(!reset.next_round("student2").is_stale_for(&reset));
+        // A new epoch supersedes every generation of the old one
+        let new_session = reset.interrupted().interrupted().next_epoch(0);
+        assert!(reset.interrupted().is_stale_for(&new_session));
+        assert!(!new_session.is_stale_for(&reset));
+
+        assert!(is_stale(&turn.to_string(), &reset.to_string()));
+        assert!(!is_stale(
+            &reset.with_speaker("tutor").to_string(),
+            &reset.to_string()
+        ));
+    }
+
+    #[test]
+    fn test_legacy_question_ids() {
+        // Round 2 (0-based 1), participant 3 of 3
+        let legacy = LegacyQuestionId {
+            round: 1,
+            participant: 2,
+            total_participants: 3,
+        };
+        assert_eq!(legacy.encode(), 290);
+        assert_eq!(LegacyQuestionId::decode(290), legacy);
+        assert!(legacy.is_last_participant());
+
+        assert_eq!(QuestionId::parse("290"), QuestionId::Legacy(legacy));
+        assert_eq!(QuestionId::parse("290").to_string(), "290");
+        assert_eq!(
+            QuestionId::parse("asr-123456"),
+            QuestionId::Other("asr-123456".to_string())
+        );
+
+        // Legacy IDs keep exact-match semantics
+        assert!(is_stale("5", "6"));
+        assert!(!is_stale("6", "6"));
+        assert!(is_stale("t1:1:0:0:tutor", "6"));
+    }
+
+    #[test]
+    fn test_read_from_metadata() {
+        let turn = TurnId::new(7).next_round("judge");
+        let metadata = BTreeMap::from([("question_id".to_string(), turn.to_string())]);
+        assert_eq!(QuestionId::read(&metadata), Some(QuestionId::Turn(turn)));
+        assert_eq!(QuestionId::read(&BTreeMap::<String, String>::new()), None);
+    }
+}
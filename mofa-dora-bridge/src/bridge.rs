@@ -24,6 +24,12 @@
 //! | AudioPlayerBridge | `mofa-audio-player` | Receives TTS audio |
 //! | PromptInputBridge | `mofa-prompt-input` | Receives chat messages |
 //! | SystemLogBridge | `mofa-system-log` | Receives log entries |
+//! | ChatViewerBridge | `mofa-chat-viewer` | Displays LLM text (read-only) |
+//! | AecInputBridge | `mofa-mic-input` | Sends mic audio and VAD events |
+//! | CastControllerBridge | `mofa-cast-controller` | Sends script segments, receives audio |
+//!
+//! Other dynamic nodes can get a bridge through
+//! [`BridgeRegistry`](crate::BridgeRegistry) without touching [`MofaNodeType`](crate::MofaNodeType).
 //!
 //! ## Connection States
 //!
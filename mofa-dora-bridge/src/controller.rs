@@ -3,14 +3,22 @@
 //! Manages the lifecycle of dora dataflows:
 //! - Start dataflow with env configuration
 //! - Stop dataflow and cleanup resources
-//! - Monitor dataflow status
+//! - Monitor dataflow and per-node status
+//! - Validate dataflow wiring before starting
 
+use crate::dora_cli::{
+    parse_dataflow_list, parse_node_list, DoraCli, DoraCommand, NodeState, NodeStatus,
+    SystemDoraCli,
+};
 use crate::error::{BridgeError, BridgeResult};
-use crate::parser::{DataflowParser, ParsedDataflow};
+use crate::parser::{DataflowParser, NodeKind, ParsedDataflow};
+use crate::readiness::Backoff;
+use crate::validator::{DataflowDiagnostic, DiagnosticKind};
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
@@ -49,6 +57,9 @@ impl DataflowState {
     }
 }
 
+/// How long to wait for a freshly spawned `dora up` to answer
+const DAEMON_READY_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Controller for managing dataflow lifecycle
 pub struct DataflowController {
     /// Path to the dataflow YAML file
@@ -61,6 +72,12 @@ pub struct DataflowController {
     env_vars: HashMap<String, String>,
     /// Dora daemon process (if we started it)
     daemon_process: Option<Child>,
+    /// Whether the running dataflow's ID came from dora (vs. generated locally)
+    dataflow_id_listed: bool,
+    /// Runs `dora` commands
+    cli: Arc<dyn DoraCli>,
+    /// Set once the CLI rejected `dora node list` (dora 0.4), so it isn't retried
+    node_list_unsupported: AtomicBool,
 }
 
 impl DataflowController {
@@ -83,9 +100,18 @@ impl DataflowController {
             state: Arc::new(RwLock::new(DataflowState::Stopped)),
             env_vars: HashMap::new(),
             daemon_process: None,
+            dataflow_id_listed: false,
+            cli: SystemDoraCli::shared(),
+            node_list_unsupported: AtomicBool::new(false),
         })
     }
 
+    /// Set how `dora` commands are run (e.g. [`FakeDoraCli`](crate::dora_cli::FakeDoraCli) in tests)
+    pub fn set_cli(&mut self, cli: Arc<dyn DoraCli>) {
+        self.cli = cli;
+        self.node_list_unsupported.store(false, Ordering::Relaxed);
+    }
+
     /// Get the parsed dataflow
     pub fn parsed(&self) -> Option<&ParsedDataflow> {
         self.parsed.as_ref()
@@ -107,36 +133,55 @@ impl DataflowController {
     }
 
     /// Check if all required env vars are set
+    ///
+    /// Only operator, custom and dynamic nodes are checked; env placeholders of
+    /// `path:` nodes are reported by [`validate`](Self::validate) instead.
     pub fn check_env_requirements(&self) -> Vec<String> {
         let mut missing = Vec::new();
         if let Some(parsed) = &self.parsed {
             for req in &parsed.env_requirements {
-                if req.required {
-                    if !self.env_vars.contains_key(&req.key) && std::env::var(&req.key).is_err() {
-                        missing.push(req.key.clone());
-                    }
+                let checked = req.used_by.iter().any(|id| {
+                    !matches!(
+                        parsed.get_node(id).map(|n| &n.kind),
+                        Some(NodeKind::Executable { .. })
+                    )
+                });
+                if req.required
+                    && checked
+                    && !self.env_vars.contains_key(&req.key)
+                    && std::env::var(&req.key).is_err()
+                {
+                    missing.push(req.key.clone());
                 }
             }
         }
         missing
     }
 
+    /// Validate the dataflow, resolving env placeholders from the configured env vars
+    pub fn validate(&self) -> Vec<DataflowDiagnostic> {
+        self.parsed
+            .as_ref()
+            .map(|p| p.validate_with_env(&self.env_vars))
+            .unwrap_or_default()
+    }
+
+    /// Run `dora list`, returning stdout if the coordinator answered
+    fn dora_list(&self) -> Option<String> {
+        let output = self.cli.run(&DoraCommand::new(["list"])).ok()?;
+        output.success.then_some(output.stdout)
+    }
+
     /// Ensure dora daemon is running
     pub fn ensure_daemon(&mut self) -> BridgeResult<()> {
         // Check if daemon is already running by using `dora list`
         // If it succeeds, daemon is running
-        let status = Command::new("dora")
-            .arg("list")
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status();
-
-        match status {
-            Ok(s) if s.success() => {
+        match self.dora_list() {
+            Some(_) => {
                 debug!("Dora daemon already running");
                 Ok(())
             }
-            _ => {
+            None => {
                 info!("Starting dora daemon...");
                 let child = Command::new("dora")
                     .arg("up")
@@ -149,8 +194,12 @@ impl DataflowController {
 
                 self.daemon_process = Some(child);
 
-                // Wait for daemon to be ready
-                std::thread::sleep(Duration::from_millis(1000));
+                // Wait until the coordinator answers `dora list`
+                let deadline = Instant::now() + DAEMON_READY_TIMEOUT;
+                Backoff::new(Duration::from_millis(100), Duration::from_secs(1), deadline)
+                    .poll("Dora daemon did not come up", || self.dora_list())
+                    .map_err(|e| BridgeError::StartFailed(e.to_string()))?;
+                debug!("Dora daemon ready");
                 Ok(())
             }
         }
@@ -169,6 +218,25 @@ impl DataflowController {
         // Update state
         *self.state.write() = DataflowState::Starting;
 
+        // Reject broken wiring before touching the daemon; unresolved env
+        // placeholders are only reported, the env check below decides on those
+        let mut errors = Vec::new();
+        for diagnostic in self.validate() {
+            if diagnostic.kind == DiagnosticKind::UnresolvedEnvPlaceholder {
+                warn!("{}", diagnostic);
+            } else if diagnostic.is_error() {
+                errors.push(diagnostic.to_string());
+            }
+        }
+        if !errors.is_empty() {
+            let msg = errors.join("; ");
+            error!("Dataflow validation failed: {}", msg);
+            *self.state.write() = DataflowState::Error {
+                message: msg.clone(),
+            };
+            return Err(BridgeError::ValidationFailed(msg));
+        }
+
         // Ensure daemon is running
         self.ensure_daemon()?;
 
@@ -188,28 +256,28 @@ impl DataflowController {
             .parent()
             .ok_or_else(|| BridgeError::StartFailed("Invalid dataflow path".to_string()))?;
 
-        let mut cmd = Command::new("dora");
-        cmd.arg("start")
+        let mut cmd = DoraCommand::new([
+            "start".to_string(),
             // Use the absolute path so dora always resolves node paths relative to
             // the actual dataflow file location.
-            .arg(&self.dataflow_path)
-            .arg("--detach")
-            .current_dir(dataflow_dir);
+            self.dataflow_path.to_string_lossy().into_owned(),
+            "--detach".to_string(),
+        ])
+        .current_dir(dataflow_dir);
 
         // Add environment variables
         for (key, value) in &self.env_vars {
-            cmd.env(key, value);
+            cmd = cmd.env(key, value);
         }
 
         // Execute
         info!("Starting dataflow: {:?}", self.dataflow_path);
-        let output = cmd.output().map_err(|e| {
+        let output = self.cli.run(&cmd).map_err(|e| {
             BridgeError::StartFailed(format!("Failed to execute dora start: {}", e))
         })?;
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let msg = format!("Dora start failed: {}", stderr);
+        if !output.success {
+            let msg = format!("Dora start failed: {}", output.stderr);
             error!("{}", msg);
             *self.state.write() = DataflowState::Error {
                 message: msg.clone(),
@@ -218,11 +286,10 @@ impl DataflowController {
         }
 
         // Parse dataflow ID from output (check both stdout and stderr - dora outputs to stderr)
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let dataflow_id = Self::parse_dataflow_id(&stderr)
-            .or_else(|| Self::parse_dataflow_id(&stdout))
-            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let parsed_id = Self::parse_dataflow_id(&output.stderr)
+            .or_else(|| Self::parse_dataflow_id(&output.stdout));
+        self.dataflow_id_listed = parsed_id.is_some();
+        let dataflow_id = parsed_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
 
         info!("Dataflow started with ID: {}", dataflow_id);
 
@@ -235,6 +302,29 @@ impl DataflowController {
         Ok(dataflow_id)
     }
 
+    /// Wait until `dora list` shows the running dataflow
+    ///
+    /// `dora start --detach` returns before the dataflow is registered; this
+    /// polls with `backoff` instead of sleeping for a fixed time.
+    pub fn wait_for_dataflow(&self, backoff: &mut Backoff) -> BridgeResult<()> {
+        let dataflow_id = match &*self.state.read() {
+            DataflowState::Running { dataflow_id, .. } => dataflow_id.clone(),
+            _ => return Err(BridgeError::DataflowNotRunning),
+        };
+
+        if !self.dataflow_id_listed {
+            // We couldn't parse dora's ID, so there's nothing to look for
+            warn!("Dataflow ID not reported by dora, skipping `dora list` check");
+            return Ok(());
+        }
+
+        backoff.poll("Dataflow not listed by dora", || {
+            self.dora_list().filter(|list| list.contains(&dataflow_id))
+        })?;
+        debug!("Dataflow {} listed by dora", dataflow_id);
+        Ok(())
+    }
+
     /// Stop the dataflow gracefully (default 15s grace period)
     pub fn stop(&mut self) -> BridgeResult<()> {
         self.stop_with_options(None)
@@ -278,23 +368,22 @@ impl DataflowController {
         info!("Stopping dataflow: {} (grace: {})", dataflow_id, grace_str);
 
         // Build dora stop command
-        let mut cmd = Command::new("dora");
-        cmd.arg("stop").arg(&dataflow_id);
+        let mut args = vec!["stop".to_string(), dataflow_id.clone()];
 
         // Add grace duration if specified
         if let Some(duration) = grace_duration {
-            cmd.arg("--grace-duration")
-                .arg(format!("{}s", duration.as_secs()));
+            args.push("--grace-duration".to_string());
+            args.push(format!("{}s", duration.as_secs()));
         }
 
         // Execute dora stop
-        let output = cmd
-            .output()
+        let output = self
+            .cli
+            .run(&DoraCommand::new(args))
             .map_err(|e| BridgeError::StopFailed(format!("Failed to execute dora stop: {}", e)))?;
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            warn!("Dora stop warning: {}", stderr);
+        if !output.success {
+            warn!("Dora stop warning: {}", output.stderr);
             // Continue anyway - the dataflow might already be stopped
         }
 
@@ -313,16 +402,32 @@ impl DataflowController {
                 ref dataflow_id,
                 ref started_at,
             } => {
-                // Query dora for node status
-                let output = Command::new("dora")
-                    .arg("list")
-                    .output()
+                // Query dora for dataflow status
+                let output = self
+                    .cli
+                    .run(&DoraCommand::new(["list"]))
                     .map_err(|e| BridgeError::Unknown(format!("Failed to query status: {}", e)))?;
 
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                let is_running = stdout.contains(dataflow_id);
+                // CLIs that print no status column only list running dataflows
+                let is_running = match parse_dataflow_list(&output.stdout)
+                    .into_iter()
+                    .find(|d| d.id == *dataflow_id)
+                {
+                    Some(d) if d.state != NodeState::Unknown => d.state.is_alive(),
+                    _ => output.stdout.contains(dataflow_id),
+                };
                 let uptime = started_at.elapsed();
 
+                // Per-node status is best effort; on dora 0.4 every node is Unknown
+                let nodes = if is_running {
+                    self.node_statuses().unwrap_or_else(|e| {
+                        debug!("Per-node status unavailable: {}", e);
+                        self.unknown_node_statuses()
+                    })
+                } else {
+                    Vec::new()
+                };
+
                 Ok(DataflowStatus {
                     state: if is_running {
                         DataflowState::Running {
@@ -339,6 +444,7 @@ impl DataflowController {
                         .as_ref()
                         .map(|p| p.mofa_nodes.len())
                         .unwrap_or(0),
+                    nodes,
                 })
             }
             other => Ok(DataflowStatus {
@@ -350,10 +456,75 @@ impl DataflowController {
                     .as_ref()
                     .map(|p| p.mofa_nodes.len())
                     .unwrap_or(0),
+                nodes: Vec::new(),
             }),
         }
     }
 
+    /// Get the runtime status of every node in the dataflow
+    ///
+    /// Queries `dora node list` and returns one [`NodeStatus`] per
+    /// [`ParsedNode`](crate::ParsedNode), in dataflow order. Nodes dora
+    /// doesn't report are [`NodeState::Unknown`].
+    ///
+    /// Not supported by dora 0.4, whose CLI has no per-node listing: the first
+    /// call fails and later calls fail without running `dora` again.
+    pub fn node_statuses(&self) -> BridgeResult<Vec<NodeStatus>> {
+        let dataflow_id = match &*self.state.read() {
+            DataflowState::Running { dataflow_id, .. } => dataflow_id.clone(),
+            _ => return Err(BridgeError::DataflowNotRunning),
+        };
+        if self.node_list_unsupported.load(Ordering::Relaxed) {
+            return Err(BridgeError::Unknown(
+                "dora CLI has no per-node status".to_string(),
+            ));
+        }
+
+        let output = self
+            .cli
+            .run(&DoraCommand::new([
+                "node",
+                "list",
+                "--dataflow",
+                &dataflow_id,
+            ]))
+            .map_err(|e| BridgeError::Unknown(format!("Failed to query node status: {}", e)))?;
+        if !output.success {
+            if is_unknown_subcommand(&output.stderr) {
+                warn!("dora CLI has no `node list`, per-node status unavailable");
+                self.node_list_unsupported.store(true, Ordering::Relaxed);
+            }
+            return Err(BridgeError::Unknown(format!(
+                "dora node list failed: {}",
+                output.stderr.trim()
+            )));
+        }
+
+        let mut reported = parse_node_list(&output.stdout);
+        Ok(self
+            .node_ids()
+            .map(|id| {
+                reported
+                    .iter()
+                    .position(|status| status.node_id == id)
+                    .map(|i| reported.swap_remove(i))
+                    .unwrap_or_else(|| NodeStatus::unknown(id))
+            })
+            .collect())
+    }
+
+    /// Every node as [`NodeState::Unknown`]
+    fn unknown_node_statuses(&self) -> Vec<NodeStatus> {
+        self.node_ids().map(NodeStatus::unknown).collect()
+    }
+
+    /// IDs of all parsed nodes, in dataflow order
+    fn node_ids(&self) -> impl Iterator<Item = &str> {
+        self.parsed
+            .iter()
+            .flat_map(|p| p.nodes.iter().map(|n| n.id.as_str()))
+    }
+
     /// Parse dataflow ID from dora start output
     fn parse_dataflow_id(output: &str) -> Option<String> {
         // Look for UUID pattern in output
@@ -369,6 +540,11 @@ impl DataflowController {
     }
 }
 
+/// Whether dora's (clap) error output rejects the subcommand itself
+fn is_unknown_subcommand(stderr: &str) -> bool {
+    stderr.contains("unrecognized subcommand")
+}
+
 impl Drop for DataflowController {
     fn drop(&mut self) {
         // Try to stop the dataflow if running
@@ -392,4 +568,125 @@ pub struct DataflowStatus {
     pub uptime: Option<Duration>,
     pub node_count: usize,
     pub mofa_node_count: usize,
+    /// Per-node runtime status (empty unless running; all unknown on dora 0.4)
+    pub nodes: Vec<NodeStatus>,
+}
+
+impl DataflowStatus {
+    /// Nodes that exited with an error
+    pub fn failed_nodes(&self) -> Vec<&NodeStatus> {
+        self.nodes.iter().filter(|n| n.state.is_failed()).collect()
+    }
+
+    /// Get the status of a node by ID
+    pub fn node(&self, node_id: &str) -> Option<&NodeStatus> {
+        self.nodes.iter().find(|n| n.node_id == node_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dora_cli::{DoraOutput, FakeDoraCli};
+
+    const DATAFLOW_ID: &str = "0190a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b";
+
+    const YAML: &str = r#"
+nodes:
+  - id: student1
+    path: llm.py
+    env:
+      API_KEY: ${MOFA_CONTROLLER_TEST_UNSET_KEY}
+    outputs:
+      - text
+  - id: primespeech-student1
+    path: tts.py
+    inputs:
+      text: student1/text
+    outputs:
+      - log
+  - id: mofa-system-log
+    path: dynamic
+    inputs:
+      tts_log: primespeech-student1/log
+"#;
+
+    /// `dora list` output with our dataflow in `status`
+    fn dora_list(status: &str) -> String {
+        format!(
+            "UUID{:34}NAME   STATUS\n{}  voice  {}\n",
+            "", DATAFLOW_ID, status
+        )
+    }
+
+    #[test]
+    fn test_node_statuses_from_cli() {
+        let path = std::env::temp_dir().join(format!("mofa-controller-{}.yml", std::process::id()));
+        std::fs::write(&path, YAML).unwrap();
+        let mut controller = DataflowController::new(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let cli = FakeDoraCli::new();
+        cli.set_response(
+            &["start"],
+            DoraOutput {
+                success: true,
+                stdout: String::new(),
+                stderr: format!("dataflow start triggered: {}\n", DATAFLOW_ID),
+            },
+        );
+        cli.respond(&["list"], dora_list("Running"));
+        cli.respond(
+            &["node", "list"],
+            "\
+NODE                  STATUS    RESTARTS  EXIT CODE
+student1              Running   0         -
+primespeech-student1  Failed    1         1
+",
+        );
+        controller.set_cli(cli.clone());
+
+        // An unset placeholder on a `path:` node is reported, not fatal
+        assert_eq!(controller.validate().len(), 1);
+        assert_eq!(controller.start().unwrap(), DATAFLOW_ID);
+        let status = controller.get_status().unwrap();
+        assert!(status.state.is_running());
+        let ids: Vec<&str> = status.nodes.iter().map(|n| n.node_id.as_str()).collect();
+        assert_eq!(ids, ["student1", "primespeech-student1", "mofa-system-log"]);
+        assert_eq!(status.node("student1").unwrap().state, NodeState::Running);
+        let failed = status.failed_nodes();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].node_id, "primespeech-student1");
+        assert_eq!(failed[0].state, NodeState::Failed { exit_code: Some(1) });
+        assert_eq!(failed[0].restarts, 1);
+        assert_eq!(
+            status.node("mofa-system-log").unwrap().state,
+            NodeState::Unknown
+        );
+
+        // dora 0.4 has no per-node status: dataflow still reported, nodes unknown
+        cli.fail(&["node", "list"], "error: unrecognized subcommand 'node'");
+        let status = controller.get_status().unwrap();
+        assert!(status.state.is_running());
+        assert!(status.nodes.iter().all(|n| n.state == NodeState::Unknown));
+        let node_lists = |cli: &FakeDoraCli| cli.calls().iter().filter(|c| c[0] == "node").count();
+        let tried = node_lists(&cli);
+        controller.get_status().unwrap();
+        assert_eq!(
+            node_lists(&cli),
+            tried,
+            "unsupported `node list` is not retried"
+        );
+
+        // A failed dataflow stays in `dora list`, but is no longer running
+        cli.respond(&["list"], dora_list("Failed"));
+        let status = controller.get_status().unwrap();
+        assert_eq!(status.state, DataflowState::Stopped);
+        assert!(status.nodes.is_empty());
+
+        controller.stop().unwrap();
+        assert!(cli
+            .calls()
+            .contains(&vec!["stop".to_string(), DATAFLOW_ID.to_string()]));
+    }
 }
@@ -10,7 +10,7 @@
 //! | [`AudioData`] | TTS audio samples with metadata | Dora → UI |
 //! | [`ChatMessage`] | Conversation messages | Dora → UI |
 //! | [`LogEntry`] | System/debug logs | Dora → UI |
-//! | [`ControlCommand`] | Dataflow control commands | UI → Dora |
+//! | [`ControlMessage`] | Typed control messages (shared `mofa-control` protocol) | UI → Dora |
 //! | [`DoraData`] | Unified wrapper for all data types | Both |
 //!
 //! ## Key Design Decisions
@@ -33,6 +33,7 @@
 //! - Debug < Info < Warning < Error
 //! - UI can filter to show only logs >= a threshold
 
+pub use mofa_control::ControlMessage;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -48,8 +49,8 @@ pub enum DoraData {
     Json(serde_json::Value),
     /// Raw binary data
     Binary(Vec<u8>),
-    /// Control command
-    Control(ControlCommand),
+    /// Control message (see [`mofa_control`])
+    Control(ControlMessage),
     /// Log entry
     Log(LogEntry),
     /// Chat message
@@ -86,12 +87,9 @@ impl DoraData {
         })
     }
 
-    /// Create control command
-    pub fn control(command: impl Into<String>) -> Self {
-        DoraData::Control(ControlCommand {
-            command: command.into(),
-            params: HashMap::new(),
-        })
+    /// Create control message
+    pub fn control(message: ControlMessage) -> Self {
+        DoraData::Control(message)
     }
 }
 
@@ -322,56 +320,6 @@ pub enum MessageRole {
     System,
 }
 
-/// Control command for dataflow orchestration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ControlCommand {
-    /// Command name (e.g., "start", "stop", "pause", "reset")
-    pub command: String,
-    /// Command parameters
-    #[serde(default)]
-    pub params: HashMap<String, serde_json::Value>,
-}
-
-impl ControlCommand {
-    /// Create a simple command without parameters
-    pub fn new(command: impl Into<String>) -> Self {
-        Self {
-            command: command.into(),
-            params: HashMap::new(),
-        }
-    }
-
-    /// Add parameter
-    pub fn with_param(
-        mut self,
-        key: impl Into<String>,
-        value: impl Into<serde_json::Value>,
-    ) -> Self {
-        self.params.insert(key.into(), value.into());
-        self
-    }
-
-    /// Create "start" command
-    pub fn start() -> Self {
-        Self::new("start")
-    }
-
-    /// Create "stop" command
-    pub fn stop() -> Self {
-        Self::new("stop")
-    }
-
-    /// Create "reset" command
-    pub fn reset() -> Self {
-        Self::new("reset")
-    }
-
-    /// Create "send_prompt" command with message
-    pub fn send_prompt(message: impl Into<String>) -> Self {
-        Self::new("send_prompt").with_param("message", serde_json::Value::String(message.into()))
-    }
-}
-
 /// Metadata from dora events
 #[derive(Debug, Clone, Default)]
 pub struct EventMetadata {
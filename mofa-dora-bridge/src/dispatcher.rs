@@ -3,17 +3,30 @@
 //! Manages connections between MoFA widgets and their corresponding
 //! dora dynamic nodes. Each widget type has its own bridge that
 //! connects as a separate dynamic node.
+//!
+//! Bridges are created from a [`BridgeRegistry`], preloaded with the
+//! built-in widget bridges. Apps add their own with
+//! [`DynamicNodeDispatcher::register_bridge`].
+//!
+//! [`DynamicNodeDispatcher::start`] waits for readiness rather than fixed
+//! sleeps; see [`readiness`](crate::readiness) and [`StartupConfig`].
+//! Once running, [`DynamicNodeDispatcher::supervise`] reconnects bridges
+//! that failed (see [`supervisor`](crate::supervisor)).
 
 use crate::bridge::{BridgeState, DoraBridge};
 use crate::controller::DataflowController;
 use crate::error::{BridgeError, BridgeResult};
 use crate::parser::MofaNodeSpec;
-use crate::shared_state::SharedDoraState;
-use crate::widgets::{AecInputBridge, AudioPlayerBridge, PromptInputBridge, SystemLogBridge};
+use crate::readiness::{self, StartupConfig};
+use crate::registry::BridgeRegistry;
+use crate::shared_state::{SharedDoraState, StartupPhase};
+use crate::supervisor::{BridgeSupervisor, ReconnectPolicy};
+use crate::transport::{DoraTransportFactory, TransportFactory};
 use crate::MofaNodeType;
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::{debug, error, info, warn};
 
 /// Binding between a widget and its dora node
@@ -21,8 +34,8 @@ use tracing::{debug, error, info, warn};
 pub struct WidgetBinding {
     /// Widget identifier in the UI
     pub widget_id: String,
-    /// MoFA node type
-    pub node_type: MofaNodeType,
+    /// MoFA node type (`None` for bridges from a custom registry entry)
+    pub node_type: Option<MofaNodeType>,
     /// Node ID in the dataflow
     pub node_id: String,
     /// Connection state
@@ -42,6 +55,14 @@ pub struct DynamicNodeDispatcher {
     bridges: HashMap<String, Box<dyn DoraBridge>>,
     /// Widget bindings
     bindings: Vec<WidgetBinding>,
+    /// Bridge factories by node-ID pattern
+    registry: BridgeRegistry,
+    /// Startup polling and readiness gate
+    startup: StartupConfig,
+    /// Transport for the readiness probe node
+    transport: Arc<dyn TransportFactory>,
+    /// Reconnects bridges that failed while running
+    supervisor: BridgeSupervisor,
 }
 
 impl DynamicNodeDispatcher {
@@ -63,9 +84,55 @@ impl DynamicNodeDispatcher {
             shared_state,
             bridges: HashMap::new(),
             bindings: Vec::new(),
+            registry: BridgeRegistry::with_builtins(),
+            startup: StartupConfig::default(),
+            transport: DoraTransportFactory::shared(),
+            supervisor: BridgeSupervisor::default(),
         }
     }
 
+    /// Set how [`supervise`](Self::supervise) reconnects failed bridges
+    pub fn set_reconnect_policy(&mut self, policy: ReconnectPolicy) {
+        self.supervisor = BridgeSupervisor::new(policy);
+    }
+
+    /// Set how [`start`](Self::start) polls for readiness
+    pub fn set_startup_config(&mut self, startup: StartupConfig) {
+        self.startup = startup;
+    }
+
+    /// Get the startup configuration
+    pub fn startup_config(&self) -> &StartupConfig {
+        &self.startup
+    }
+
+    /// Set the transport used by the readiness probe (e.g. mock runtime)
+    pub fn set_transport(&mut self, transport: Arc<dyn TransportFactory>) {
+        self.transport = transport;
+    }
+
+    /// Register a bridge factory for dynamic nodes matching `pattern`
+    ///
+    /// `pattern` is a node ID with optional `*` wildcards. Later registrations
+    /// take precedence, including over the built-in widget bridges.
+    /// Must be called before [`create_bridges`](Self::create_bridges) / [`start`](Self::start).
+    pub fn register_bridge<F>(&mut self, pattern: impl Into<String>, factory: F)
+    where
+        F: Fn(&str, Arc<SharedDoraState>) -> Box<dyn DoraBridge> + Send + Sync + 'static,
+    {
+        self.registry.register(pattern, factory);
+    }
+
+    /// Get the bridge registry
+    pub fn registry(&self) -> &BridgeRegistry {
+        &self.registry
+    }
+
+    /// Get the bridge registry for modification
+    pub fn registry_mut(&mut self) -> &mut BridgeRegistry {
+        &mut self.registry
+    }
+
     /// Get the dataflow controller
     pub fn controller(&self) -> &Arc<RwLock<DataflowController>> {
         &self.controller
@@ -85,54 +152,46 @@ impl DynamicNodeDispatcher {
             .unwrap_or_default()
     }
 
-    /// Create bridges for all discovered MoFA nodes
+    /// Discover node IDs that may get a bridge: dynamic nodes and MoFA nodes
+    pub fn discover_bridge_nodes(&self) -> Vec<String> {
+        self.controller
+            .read()
+            .parsed()
+            .map(|p| {
+                p.nodes
+                    .iter()
+                    .filter(|n| n.is_dynamic || MofaNodeType::from_node_id(&n.id).is_some())
+                    .map(|n| n.id.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Create bridges for all discovered nodes with a registered factory
     pub fn create_bridges(&mut self) -> BridgeResult<()> {
-        let mofa_nodes = self.discover_mofa_nodes();
-        info!("Creating bridges for {} discovered mofa nodes", mofa_nodes.len());
-        let shared_state = Some(self.shared_state.clone());
-
-        for node_spec in mofa_nodes {
-            info!("Creating bridge for node '{}' (type: {:?})", node_spec.id, node_spec.node_type);
-            let bridge: Box<dyn DoraBridge> = match node_spec.node_type {
-                MofaNodeType::AudioPlayer => Box::new(AudioPlayerBridge::with_shared_state(
-                    &node_spec.id,
-                    shared_state.clone(),
-                )),
-                MofaNodeType::SystemLog => Box::new(SystemLogBridge::with_shared_state(
-                    &node_spec.id,
-                    shared_state.clone(),
-                )),
-                MofaNodeType::PromptInput => Box::new(PromptInputBridge::with_shared_state(
-                    &node_spec.id,
-                    shared_state.clone(),
-                )),
-                MofaNodeType::MoFACast => Box::new(CastControllerBridge::with_shared_state(
-                    &node_spec.id,
-                    shared_state.clone(),
-                )),
-                MofaNodeType::MicInput => {
-                    Box::new(AecInputBridge::with_shared_state(&node_spec.id, shared_state.clone()))
-                }
-                MofaNodeType::ChatViewer => {
-                    // TODO: Implement ChatViewerBridge
-                    continue;
-                }
-                MofaNodeType::ParticipantPanel => {
-                    // ParticipantPanel functionality consolidated into AudioPlayerBridge
-                    // No separate bridge needed - AudioPlayerBridge now handles LED visualization
-                    info!("Skipping ParticipantPanel bridge - consolidated into AudioPlayerBridge");
-                    continue;
-                }
+        let node_ids = self.discover_bridge_nodes();
+        info!("Creating bridges for {} discovered dynamic nodes", node_ids.len());
+
+        for node_id in node_ids {
+            let node_type = MofaNodeType::from_node_id(&node_id);
+            let Some(bridge) = self.registry.create(&node_id, self.shared_state.clone()) else {
+                // e.g. ParticipantPanel - consolidated into AudioPlayerBridge
+                info!(
+                    "No bridge registered for node '{}' (type: {:?}), skipping",
+                    node_id, node_type
+                );
+                continue;
             };
+            info!("Created bridge for node '{}' (type: {:?})", node_id, node_type);
 
             self.bindings.push(WidgetBinding {
-                widget_id: node_spec.id.clone(),
-                node_type: node_spec.node_type,
-                node_id: node_spec.id.clone(),
+                widget_id: node_id.clone(),
+                node_type,
+                node_id: node_id.clone(),
                 state: BridgeState::Disconnected,
             });
 
-            self.bridges.insert(node_spec.id, bridge);
+            self.bridges.insert(node_id, bridge);
         }
 
         info!("Created {} bridges with shared state", self.bridges.len());
@@ -223,67 +282,154 @@ impl DynamicNodeDispatcher {
     }
 
     /// Start the dataflow and connect all bridges
+    ///
+    /// Waits until dora lists the dataflow, then for any gated nodes to report
+    /// ready, then connects bridges with backoff. Progress is published per
+    /// node in `SharedDoraState.status.startup`. Fails with
+    /// [`BridgeError::Timeout`] if [`StartupConfig::timeout`] passes first.
     pub fn start(&mut self) -> BridgeResult<String> {
+        let deadline = Instant::now() + self.startup.timeout;
+        self.shared_state.clear_startup();
+        self.supervisor.reset();
+
         // Start the dataflow
         let dataflow_id = {
             let mut controller = self.controller.write();
             controller.start()?
         };
 
-        // Wait for dataflow to initialize and register dynamic nodes
-        // This is necessary because `dora start --detach` returns immediately
+        let node_ids: Vec<String> = self
+            .controller
+            .read()
+            .parsed()
+            .map(|p| p.nodes.iter().map(|n| n.id.clone()).collect())
+            .unwrap_or_default();
+        for node_id in &node_ids {
+            self.shared_state.set_startup_phase(node_id, StartupPhase::Starting);
+        }
+
+        // `dora start --detach` returns before the dataflow is registered
         info!("Waiting for dataflow to initialize...");
-        std::thread::sleep(std::time::Duration::from_secs(2));
+        let listed = self
+            .controller
+            .read()
+            .wait_for_dataflow(&mut self.startup.backoff(deadline));
+        if let Err(e) = listed {
+            self.fail_startup(&e);
+            return Err(e);
+        }
+        for node_id in node_ids.iter().filter(|id| !self.startup.ready_nodes.contains(id)) {
+            self.shared_state.set_startup_phase(node_id, StartupPhase::Started);
+        }
 
-        // Additional wait for TTS nodes to load their models
-        // Kokoro TTS needs time to load MLX/CPU models before accepting input
-        info!("Waiting for TTS nodes to load models...");
-        std::thread::sleep(std::time::Duration::from_secs(3));
+        // Wait for gated nodes (e.g. TTS loading models) to report ready
+        let ready = match self.controller.read().parsed() {
+            Some(parsed) => readiness::wait_for_ready(
+                self.transport.as_ref(),
+                parsed,
+                &self.startup,
+                deadline,
+                &self.shared_state,
+            ),
+            None => Ok(()),
+        };
+        if let Err(e) = ready {
+            self.fail_startup(&e);
+            return Err(e);
+        }
 
         // Create bridges if not already created
         if self.bridges.is_empty() {
             self.create_bridges()?;
         }
 
-        info!("Connecting {} bridges to dora...", self.bridges.len());
+        self.connect_until(deadline)?;
+        Ok(dataflow_id)
+    }
 
-        const MAX_CONNECT_ATTEMPTS: usize = 15;
-        let connect_retry_delay = std::time::Duration::from_secs(2);
+    /// Connect bridges, retrying with backoff until all are connected
+    ///
+    /// Bridges whose dynamic node isn't up yet end in [`BridgeState::Error`]
+    /// and are reconnected on the next attempt.
+    pub fn connect_until(&mut self, deadline: Instant) -> BridgeResult<()> {
+        info!("Connecting {} bridges to dora...", self.bridges.len());
+        let mut backoff = self.startup.backoff(deadline);
+        let mut attempts = 0;
+
+        let result = backoff.poll("Bridges did not connect", || {
+            attempts += 1;
+            let mut all_connected = true;
+            for (node_id, bridge) in &mut self.bridges {
+                if matches!(bridge.state(), BridgeState::Disconnected | BridgeState::Error) {
+                    self.shared_state.set_startup_phase(node_id, StartupPhase::Connecting);
+                    if let Err(e) = bridge.connect() {
+                        warn!(
+                            "Bridge connection attempt {} for {} failed: {}",
+                            attempts, node_id, e
+                        );
+                    }
+                }
 
-        let mut last_err: Option<BridgeError> = None;
-        for attempt in 1..=MAX_CONNECT_ATTEMPTS {
-            match self.connect_all() {
-                Ok(()) => {
-                    info!("All bridges connected after {} attempt(s)", attempt);
-                    last_err = None;
-                    break;
+                let state = bridge.state();
+                if let Some(binding) = self.bindings.iter_mut().find(|b| &b.node_id == node_id)
+                {
+                    binding.state = state;
                 }
-                Err(e) => {
-                    warn!(
-                        "Bridge connection attempt {} failed: {}. Retrying...",
-                        attempt, e
-                    );
-                    last_err = Some(e);
-                    std::thread::sleep(connect_retry_delay);
+                if state == BridgeState::Connected {
+                    self.shared_state.set_startup_phase(node_id, StartupPhase::Connected);
+                } else {
+                    all_connected = false;
                 }
             }
+            all_connected.then_some(())
+        });
+
+        match result {
+            Ok(()) => {
+                info!("All bridges connected after {} attempt(s)", attempts);
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed to connect bridges after {} attempts: {}", attempts, e);
+                self.fail_startup(&e);
+                Err(e)
+            }
         }
+    }
 
-        if let Some(err) = last_err {
-            error!(
-                "Failed to connect bridges after {} attempts: {}",
-                MAX_CONNECT_ATTEMPTS, err
-            );
-            return Err(err);
+    /// Reconnect bridges that failed, with backoff
+    ///
+    /// Call periodically (e.g. from the loop that owns the dispatcher). Does
+    /// nothing unless the dataflow is running. Attempts and outcomes are
+    /// published in `SharedDoraState.status.reconnects`.
+    pub fn supervise(&mut self) {
+        if !self.is_running() {
+            return;
         }
 
-        Ok(dataflow_id)
+        self.supervisor.tick(&mut self.bridges, &self.shared_state);
+        for binding in &mut self.bindings {
+            if let Some(bridge) = self.bridges.get(&binding.node_id) {
+                binding.state = bridge.state();
+            }
+        }
+    }
+
+    /// Mark every node that hasn't finished starting as failed
+    fn fail_startup(&self, error: &BridgeError) {
+        let status = self.shared_state.status.read();
+        for node_id in status.startup_pending() {
+            self.shared_state
+                .set_startup_phase(node_id, StartupPhase::Failed(error.to_string()));
+        }
+        self.shared_state.set_error(Some(error.to_string()));
     }
 
     /// Stop the dataflow and disconnect all bridges (graceful, default 15s)
     pub fn stop(&mut self) -> BridgeResult<()> {
         // Disconnect bridges first
         self.disconnect_all()?;
+        self.supervisor.reset();
 
         // Stop the dataflow
         let mut controller = self.controller.write();
@@ -301,6 +447,7 @@ impl DynamicNodeDispatcher {
     ) -> BridgeResult<()> {
         // Disconnect bridges first
         self.disconnect_all()?;
+        self.supervisor.reset();
 
         // Stop the dataflow with grace duration
         let mut controller = self.controller.write();
@@ -315,6 +462,7 @@ impl DynamicNodeDispatcher {
     pub fn force_stop(&mut self) -> BridgeResult<()> {
         // Disconnect bridges first
         self.disconnect_all()?;
+        self.supervisor.reset();
 
         // Force stop the dataflow
         let mut controller = self.controller.write();
@@ -340,6 +488,9 @@ impl Drop for DynamicNodeDispatcher {
 pub struct DispatcherBuilder {
     controller: Option<DataflowController>,
     auto_connect: bool,
+    registry: BridgeRegistry,
+    startup: StartupConfig,
+    reconnect: ReconnectPolicy,
 }
 
 impl DispatcherBuilder {
@@ -347,6 +498,9 @@ impl DispatcherBuilder {
         Self {
             controller: None,
             auto_connect: false,
+            registry: BridgeRegistry::with_builtins(),
+            startup: StartupConfig::default(),
+            reconnect: ReconnectPolicy::default(),
         }
     }
 
@@ -360,12 +514,36 @@ impl DispatcherBuilder {
         self
     }
 
+    /// Register a custom bridge factory (see [`DynamicNodeDispatcher::register_bridge`])
+    pub fn register_bridge<F>(mut self, pattern: impl Into<String>, factory: F) -> Self
+    where
+        F: Fn(&str, Arc<SharedDoraState>) -> Box<dyn DoraBridge> + Send + Sync + 'static,
+    {
+        self.registry.register(pattern, factory);
+        self
+    }
+
+    /// Set startup polling and readiness (see [`StartupConfig`])
+    pub fn startup(mut self, startup: StartupConfig) -> Self {
+        self.startup = startup;
+        self
+    }
+
+    /// Set how failed bridges are reconnected (see [`ReconnectPolicy`])
+    pub fn reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
     pub fn build(self) -> BridgeResult<DynamicNodeDispatcher> {
         let controller = self
             .controller
             .ok_or_else(|| BridgeError::Unknown("No controller provided".to_string()))?;
 
         let mut dispatcher = DynamicNodeDispatcher::new(controller);
+        dispatcher.registry = self.registry;
+        dispatcher.startup = self.startup;
+        dispatcher.set_reconnect_policy(self.reconnect);
 
         if self.auto_connect {
             dispatcher.start()?;
@@ -380,3 +558,180 @@ impl Default for DispatcherBuilder {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dora_cli::{DoraOutput, FakeDoraCli};
+    use crate::shared_state::{ReconnectOutcome, ReconnectStatus};
+    use crate::transport::MockDoraRuntime;
+    use crate::widgets::SystemLogBridge;
+    use std::time::Duration;
+
+    const YAML: &str = r#"
+nodes:
+  - id: tts
+    path: tts.py
+    outputs:
+      - log
+  - id: mofa-system-log
+    path: dynamic
+    inputs:
+      tts_log: tts/log
+"#;
+
+    /// Dispatcher with a mock-runtime system log bridge and fast polling
+    fn mock_dispatcher(
+        name: &str,
+        runtime: &Arc<MockDoraRuntime>,
+        state: &Arc<SharedDoraState>,
+    ) -> DynamicNodeDispatcher {
+        let path = std::env::temp_dir().join(format!("{}-{}.yml", name, std::process::id()));
+        std::fs::write(&path, YAML).unwrap();
+        let controller = DataflowController::new(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let mut dispatcher = DynamicNodeDispatcher::with_shared_state(controller, state.clone());
+        let transport = runtime.clone();
+        dispatcher.register_bridge("mofa-system-log", move |id, ss| {
+            Box::new(SystemLogBridge::with_transport(id, Some(ss), transport.clone()))
+        });
+        dispatcher.set_startup_config(StartupConfig {
+            initial_poll: Duration::from_millis(10),
+            max_poll: Duration::from_millis(50),
+            ..Default::default()
+        });
+        dispatcher
+    }
+
+    #[test]
+    fn test_connect_until_retries_and_reports_phases() {
+        let runtime = MockDoraRuntime::new();
+        let state = SharedDoraState::new();
+        let mut dispatcher = mock_dispatcher("mofa-dispatcher-connect", &runtime, &state);
+        dispatcher.create_bridges().unwrap();
+
+        // Dynamic node never comes up: deadline passes, phase records the failure
+        runtime.refuse("mofa-system-log");
+        let err = dispatcher.connect_until(Instant::now() + Duration::from_millis(300));
+        assert!(matches!(err, Err(BridgeError::Timeout(_))));
+        assert!(matches!(
+            state.status.read().startup.get("mofa-system-log"),
+            Some(StartupPhase::Failed(_))
+        ));
+
+        // Node is up now: the errored bridge is reconnected
+        runtime.accept("mofa-system-log");
+        dispatcher
+            .connect_until(Instant::now() + Duration::from_secs(5))
+            .unwrap();
+        assert!(dispatcher.get_bridge("mofa-system-log").unwrap().is_connected());
+        assert_eq!(
+            dispatcher.get_binding("mofa-system-log").unwrap().state,
+            BridgeState::Connected
+        );
+        assert_eq!(
+            state.status.read().startup.get("mofa-system-log"),
+            Some(&StartupPhase::Connected)
+        );
+        dispatcher.disconnect_all().unwrap();
+    }
+
+    #[test]
+    fn test_supervisor_ignores_receive_timeouts() {
+        let runtime = MockDoraRuntime::new();
+        let state = SharedDoraState::new();
+        let mut dispatcher = mock_dispatcher("mofa-dispatcher-timeouts", &runtime, &state);
+        dispatcher.create_bridges().unwrap();
+        dispatcher
+            .connect_until(Instant::now() + Duration::from_secs(5))
+            .unwrap();
+
+        // dora reports an idle event stream as timeout errors, not as a failure
+        for _ in 0..5 {
+            runtime.node("mofa-system-log").inject_timeout();
+        }
+        std::thread::sleep(Duration::from_millis(100));
+        dispatcher.supervisor.tick(&mut dispatcher.bridges, &state);
+
+        assert!(dispatcher.get_bridge("mofa-system-log").unwrap().is_connected());
+        assert!(state.status.read().reconnects.get("mofa-system-log").is_none());
+        dispatcher.disconnect_all().unwrap();
+    }
+
+    #[test]
+    fn test_supervisor_reconnects_closed_bridge() {
+        const DATAFLOW_ID: &str = "0190a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b";
+        let runtime = MockDoraRuntime::new();
+        let state = SharedDoraState::new();
+        let mut dispatcher = mock_dispatcher("mofa-dispatcher-supervise", &runtime, &state);
+        dispatcher.set_reconnect_policy(ReconnectPolicy {
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(40),
+            max_attempts: None,
+        });
+
+        let cli = FakeDoraCli::new();
+        cli.set_response(
+            &["start"],
+            DoraOutput {
+                success: true,
+                stdout: String::new(),
+                stderr: format!("dataflow start triggered: {}", DATAFLOW_ID),
+            },
+        );
+        cli.respond(&["list"], DATAFLOW_ID);
+        dispatcher.controller().write().set_cli(cli);
+        dispatcher.start().unwrap();
+        assert_eq!(
+            state.status.read().startup.get("tts"),
+            Some(&StartupPhase::Started)
+        );
+        assert_eq!(
+            state.status.read().startup.get("mofa-system-log"),
+            Some(&StartupPhase::Connected)
+        );
+
+        // Dora drops the node and it can't come back yet
+        runtime.refuse("mofa-system-log");
+        runtime.node("mofa-system-log").close();
+        let supervise_until = |dispatcher: &mut DynamicNodeDispatcher, done: &dyn Fn() -> bool| {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while !done() {
+                assert!(Instant::now() < deadline, "supervisor did not converge");
+                dispatcher.supervise();
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        };
+        supervise_until(&mut dispatcher, &|| {
+            state
+                .status
+                .read()
+                .reconnects
+                .get("mofa-system-log")
+                .is_some_and(|r| r.attempts >= 2)
+        });
+        assert_eq!(
+            dispatcher.get_binding("mofa-system-log").unwrap().state,
+            BridgeState::Error
+        );
+
+        // Node is back: the next attempt succeeds
+        runtime.accept("mofa-system-log");
+        supervise_until(&mut dispatcher, &|| {
+            state
+                .status
+                .read()
+                .reconnects
+                .get("mofa-system-log")
+                .is_some_and(|r| r.outcome == ReconnectOutcome::Reconnected)
+        });
+        assert!(dispatcher.get_bridge("mofa-system-log").unwrap().is_connected());
+        assert!(matches!(
+            state.status.read().reconnects.get("mofa-system-log"),
+            Some(ReconnectStatus { attempts, .. }) if *attempts >= 3
+        ));
+
+        dispatcher.stop().unwrap();
+    }
+}
@@ -0,0 +1,514 @@
+//! Dora CLI abstraction and status parsing
+//!
+//! [`DataflowController`](crate::DataflowController) drives dora through the
+//! `dora` command line. Commands go through the [`DoraCli`] trait so tests
+//! can script the CLI with [`FakeDoraCli`] instead of needing a daemon.
+//!
+//! The dora 0.4 CLI only reports status per dataflow. `dora list` prints a
+//! column-aligned table with one row per dataflow:
+//!
+//! ```text
+//! UUID                                  Name        Status   Nodes  CPU    Memory
+//! 0190a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b  voice-chat  Running  5      3.10%  0.42 GB
+//! ```
+//!
+//! Per-node status is not available on dora 0.4: its CLI has no per-node
+//! listing, so every node is reported as [`NodeState::Unknown`] and a crashed
+//! node only shows once the dataflow itself is listed as `Failed`. The
+//! controller still asks `dora node list --dataflow <id>` once, for CLIs that
+//! provide it, and stops asking after dora rejects the subcommand.
+//! [`parse_node_list`] expects the same table layout as `dora list`; it has
+//! not been checked against a real dora release:
+//!
+//! ```text
+//! NODE                   STATUS     PID     RESTARTS  EXIT CODE
+//! primespeech-student1   Failed     -       0         1
+//! student1               Running    41233   0         -
+//! ```
+//!
+//! Columns are located by header name, so extra or reordered columns are
+//! fine. Exit codes are also read from statuses like `Failed (exit code 1)`.
+
+use parking_lot::Mutex;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+/// A `dora` invocation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DoraCommand {
+    /// Arguments after `dora`
+    pub args: Vec<String>,
+    /// Working directory
+    pub current_dir: Option<PathBuf>,
+    /// Extra environment variables
+    pub env: Vec<(String, String)>,
+}
+
+impl DoraCommand {
+    /// Create a command from its arguments
+    pub fn new<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            args: args.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the working directory
+    pub fn current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Add an environment variable
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+}
+
+/// Captured result of a `dora` invocation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DoraOutput {
+    /// Whether the command exited successfully
+    pub success: bool,
+    /// Standard output
+    pub stdout: String,
+    /// Standard error (dora prints most messages here)
+    pub stderr: String,
+}
+
+/// Runs `dora` commands
+pub trait DoraCli: Send + Sync {
+    /// Run a command to completion and capture its output
+    fn run(&self, command: &DoraCommand) -> io::Result<DoraOutput>;
+}
+
+/// The real `dora` executable on `PATH`
+#[derive(Debug, Default)]
+pub struct SystemDoraCli;
+
+impl SystemDoraCli {
+    /// Get a shared instance
+    pub fn shared() -> Arc<dyn DoraCli> {
+        Arc::new(SystemDoraCli)
+    }
+}
+
+impl DoraCli for SystemDoraCli {
+    fn run(&self, command: &DoraCommand) -> io::Result<DoraOutput> {
+        let mut cmd = Command::new("dora");
+        cmd.args(&command.args).stdin(Stdio::null());
+        if let Some(dir) = &command.current_dir {
+            cmd.current_dir(dir);
+        }
+        for (key, value) in &command.env {
+            cmd.env(key, value);
+        }
+
+        let output = cmd.output()?;
+        Ok(DoraOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+/// Scripted stand-in for the `dora` CLI (tests)
+///
+/// Responses are matched by argument prefix, most recent first. Unmatched
+/// commands succeed with empty output.
+///
+/// ```rust,ignore
+/// let cli = FakeDoraCli::new();
+/// cli.respond(&["start"], "dataflow started: 01234567-89ab-cdef-0123-456789abcdef");
+/// cli.respond(&["node", "list"], "NODE  STATUS\ntts   Running\n");
+/// controller.set_cli(cli.clone());
+/// ```
+#[derive(Default)]
+pub struct FakeDoraCli {
+    responses: Mutex<Vec<(Vec<String>, DoraOutput)>>,
+    calls: Mutex<Vec<DoraCommand>>,
+}
+
+impl FakeDoraCli {
+    /// Create a new fake CLI
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Succeed with `stdout` for commands starting with `args`
+    pub fn respond(&self, args: &[&str], stdout: impl Into<String>) {
+        self.set_response(
+            args,
+            DoraOutput {
+                success: true,
+                stdout: stdout.into(),
+                stderr: String::new(),
+            },
+        );
+    }
+
+    /// Fail with `stderr` for commands starting with `args`
+    pub fn fail(&self, args: &[&str], stderr: impl Into<String>) {
+        self.set_response(
+            args,
+            DoraOutput {
+                success: false,
+                stdout: String::new(),
+                stderr: stderr.into(),
+            },
+        );
+    }
+
+    /// Set the full output for commands starting with `args`
+    pub fn set_response(&self, args: &[&str], output: DoraOutput) {
+        let prefix = args.iter().map(|a| a.to_string()).collect();
+        self.responses.lock().push((prefix, output));
+    }
+
+    /// Arguments of every command run so far
+    pub fn calls(&self) -> Vec<Vec<String>> {
+        self.calls.lock().iter().map(|c| c.args.clone()).collect()
+    }
+}
+
+impl DoraCli for FakeDoraCli {
+    fn run(&self, command: &DoraCommand) -> io::Result<DoraOutput> {
+        self.calls.lock().push(command.clone());
+        let output = self
+            .responses
+            .lock()
+            .iter()
+            .rev()
+            .find(|(prefix, _)| command.args.starts_with(prefix))
+            .map(|(_, output)| output.clone())
+            .unwrap_or(DoraOutput {
+                success: true,
+                ..Default::default()
+            });
+        Ok(output)
+    }
+}
+
+/// Runtime state of a single node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeState {
+    /// Spawned but not running yet
+    Starting,
+    /// Running normally
+    Running,
+    /// Being restarted after exiting
+    Restarting,
+    /// Exited successfully
+    Finished,
+    /// Exited with an error
+    Failed {
+        /// Exit code, if dora reported one
+        exit_code: Option<i32>,
+    },
+    /// Not listed by dora (e.g. dynamic node not connected) or unrecognized
+    Unknown,
+}
+
+impl NodeState {
+    /// Whether the node is running (or about to be)
+    pub fn is_alive(&self) -> bool {
+        matches!(
+            self,
+            NodeState::Starting | NodeState::Running | NodeState::Restarting
+        )
+    }
+
+    /// Whether the node exited with an error
+    pub fn is_failed(&self) -> bool {
+        matches!(self, NodeState::Failed { .. })
+    }
+}
+
+/// Runtime status of a node, keyed by [`ParsedNode::id`](crate::ParsedNode::id)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeStatus {
+    /// Node ID from the dataflow
+    pub node_id: String,
+    /// Current state
+    pub state: NodeState,
+    /// How many times dora restarted the node
+    pub restarts: u32,
+}
+
+impl NodeStatus {
+    /// Status for a node dora didn't report
+    pub fn unknown(node_id: impl Into<String>) -> Self {
+        Self {
+            node_id: node_id.into(),
+            state: NodeState::Unknown,
+            restarts: 0,
+        }
+    }
+}
+
+/// Parse `dora node list` output
+///
+/// Returns an empty list if the header has no node or status column.
+pub fn parse_node_list(output: &str) -> Vec<NodeStatus> {
+    let Some(table) = Table::parse(output) else {
+        return Vec::new();
+    };
+    let (Some(node_col), Some(status_col)) = (
+        table.column(&["NODE", "ID", "NAME"]),
+        table.column(&["STATUS", "STATE"]),
+    ) else {
+        return Vec::new();
+    };
+    let restarts_col = table.column(&["RESTARTS", "RESTART"]);
+    let exit_col = table.column(&["EXIT CODE", "EXIT", "CODE"]);
+
+    table
+        .rows
+        .iter()
+        .filter_map(|line| {
+            let node_id = table.cell(line, node_col);
+            if node_id.is_empty() {
+                return None;
+            }
+            let status = table.cell(line, status_col);
+            let exit_code = exit_col
+                .and_then(|i| table.cell(line, i).parse().ok())
+                .or_else(|| trailing_int(&status));
+            let restarts = restarts_col
+                .and_then(|i| table.cell(line, i).parse().ok())
+                .unwrap_or(0);
+
+            Some(NodeStatus {
+                node_id,
+                state: parse_state(&status, exit_code),
+                restarts,
+            })
+        })
+        .collect()
+}
+
+/// A dataflow reported by `dora list`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataflowEntry {
+    /// Dataflow UUID
+    pub id: String,
+    /// Dataflow name (may be empty)
+    pub name: String,
+    /// Dataflow status (`Succeeded` maps to [`NodeState::Finished`])
+    pub state: NodeState,
+}
+
+/// Parse `dora list` output
+///
+/// Returns an empty list if the header has no UUID column. Without a status
+/// column every dataflow is [`NodeState::Unknown`].
+pub fn parse_dataflow_list(output: &str) -> Vec<DataflowEntry> {
+    let Some(table) = Table::parse(output) else {
+        return Vec::new();
+    };
+    let Some(id_col) = table.column(&["UUID", "ID"]) else {
+        return Vec::new();
+    };
+    let name_col = table.column(&["NAME"]);
+    let status_col = table.column(&["STATUS", "STATE"]);
+
+    table
+        .rows
+        .iter()
+        .filter_map(|line| {
+            let id = table.cell(line, id_col);
+            if id.is_empty() {
+                return None;
+            }
+            Some(DataflowEntry {
+                id,
+                name: name_col.map(|i| table.cell(line, i)).unwrap_or_default(),
+                state: status_col
+                    .map(|i| parse_state(&table.cell(line, i), None))
+                    .unwrap_or(NodeState::Unknown),
+            })
+        })
+        .collect()
+}
+
+/// Column-aligned table printed by the dora CLI
+struct Table<'a> {
+    /// Start offset and name of each header column
+    columns: Vec<(usize, String)>,
+    /// Non-empty lines after the header
+    rows: Vec<&'a str>,
+}
+
+impl<'a> Table<'a> {
+    fn parse(output: &'a str) -> Option<Self> {
+        let mut lines = output.lines().filter(|l| !l.trim().is_empty());
+        let columns = column_starts(lines.next()?);
+        Some(Self {
+            columns,
+            rows: lines.collect(),
+        })
+    }
+
+    /// Index of the first column with one of `names`
+    fn column(&self, names: &[&str]) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|(_, name)| names.contains(&name.as_str()))
+    }
+
+    /// Trimmed text of column `index` in `line`
+    fn cell(&self, line: &str, index: usize) -> String {
+        let start = self.columns[index].0;
+        let end = self
+            .columns
+            .get(index + 1)
+            .map(|c| c.0)
+            .unwrap_or(usize::MAX);
+        let end = end.min(line.len());
+        line.get(start.min(end)..end)
+            .unwrap_or("")
+            .trim()
+            .to_string()
+    }
+}
+
+/// Map a status cell to a [`NodeState`]
+fn parse_state(status: &str, exit_code: Option<i32>) -> NodeState {
+    let status = status.to_lowercase();
+    let word = status
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or("");
+
+    match word {
+        "running" | "ready" => NodeState::Running,
+        "starting" | "spawning" | "spawned" | "pending" => NodeState::Starting,
+        "restarting" | "restarted" => NodeState::Restarting,
+        "finished" | "succeeded" | "done" | "stopped" => NodeState::Finished,
+        "exited" if exit_code.unwrap_or(0) == 0 => NodeState::Finished,
+        "failed" | "crashed" | "error" | "exited" | "killed" => NodeState::Failed { exit_code },
+        _ => NodeState::Unknown,
+    }
+}
+
+/// Last integer in a string (e.g. "Failed (exit code 1)" -> 1)
+fn trailing_int(s: &str) -> Option<i32> {
+    s.split(|c: char| !(c.is_ascii_digit() || c == '-'))
+        .filter(|part| !part.is_empty())
+        .filter_map(|part| part.parse().ok())
+        .next_back()
+}
+
+/// Start offset and name of each header column
+///
+/// Column names may contain single spaces ("EXIT CODE"); columns are
+/// separated by two or more spaces or a tab.
+fn column_starts(header: &str) -> Vec<(usize, String)> {
+    let mut columns = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut gap = 2;
+
+    for (i, c) in header.char_indices() {
+        if c == ' ' || c == '\t' {
+            gap += if c == '\t' { 2 } else { 1 };
+            continue;
+        }
+        if gap >= 2 {
+            if let Some(s) = start {
+                columns.push((s, header[s..i].trim().to_uppercase()));
+            }
+            start = Some(i);
+        }
+        gap = 0;
+    }
+    if let Some(s) = start {
+        columns.push((s, header[s..].trim().to_uppercase()));
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_node_list() {
+        let output = "\
+NODE                   STATUS                 PID     RESTARTS  EXIT CODE
+primespeech-student1   Failed                 -       0         1
+student1               Running                41233   2         -
+asr                    Finished               -       0         0
+mofa-audio-player      Pending                -       0         -
+";
+        let nodes = parse_node_list(output);
+        assert_eq!(nodes.len(), 4);
+        assert_eq!(nodes[0].node_id, "primespeech-student1");
+        assert_eq!(nodes[0].state, NodeState::Failed { exit_code: Some(1) });
+        assert_eq!(nodes[1].state, NodeState::Running);
+        assert_eq!(nodes[1].restarts, 2);
+        assert_eq!(nodes[2].state, NodeState::Finished);
+        assert_eq!(nodes[3].state, NodeState::Starting);
+
+        // Minimal table with the exit code embedded in the status
+        let output = "ID   STATE\ntts  Failed (exit code 137)\nllm  Restarting\n";
+        let nodes = parse_node_list(output);
+        assert_eq!(
+            nodes[0].state,
+            NodeState::Failed {
+                exit_code: Some(137)
+            }
+        );
+        assert_eq!(nodes[1].state, NodeState::Restarting);
+
+        assert!(parse_node_list("No running dataflows\n").is_empty());
+        assert!(parse_node_list("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_dataflow_list() {
+        let output = "\
+UUID                                  Name        Status     Nodes  CPU    Memory
+0190a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b  voice-chat  Running    5      3.10%  0.42 GB
+0190a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5c              Failed     2      0.00%  0.00 GB
+0190a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5d  debate      Succeeded  3      0.00%  0.00 GB
+";
+        let dataflows = parse_dataflow_list(output);
+        assert_eq!(dataflows.len(), 3);
+        assert_eq!(dataflows[0].id, "0190a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b");
+        assert_eq!(dataflows[0].name, "voice-chat");
+        assert_eq!(dataflows[0].state, NodeState::Running);
+        assert_eq!(dataflows[1].name, "");
+        assert_eq!(dataflows[1].state, NodeState::Failed { exit_code: None });
+        assert_eq!(dataflows[2].state, NodeState::Finished);
+
+        // Header without a status column
+        let dataflows = parse_dataflow_list("UUID  NAME\nabc   voice\n");
+        assert_eq!(dataflows[0].state, NodeState::Unknown);
+        assert!(parse_dataflow_list("No running dataflows\n").is_empty());
+    }
+
+    #[test]
+    fn test_fake_cli_matches_latest_prefix() {
+        let cli = FakeDoraCli::new();
+        cli.respond(&["list"], "old");
+        cli.respond(&["list"], "new");
+        cli.fail(&["node", "list"], "unknown command");
+
+        let out = cli.run(&DoraCommand::new(["list"])).unwrap();
+        assert_eq!(out.stdout, "new");
+        let out = cli
+            .run(&DoraCommand::new(["node", "list", "--dataflow", "x"]))
+            .unwrap();
+        assert!(!out.success);
+        assert!(cli.run(&DoraCommand::new(["up"])).unwrap().success);
+        assert_eq!(cli.calls().len(), 3);
+    }
+}
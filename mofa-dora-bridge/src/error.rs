@@ -32,6 +32,9 @@ pub enum BridgeError {
     #[error("Failed to parse dataflow: {0}")]
     ParseError(String),
 
+    #[error("Invalid dataflow: {0}")]
+    ValidationFailed(String),
+
     #[error("Node not found: {0}")]
     NodeNotFound(String),
 
@@ -40,19 +40,38 @@
 //! - [`AudioState`] - Ring buffer for audio chunks (consumed by audio player)
 //! - [`DirtyVec`] - Generic dirty-trackable collection
 //! - [`DirtyValue`] - Generic dirty-trackable single value
+//! - [`StateSubscription`] - Opt-in [`StateChange`] events for consumers without a UI timer
 //!
 //! ### Data Types ([`data`] module)
 //!
 //! - [`AudioData`] - Audio samples with metadata (participant_id, question_id)
 //! - [`ChatMessage`] - Chat message with sender, role, streaming status
 //! - [`LogEntry`] - Log entry with level, node_id, timestamp
-//! - [`ControlCommand`] - Dataflow control commands (start, stop, reset)
+//! - [`ControlMessage`] - Typed control messages shared with node-hub nodes (reset, cancel, prompt, ...)
 //!
 //! ### Bridge Infrastructure
 //!
 //! - [`DoraBridge`] trait - Interface for widget bridges
 //! - [`BridgeState`] - Connection state (Disconnected, Connecting, Connected, Error)
 //! - [`MofaNodeType`] - Enum of known widget node types
+//! - [`BridgeRegistry`] - Node-ID patterns to bridge factories, for custom bridges
+//! - [`TransportFactory`] - How bridges reach dora; [`MockDoraRuntime`] for tests
+//! - [`BridgeSupervisor`] - Reconnects failed bridges per [`ReconnectPolicy`]
+//!
+//! ### Dataflow Status ([`dora_cli`] module)
+//!
+//! - [`DataflowController::node_statuses`] - Per-node [`NodeStatus`] parsed from the dora CLI
+//! - [`DoraCli`] - How the controller runs `dora`; [`FakeDoraCli`] for tests
+//!
+//! ### Startup ([`readiness`] module)
+//!
+//! - [`StartupConfig`] - Backoff, deadline and optional ready gate for `start()`
+//! - [`StartupPhase`] - Per-node progress in [`DoraStatus::startup`]
+//!
+//! ### Dataflow Validation ([`validator`] module)
+//!
+//! - [`ParsedDataflow::validate`] - Lint a dataflow without starting dora
+//! - [`DataflowDiagnostic`] - Finding with [`Severity`], node ID and message
 //!
 //! ## Usage Example
 //!
@@ -108,22 +127,34 @@ pub mod bridge;
 pub mod controller;
 pub mod data;
 pub mod dispatcher;
+pub mod dora_cli;
 pub mod error;
 pub mod parser;
+pub mod readiness;
+pub mod registry;
 pub mod shared_state;
+pub mod supervisor;
+pub mod transport;
+pub mod validator;
 
 // Widget-specific bridges
 pub mod widgets;
 
 // Re-exports
 pub use bridge::{BridgeState, DoraBridge};
-pub use controller::{DataflowController, DataflowState};
-pub use data::{AudioData, ChatMessage, ControlCommand, DoraData, LogEntry};
+pub use controller::{DataflowController, DataflowState, DataflowStatus};
+pub use dora_cli::{DoraCli, FakeDoraCli, NodeState, NodeStatus};
+pub use data::{AudioData, ChatMessage, ControlMessage, DoraData, LogEntry};
 pub use dispatcher::{DynamicNodeDispatcher, WidgetBinding};
 pub use error::{BridgeError, BridgeResult};
-pub use shared_state::{SharedDoraState, DoraStatus, ChatState, AudioState, DirtyVec, DirtyValue, MicState};
+pub use shared_state::{SharedDoraState, DoraStatus, StartupPhase, ReconnectStatus, ReconnectOutcome, ChatState, AudioState, DirtyVec, DirtyValue, MicState, MicChange, StateChange, StateSubscription};
 pub use widgets::AecControlCommand;
 pub use parser::{DataflowParser, EnvRequirement, LogSource, ParsedDataflow, ParsedNode};
+pub use readiness::{Backoff, StartupConfig};
+pub use registry::{BridgeFactory, BridgeRegistry};
+pub use supervisor::{BridgeSupervisor, ReconnectPolicy};
+pub use transport::{BridgeEvent, DoraTransportFactory, MockDoraRuntime, NodeTransport, TransportFactory};
+pub use validator::{DataflowDiagnostic, DiagnosticKind, Severity};
 
 /// Prefix for MoFA built-in dynamic nodes in dataflow YAML
 pub const MOFA_NODE_PREFIX: &str = "mofa-";
@@ -148,6 +179,44 @@ pub enum MofaNodeType {
 }
 
 impl MofaNodeType {
+    /// All known widget node types, in declaration order
+    pub const ALL: [MofaNodeType; Self::COUNT] = {
+        let mut all = [MofaNodeType::AudioPlayer; Self::COUNT];
+        let mut i = 1;
+        while i < Self::COUNT {
+            all[i] = match all[i - 1].next() {
+                Some(next) => next,
+                None => unreachable!(),
+            };
+            i += 1;
+        }
+        all
+    };
+
+    const COUNT: usize = {
+        let mut count = 1;
+        let mut node_type = MofaNodeType::AudioPlayer;
+        while let Some(next) = node_type.next() {
+            node_type = next;
+            count += 1;
+        }
+        count
+    };
+
+    /// The variant declared after this one. `ALL` is built from this chain,
+    /// so a new variant does not compile until it is linked in here.
+    const fn next(self) -> Option<Self> {
+        match self {
+            MofaNodeType::AudioPlayer => Some(MofaNodeType::SystemLog),
+            MofaNodeType::SystemLog => Some(MofaNodeType::PromptInput),
+            MofaNodeType::PromptInput => Some(MofaNodeType::MicInput),
+            MofaNodeType::MicInput => Some(MofaNodeType::ChatViewer),
+            MofaNodeType::ChatViewer => Some(MofaNodeType::ParticipantPanel),
+            MofaNodeType::ParticipantPanel => Some(MofaNodeType::MoFACast),
+            MofaNodeType::MoFACast => None,
+        }
+    }
+
     /// Get the node ID for this widget type
     pub fn node_id(&self) -> &'static str {
         match self {
@@ -161,17 +230,9 @@ impl MofaNodeType {
         }
     }
 
-    /// Parse node type from node ID
+    /// Parse node type from node ID (inverse of [`MofaNodeType::node_id`])
     pub fn from_node_id(node_id: &str) -> Option<Self> {
-        match node_id {
-            "mofa-audio-player" => Some(MofaNodeType::AudioPlayer),
-            "mofa-system-log" => Some(MofaNodeType::SystemLog),
-            "mofa-prompt-input" => Some(MofaNodeType::PromptInput),
-            "mofa-mic-input" => Some(MofaNodeType::MicInput),
-            "mofa-chat-viewer" => Some(MofaNodeType::ChatViewer),
-            "mofa-participant-panel" => Some(MofaNodeType::ParticipantPanel),
-            _ => None,
-        }
+        Self::ALL.into_iter().find(|t| t.node_id() == node_id)
     }
 
     /// Check if a node ID is a MoFA widget node
@@ -179,3 +240,25 @@ impl MofaNodeType {
         node_id.starts_with(MOFA_NODE_PREFIX)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_type_round_trip() {
+        for node_type in MofaNodeType::ALL {
+            assert_eq!(MofaNodeType::from_node_id(node_type.node_id()), Some(node_type));
+            assert!(MofaNodeType::is_mofa_node(node_type.node_id()));
+        }
+        assert_eq!(
+            MofaNodeType::from_node_id("mofa-cast-controller"),
+            Some(MofaNodeType::MoFACast)
+        );
+        assert_eq!(MofaNodeType::from_node_id("mofa-translation-overlay"), None);
+
+        let distinct: std::collections::HashSet<_> = MofaNodeType::ALL.into_iter().collect();
+        assert_eq!(distinct.len(), MofaNodeType::ALL.len());
+        assert_eq!(MofaNodeType::ALL[0], MofaNodeType::AudioPlayer);
+    }
+}
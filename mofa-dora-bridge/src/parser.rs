@@ -71,6 +71,11 @@ pub enum NodeKind {
         source: String,
         args: Option<String>,
     },
+    /// Standalone node executable or package (`path: ...`)
+    Executable {
+        path: String,
+        args: Option<String>,
+    },
     /// Dynamic node (connected at runtime)
     Dynamic,
 }
@@ -89,6 +94,9 @@ pub struct InputDef {
 pub struct EnvRequirement {
     /// Variable name (e.g., "OPENAI_API_KEY")
     pub key: String,
+    /// Variable the value references, if it is a placeholder
+    /// (`VOICE` for `${VOICE:-Doubao}`)
+    pub variable: Option<String>,
     /// Human-readable description
     pub description: String,
     /// Whether this variable is required
@@ -216,8 +224,19 @@ impl DataflowParser {
                 .and_then(|a| a.as_str())
                 .map(|s| s.to_string());
             NodeKind::Custom { source, args }
-        } else if value.get("path").and_then(|p| p.as_str()) == Some("dynamic") {
-            NodeKind::Dynamic
+        } else if let Some(path) = value.get("path").and_then(|p| p.as_str()) {
+            if path == "dynamic" {
+                NodeKind::Dynamic
+            } else {
+                let args = value
+                    .get("args")
+                    .and_then(|a| a.as_str())
+                    .map(|s| s.to_string());
+                NodeKind::Executable {
+                    path: path.to_string(),
+                    args,
+                }
+            }
         } else {
             return None;
         };
@@ -320,36 +339,27 @@ impl DataflowParser {
             || key.to_uppercase().contains("PASSWORD")
             || key.to_uppercase().contains("TOKEN");
 
-        // Parse placeholder syntax: ${VAR}, ${VAR:-default}, or $VAR
-        let (is_placeholder, has_default, default_value) =
-            if value.starts_with("${") && value.ends_with("}") {
-                // ${VAR} or ${VAR:-default}
-                let inner = &value[2..value.len() - 1];
-                if let Some(pos) = inner.find(":-") {
-                    // ${VAR:-default} - has a default value
-                    let default = inner[pos + 2..].to_string();
-                    (true, true, Some(default))
-                } else {
-                    // ${VAR} - no default, required
-                    (true, false, None)
-                }
-            } else if value.starts_with("$") {
-                // $VAR - no default, required
-                (true, false, None)
-            } else {
-                // Literal value
-                (false, false, Some(value.clone()))
-            };
-
         // Only required if it's a placeholder WITHOUT a default
-        let required = is_placeholder && !has_default;
+        let (variable, required, default_value) = match parse_placeholder(&value) {
+            Some((variable, default)) => (
+                Some(variable.to_string()),
+                default.is_none(),
+                default.map(str::to_string),
+            ),
+            None => (None, false, Some(value.clone())),
+        };
 
-        // Find existing or create new
-        if let Some(existing) = requirements.iter_mut().find(|r| r.key == key) {
+        // Find existing or create new; the same key referencing another
+        // variable is a separate requirement
+        if let Some(existing) = requirements
+            .iter_mut()
+            .find(|r| r.key == key && r.variable == variable)
+        {
             existing.used_by.push(node_id);
         } else {
             requirements.push(EnvRequirement {
                 key,
+                variable,
                 description: String::new(),
                 required,
                 default: default_value,
@@ -360,6 +370,23 @@ impl DataflowParser {
     }
 }
 
+/// Parse `${VAR}`, `${VAR:-default}` or `$VAR...` into the variable name and default
+pub(crate) fn parse_placeholder(value: &str) -> Option<(&str, Option<&str>)> {
+    if let Some(inner) = value.strip_prefix("${").and_then(|v| v.strip_suffix('}')) {
+        match inner.split_once(":-") {
+            Some((name, default)) => Some((name, Some(default))),
+            None => Some((inner, None)),
+        }
+    } else {
+        // `$HOME/.dora/models` only references `HOME`
+        let rest = value.strip_prefix('$')?;
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        (end > 0).then_some((&rest[..end], None))
+    }
+}
+
 impl ParsedDataflow {
     /// Get all MoFA node IDs
     pub fn mofa_node_ids(&self) -> Vec<&str> {
@@ -443,4 +470,74 @@ nodes:
         assert_eq!(parsed.log_sources[1].node_id, "mofa-audio-player");
         assert_eq!(parsed.log_sources[1].output_id, "buffer_status");
     }
+
+    #[test]
+    fn test_parse_executable_nodes() {
+        let yaml = r#"
+nodes:
+  - id: asr
+    path: dora-asr
+    args: --model paraformer
+    outputs:
+      - text
+      - asr_log
+    env:
+      MODEL_DIR: $HOME/.dora/models
+      LANGUAGE: zh
+
+  - id: llm
+    path: ../../target/release/dora-maas-client
+    inputs:
+      text: asr/text
+    outputs:
+      - text
+    env:
+      API_KEY: ${OPENAI_API_KEY}
+      MODEL: ${MODEL:-gpt-4o}
+"#;
+
+        let parsed = DataflowParser::parse_string(yaml, PathBuf::from("test.yml")).unwrap();
+
+        assert_eq!(parsed.nodes.len(), 2);
+        assert!(matches!(
+            &parsed.nodes[0].kind,
+            NodeKind::Executable { path, args }
+                if path == "dora-asr" && args.as_deref() == Some("--model paraformer")
+        ));
+        assert!(!parsed.nodes[1].is_dynamic);
+        assert!(parsed.mofa_nodes.is_empty());
+
+        assert_eq!(parsed.log_sources.len(), 1);
+        assert_eq!(parsed.log_sources[0].node_id, "asr");
+        assert_eq!(parsed.log_sources[0].output_id, "asr_log");
+
+        // Node env is a map, so requirements come in no particular order
+        let mut env: Vec<_> = parsed
+            .env_requirements
+            .iter()
+            .map(|r| (r.key.as_str(), r.variable.as_deref(), r.required))
+            .collect();
+        env.sort();
+        assert_eq!(
+            env,
+            [
+                ("API_KEY", Some("OPENAI_API_KEY"), true),
+                ("LANGUAGE", None, false),
+                ("MODEL", Some("MODEL"), false),
+                ("MODEL_DIR", Some("HOME"), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_placeholder() {
+        assert_eq!(parse_placeholder("${KEY}"), Some(("KEY", None)));
+        assert_eq!(parse_placeholder("${KEY:-x}"), Some(("KEY", Some("x"))));
+        assert_eq!(parse_placeholder("$KEY"), Some(("KEY", None)));
+        assert_eq!(
+            parse_placeholder("$HOME/.dora/models"),
+            Some(("HOME", None))
+        );
+        assert_eq!(parse_placeholder("literal"), None);
+    }
 }
@@ -0,0 +1,419 @@
+//! Readiness-based dataflow startup
+//!
+//! `dora start --detach` returns before nodes are up, and model-loading nodes
+//! (TTS, ASR) can take a while longer. Instead of sleeping for fixed
+//! durations, the dispatcher polls with exponential [`Backoff`] under one
+//! overall deadline from [`StartupConfig`]:
+//!
+//! 1. Wait until `dora list` shows the dataflow
+//! 2. Optionally wait for named nodes to send `ready` on their status output
+//! 3. Connect bridges, retrying the ones whose dynamic node isn't up yet
+//!
+//! Each step is reported per node as a [`StartupPhase`] in
+//! [`DoraStatus::startup`](crate::DoraStatus::startup).
+//!
+//! # Ready Gate
+//!
+//! Ready statuses are received by a dedicated dynamic node (the probe), which
+//! must be declared in the dataflow with an input from each gated node:
+//!
+//! ```yaml
+//! - id: mofa-readiness
+//!   path: dynamic
+//!   inputs:
+//!     tts_status: primespeech-tutor/status
+//!     asr_status: asr/status
+//! ```
+//!
+//! ```rust,ignore
+//! let startup = StartupConfig::default()
+//!     .wait_for_ready(["primespeech-tutor", "asr"]);
+//! dispatcher.set_startup_config(startup);
+//! dispatcher.start()?;
+//! ```
+
+use crate::error::{BridgeError, BridgeResult};
+use crate::parser::ParsedDataflow;
+use crate::shared_state::{SharedDoraState, StartupPhase};
+use crate::transport::{BridgeEvent, TransportFactory};
+use arrow::array::{Array, ArrayRef};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Default ID of the dynamic node receiving ready statuses
+pub const DEFAULT_READY_PROBE: &str = "mofa-readiness";
+
+/// Startup polling and readiness configuration
+#[derive(Debug, Clone)]
+pub struct StartupConfig {
+    /// First delay between polls
+    pub initial_poll: Duration,
+    /// Upper bound for the (doubling) delay between polls
+    pub max_poll: Duration,
+    /// Overall deadline for the whole startup
+    pub timeout: Duration,
+    /// Nodes that must report ready before bridges connect
+    pub ready_nodes: Vec<String>,
+    /// Dynamic node that receives the ready statuses
+    pub ready_probe: String,
+    /// Output carrying the status (e.g., "status")
+    pub ready_output: String,
+    /// Status value meaning ready (compared case-insensitively)
+    pub ready_value: String,
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        Self {
+            initial_poll: Duration::from_millis(200),
+            max_poll: Duration::from_secs(2),
+            timeout: Duration::from_secs(60),
+            ready_nodes: Vec::new(),
+            ready_probe: DEFAULT_READY_PROBE.to_string(),
+            ready_output: "status".to_string(),
+            ready_value: "ready".to_string(),
+        }
+    }
+}
+
+impl StartupConfig {
+    /// Gate bridge connection on these nodes reporting ready
+    pub fn wait_for_ready<I, S>(mut self, nodes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.ready_nodes.extend(nodes.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the overall startup deadline
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Create a backoff for one startup step, bounded by `deadline`
+    pub fn backoff(&self, deadline: Instant) -> Backoff {
+        Backoff::new(self.initial_poll, self.max_poll, deadline)
+    }
+}
+
+/// Exponential backoff with a deadline
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    next: Duration,
+    max: Duration,
+    deadline: Instant,
+    started: Instant,
+}
+
+impl Backoff {
+    /// Create a backoff starting at `initial`, doubling up to `max`
+    pub fn new(initial: Duration, max: Duration, deadline: Instant) -> Self {
+        Self {
+            next: initial,
+            max: max.max(initial),
+            deadline,
+            started: Instant::now(),
+        }
+    }
+
+    /// Time left until the deadline
+    pub fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+
+    /// Whether the deadline has passed
+    pub fn expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Next delay (capped by the deadline), advancing the backoff
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.next.min(self.remaining());
+        self.next = (self.next * 2).min(self.max);
+        delay
+    }
+
+    /// Call `check` until it returns `Some`, sleeping between attempts
+    ///
+    /// Fails with [`BridgeError::Timeout`] once the deadline passes.
+    pub fn poll<T>(&mut self, what: &str, mut check: impl FnMut() -> Option<T>) -> BridgeResult<T> {
+        loop {
+            if let Some(value) = check() {
+                return Ok(value);
+            }
+            if self.expired() {
+                return Err(BridgeError::Timeout(format!(
+                    "{} (gave up after {:?})",
+                    what,
+                    self.started.elapsed()
+                )));
+            }
+            let delay = self.next_delay();
+            debug!("{}: retrying in {:?}", what, delay);
+            std::thread::sleep(delay);
+        }
+    }
+}
+
+/// Wait for every node in [`StartupConfig::ready_nodes`] to report ready
+///
+/// Connects to the probe node through `transport` and watches its inputs.
+/// Returns immediately if no nodes are gated.
+pub fn wait_for_ready(
+    transport: &dyn TransportFactory,
+    parsed: &ParsedDataflow,
+    config: &StartupConfig,
+    deadline: Instant,
+    shared_state: &SharedDoraState,
+) -> BridgeResult<()> {
+    if config.ready_nodes.is_empty() {
+        return Ok(());
+    }
+
+    let inputs = probe_inputs(parsed, config)?;
+    let mut pending: Vec<&str> = config.ready_nodes.iter().map(String::as_str).collect();
+    for node_id in &pending {
+        shared_state.set_startup_phase(node_id, StartupPhase::WaitingForReady);
+    }
+
+    // The probe's dynamic node may not be registered with the daemon yet
+    let mut backoff = config.backoff(deadline);
+    let connected = backoff.poll("Readiness probe did not connect", || {
+        match transport.connect(&config.ready_probe) {
+            Ok(node) => Some(node),
+            Err(e) => {
+                debug!(
+                    "Readiness probe '{}' not available: {}",
+                    config.ready_probe, e
+                );
+                None
+            }
+        }
+    });
+    let mut probe = match connected {
+        Ok(probe) => probe,
+        Err(e) => {
+            fail_pending(shared_state, &pending, "readiness probe unavailable");
+            return Err(e);
+        }
+    };
+    info!(
+        "Waiting for {} node(s) to report ready: {:?}",
+        pending.len(),
+        pending
+    );
+
+    while !pending.is_empty() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            let msg = format!("Nodes not ready before deadline: {}", pending.join(", "));
+            fail_pending(shared_state, &pending, "not ready before deadline");
+            return Err(BridgeError::Timeout(msg));
+        }
+
+        match probe.recv_timeout(remaining.min(Duration::from_millis(100))) {
+            Some(BridgeEvent::Input { id, data, metadata }) => {
+                let Some(&node_id) = inputs.get(id.as_str()) else {
+                    continue;
+                };
+                let status = metadata
+                    .get("status")
+                    .map(str::to_string)
+                    .or_else(|| extract_string(&data));
+                if status.is_some_and(|s| s.eq_ignore_ascii_case(&config.ready_value)) {
+                    info!("Node '{}' is ready", node_id);
+                    pending.retain(|n| *n != node_id);
+                    shared_state.set_startup_phase(node_id, StartupPhase::Ready);
+                }
+            }
+            Some(BridgeEvent::InputClosed { id }) => {
+                if let Some(&node_id) = inputs.get(id.as_str()) {
+                    if pending.contains(&node_id) {
+                        let msg = format!("Node '{}' exited before reporting ready", node_id);
+                        warn!("{}", msg);
+                        shared_state.set_startup_phase(
+                            node_id,
+                            StartupPhase::Failed("exited before ready".to_string()),
+                        );
+                        return Err(BridgeError::StartFailed(msg));
+                    }
+                }
+            }
+            Some(BridgeEvent::Stop | BridgeEvent::Closed) => {
+                fail_pending(shared_state, &pending, "dataflow stopped");
+                return Err(BridgeError::DataflowNotRunning);
+            }
+            // Real runtime errors only: receive timeouts come back as `None`
+            Some(BridgeEvent::Error(e)) => {
+                warn!("Readiness probe error: {}", e);
+            }
+            None => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Map probe input IDs to the gated node feeding them
+fn probe_inputs<'a>(
+    parsed: &'a ParsedDataflow,
+    config: &'a StartupConfig,
+) -> BridgeResult<HashMap<&'a str, &'a str>> {
+    let probe = parsed.get_node(&config.ready_probe).ok_or_else(|| {
+        BridgeError::ValidationFailed(format!(
+            "readiness probe node '{}' is not in the dataflow",
+            config.ready_probe
+        ))
+    })?;
+
+    let mut inputs = HashMap::new();
+    for node_id in &config.ready_nodes {
+        let source = format!("{}/{}", node_id, config.ready_output);
+        let input = probe
+            .inputs
+            .iter()
+            .find(|input| input.source == source)
+            .ok_or_else(|| {
+                BridgeError::ValidationFailed(format!(
+                    "readiness probe '{}' has no input from '{}'",
+                    config.ready_probe, source
+                ))
+            })?;
+        inputs.insert(input.id.as_str(), node_id.as_str());
+    }
+    Ok(inputs)
+}
+
+/// Mark nodes that never became ready as failed
+fn fail_pending(shared_state: &SharedDoraState, nodes: &[&str], reason: &str) {
+    for node_id in nodes {
+        shared_state.set_startup_phase(node_id, StartupPhase::Failed(reason.to_string()));
+    }
+}
+
+/// Extract the first string from arrow data
+fn extract_string(data: &ArrayRef) -> Option<String> {
+    let array = data.as_any().downcast_ref::<arrow::array::StringArray>()?;
+    (array.len() > 0).then(|| array.value(0).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::DataflowParser;
+    use crate::transport::MockDoraRuntime;
+    use arrow::array::StringArray;
+    use std::path::PathBuf;
+
+    const YAML: &str = r#"
+nodes:
+  - id: tts
+    path: tts.py
+    outputs:
+      - audio
+      - status
+  - id: asr
+    path: asr.py
+    outputs:
+      - status
+  - id: mofa-readiness
+    path: dynamic
+    inputs:
+      tts_status: tts/status
+      asr_status: asr/status
+"#;
+
+    fn parsed() -> ParsedDataflow {
+        DataflowParser::parse_string(YAML, PathBuf::from("test.yml")).unwrap()
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_times_out() {
+        let deadline = Instant::now() + Duration::from_millis(30);
+        let mut backoff =
+            Backoff::new(Duration::from_millis(1), Duration::from_millis(4), deadline);
+        assert_eq!(backoff.next_delay(), Duration::from_millis(1));
+        assert_eq!(backoff.next_delay(), Duration::from_millis(2));
+        assert_eq!(backoff.next_delay(), Duration::from_millis(4));
+        assert_eq!(backoff.next_delay(), Duration::from_millis(4));
+
+        let mut attempts = 0;
+        let result: BridgeResult<()> = backoff.poll("never", || {
+            attempts += 1;
+            None
+        });
+        assert!(matches!(result, Err(BridgeError::Timeout(_))));
+        assert!(attempts > 1);
+
+        let mut backoff = StartupConfig::default().backoff(Instant::now() + Duration::from_secs(1));
+        let mut calls = 0;
+        assert_eq!(
+            backoff
+                .poll("third time", || {
+                    calls += 1;
+                    (calls == 3).then_some(calls)
+                })
+                .unwrap(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_wait_for_ready_gates_on_status() {
+        let runtime = MockDoraRuntime::new();
+        let state = SharedDoraState::new();
+        let config = StartupConfig::default().wait_for_ready(["tts", "asr"]);
+
+        // Timeouts between status updates, as dora reports an idle probe
+        let probe = runtime.node(DEFAULT_READY_PROBE);
+        probe.inject_input("tts_status", StringArray::from(vec!["loading"]), &[]);
+        probe.inject_timeout();
+        probe.inject_input("tts_status", StringArray::from(vec!["Ready"]), &[]);
+        probe.inject_timeout();
+        probe.inject_input(
+            "asr_status",
+            StringArray::from(vec!["x"]),
+            &[("status", "ready")],
+        );
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        wait_for_ready(runtime.as_ref(), &parsed(), &config, deadline, &state).unwrap();
+        let status = state.status.read();
+        assert_eq!(status.startup.get("tts"), Some(&StartupPhase::Ready));
+        assert_eq!(status.startup.get("asr"), Some(&StartupPhase::Ready));
+        assert!(status.startup_pending().is_empty());
+    }
+
+    #[test]
+    fn test_wait_for_ready_reports_failures() {
+        let runtime = MockDoraRuntime::new();
+        let state = SharedDoraState::new();
+
+        // Probe without an input from the gated node
+        let config = StartupConfig::default().wait_for_ready(["llm"]);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let err = wait_for_ready(runtime.as_ref(), &parsed(), &config, deadline, &state);
+        assert!(matches!(err, Err(BridgeError::ValidationFailed(_))));
+
+        // Deadline passes while a node is still loading
+        let config = StartupConfig::default().wait_for_ready(["tts", "asr"]);
+        runtime.node(DEFAULT_READY_PROBE).inject_input(
+            "tts_status",
+            StringArray::from(vec!["ready"]),
+            &[],
+        );
+        let deadline = Instant::now() + Duration::from_millis(300);
+        let err = wait_for_ready(runtime.as_ref(), &parsed(), &config, deadline, &state);
+        assert!(matches!(err, Err(BridgeError::Timeout(_))));
+        let status = state.status.read();
+        assert_eq!(status.startup.get("tts"), Some(&StartupPhase::Ready));
+        assert!(matches!(
+            status.startup.get("asr"),
+            Some(StartupPhase::Failed(_))
+        ));
+    }
+}
@@ -0,0 +1,178 @@
+//! Bridge registry
+//!
+//! Maps dynamic node IDs to bridge factories so the dispatcher can create
+//! bridges for nodes that aren't part of [`MofaNodeType`]. Patterns are node
+//! IDs with optional `*` wildcards:
+//!
+//! ```rust,ignore
+//! let mut dispatcher = DynamicNodeDispatcher::with_shared_state(controller, state);
+//! dispatcher.register_bridge("mofa-translation-*", |node_id, shared_state| {
+//!     Box::new(TranslationOverlayBridge::new(node_id, shared_state))
+//! });
+//! dispatcher.start()?;
+//! ```
+//!
+//! The most recently registered matching pattern wins, so apps can also
+//! replace a built-in bridge by registering its node ID.
+
+use crate::bridge::DoraBridge;
+use crate::shared_state::SharedDoraState;
+use crate::widgets::{
+    AecInputBridge, AudioPlayerBridge, CastControllerBridge, ChatViewerBridge, PromptInputBridge,
+    SystemLogBridge,
+};
+use crate::MofaNodeType;
+use std::sync::Arc;
+
+/// Creates a bridge for a node ID
+pub type BridgeFactory =
+    Arc<dyn Fn(&str, Arc<SharedDoraState>) -> Box<dyn DoraBridge> + Send + Sync>;
+
+/// Node-ID patterns mapped to bridge factories
+#[derive(Clone, Default)]
+pub struct BridgeRegistry {
+    entries: Vec<(String, BridgeFactory)>,
+}
+
+impl BridgeRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with a factory for every built-in [`MofaNodeType`] bridge
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register(MofaNodeType::AudioPlayer.node_id(), |id, ss| {
+            Box::new(AudioPlayerBridge::with_shared_state(id, Some(ss)))
+        });
+        registry.register(MofaNodeType::SystemLog.node_id(), |id, ss| {
+            Box::new(SystemLogBridge::with_shared_state(id, Some(ss)))
+        });
+        registry.register(MofaNodeType::PromptInput.node_id(), |id, ss| {
+            Box::new(PromptInputBridge::with_shared_state(id, Some(ss)))
+        });
+        registry.register(MofaNodeType::MicInput.node_id(), |id, ss| {
+            Box::new(AecInputBridge::with_shared_state(id, Some(ss)))
+        });
+        registry.register(MofaNodeType::ChatViewer.node_id(), |id, ss| {
+            Box::new(ChatViewerBridge::with_shared_state(id, Some(ss)))
+        });
+        registry.register(MofaNodeType::MoFACast.node_id(), |id, ss| {
+            Box::new(CastControllerBridge::with_shared_state(id, Some(ss)))
+        });
+        // ParticipantPanel functionality is consolidated into AudioPlayerBridge
+        registry
+    }
+
+    /// Register a factory for node IDs matching `pattern`
+    pub fn register<F>(&mut self, pattern: impl Into<String>, factory: F)
+    where
+        F: Fn(&str, Arc<SharedDoraState>) -> Box<dyn DoraBridge> + Send + Sync + 'static,
+    {
+        self.entries.push((pattern.into(), Arc::new(factory)));
+    }
+
+    /// Find the factory for a node ID (latest registration wins)
+    pub fn resolve(&self, node_id: &str) -> Option<&BridgeFactory> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(pattern, _)| pattern_matches(pattern, node_id))
+            .map(|(_, factory)| factory)
+    }
+
+    /// Create a bridge for a node ID, if any pattern matches
+    pub fn create(
+        &self,
+        node_id: &str,
+        shared_state: Arc<SharedDoraState>,
+    ) -> Option<Box<dyn DoraBridge>> {
+        self.resolve(node_id)
+            .map(|factory| factory(node_id, shared_state))
+    }
+
+    /// Registered patterns, in registration order
+    pub fn patterns(&self) -> Vec<&str> {
+        self.entries.iter().map(|(p, _)| p.as_str()).collect()
+    }
+}
+
+impl std::fmt::Debug for BridgeRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BridgeRegistry")
+            .field("patterns", &self.patterns())
+            .finish()
+    }
+}
+
+/// Match a node ID against a pattern where `*` matches any run of characters
+pub fn pattern_matches(pattern: &str, node_id: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // split always yields at least one part
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = node_id.strip_prefix(first) else {
+        return false;
+    };
+
+    let middle: Vec<&str> = parts.collect();
+    let Some((last, middle)) = middle.split_last() else {
+        // No wildcard: exact match
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_matches() {
+        assert!(pattern_matches("mofa-audio-player", "mofa-audio-player"));
+        assert!(!pattern_matches("mofa-audio-player", "mofa-audio-player-2"));
+        assert!(pattern_matches(
+            "mofa-translation-*",
+            "mofa-translation-overlay"
+        ));
+        assert!(pattern_matches("*-overlay", "mofa-translation-overlay"));
+        assert!(pattern_matches(
+            "mofa-*-overlay",
+            "mofa-translation-overlay"
+        ));
+        assert!(!pattern_matches("mofa-*-overlay", "mofa-overlay"));
+        assert!(pattern_matches("*", "anything"));
+    }
+
+    #[test]
+    fn test_latest_registration_wins() {
+        let mut registry = BridgeRegistry::with_builtins();
+        assert!(registry.resolve("mofa-cast-controller").is_some());
+        assert!(registry.resolve("mofa-participant-panel").is_none());
+        assert!(registry.resolve("mofa-translation-overlay").is_none());
+
+        registry.register("mofa-translation-*", |id, ss| {
+            Box::new(ChatViewerBridge::with_shared_state(id, Some(ss)))
+        });
+        registry.register("mofa-audio-player", |id, ss| {
+            Box::new(SystemLogBridge::with_shared_state(id, Some(ss)))
+        });
+
+        let state = SharedDoraState::new();
+        let overlay = registry
+            .create("mofa-translation-overlay", state.clone())
+            .unwrap();
+        assert_eq!(overlay.node_id(), "mofa-translation-overlay");
+
+        // Overridden built-in: system log bridge only expects "log"
+        let player = registry.create("mofa-audio-player", state).unwrap();
+        assert_eq!(player.expected_inputs(), vec!["log".to_string()]);
+    }
+}
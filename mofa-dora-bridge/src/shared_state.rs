@@ -24,6 +24,7 @@
 //! - [`ChatState`] - Chat messages with automatic streaming consolidation
 //! - [`AudioState`] - Ring buffer for audio chunks (producer-consumer pattern)
 //! - [`SharedDoraState`] - Unified container for all Dora↔UI state
+//! - [`StateSubscription`] - Optional change events for consumers without a UI timer
 //!
 //! ## Usage Pattern
 //!
@@ -43,6 +44,25 @@
 //! }
 //! ```
 //!
+//! ## Event-Driven Consumers
+//!
+//! Headless consumers (CLI, test harness, recording sink) can block on changes
+//! instead of polling. Subscribing does not consume dirty flags, so the UI timer
+//! keeps working alongside any number of subscribers:
+//!
+//! ```rust,ignore
+//! let changes = state.subscribe();
+//! while let Some(change) = changes.recv() {
+//!     match change {
+//!         StateChange::Chat(msg) => println!("{}: {}", msg.sender, msg.content),
+//!         StateChange::Log(entry) => println!("[{}] {}", entry.node_id, entry.message),
+//!         _ => {}
+//!     }
+//! }
+//! ```
+//!
+//! Audio is not published; it stays on the [`AudioState`] drain path.
+//!
 //! ## Thread Safety
 //!
 //! All types use `parking_lot::RwLock` for data and `AtomicBool` for dirty flags.
@@ -51,13 +71,141 @@
 //! - Concurrent reads (RwLock read lock)
 //! - Exclusive writes (RwLock write lock)
 
-use parking_lot::RwLock;
-use std::collections::VecDeque;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use parking_lot::{Mutex, RwLock};
+use std::collections::{BTreeMap, VecDeque};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::data::{AudioData, ChatMessage, LogEntry};
 
+/// A change published to [`StateSubscription`]s
+#[derive(Debug, Clone)]
+pub enum StateChange {
+    /// A chat message was added or a streaming message grew (consolidated content)
+    Chat(ChatMessage),
+    /// Chat history was cleared
+    ChatCleared,
+    /// A log entry was added
+    Log(LogEntry),
+    /// Log history was cleared
+    LogsCleared,
+    /// Connection/dataflow status changed
+    Status(DoraStatus),
+    /// Microphone state changed
+    Mic(MicChange),
+}
+
+/// Microphone field that changed (see [`MicState`])
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MicChange {
+    Level(f32),
+    Speaking(bool),
+    Recording(bool),
+    AecEnabled(bool),
+}
+
+/// Fans state changes out to subscribers.
+///
+/// Publishing is a single atomic load when nobody is subscribed, so producers
+/// pay nothing unless a consumer opted in. Dropped subscriptions are pruned
+/// on the next publish.
+#[derive(Default)]
+pub(crate) struct ChangeNotifier {
+    subscribers: Mutex<Vec<Sender<StateChange>>>,
+    active: AtomicBool,
+}
+
+impl ChangeNotifier {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Register a new subscriber
+    pub fn subscribe(&self) -> StateSubscription {
+        let (tx, rx) = unbounded();
+        self.subscribers.lock().push(tx);
+        self.active.store(true, Ordering::Release);
+        StateSubscription { receiver: rx }
+    }
+
+    /// Whether anyone is listening (cheap check before building an event)
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+
+    /// Send a change to all live subscribers
+    pub fn publish(&self, change: StateChange) {
+        if !self.is_active() {
+            return;
+        }
+        let mut subscribers = self.subscribers.lock();
+        subscribers.retain(|tx| tx.send(change.clone()).is_ok());
+        if subscribers.is_empty() {
+            self.active.store(false, Ordering::Release);
+        }
+    }
+}
+
+/// Receiving end of [`SharedDoraState::subscribe`]
+///
+/// Events arrive in publish order per producer. The channel is unbounded,
+/// so a subscriber that stops reading should be dropped.
+pub struct StateSubscription {
+    receiver: Receiver<StateChange>,
+}
+
+impl StateSubscription {
+    /// Block until the next change; `None` once the state has been dropped
+    pub fn recv(&self) -> Option<StateChange> {
+        self.receiver.recv().ok()
+    }
+
+    /// Block up to `timeout` for the next change
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<StateChange> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
+
+    /// Take the next change without blocking
+    pub fn try_recv(&self) -> Option<StateChange> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Take all pending changes without blocking
+    pub fn drain(&self) -> Vec<StateChange> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+/// Publishes changes of a dirty-tracked container
+struct ChangeHook<T> {
+    notifier: Arc<ChangeNotifier>,
+    on_change: fn(&T) -> StateChange,
+    on_clear: Option<StateChange>,
+}
+
+impl<T> ChangeHook<T> {
+    /// Event for a new `value`, if anyone is listening
+    ///
+    /// Built before the value is stored and published once it is readable.
+    fn change(&self, value: &T) -> Option<StateChange> {
+        self.notifier.is_active().then(|| (self.on_change)(value))
+    }
+
+    fn publish(&self, change: Option<StateChange>) {
+        if let Some(change) = change {
+            self.notifier.publish(change);
+        }
+    }
+
+    fn cleared(&self) {
+        if let Some(ref change) = self.on_clear {
+            self.notifier.publish(change.clone());
+        }
+    }
+}
+
 /// Thread-safe vector with dirty tracking and maximum size enforcement.
 ///
 /// Designed for producer-consumer scenarios where:
@@ -100,6 +248,7 @@ pub struct DirtyVec<T> {
     data: RwLock<Vec<T>>,
     dirty: AtomicBool,
     max_size: usize,
+    hook: Option<ChangeHook<T>>,
 }
 
 impl<T: Clone> DirtyVec<T> {
@@ -108,17 +257,39 @@ impl<T: Clone> DirtyVec<T> {
             data: RwLock::new(Vec::new()),
             dirty: AtomicBool::new(false),
             max_size,
+            hook: None,
         }
     }
 
+    /// Publish pushes and clears to `notifier`
+    fn with_notifier(
+        mut self,
+        notifier: &Arc<ChangeNotifier>,
+        on_push: fn(&T) -> StateChange,
+        on_clear: StateChange,
+    ) -> Self {
+        self.hook = Some(ChangeHook {
+            notifier: Arc::clone(notifier),
+            on_change: on_push,
+            on_clear: Some(on_clear),
+        });
+        self
+    }
+
     /// Push item, mark dirty, enforce max size
     pub fn push(&self, item: T) {
-        let mut data = self.data.write();
-        data.push(item);
-        if data.len() > self.max_size {
-            data.remove(0);
+        let change = self.hook.as_ref().and_then(|hook| hook.change(&item));
+        {
+            let mut data = self.data.write();
+            data.push(item);
+            if data.len() > self.max_size {
+                data.remove(0);
+            }
         }
         self.dirty.store(true, Ordering::Release);
+        if let Some(ref hook) = self.hook {
+            hook.publish(change);
+        }
     }
 
     /// Read all data if dirty, clearing dirty flag
@@ -139,6 +310,9 @@ impl<T: Clone> DirtyVec<T> {
     pub fn clear(&self) {
         self.data.write().clear();
         self.dirty.store(true, Ordering::Release);
+        if let Some(ref hook) = self.hook {
+            hook.cleared();
+        }
     }
 
     /// Check if dirty without consuming
@@ -170,6 +344,7 @@ impl<T: Clone> DirtyVec<T> {
 pub struct DirtyValue<T> {
     data: RwLock<T>,
     dirty: AtomicBool,
+    hook: Option<ChangeHook<T>>,
 }
 
 impl<T: Clone + Default> DirtyValue<T> {
@@ -177,13 +352,32 @@ impl<T: Clone + Default> DirtyValue<T> {
         Self {
             data: RwLock::new(initial),
             dirty: AtomicBool::new(false),
+            hook: None,
         }
     }
 
+    /// Publish every `set` to `notifier`
+    fn with_notifier(
+        mut self,
+        notifier: &Arc<ChangeNotifier>,
+        on_set: fn(&T) -> StateChange,
+    ) -> Self {
+        self.hook = Some(ChangeHook {
+            notifier: Arc::clone(notifier),
+            on_change: on_set,
+            on_clear: None,
+        });
+        self
+    }
+
     /// Set value and mark dirty
     pub fn set(&self, value: T) {
+        let change = self.hook.as_ref().and_then(|hook| hook.change(&value));
         *self.data.write() = value;
         self.dirty.store(true, Ordering::Release);
+        if let Some(ref hook) = self.hook {
+            hook.publish(change);
+        }
     }
 
     /// Read value if dirty, clearing dirty flag
@@ -206,6 +400,7 @@ impl<T: Default> Default for DirtyValue<T> {
         Self {
             data: RwLock::new(T::default()),
             dirty: AtomicBool::new(false),
+            hook: None,
         }
     }
 }
@@ -246,6 +441,7 @@ pub struct ChatState {
     messages: RwLock<Vec<ChatMessage>>,
     dirty: AtomicBool,
     max_messages: usize,
+    notifier: Option<Arc<ChangeNotifier>>,
 }
 
 impl ChatState {
@@ -254,9 +450,16 @@ impl ChatState {
             messages: RwLock::new(Vec::new()),
             dirty: AtomicBool::new(false),
             max_messages,
+            notifier: None,
         }
     }
 
+    /// Publish consolidated messages and clears to `notifier`
+    fn with_notifier(mut self, notifier: &Arc<ChangeNotifier>) -> Self {
+        self.notifier = Some(Arc::clone(notifier));
+        self
+    }
+
     /// Push message with automatic streaming consolidation
     ///
     /// If message is streaming, ACCUMULATES content to existing streaming message from same sender/session.
@@ -274,7 +477,7 @@ impl ChatState {
                 && m.session_id == msg.session_id
         });
 
-        if let Some(idx) = existing_idx {
+        let updated = if let Some(idx) = existing_idx {
             // ACCUMULATE content for streaming messages (append, not replace)
             messages[idx].content.push_str(&msg.content);
             if !msg.is_streaming {
@@ -282,6 +485,7 @@ impl ChatState {
                 messages[idx].is_streaming = false;
                 messages[idx].timestamp = msg.timestamp;
             }
+            idx
         } else {
             // New message
             messages.push(msg);
@@ -290,9 +494,18 @@ impl ChatState {
             if messages.len() > self.max_messages {
                 messages.remove(0);
             }
-        }
+            messages.len() - 1
+        };
 
         self.dirty.store(true, Ordering::Release);
+
+        if let Some(ref notifier) = self.notifier {
+            if notifier.is_active() {
+                let message = messages[updated].clone();
+                drop(messages);
+                notifier.publish(StateChange::Chat(message));
+            }
+        }
     }
 
     /// Read all messages if dirty
@@ -313,6 +526,9 @@ impl ChatState {
     pub fn clear(&self) {
         self.messages.write().clear();
         self.dirty.store(true, Ordering::Release);
+        if let Some(ref notifier) = self.notifier {
+            notifier.publish(StateChange::ChatCleared);
+        }
     }
 
     /// Get message count
@@ -469,6 +685,73 @@ pub struct DoraStatus {
     pub active_bridges: Vec<String>,
     /// Last error message if any
     pub last_error: Option<String>,
+    /// Startup phase per node ID, while the dispatcher brings the dataflow up
+    pub startup: BTreeMap<String, StartupPhase>,
+    /// Reconnect progress per bridge node ID (only bridges that failed)
+    pub reconnects: BTreeMap<String, ReconnectStatus>,
+}
+
+impl DoraStatus {
+    /// Nodes that haven't reached a final startup phase yet
+    pub fn startup_pending(&self) -> Vec<&str> {
+        self.startup
+            .iter()
+            .filter(|(_, phase)| !phase.is_settled())
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+}
+
+/// Reconnect progress of a failed bridge (see [`DoraStatus::reconnects`])
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectStatus {
+    /// Reconnect attempts since the bridge failed
+    pub attempts: u32,
+    /// Outcome so far
+    pub outcome: ReconnectOutcome,
+}
+
+/// Outcome of reconnecting a failed bridge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconnectOutcome {
+    /// Waiting for the next attempt (or for the last one to settle)
+    Retrying,
+    /// Bridge is connected again
+    Reconnected,
+    /// Attempt limit reached; the bridge stays down until restart
+    GaveUp,
+}
+
+/// Startup phase of a single node (see [`DoraStatus::startup`])
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StartupPhase {
+    /// `dora start` issued, dataflow not listed by dora yet
+    Starting,
+    /// Dataflow is running; the node isn't gated on readiness
+    Started,
+    /// Waiting for the node to report ready on its status output
+    WaitingForReady,
+    /// Node reported ready
+    Ready,
+    /// Bridge is connecting to its dynamic node
+    Connecting,
+    /// Bridge connected
+    Connected,
+    /// Startup failed for this node
+    Failed(String),
+}
+
+impl StartupPhase {
+    /// Whether the node is done starting (successfully or not)
+    pub fn is_settled(&self) -> bool {
+        matches!(
+            self,
+            StartupPhase::Started
+                | StartupPhase::Ready
+                | StartupPhase::Connected
+                | StartupPhase::Failed(_)
+        )
+    }
 }
 
 /// Microphone input state (from AEC bridge)
@@ -510,6 +793,20 @@ impl MicState {
         }
     }
 
+    /// Publish every field change to `notifier`
+    fn with_notifier(notifier: &Arc<ChangeNotifier>) -> Self {
+        Self {
+            level: DirtyValue::new(0.0)
+                .with_notifier(notifier, |v| StateChange::Mic(MicChange::Level(*v))),
+            is_speaking: DirtyValue::new(false)
+                .with_notifier(notifier, |v| StateChange::Mic(MicChange::Speaking(*v))),
+            is_recording: DirtyValue::new(false)
+                .with_notifier(notifier, |v| StateChange::Mic(MicChange::Recording(*v))),
+            aec_enabled: DirtyValue::new(true)
+                .with_notifier(notifier, |v| StateChange::Mic(MicChange::AecEnabled(*v))),
+        }
+    }
+
     // Setters (for AEC bridge thread)
 
     /// Set mic level (0.0 - 1.0)
@@ -638,6 +935,12 @@ impl Default for MicState {
 /// - Logs: 1000 entries
 ///
 /// Use [`SharedDoraState::with_capacities`] for custom limits.
+///
+/// # Subscriptions
+///
+/// [`SharedDoraState::subscribe`] returns a [`StateSubscription`] that receives a
+/// [`StateChange`] for every chat, log, status and mic update. Dirty flags are
+/// unaffected, so subscribers and the UI timer can coexist.
 pub struct SharedDoraState {
     /// Chat messages (with streaming consolidation)
     pub chat: ChatState,
@@ -653,29 +956,46 @@ pub struct SharedDoraState {
 
     /// Microphone input state (from AEC bridge)
     pub mic: MicState,
+
+    /// Change fan-out for event-driven consumers
+    notifier: Arc<ChangeNotifier>,
 }
 
 impl SharedDoraState {
     /// Create new shared state with default capacities
     pub fn new() -> Arc<Self> {
-        Arc::new(Self {
-            chat: ChatState::new(500),   // 500 max chat messages
-            audio: AudioState::new(100), // 100 max pending audio chunks
-            logs: DirtyVec::new(1000),   // 1000 max log entries
-            status: DirtyValue::default(),
-            mic: MicState::new(),
-        })
+        // 500 max chat messages, 100 max pending audio chunks, 1000 max log entries
+        Arc::new(Self::build(500, 100, 1000))
     }
 
     /// Create with custom capacities
     pub fn with_capacities(max_chat: usize, max_audio_chunks: usize, max_logs: usize) -> Arc<Self> {
-        Arc::new(Self {
-            chat: ChatState::new(max_chat),
+        Arc::new(Self::build(max_chat, max_audio_chunks, max_logs))
+    }
+
+    fn build(max_chat: usize, max_audio_chunks: usize, max_logs: usize) -> Self {
+        let notifier = ChangeNotifier::new();
+        Self {
+            chat: ChatState::new(max_chat).with_notifier(&notifier),
             audio: AudioState::new(max_audio_chunks),
-            logs: DirtyVec::new(max_logs),
-            status: DirtyValue::default(),
-            mic: MicState::new(),
-        })
+            logs: DirtyVec::new(max_logs).with_notifier(
+                &notifier,
+                |entry| StateChange::Log(entry.clone()),
+                StateChange::LogsCleared,
+            ),
+            status: DirtyValue::default()
+                .with_notifier(&notifier, |status| StateChange::Status(status.clone())),
+            mic: MicState::with_notifier(&notifier),
+            notifier,
+        }
+    }
+
+    /// Subscribe to chat, log, status and mic changes
+    ///
+    /// For consumers without a UI timer (headless CLI, tests, recorders).
+    /// Does not touch dirty flags. Drop the subscription to unsubscribe.
+    pub fn subscribe(&self) -> StateSubscription {
+        self.notifier.subscribe()
     }
 
     /// Clear all state (on dataflow stop/reset)
@@ -709,17 +1029,39 @@ impl SharedDoraState {
         status.last_error = error;
         self.status.set(status);
     }
+
+    /// Set the startup phase of a node
+    pub fn set_startup_phase(&self, node_id: &str, phase: StartupPhase) {
+        let mut status = self.status.read();
+        if status.startup.get(node_id) != Some(&phase) {
+            status.startup.insert(node_id.to_string(), phase);
+            self.status.set(status);
+        }
+    }
+
+    /// Set the reconnect progress of a bridge
+    pub fn set_reconnect(&self, node_id: &str, reconnect: ReconnectStatus) {
+        let mut status = self.status.read();
+        if status.reconnects.get(node_id) != Some(&reconnect) {
+            status.reconnects.insert(node_id.to_string(), reconnect);
+            self.status.set(status);
+        }
+    }
+
+    /// Forget all startup phases and reconnect progress (before a new start)
+    pub fn clear_startup(&self) {
+        let mut status = self.status.read();
+        if !status.startup.is_empty() || !status.reconnects.is_empty() {
+            status.startup.clear();
+            status.reconnects.clear();
+            self.status.set(status);
+        }
+    }
 }
 
 impl Default for SharedDoraState {
     fn default() -> Self {
-        Self {
-            chat: ChatState::new(500),
-            audio: AudioState::new(100),
-            logs: DirtyVec::new(1000),
-            status: DirtyValue::default(),
-            mic: MicState::new(),
-        }
+        Self::build(500, 100, 1000)
     }
 }
 
@@ -907,4 +1249,78 @@ mod tests {
         assert_eq!(chunks.len(), 2);
         assert_eq!(audio.len(), 0);
     }
+
+    #[test]
+    fn test_subscribe_receives_typed_changes() {
+        let state = SharedDoraState::new();
+        let changes = state.subscribe();
+
+        state.chat.push(ChatMessage {
+            content: "Hel".to_string(),
+            sender: "Bot".to_string(),
+            role: MessageRole::Assistant,
+            timestamp: 1000,
+            is_streaming: true,
+            session_id: Some("s1".to_string()),
+        });
+        state.chat.push(ChatMessage {
+            content: "lo".to_string(),
+            sender: "Bot".to_string(),
+            role: MessageRole::Assistant,
+            timestamp: 1001,
+            is_streaming: false,
+            session_id: Some("s1".to_string()),
+        });
+        state.add_bridge("mofa-audio-player".to_string());
+        state.mic.set_speaking(true);
+        state.logs.clear();
+
+        let events = changes.drain();
+        assert_eq!(events.len(), 5);
+        match &events[1] {
+            StateChange::Chat(msg) => {
+                assert_eq!(msg.content, "Hello");
+                assert!(!msg.is_streaming);
+            }
+            other => panic!("unexpected change: {:?}", other),
+        }
+        match &events[2] {
+            StateChange::Status(status) => {
+                assert_eq!(status.active_bridges, vec!["mofa-audio-player".to_string()])
+            }
+            other => panic!("unexpected change: {:?}", other),
+        }
+        assert!(matches!(events[3], StateChange::Mic(MicChange::Speaking(true))));
+        assert!(matches!(events[4], StateChange::LogsCleared));
+
+        // Dirty flags are independent of subscriptions
+        assert_eq!(state.chat.read_if_dirty().unwrap().len(), 1);
+        assert_eq!(state.mic.read_speaking_if_dirty(), Some(true));
+    }
+
+    #[test]
+    fn test_subscribe_wakes_waiter_and_prunes_dropped() {
+        let state = SharedDoraState::new();
+        let first = state.subscribe();
+        let second = state.subscribe();
+        drop(second);
+
+        let producer = Arc::clone(&state);
+        let handle = std::thread::spawn(move || {
+            producer
+                .logs
+                .push(LogEntry::new(crate::data::LogLevel::Info, "ready", "asr"));
+        });
+
+        match first.recv_timeout(Duration::from_secs(5)) {
+            Some(StateChange::Log(entry)) => assert_eq!(entry.message, "ready"),
+            other => panic!("unexpected change: {:?}", other),
+        }
+        handle.join().unwrap();
+        assert_eq!(state.notifier.subscribers.lock().len(), 1);
+
+        drop(first);
+        state.mic.set_level(0.5);
+        assert!(!state.notifier.is_active());
+    }
 }
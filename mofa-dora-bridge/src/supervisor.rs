@@ -0,0 +1,174 @@
+//! Bridge supervisor
+//!
+//! A bridge's worker thread exits in [`BridgeState::Error`] when its dynamic
+//! node can't be initialized or dora closes its event stream. The supervisor
+//! reconnects such bridges with exponential backoff while the dataflow keeps
+//! running.
+//!
+//! It is tick-based, like the rest of the UI polling: the app calls
+//! [`DynamicNodeDispatcher::supervise`](crate::DynamicNodeDispatcher::supervise)
+//! from the loop that owns the dispatcher. Progress is published per bridge
+//! in [`DoraStatus::reconnects`](crate::DoraStatus::reconnects).
+//!
+//! ```rust,ignore
+//! loop {
+//!     // ... handle commands ...
+//!     dispatcher.supervise();
+//!     std::thread::sleep(Duration::from_millis(10));
+//! }
+//! ```
+
+use crate::bridge::{BridgeState, DoraBridge};
+use crate::shared_state::{ReconnectOutcome, ReconnectStatus, SharedDoraState};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// How failed bridges are reconnected
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// Delay before the first attempt after a failure
+    pub initial_delay: Duration,
+    /// Upper bound for the (doubling) delay between attempts
+    pub max_delay: Duration,
+    /// Give up after this many attempts (`None` retries while the dataflow runs)
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            max_attempts: None,
+        }
+    }
+}
+
+/// Reconnect state of one failed bridge
+#[derive(Debug)]
+struct Failure {
+    attempts: u32,
+    delay: Duration,
+    next_attempt: Instant,
+    gave_up: bool,
+}
+
+/// Reconnects failed bridges with backoff
+#[derive(Debug, Default)]
+pub struct BridgeSupervisor {
+    policy: ReconnectPolicy,
+    failures: HashMap<String, Failure>,
+}
+
+impl BridgeSupervisor {
+    /// Create a supervisor with a reconnect policy
+    pub fn new(policy: ReconnectPolicy) -> Self {
+        Self {
+            policy,
+            failures: HashMap::new(),
+        }
+    }
+
+    /// Get the reconnect policy
+    pub fn policy(&self) -> &ReconnectPolicy {
+        &self.policy
+    }
+
+    /// Forget all failures (on dataflow start/stop)
+    pub fn reset(&mut self) {
+        self.failures.clear();
+    }
+
+    /// Check every bridge once, reconnecting failed ones whose backoff elapsed
+    pub fn tick(
+        &mut self,
+        bridges: &mut HashMap<String, Box<dyn DoraBridge>>,
+        shared_state: &SharedDoraState,
+    ) {
+        let now = Instant::now();
+
+        for (node_id, bridge) in bridges.iter_mut() {
+            match bridge.state() {
+                BridgeState::Connected => {
+                    if let Some(failure) = self.failures.remove(node_id) {
+                        info!(
+                            "Bridge {} reconnected after {} attempt(s)",
+                            node_id, failure.attempts
+                        );
+                        shared_state.set_reconnect(
+                            node_id,
+                            ReconnectStatus {
+                                attempts: failure.attempts,
+                                outcome: ReconnectOutcome::Reconnected,
+                            },
+                        );
+                    }
+                }
+                BridgeState::Error => {
+                    let policy = &self.policy;
+                    let failure = self.failures.entry(node_id.clone()).or_insert_with(|| {
+                        warn!(
+                            "Bridge {} failed, reconnecting in {:?}",
+                            node_id, policy.initial_delay
+                        );
+                        Failure {
+                            attempts: 0,
+                            delay: policy.initial_delay,
+                            next_attempt: now + policy.initial_delay,
+                            gave_up: false,
+                        }
+                    });
+                    if failure.gave_up || now < failure.next_attempt {
+                        continue;
+                    }
+
+                    if policy
+                        .max_attempts
+                        .is_some_and(|max| failure.attempts >= max)
+                    {
+                        warn!(
+                            "Giving up on bridge {} after {} attempt(s)",
+                            node_id, failure.attempts
+                        );
+                        failure.gave_up = true;
+                        shared_state.set_reconnect(
+                            node_id,
+                            ReconnectStatus {
+                                attempts: failure.attempts,
+                                outcome: ReconnectOutcome::GaveUp,
+                            },
+                        );
+                        shared_state.set_error(Some(format!(
+                            "Bridge {} could not reconnect after {} attempts",
+                            node_id, failure.attempts
+                        )));
+                        continue;
+                    }
+
+                    failure.attempts += 1;
+                    info!(
+                        "Reconnecting bridge {} (attempt {})",
+                        node_id, failure.attempts
+                    );
+                    shared_state.set_reconnect(
+                        node_id,
+                        ReconnectStatus {
+                            attempts: failure.attempts,
+                            outcome: ReconnectOutcome::Retrying,
+                        },
+                    );
+                    if let Err(e) = bridge.connect() {
+                        warn!("Reconnect of bridge {} failed: {}", node_id, e);
+                    }
+
+                    failure.next_attempt = Instant::now() + failure.delay;
+                    failure.delay = (failure.delay * 2).min(policy.max_delay);
+                }
+                // Disconnected bridges were stopped on purpose; Connecting ones
+                // are still settling
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,458 @@
+//! # Dora Transport Abstraction
+//!
+//! Bridges don't talk to [`DoraNode`] directly. Instead they receive a
+//! [`TransportFactory`] and open a [`NodeTransport`] from their worker thread.
+//! This keeps event handling independent of a running dora daemon.
+//!
+//! ## Implementations
+//!
+//! | Factory | Transport | Purpose |
+//! |---------|-----------|---------|
+//! | [`DoraTransportFactory`] | [`DoraNodeTransport`] | Production - real dynamic node |
+//! | [`MockDoraRuntime`] | [`MockTransport`] | Tests - in-memory events and outputs |
+//!
+//! ## Testing with the Mock Runtime
+//!
+//! ```rust,ignore
+//! use mofa_dora_bridge::transport::MockDoraRuntime;
+//! use mofa_dora_bridge::widgets::AudioPlayerBridge;
+//!
+//! let runtime = MockDoraRuntime::new();
+//! let state = SharedDoraState::new();
+//! let mut bridge =
+//!     AudioPlayerBridge::with_transport("mofa-audio-player", Some(state.clone()), runtime.clone());
+//! bridge.connect()?;
+//!
+//! // Inject an input as if an upstream TTS node had sent it
+//! let node = runtime.node("mofa-audio-player");
+//! node.inject_input(
+//!     "audio_tutor",
+//!     Float32Array::from(vec![0.1, 0.2]),
+//!     &[("question_id", "1")],
+//! );
+//!
+//! // Inspect outputs the bridge sent back
+//! let acks = node.sent_on("audio_complete");
+//! ```
+
+use crate::data::EventMetadata;
+use crate::error::{BridgeError, BridgeResult};
+use arrow::array::{Array, ArrayRef};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use dora_node_api::{
+    dora_core::config::{DataId, NodeId},
+    DoraNode, Event, EventStream, Parameter,
+};
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Event delivered to a bridge by its transport
+///
+/// Mirrors the dora [`Event`] variants bridges care about, with input
+/// metadata already flattened into [`EventMetadata`].
+#[derive(Debug, Clone)]
+pub enum BridgeEvent {
+    /// Data arrived on an input
+    Input {
+        /// Input ID as declared in the dataflow (e.g., "audio_student1")
+        id: String,
+        /// Arrow payload
+        data: ArrayRef,
+        /// Metadata parameters as strings
+        metadata: EventMetadata,
+    },
+    /// An upstream input was closed
+    InputClosed {
+        /// Input ID that closed
+        id: String,
+    },
+    /// The dataflow asked this node to stop
+    Stop,
+    /// The runtime reported an error (never a receive timeout)
+    Error(String),
+    /// The event stream ended; no more events will arrive
+    ///
+    /// Bridges exit their event loop in [`BridgeState::Error`](crate::BridgeState::Error)
+    /// so the dispatcher's supervisor can reconnect them.
+    Closed,
+}
+
+impl BridgeEvent {
+    /// Create an input event (mainly for tests)
+    pub fn input(
+        id: impl Into<String>,
+        data: impl Array + 'static,
+        metadata: &[(&str, &str)],
+    ) -> Self {
+        let mut event_meta = EventMetadata::default();
+        for (key, value) in metadata {
+            event_meta
+                .values
+                .insert((*key).to_string(), (*value).to_string());
+        }
+        BridgeEvent::Input {
+            id: id.into(),
+            data: Arc::new(data),
+            metadata: event_meta,
+        }
+    }
+}
+
+/// A connected dynamic node, as seen by a bridge
+pub trait NodeTransport {
+    /// Wait up to `timeout` for the next event
+    ///
+    /// Returns `None` on timeout or for events bridges don't handle, and
+    /// [`BridgeEvent::Closed`] once the event stream has ended.
+    fn recv_timeout(&mut self, timeout: Duration) -> Option<BridgeEvent>;
+
+    /// Send data on one of the node's outputs
+    fn send_output(
+        &mut self,
+        output_id: &str,
+        parameters: BTreeMap<String, Parameter>,
+        data: ArrayRef,
+    ) -> BridgeResult<()>;
+}
+
+/// Opens transports for bridges
+///
+/// Called from the bridge worker thread, once per connection attempt.
+pub trait TransportFactory: Send + Sync {
+    /// Connect as the given dynamic node
+    fn connect(&self, node_id: &str) -> BridgeResult<Box<dyn NodeTransport>>;
+}
+
+// ============================================================================
+// Production transport (real dora)
+// ============================================================================
+
+/// Factory that connects to a running dora dataflow
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DoraTransportFactory;
+
+impl DoraTransportFactory {
+    /// Shared factory instance for bridge constructors
+    pub fn shared() -> Arc<dyn TransportFactory> {
+        Arc::new(DoraTransportFactory)
+    }
+}
+
+impl TransportFactory for DoraTransportFactory {
+    fn connect(&self, node_id: &str) -> BridgeResult<Box<dyn NodeTransport>> {
+        let (node, events) = DoraNode::init_from_node_id(NodeId::from(node_id.to_string()))
+            .map_err(|e| BridgeError::ConnectionFailed(e.to_string()))?;
+        Ok(Box::new(DoraNodeTransport { node, events }))
+    }
+}
+
+/// Transport backed by a real [`DoraNode`]
+pub struct DoraNodeTransport {
+    node: DoraNode,
+    events: EventStream,
+}
+
+impl NodeTransport for DoraNodeTransport {
+    fn recv_timeout(&mut self, timeout: Duration) -> Option<BridgeEvent> {
+        // dora returns `None` only once the stream is closed; a timeout comes
+        // back as an `Event::Error`, which is mapped to `None` below
+        let Some(event) = self.events.recv_timeout(timeout) else {
+            return Some(BridgeEvent::Closed);
+        };
+        match event {
+            Event::Input { id, metadata, data } => Some(BridgeEvent::Input {
+                id: id.to_string(),
+                data: data.0,
+                metadata: event_metadata(&metadata.parameters),
+            }),
+            Event::InputClosed { id } => Some(BridgeEvent::InputClosed { id: id.to_string() }),
+            Event::Stop(_) => Some(BridgeEvent::Stop),
+            Event::Error(e) if is_timeout_error(&e) => None,
+            Event::Error(e) => Some(BridgeEvent::Error(e)),
+            _ => None,
+        }
+    }
+
+    fn send_output(
+        &mut self,
+        output_id: &str,
+        parameters: BTreeMap<String, Parameter>,
+        data: ArrayRef,
+    ) -> BridgeResult<()> {
+        let output_id: DataId = output_id.to_string().into();
+        self.node
+            .send_output(output_id, parameters, data)
+            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+    }
+}
+
+/// Prefix of the `Event::Error` dora returns when `recv_timeout` times out
+const DORA_TIMEOUT_ERROR: &str = "Timeout event stream error";
+
+/// Whether an `Event::Error` message is dora's timeout rather than a real error
+fn is_timeout_error(message: &str) -> bool {
+    message.starts_with(DORA_TIMEOUT_ERROR)
+}
+
+/// Convert dora metadata parameters to string values
+fn event_metadata(parameters: &BTreeMap<String, Parameter>) -> EventMetadata {
+    let mut event_meta = EventMetadata::default();
+    for (key, value) in parameters.iter() {
+        let string_value = match value {
+            Parameter::String(s) => s.clone(),
+            Parameter::Integer(i) => i.to_string(),
+            Parameter::Float(f) => f.to_string(),
+            Parameter::Bool(b) => b.to_string(),
+            Parameter::ListInt(l) => format!("{:?}", l),
+            Parameter::ListFloat(l) => format!("{:?}", l),
+            Parameter::ListString(l) => format!("{:?}", l),
+        };
+        event_meta.values.insert(key.clone(), string_value);
+    }
+    event_meta
+}
+
+// ============================================================================
+// In-memory transport (tests)
+// ============================================================================
+
+/// Output captured by the mock runtime
+#[derive(Debug, Clone)]
+pub struct SentOutput {
+    /// Output ID the bridge sent on
+    pub output_id: String,
+    /// Metadata parameters attached to the output
+    pub parameters: BTreeMap<String, Parameter>,
+    /// Arrow payload
+    pub data: ArrayRef,
+}
+
+impl SentOutput {
+    /// Get a string metadata parameter
+    pub fn string_param(&self, key: &str) -> Option<&str> {
+        match self.parameters.get(key) {
+            Some(Parameter::String(s)) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// In-memory dora runtime for testing bridges without a daemon
+///
+/// Each node ID gets a [`MockNodeHandle`] that tests use to inject events
+/// and inspect outputs. The runtime itself is the [`TransportFactory`]
+/// handed to bridges.
+#[derive(Default)]
+pub struct MockDoraRuntime {
+    nodes: Mutex<HashMap<String, MockNodeHandle>>,
+    refused: Mutex<HashSet<String>>,
+}
+
+impl MockDoraRuntime {
+    /// Create a new mock runtime
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Get (or create) the handle for a node
+    pub fn node(&self, node_id: &str) -> MockNodeHandle {
+        self.nodes
+            .lock()
+            .entry(node_id.to_string())
+            .or_insert_with(MockNodeHandle::new)
+            .clone()
+    }
+
+    /// Make `connect` fail for a node, as if it weren't in the dataflow
+    pub fn refuse(&self, node_id: &str) {
+        self.refused.lock().insert(node_id.to_string());
+    }
+
+    /// Let a previously refused node connect again
+    pub fn accept(&self, node_id: &str) {
+        self.refused.lock().remove(node_id);
+    }
+}
+
+impl TransportFactory for MockDoraRuntime {
+    fn connect(&self, node_id: &str) -> BridgeResult<Box<dyn NodeTransport>> {
+        if self.refused.lock().contains(node_id) {
+            return Err(BridgeError::ConnectionFailed(format!(
+                "node '{}' refused by mock runtime",
+                node_id
+            )));
+        }
+        Ok(Box::new(self.node(node_id).transport()))
+    }
+}
+
+/// Test-side handle to a mock node
+#[derive(Clone)]
+pub struct MockNodeHandle {
+    event_sender: Sender<BridgeEvent>,
+    event_receiver: Receiver<BridgeEvent>,
+    sent: Arc<Mutex<Vec<SentOutput>>>,
+}
+
+impl MockNodeHandle {
+    fn new() -> Self {
+        let (event_tx, event_rx) = unbounded();
+        Self {
+            event_sender: event_tx,
+            event_receiver: event_rx,
+            sent: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Queue an event for the bridge
+    pub fn inject(&self, event: BridgeEvent) {
+        let _ = self.event_sender.send(event);
+    }
+
+    /// Close the bridge's event stream, as if the dataflow dropped the node
+    pub fn close(&self) {
+        self.inject(BridgeEvent::Closed);
+    }
+
+    /// Queue the error dora's `recv_timeout` returns when no event arrived
+    pub fn inject_timeout(&self) {
+        self.inject(BridgeEvent::Error(format!(
+            "{}: Receiver timed out",
+            DORA_TIMEOUT_ERROR
+        )));
+    }
+
+    /// Queue an input event with string metadata
+    pub fn inject_input(
+        &self,
+        input_id: &str,
+        data: impl Array + 'static,
+        metadata: &[(&str, &str)],
+    ) {
+        self.inject(BridgeEvent::input(input_id, data, metadata));
+    }
+
+    /// All outputs sent so far
+    pub fn sent(&self) -> Vec<SentOutput> {
+        self.sent.lock().clone()
+    }
+
+    /// Outputs sent on a specific output ID
+    pub fn sent_on(&self, output_id: &str) -> Vec<SentOutput> {
+        self.sent
+            .lock()
+            .iter()
+            .filter(|o| o.output_id == output_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Take and clear all captured outputs
+    pub fn take_sent(&self) -> Vec<SentOutput> {
+        std::mem::take(&mut *self.sent.lock())
+    }
+
+    /// Create a transport bound to this node (for driving handlers directly)
+    pub fn transport(&self) -> MockTransport {
+        MockTransport {
+            events: self.event_receiver.clone(),
+            sent: Arc::clone(&self.sent),
+        }
+    }
+}
+
+/// Bridge-side end of a mock node
+pub struct MockTransport {
+    events: Receiver<BridgeEvent>,
+    sent: Arc<Mutex<Vec<SentOutput>>>,
+}
+
+impl NodeTransport for MockTransport {
+    fn recv_timeout(&mut self, timeout: Duration) -> Option<BridgeEvent> {
+        // Same mapping as the dora transport: timeout errors are timeouts
+        match self.events.recv_timeout(timeout).ok()? {
+            BridgeEvent::Error(e) if is_timeout_error(&e) => None,
+            event => Some(event),
+        }
+    }
+
+    fn send_output(
+        &mut self,
+        output_id: &str,
+        parameters: BTreeMap<String, Parameter>,
+        data: ArrayRef,
+    ) -> BridgeResult<()> {
+        self.sent.lock().push(SentOutput {
+            output_id: output_id.to_string(),
+            parameters,
+            data,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::StringArray;
+
+    #[test]
+    fn test_mock_roundtrip() {
+        let runtime = MockDoraRuntime::new();
+        let mut transport = runtime.connect("mofa-test").unwrap();
+
+        runtime.node("mofa-test").inject_input(
+            "text",
+            StringArray::from(vec!["hello"]),
+            &[("question_id", "7")],
+        );
+
+        match transport.recv_timeout(Duration::from_millis(10)) {
+            Some(BridgeEvent::Input { id, metadata, .. }) => {
+                assert_eq!(id, "text");
+                assert_eq!(metadata.question_id(), Some("7"));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(transport.recv_timeout(Duration::from_millis(1)).is_none());
+
+        let mut params = BTreeMap::new();
+        params.insert(
+            "participant".to_string(),
+            Parameter::String("tutor".to_string()),
+        );
+        transport
+            .send_output("status", params, Arc::new(StringArray::from(vec!["ok"])))
+            .unwrap();
+
+        let sent = runtime.node("mofa-test").sent_on("status");
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].string_param("participant"), Some("tutor"));
+    }
+
+    #[test]
+    fn test_mock_timeout_is_not_an_error() {
+        let runtime = MockDoraRuntime::new();
+        let mut transport = runtime.connect("mofa-test").unwrap();
+        let node = runtime.node("mofa-test");
+
+        node.inject_timeout();
+        node.inject(BridgeEvent::Error("daemon connection lost".to_string()));
+        assert!(transport.recv_timeout(Duration::from_millis(10)).is_none());
+        assert!(matches!(
+            transport.recv_timeout(Duration::from_millis(10)),
+            Some(BridgeEvent::Error(e)) if e == "daemon connection lost"
+        ));
+    }
+
+    #[test]
+    fn test_mock_refuse() {
+        let runtime = MockDoraRuntime::new();
+        runtime.refuse("mofa-test");
+        assert!(runtime.connect("mofa-test").is_err());
+        runtime.accept("mofa-test");
+        assert!(runtime.connect("mofa-test").is_ok());
+    }
+}
@@ -0,0 +1,449 @@
+//! Dataflow validation
+//!
+//! Lints a [`ParsedDataflow`] without starting dora, so wiring mistakes are
+//! reported up front instead of surfacing as bridge connection retries:
+//! - Inputs whose source names a missing node or output
+//! - MoFA dynamic nodes whose inputs don't match their bridge
+//! - Duplicate node IDs
+//! - `${VAR}` / `$VAR` env placeholders with no value
+
+use crate::bridge::DoraBridge;
+use crate::parser::{MofaNodeSpec, ParsedDataflow};
+use crate::widgets::{AecInputBridge, AudioPlayerBridge, CastControllerBridge, PromptInputBridge};
+use crate::MofaNodeType;
+use std::collections::{HashMap, HashSet};
+
+/// Prefix of dora built-in sources (e.g. `dora/timer/millis/100`)
+const DORA_BUILTIN_PREFIX: &str = "dora/";
+
+/// Severity of a validation finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Informational, nothing is wrong
+    Info,
+    /// Likely a mistake, but the dataflow can still start
+    Warning,
+    /// The dataflow will not work as written
+    Error,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Info => write!(f, "INFO"),
+            Severity::Warning => write!(f, "WARNING"),
+            Severity::Error => write!(f, "ERROR"),
+        }
+    }
+}
+
+/// Category of a validation finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticKind {
+    /// Input source is not in `node_id/output_id` format
+    MalformedSource,
+    /// Input source names a node that doesn't exist
+    MissingSourceNode,
+    /// Input source names an output the source node doesn't declare
+    MissingSourceOutput,
+    /// MoFA node declares an input its bridge doesn't expect
+    UnexpectedBridgeInput,
+    /// MoFA node declares none of the inputs its bridge expects
+    MissingBridgeInputs,
+    /// Two or more nodes share the same ID
+    DuplicateNodeId,
+    /// Env placeholder has no default and no value
+    UnresolvedEnvPlaceholder,
+}
+
+/// A single validation finding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataflowDiagnostic {
+    /// How serious the finding is
+    pub severity: Severity,
+    /// What kind of problem was found
+    pub kind: DiagnosticKind,
+    /// Node the finding applies to
+    pub node_id: String,
+    /// Human-readable description
+    pub message: String,
+}
+
+impl DataflowDiagnostic {
+    fn new(
+        severity: Severity,
+        kind: DiagnosticKind,
+        node_id: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity,
+            kind,
+            node_id: node_id.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Check if this finding prevents the dataflow from working
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl std::fmt::Display for DataflowDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}: {}", self.severity, self.node_id, self.message)
+    }
+}
+
+impl ParsedDataflow {
+    /// Validate the dataflow against the current process environment
+    pub fn validate(&self) -> Vec<DataflowDiagnostic> {
+        self.validate_with_env(&HashMap::new())
+    }
+
+    /// Validate the dataflow, resolving env placeholders from `env` first
+    /// and then from the process environment
+    pub fn validate_with_env(&self, env: &HashMap<String, String>) -> Vec<DataflowDiagnostic> {
+        let mut diagnostics = Vec::new();
+
+        self.check_duplicate_ids(&mut diagnostics);
+        self.check_input_sources(&mut diagnostics);
+        for spec in &self.mofa_nodes {
+            Self::check_bridge_inputs(spec, &mut diagnostics);
+        }
+        self.check_env_placeholders(env, &mut diagnostics);
+
+        diagnostics
+    }
+
+    /// Node IDs as declared in the raw YAML, including nodes the parser skipped
+    fn declared_node_ids(&self) -> Vec<&str> {
+        self.raw_yaml
+            .get("nodes")
+            .and_then(|n| n.as_sequence())
+            .map(|nodes| {
+                nodes
+                    .iter()
+                    .filter_map(|n| n.get("id").and_then(|id| id.as_str()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn check_duplicate_ids(&self, diagnostics: &mut Vec<DataflowDiagnostic>) {
+        let mut seen = HashSet::new();
+        let mut reported = HashSet::new();
+        for id in self.declared_node_ids() {
+            if !seen.insert(id) && reported.insert(id) {
+                diagnostics.push(DataflowDiagnostic::new(
+                    Severity::Error,
+                    DiagnosticKind::DuplicateNodeId,
+                    id,
+                    format!("node ID '{}' is declared more than once", id),
+                ));
+            }
+        }
+    }
+
+    fn check_input_sources(&self, diagnostics: &mut Vec<DataflowDiagnostic>) {
+        let declared: HashSet<&str> = self.declared_node_ids().into_iter().collect();
+
+        for node in &self.nodes {
+            for input in &node.inputs {
+                if input.source.starts_with(DORA_BUILTIN_PREFIX) {
+                    continue;
+                }
+
+                let Some((source_node, source_output)) = input.source.split_once('/') else {
+                    diagnostics.push(DataflowDiagnostic::new(
+                        Severity::Error,
+                        DiagnosticKind::MalformedSource,
+                        &node.id,
+                        format!(
+                            "input '{}' has source '{}', expected 'node_id/output_id'",
+                            input.id, input.source
+                        ),
+                    ));
+                    continue;
+                };
+
+                if !declared.contains(source_node) {
+                    diagnostics.push(DataflowDiagnostic::new(
+                        Severity::Error,
+                        DiagnosticKind::MissingSourceNode,
+                        &node.id,
+                        format!(
+                            "input '{}' reads from unknown node '{}'",
+                            input.id, source_node
+                        ),
+                    ));
+                    continue;
+                }
+
+                // Only check outputs for nodes the parser understood
+                if let Some(source) = self.get_node(source_node) {
+                    if !source.outputs.iter().any(|o| o == source_output) {
+                        diagnostics.push(DataflowDiagnostic::new(
+                            Severity::Error,
+                            DiagnosticKind::MissingSourceOutput,
+                            &node.id,
+                            format!(
+                                "input '{}' reads '{}', but '{}' has no output '{}'",
+                                input.id, input.source, source_node, source_output
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_bridge_inputs(spec: &MofaNodeSpec, diagnostics: &mut Vec<DataflowDiagnostic>) {
+        let Some(expected) = bridge_expected_inputs(spec.node_type, &spec.id) else {
+            return;
+        };
+
+        for input in &spec.inputs {
+            if !expected.contains(&input.id) {
+                diagnostics.push(DataflowDiagnostic::new(
+                    Severity::Warning,
+                    DiagnosticKind::UnexpectedBridgeInput,
+                    &spec.id,
+                    format!(
+                        "input '{}' is not one of the bridge inputs ({})",
+                        input.id,
+                        expected.join(", ")
+                    ),
+                ));
+            }
+        }
+
+        if !expected.is_empty() && !spec.inputs.iter().any(|i| expected.contains(&i.id)) {
+            diagnostics.push(DataflowDiagnostic::new(
+                Severity::Info,
+                DiagnosticKind::MissingBridgeInputs,
+                &spec.id,
+                format!(
+                    "none of the bridge inputs are wired ({})",
+                    expected.join(", ")
+                ),
+            ));
+        }
+    }
+
+    fn check_env_placeholders(
+        &self,
+        env: &HashMap<String, String>,
+        diagnostics: &mut Vec<DataflowDiagnostic>,
+    ) {
+        let mut unresolved = Vec::new();
+        for requirement in &self.env_requirements {
+            let Some(variable) = requirement.variable.as_deref() else {
+                continue;
+            };
+            if !requirement.required
+                || env.contains_key(variable)
+                || std::env::var(variable).is_ok()
+            {
+                continue;
+            }
+            for node_id in &requirement.used_by {
+                unresolved.push((node_id.as_str(), requirement.key.as_str(), variable));
+            }
+        }
+
+        // Report in node order, then by key
+        unresolved.sort_by_key(|&(node_id, key, _)| {
+            (self.nodes.iter().position(|n| n.id == node_id), key)
+        });
+        for (node_id, key, variable) in unresolved {
+            diagnostics.push(DataflowDiagnostic::new(
+                Severity::Error,
+                DiagnosticKind::UnresolvedEnvPlaceholder,
+                node_id,
+                format!("env '{}' references unset variable '{}'", key, variable),
+            ));
+        }
+    }
+}
+
+/// Inputs the bridge for a MoFA node type handles, if it has a bridge.
+///
+/// `SystemLog` accepts any input (sources are discovered from the dataflow),
+/// so it is not checked.
+fn bridge_expected_inputs(node_type: MofaNodeType, node_id: &str) -> Option<Vec<String>> {
+    match node_type {
+        MofaNodeType::AudioPlayer => Some(AudioPlayerBridge::new(node_id).expected_inputs()),
+        MofaNodeType::PromptInput => Some(PromptInputBridge::new(node_id).expected_inputs()),
+        MofaNodeType::MicInput => Some(AecInputBridge::new(node_id).expected_inputs()),
+        MofaNodeType::MoFACast => Some(CastControllerBridge::new(node_id).expected_inputs()),
+        // These accept any input ID
+        MofaNodeType::SystemLog | MofaNodeType::ChatViewer | MofaNodeType::ParticipantPanel => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn parse(yaml: &str) -> ParsedDataflow {
+        crate::parser::DataflowParser::parse_string(yaml, PathBuf::from("test.yml")).unwrap()
+    }
+
+    fn kinds(diagnostics: &[DataflowDiagnostic]) -> Vec<(DiagnosticKind, &str)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.kind, d.node_id.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_dataflow_has_no_errors() {
+        let parsed = parse(
+            r#"
+nodes:
+  - id: tts
+    path: dora-primespeech
+    inputs:
+      tick: dora/timer/millis/100
+    outputs:
+      - audio
+      - log
+    env:
+      VOICE: ${VOICE_NAME:-Doubao}
+
+  - id: mofa-audio-player
+    path: dynamic
+    inputs:
+      audio:
+        source: tts/audio
+        queue_size: 1000
+"#,
+        );
+
+        let diagnostics = parsed.validate();
+        assert!(
+            diagnostics.iter().all(|d| !d.is_error()),
+            "{:?}",
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn test_missing_source_node_and_output() {
+        let parsed = parse(
+            r#"
+nodes:
+  - id: tts
+    path: dora-primespeech
+    outputs:
+      - audio
+
+  - id: asr
+    path: dora-asr
+    inputs:
+      audio: ttz/audio
+      text: tts/text
+      bad: tts
+"#,
+        );
+
+        let diagnostics = parsed.validate();
+        assert_eq!(
+            kinds(&diagnostics),
+            vec![
+                (DiagnosticKind::MissingSourceNode, "asr"),
+                (DiagnosticKind::MissingSourceOutput, "asr"),
+                (DiagnosticKind::MalformedSource, "asr"),
+            ]
+        );
+        assert!(diagnostics.iter().all(|d| d.is_error()));
+    }
+
+    #[test]
+    fn test_duplicate_node_ids() {
+        let parsed = parse(
+            r#"
+nodes:
+  - id: tts
+    path: dora-primespeech
+  - id: tts
+    path: dora-kokoro-tts
+  - id: tts
+    path: dora-kokoro-tts
+"#,
+        );
+
+        let diagnostics = parsed.validate();
+        assert_eq!(
+            kinds(&diagnostics),
+            vec![(DiagnosticKind::DuplicateNodeId, "tts")]
+        );
+    }
+
+    #[test]
+    fn test_bridge_input_mismatch() {
+        let parsed = parse(
+            r#"
+nodes:
+  - id: tts
+    path: dora-primespeech
+    outputs:
+      - audio
+
+  - id: mofa-audio-player
+    path: dynamic
+    inputs:
+      speech: tts/audio
+"#,
+        );
+
+        let diagnostics = parsed.validate();
+        assert_eq!(
+            kinds(&diagnostics),
+            vec![
+                (DiagnosticKind::UnexpectedBridgeInput, "mofa-audio-player"),
+                (DiagnosticKind::MissingBridgeInputs, "mofa-audio-player"),
+            ]
+        );
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[1].severity, Severity::Info);
+    }
+
+    #[test]
+    fn test_unresolved_env_placeholder() {
+        let parsed = parse(
+            r#"
+nodes:
+  - id: llm
+    path: dora-maas-client
+    env:
+      API_KEY: ${MOFA_VALIDATOR_TEST_UNSET_KEY}
+      MODEL: ${MOFA_VALIDATOR_TEST_MODEL:-gpt-4o}
+      BASE_URL: $MOFA_VALIDATOR_TEST_PROVIDED
+"#,
+        );
+
+        let diagnostics = parsed.validate();
+        assert_eq!(
+            kinds(&diagnostics),
+            vec![
+                (DiagnosticKind::UnresolvedEnvPlaceholder, "llm"),
+                (DiagnosticKind::UnresolvedEnvPlaceholder, "llm"),
+            ]
+        );
+
+        let mut env = HashMap::new();
+        env.insert(
+            "MOFA_VALIDATOR_TEST_UNSET_KEY".to_string(),
+            "sk-test".to_string(),
+        );
+        env.insert(
+            "MOFA_VALIDATOR_TEST_PROVIDED".to_string(),
+            "http://localhost".to_string(),
+        );
+        assert!(parsed.validate_with_env(&env).is_empty());
+    }
+}
@@ -12,11 +12,9 @@ use crate::bridge::{BridgeState, DoraBridge};
 use crate::data::DoraData;
 use crate::error::{BridgeError, BridgeResult};
 use crate::shared_state::SharedDoraState;
+use crate::transport::{BridgeEvent, DoraTransportFactory, NodeTransport, TransportFactory};
 use crossbeam_channel::{bounded, Receiver, Sender};
-use dora_node_api::{
-    dora_core::config::{DataId, NodeId},
-    DoraNode, Event, IntoArrow, Parameter,
-};
+use dora_node_api::{IntoArrow, Parameter};
 use libloading::{Library, Symbol};
 use parking_lot::RwLock;
 use std::collections::BTreeMap;
@@ -357,6 +355,7 @@ pub struct AecInputBridge {
     node_id: String,
     state: Arc<RwLock<BridgeState>>,
     shared_state: Option<Arc<SharedDoraState>>,
+    transport: Arc<dyn TransportFactory>,
     control_sender: Sender<AecControlCommand>,
     control_receiver: Receiver<AecControlCommand>,
     stop_sender: Option<Sender<()>>,
@@ -371,12 +370,21 @@ impl AecInputBridge {
     }
 
     pub fn with_shared_state(node_id: &str, shared_state: Option<Arc<SharedDoraState>>) -> Self {
+        Self::with_transport(node_id, shared_state, DoraTransportFactory::shared())
+    }
+
+    pub fn with_transport(
+        node_id: &str,
+        shared_state: Option<Arc<SharedDoraState>>,
+        transport: Arc<dyn TransportFactory>,
+    ) -> Self {
         let (control_tx, control_rx) = bounded(10);
 
         Self {
             node_id: node_id.to_string(),
             state: Arc::new(RwLock::new(BridgeState::Disconnected)),
             shared_state,
+            transport,
             control_sender: control_tx,
             control_receiver: control_rx,
             stop_sender: None,
@@ -435,10 +443,12 @@ impl AecInputBridge {
     }
 
     /// Run the event loop
+    #[allow(clippy::too_many_arguments)]
     fn run_event_loop(
         node_id: String,
         state: Arc<RwLock<BridgeState>>,
         shared_state: Option<Arc<SharedDoraState>>,
+        transport: Arc<dyn TransportFactory>,
         control_receiver: Receiver<AecControlCommand>,
         stop_receiver: Receiver<()>,
         is_recording: Arc<AtomicBool>,
@@ -486,21 +496,20 @@ impl AecInputBridge {
 
         // Initialize dora node
         eprintln!("[AecInput] Initializing dora node for {}", node_id);
-        let (mut node, mut events) =
-            match DoraNode::init_from_node_id(NodeId::from(node_id.clone())) {
-                Ok(n) => {
-                    eprintln!("[AecInput] Dora node init SUCCESS for {}", node_id);
-                    n
-                }
-                Err(e) => {
-                    eprintln!("[AecInput] FAILED to init dora node {}: {}", node_id, e);
-                    *state.write() = BridgeState::Error;
-                    if let Some(ref ss) = shared_state {
-                        ss.set_error(Some(format!("Dora init failed: {}", e)));
-                    }
-                    return;
+        let mut node = match transport.connect(&node_id) {
+            Ok(n) => {
+                eprintln!("[AecInput] Dora node init SUCCESS for {}", node_id);
+                n
+            }
+            Err(e) => {
+                eprintln!("[AecInput] FAILED to init dora node {}: {}", node_id, e);
+                *state.write() = BridgeState::Error;
+                if let Some(ref ss) = shared_state {
+                    ss.set_error(Some(format!("Dora init failed: {}", e)));
                 }
-            };
+                return;
+            }
+        };
 
         *state.write() = BridgeState::Connected;
         eprintln!("[AecInput] Bridge state set to CONNECTED for {}", node_id);
@@ -515,7 +524,7 @@ impl AecInputBridge {
 
         // Log config on startup (matching Python behavior)
         let _ = Self::send_log(
-            &mut node,
+            node.as_mut(),
             &node_id,
             "INFO",
             &format!(
@@ -526,7 +535,7 @@ impl AecInputBridge {
         let speech_end_ms = vad_state.speech_end_threshold * 10; // ~10ms per frame
         let total_silence_ms = speech_end_ms as f64 + vad_state.question_end_silence_ms;
         let _ = Self::send_log(
-            &mut node,
+            node.as_mut(),
             &node_id,
             "INFO",
             &format!(
@@ -543,12 +552,12 @@ impl AecInputBridge {
             if let Some(ref mut aec) = aec_capture {
                 aec.start();
             }
-            let _ = Self::send_log(&mut node, &node_id, "INFO", "🎙️ Recording started with AEC (echo cancellation ON)");
+            let _ = Self::send_log(node.as_mut(), &node_id, "INFO", "🎙️ Recording started with AEC (echo cancellation ON)");
         } else {
             if let Err(e) = cpal_capture.start() {
                 error!("Failed to start CPAL capture: {}", e);
             }
-            let _ = Self::send_log(&mut node, &node_id, "INFO", "🎙️ Recording started without AEC (regular mic)");
+            let _ = Self::send_log(node.as_mut(), &node_id, "INFO", "🎙️ Recording started without AEC (regular mic)");
         }
         is_recording.store(true, Ordering::Release);
         recording_active = true;
@@ -560,19 +569,21 @@ impl AecInputBridge {
         }
 
         let _ = Self::send_log(
-            &mut node,
+            node.as_mut(),
             &node_id,
             "INFO",
             "Node ready - outputting: audio, is_speaking, speech_started, speech_ended, audio_segment, question_ended",
         );
 
         // Send initial status
-        let _ = Self::send_status(&mut node, "recording");
-        let _ = Self::send_log(&mut node, &node_id, "INFO", "🎙️ Mic recording STARTED (auto-start on connect)");
+        let _ = Self::send_status(node.as_mut(), "recording");
+        let _ = Self::send_log(node.as_mut(), &node_id, "INFO", "🎙️ Mic recording STARTED (auto-start on connect)");
 
         // Main event loop
         let poll_interval = Duration::from_millis(10);
         let mut last_poll = Instant::now();
+        // Ends in Error if dora closes the event stream
+        let mut exit_state = BridgeState::Disconnected;
 
         loop {
             // Check for stop signal
@@ -592,19 +603,19 @@ impl AecInputBridge {
                                 if let Some(ref mut aec) = aec_capture {
                                     aec.start();
                                 }
-                                let _ = Self::send_log(&mut node, &node_id, "INFO", "🎙️ Recording STARTED with AEC");
+                                let _ = Self::send_log(node.as_mut(), &node_id, "INFO", "🎙️ Recording STARTED with AEC");
                             } else {
                                 if let Err(e) = cpal_capture.start() {
                                     error!("Failed to start CPAL: {}", e);
                                 }
-                                let _ = Self::send_log(&mut node, &node_id, "INFO", "🎙️ Recording STARTED without AEC");
+                                let _ = Self::send_log(node.as_mut(), &node_id, "INFO", "🎙️ Recording STARTED without AEC");
                             }
                             recording_active = true;
                             is_recording.store(true, Ordering::Release);
                             if let Some(ref ss) = shared_state {
                                 ss.mic.set_recording(true);
                             }
-                            let _ = Self::send_status(&mut node, "recording");
+                            let _ = Self::send_status(node.as_mut(), "recording");
                         }
                     }
                     AecControlCommand::StopRecording => {
@@ -619,8 +630,8 @@ impl AecInputBridge {
                             if let Some(ref ss) = shared_state {
                                 ss.mic.set_recording(false);
                             }
-                            let _ = Self::send_status(&mut node, "stopped");
-                            let _ = Self::send_log(&mut node, &node_id, "INFO", "🔇 Mic recording STOPPED");
+                            let _ = Self::send_status(node.as_mut(), "stopped");
+                            let _ = Self::send_log(node.as_mut(), &node_id, "INFO", "🔇 Mic recording STOPPED");
                         }
                     }
                     AecControlCommand::SetAecEnabled(enabled) => {
@@ -648,12 +659,12 @@ impl AecInputBridge {
                                     if let Some(ref mut aec) = aec_capture {
                                         aec.start();
                                     }
-                                    let _ = Self::send_log(&mut node, &node_id, "INFO", "🔄 Switched to AEC capture (echo cancellation ON)");
+                                    let _ = Self::send_log(node.as_mut(), &node_id, "INFO", "🔄 Switched to AEC capture (echo cancellation ON)");
                                 } else {
                                     if let Err(e) = cpal_capture.start() {
                                         error!("Failed to start CPAL: {}", e);
                                     }
-                                    let _ = Self::send_log(&mut node, &node_id, "INFO", "🔄 Switched to regular mic (echo cancellation OFF)");
+                                    let _ = Self::send_log(node.as_mut(), &node_id, "INFO", "🔄 Switched to regular mic (echo cancellation OFF)");
                                 }
                             }
                         }
@@ -731,19 +742,19 @@ impl AecInputBridge {
                 if question_ended {
                     let old_qid = vad_state.current_question_id;
                     let _ = Self::send_log(
-                        &mut node,
+                        node.as_mut(),
                         &node_id,
                         "INFO",
                         &format!("📤 SENDING question_ended with OLD question_id={}", old_qid),
                     );
-                    if let Err(e) = Self::send_question_ended(&mut node, old_qid) {
+                    if let Err(e) = Self::send_question_ended(node.as_mut(), old_qid) {
                         warn!("Failed to send question_ended: {}", e);
                     }
                     // Generate new question_id for next question
                     let new_qid = rand::random::<u32>() % 900000 + 100000;
                     vad_state.current_question_id = new_qid;
                     let _ = Self::send_log(
-                        &mut node,
+                        node.as_mut(),
                         &node_id,
                         "INFO",
                         &format!("🆕 GENERATED NEW question_id={} for NEXT question", new_qid),
@@ -761,7 +772,7 @@ impl AecInputBridge {
                 }
 
                 // Send continuous audio stream (matching Python behavior)
-                if let Err(e) = Self::send_audio(&mut node, &all_audio) {
+                if let Err(e) = Self::send_audio(node.as_mut(), &all_audio) {
                     warn!("Failed to send audio: {}", e);
                 }
 
@@ -847,14 +858,14 @@ impl AecInputBridge {
 
                 // Send dora outputs
                 if speech_started {
-                    if let Err(e) = Self::send_speech_started(&mut node) {
+                    if let Err(e) = Self::send_speech_started(node.as_mut()) {
                         warn!("Failed to send speech_started: {}", e);
                     }
-                    if let Err(e) = Self::send_is_speaking(&mut node, true) {
+                    if let Err(e) = Self::send_is_speaking(node.as_mut(), true) {
                         warn!("Failed to send is_speaking: {}", e);
                     }
                     let _ = Self::send_log(
-                        &mut node,
+                        node.as_mut(),
                         &node_id,
                         "INFO",
                         &format!(
@@ -865,14 +876,14 @@ impl AecInputBridge {
                 }
 
                 if speech_ended {
-                    if let Err(e) = Self::send_speech_ended(&mut node) {
+                    if let Err(e) = Self::send_speech_ended(node.as_mut()) {
                         warn!("Failed to send speech_ended: {}", e);
                     }
-                    if let Err(e) = Self::send_is_speaking(&mut node, false) {
+                    if let Err(e) = Self::send_is_speaking(node.as_mut(), false) {
                         warn!("Failed to send is_speaking: {}", e);
                     }
                     let _ = Self::send_log(
-                        &mut node,
+                        node.as_mut(),
                         &node_id,
                         "INFO",
                         &format!(
@@ -885,7 +896,7 @@ impl AecInputBridge {
                 // Send audio segment for ASR
                 if let Some(segment) = audio_segment {
                     if let Err(e) =
-                        Self::send_audio_segment(&mut node, &segment, vad_state.current_question_id)
+                        Self::send_audio_segment(node.as_mut(), &segment, vad_state.current_question_id)
                     {
                         warn!("Failed to send audio_segment: {}", e);
                     } else {
@@ -895,7 +906,7 @@ impl AecInputBridge {
                             vad_state.current_question_id
                         );
                         let _ = Self::send_log(
-                            &mut node,
+                            node.as_mut(),
                             &node_id,
                             "INFO",
                             &format!(
@@ -909,16 +920,21 @@ impl AecInputBridge {
             }
 
             // Handle dora events (control inputs)
-            match events.recv_timeout(Duration::from_millis(1)) {
-                Some(Event::Input { id, .. }) => {
-                    debug!("Received input: {}", id.as_str());
+            match node.recv_timeout(Duration::from_millis(1)) {
+                Some(BridgeEvent::Input { id, .. }) => {
+                    debug!("Received input: {}", id);
                     // Handle control inputs if needed
                 }
-                Some(Event::Stop(_)) => {
+                Some(BridgeEvent::Stop) => {
                     // Don't break on Stop - other bridges ignore it too
                     // Breaking causes immediate disconnect and retry loops
                     eprintln!("[AecInput] Received Stop event from dora (ignoring)");
                 }
+                Some(BridgeEvent::Closed) => {
+                    warn!("{} event stream closed", node_id);
+                    exit_state = BridgeState::Error;
+                    break;
+                }
                 _ => {}
             }
         }
@@ -930,8 +946,8 @@ impl AecInputBridge {
         }
         cpal_capture.stop();
         is_recording.store(false, Ordering::Release);
-        *state.write() = BridgeState::Disconnected;
-        eprintln!("[AecInput] State set to DISCONNECTED");
+        *state.write() = exit_state;
+        eprintln!("[AecInput] State set to {:?}", exit_state);
         if let Some(ref ss) = shared_state {
             ss.remove_bridge(&node_id);
             ss.mic.set_recording(false);
@@ -939,43 +955,36 @@ impl AecInputBridge {
         info!("AEC input bridge event loop ended");
     }
 
-    fn send_speech_started(node: &mut DoraNode) -> BridgeResult<()> {
+    fn send_speech_started(node: &mut dyn NodeTransport) -> BridgeResult<()> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_secs_f64())
             .unwrap_or(0.0);
         let data = vec![now].into_arrow();
-        let output_id: DataId = "speech_started".to_string().into();
-        node.send_output(output_id, BTreeMap::new(), data)
-            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+        node.send_output("speech_started", BTreeMap::new(), Arc::new(data))
     }
 
-    fn send_speech_ended(node: &mut DoraNode) -> BridgeResult<()> {
+    fn send_speech_ended(node: &mut dyn NodeTransport) -> BridgeResult<()> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_secs_f64())
             .unwrap_or(0.0);
         let data = vec![now].into_arrow();
-        let output_id: DataId = "speech_ended".to_string().into();
-        node.send_output(output_id, BTreeMap::new(), data)
-            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+        node.send_output("speech_ended", BTreeMap::new(), Arc::new(data))
     }
 
-    fn send_is_speaking(node: &mut DoraNode, speaking: bool) -> BridgeResult<()> {
+    fn send_is_speaking(node: &mut dyn NodeTransport, speaking: bool) -> BridgeResult<()> {
         // Convert bool to u8 since Vec<bool> doesn't implement IntoArrow
         let data = vec![speaking as u8].into_arrow();
-        let output_id: DataId = "is_speaking".to_string().into();
-        node.send_output(output_id, BTreeMap::new(), data)
-            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+        node.send_output("is_speaking", BTreeMap::new(), Arc::new(data))
     }
 
-    fn send_question_ended(node: &mut DoraNode, question_id: u32) -> BridgeResult<()> {
+    fn send_question_ended(node: &mut dyn NodeTransport, question_id: u32) -> BridgeResult<()> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_secs_f64())
             .unwrap_or(0.0);
         let data = vec![now].into_arrow();
-        let output_id: DataId = "question_ended".to_string().into();
 
         let mut params: BTreeMap<String, Parameter> = BTreeMap::new();
         params.insert(
@@ -983,17 +992,15 @@ impl AecInputBridge {
             Parameter::Integer(question_id as i64),
         );
 
-        node.send_output(output_id, params, data)
-            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+        node.send_output("question_ended", params, Arc::new(data))
     }
 
     fn send_audio_segment(
-        node: &mut DoraNode,
+        node: &mut dyn NodeTransport,
         samples: &[f32],
         question_id: u32,
     ) -> BridgeResult<()> {
         let data = samples.to_vec().into_arrow();
-        let output_id: DataId = "audio_segment".to_string().into();
 
         let mut params: BTreeMap<String, Parameter> = BTreeMap::new();
         params.insert(
@@ -1002,20 +1009,17 @@ impl AecInputBridge {
         );
         params.insert("sample_rate".to_string(), Parameter::Integer(16000));
 
-        node.send_output(output_id, params, data)
-            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+        node.send_output("audio_segment", params, Arc::new(data))
     }
 
     /// Send continuous audio stream (for recording/monitoring)
-    fn send_audio(node: &mut DoraNode, samples: &[f32]) -> BridgeResult<()> {
+    fn send_audio(node: &mut dyn NodeTransport, samples: &[f32]) -> BridgeResult<()> {
         let data = samples.to_vec().into_arrow();
-        let output_id: DataId = "audio".to_string().into();
-        node.send_output(output_id, BTreeMap::new(), data)
-            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+        node.send_output("audio", BTreeMap::new(), Arc::new(data))
     }
 
     /// Send log message to dora log output
-    fn send_log(node: &mut DoraNode, node_id: &str, level: &str, message: &str) -> BridgeResult<()> {
+    fn send_log(node: &mut dyn NodeTransport, node_id: &str, level: &str, message: &str) -> BridgeResult<()> {
         let log_entry = serde_json::json!({
             "level": level,
             "message": message,
@@ -1027,17 +1031,13 @@ impl AecInputBridge {
         });
         let log_str = log_entry.to_string();
         let data = vec![log_str].into_arrow();
-        let output_id: DataId = "log".to_string().into();
-        node.send_output(output_id, BTreeMap::new(), data)
-            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+        node.send_output("log", BTreeMap::new(), Arc::new(data))
     }
 
     /// Send status update (recording/stopped)
-    fn send_status(node: &mut DoraNode, status: &str) -> BridgeResult<()> {
+    fn send_status(node: &mut dyn NodeTransport, status: &str) -> BridgeResult<()> {
         let data = vec![status.to_string()].into_arrow();
-        let output_id: DataId = "status".to_string().into();
-        node.send_output(output_id, BTreeMap::new(), data)
-            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+        node.send_output("status", BTreeMap::new(), Arc::new(data))
     }
 }
 
@@ -1076,6 +1076,7 @@ impl DoraBridge for AecInputBridge {
         let node_id = self.node_id.clone();
         let state = Arc::clone(&self.state);
         let shared_state = self.shared_state.clone();
+        let transport = Arc::clone(&self.transport);
         let control_receiver = self.control_receiver.clone();
         let is_recording = Arc::clone(&self.is_recording);
         let aec_enabled = Arc::clone(&self.aec_enabled);
@@ -1085,6 +1086,7 @@ impl DoraBridge for AecInputBridge {
                 node_id,
                 state,
                 shared_state,
+                transport,
                 control_receiver,
                 stop_rx,
                 is_recording,
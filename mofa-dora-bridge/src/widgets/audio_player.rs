@@ -40,30 +40,37 @@
 //! After a reset, stale audio chunks (from the previous question) may still be
 //! in-flight in the Dora pipeline. Playing these would cause brief "garbled" audio.
 //!
-//! Smart reset prevents this by filtering incoming audio by `question_id`:
+//! Smart reset prevents this by filtering incoming audio by `question_id`.
+//! Turn IDs (`t1:<epoch>:<round>:<generation>:<speaker>`, see
+//! `mofa_stream::turn`) are compared by epoch and interrupt generation, so
+//! any later turn of the reset's generation is accepted whatever its round
+//! or speaker:
 //!
 //! ```text
 //! ┌─────────────────────────────────────────────────────────────────────┐
 //! │                     Smart Reset Flow                                │
 //! │                                                                     │
-//! │  State: AI speaking (question_id=5)                                 │
+//! │  State: AI speaking (question_id=t1:E:5:0:tutor)                    │
 //! │                                                                     │
 //! │  1. Human interrupts                                                │
-//! │  2. Controller sends reset with question_id=6 (new question)        │
+//! │  2. Controller sends reset with question_id=t1:E:6:1: (gen 1)       │
 //! │  3. Audio player:                                                   │
 //! │     a. Clears buffer (instant silence via force_mute)               │
 //! │     b. Sets filtering_mode = true                                   │
-//! │     c. Sets reset_question_id = "6"                                 │
+//! │     c. Sets reset_question_id = "t1:E:6:1:"                         │
 //! │                                                                     │
-//! │  4. Stale audio arrives (question_id=5)                             │
-//! │     → REJECTED (doesn't match reset_question_id)                    │
+//! │  4. Stale audio arrives (question_id=t1:E:5:0:tutor)                │
+//! │     → REJECTED (generation 0 < 1)                                   │
 //! │                                                                     │
-//! │  5. New audio arrives (question_id=6)                               │
+//! │  5. New audio arrives (question_id=t1:E:6:1:tutor)                  │
 //! │     → ACCEPTED, exits filtering_mode                                │
 //! │     → Normal playback resumes                                       │
 //! └─────────────────────────────────────────────────────────────────────┘
 //! ```
 //!
+//! Legacy numeric question IDs keep the old rule: only an identical
+//! `question_id` ends filtering.
+//!
 //! ### Reset Types
 //!
 //! | Reset Type | question_id | Behavior |
@@ -128,13 +135,12 @@ use crate::bridge::{BridgeState, DoraBridge};
 use crate::data::{AudioData, DoraData, EventMetadata};
 use crate::error::{BridgeError, BridgeResult};
 use crate::shared_state::SharedDoraState;
-use arrow::array::Array;
+use crate::transport::{BridgeEvent, DoraTransportFactory, NodeTransport, TransportFactory};
+use arrow::array::{Array, ArrayRef};
 use crossbeam_channel::{bounded, Receiver, Sender};
-use dora_node_api::{
-    dora_core::config::{DataId, NodeId},
-    DoraNode, Event, IntoArrow, Parameter,
-};
+use dora_node_api::{IntoArrow, Parameter};
 use parking_lot::RwLock;
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::thread;
 use tracing::{debug, error, info, warn};
@@ -143,6 +149,21 @@ use tracing::{debug, error, info, warn};
 // This is more accurate since it reflects what's actually being played,
 // not what's being received (which may be buffered ahead of playback)
 
+/// Per-connection playback bookkeeping owned by the worker thread
+#[derive(Debug, Default)]
+struct PlaybackSession {
+    /// question_ids we've sent session_start for, to avoid flooding the controller
+    session_start_sent_for: HashSet<String>,
+    /// Active participant tracking for LED visualization
+    active_participant: Option<String>,
+    active_switch_for: HashSet<String>,
+    /// Smart reset state (matches Python audio_player.py)
+    /// When reset arrives with question_id, we enter filtering_mode
+    /// and reject audio chunks until one arrives that isn't stale for it
+    filtering_mode: bool,
+    reset_question_id: Option<String>,
+}
+
 /// Audio player bridge - receives audio from dora, provides to widget
 ///
 /// Status updates (connected/disconnected/error) are communicated via SharedDoraState.
@@ -154,6 +175,8 @@ pub struct AudioPlayerBridge {
     state: Arc<RwLock<BridgeState>>,
     /// Shared state for direct UI communication
     shared_state: Option<Arc<SharedDoraState>>,
+    /// Connects the worker thread to dora
+    transport: Arc<dyn TransportFactory>,
     /// Buffer status sender from widget
     buffer_status_sender: Sender<f64>,
     /// Buffer status receiver for dora
@@ -172,12 +195,22 @@ impl AudioPlayerBridge {
 
     /// Create a new audio player bridge with shared state
     pub fn with_shared_state(node_id: &str, shared_state: Option<Arc<SharedDoraState>>) -> Self {
+        Self::with_transport(node_id, shared_state, DoraTransportFactory::shared())
+    }
+
+    /// Create a new audio player bridge with a custom transport (e.g. mock runtime)
+    pub fn with_transport(
+        node_id: &str,
+        shared_state: Option<Arc<SharedDoraState>>,
+        transport: Arc<dyn TransportFactory>,
+    ) -> Self {
         let (buffer_tx, buffer_rx) = bounded(10);
 
         Self {
             node_id: node_id.to_string(),
             state: Arc::new(RwLock::new(BridgeState::Disconnected)),
             shared_state,
+            transport,
             buffer_status_sender: buffer_tx,
             buffer_status_receiver: buffer_rx,
             stop_sender: None,
@@ -197,47 +230,34 @@ impl AudioPlayerBridge {
         node_id: String,
         state: Arc<RwLock<BridgeState>>,
         shared_state: Option<Arc<SharedDoraState>>,
+        transport: Arc<dyn TransportFactory>,
         buffer_status_receiver: Receiver<f64>,
         stop_receiver: Receiver<()>,
     ) {
         info!("Starting audio player bridge event loop for {}", node_id);
 
         // Initialize dora node
-        let (mut node, mut events) =
-            match DoraNode::init_from_node_id(NodeId::from(node_id.clone())) {
-                Ok(n) => n,
-                Err(e) => {
-                    error!("Failed to init dora node {}: {}", node_id, e);
-                    *state.write() = BridgeState::Error;
-                    if let Some(ref ss) = shared_state {
-                        ss.set_error(Some(format!("Init failed: {}", e)));
-                    }
-                    return;
+        let mut node = match transport.connect(&node_id) {
+            Ok(n) => n,
+            Err(e) => {
+                error!("Failed to init dora node {}: {}", node_id, e);
+                *state.write() = BridgeState::Error;
+                if let Some(ref ss) = shared_state {
+                    ss.set_error(Some(format!("Init failed: {}", e)));
                 }
-            };
+                return;
+            }
+        };
 
         *state.write() = BridgeState::Connected;
         if let Some(ref ss) = shared_state {
             ss.add_bridge(node_id.clone());
         }
 
-        // Session tracking - track which question_ids we've sent session_start for
-        // to avoid flooding the controller with duplicate signals
-        let mut session_start_sent_for: std::collections::HashSet<String> =
-            std::collections::HashSet::new();
+        let mut session = PlaybackSession::default();
 
-        // Active participant tracking for LED visualization
-        let mut active_participant: Option<String> = None;
-        let mut active_switch_for: std::collections::HashSet<String> =
-            std::collections::HashSet::new();
-
-        // Smart reset state (matches Python audio_player.py)
-        // When reset arrives with question_id, we enter filtering_mode
-        // and reject audio chunks until we receive one with matching question_id
-        let mut filtering_mode = false;
-        let mut reset_question_id: Option<String> = None;
-
-        // Event loop
+        // Event loop (ends in Error if dora closes the event stream)
+        let mut exit_state = BridgeState::Disconnected;
         loop {
             // Check for stop signal
             if stop_receiver.try_recv().is_ok() {
@@ -249,7 +269,7 @@ impl AudioPlayerBridge {
             // The actual buffer fill percentage comes from CircularAudioBuffer::fill_percentage()
             // in the UI layer, sent here via channel every 50ms
             while let Ok(status) = buffer_status_receiver.try_recv() {
-                if let Err(e) = Self::send_buffer_status_to_dora(&mut node, status) {
+                if let Err(e) = Self::send_buffer_status_to_dora(node.as_mut(), status) {
                     warn!("Failed to send buffer status: {}", e);
                 } else {
                     debug!("Buffer status: {:.1}%", status);
@@ -257,17 +277,18 @@ impl AudioPlayerBridge {
             }
 
             // Receive dora events with timeout
-            match events.recv_timeout(std::time::Duration::from_millis(100)) {
+            match node.recv_timeout(std::time::Duration::from_millis(100)) {
+                Some(BridgeEvent::Closed) => {
+                    warn!("{} event stream closed", node_id);
+                    exit_state = BridgeState::Error;
+                    break;
+                }
                 Some(event) => {
                     Self::handle_dora_event(
                         event,
-                        &mut node,
+                        node.as_mut(),
                         shared_state.as_ref(),
-                        &mut session_start_sent_for,
-                        &mut active_participant,
-                        &mut active_switch_for,
-                        &mut filtering_mode,
-                        &mut reset_question_id,
+                        &mut session,
                     );
                 }
                 None => {
@@ -276,7 +297,7 @@ impl AudioPlayerBridge {
             }
         }
 
-        *state.write() = BridgeState::Disconnected;
+        *state.write() = exit_state;
         if let Some(ref ss) = shared_state {
             ss.remove_bridge(&node_id);
         }
@@ -285,34 +306,27 @@ impl AudioPlayerBridge {
 
     /// Handle a dora event
     fn handle_dora_event(
-        event: Event,
-        node: &mut DoraNode,
+        event: BridgeEvent,
+        node: &mut dyn NodeTransport,
         shared_state: Option<&Arc<SharedDoraState>>,
-        session_start_sent_for: &mut std::collections::HashSet<String>,
-        active_participant: &mut Option<String>,
-        active_switch_for: &mut std::collections::HashSet<String>,
-        filtering_mode: &mut bool,
-        reset_question_id: &mut Option<String>,
+        session: &mut PlaybackSession,
     ) {
+        let PlaybackSession {
+            session_start_sent_for,
+            active_participant,
+            active_switch_for,
+            filtering_mode,
+            reset_question_id,
+        } = session;
+
         match event {
-            Event::Input { id, data, metadata } => {
+            BridgeEvent::Input {
+                id,
+                data,
+                metadata: event_meta,
+            } => {
                 let input_id = id.as_str();
 
-                // Extract metadata (handle all parameter types like conference-dashboard)
-                let mut event_meta = EventMetadata::default();
-                for (key, value) in metadata.parameters.iter() {
-                    let string_value = match value {
-                        Parameter::String(s) => s.clone(),
-                        Parameter::Integer(i) => i.to_string(),
-                        Parameter::Float(f) => f.to_string(),
-                        Parameter::Bool(b) => b.to_string(),
-                        Parameter::ListInt(l) => format!("{:?}", l),
-                        Parameter::ListFloat(l) => format!("{:?}", l),
-                        Parameter::ListString(l) => format!("{:?}", l),
-                    };
-                    event_meta.values.insert(key.clone(), string_value);
-                }
-
                 // Handle reset input - immediately clear audio buffer (human speaking interrupt)
                 // Smart reset: if question_id is provided, filter incoming audio until matching question_id arrives
                 if input_id == "reset" {
@@ -387,22 +401,24 @@ impl AudioPlayerBridge {
                         // Get question_id from metadata
                         let question_id = event_meta.get("question_id");
 
-                        // Smart reset filtering: reject stale audio until matching question_id arrives
+                        // Smart reset filtering: reject stale audio until a current question_id arrives
                         if *filtering_mode {
                             let incoming_qid = question_id.map(|s| s.to_string());
                             let expected_qid = reset_question_id.as_ref();
 
                             match (&incoming_qid, expected_qid) {
-                                (Some(incoming), Some(expected)) if incoming == expected => {
-                                    // First chunk with matching question_id - exit filtering mode
+                                (Some(incoming), Some(expected))
+                                    if !mofa_stream::turn::is_stale(incoming, expected) =>
+                                {
+                                    // First chunk from the reset's generation - exit filtering mode
                                     *filtering_mode = false;
                                     info!(
-                                        "✅ Exiting filtering mode: received matching question_id={} from {}",
+                                        "✅ Exiting filtering mode: received current question_id={} from {}",
                                         incoming, participant_id
                                     );
                                 }
                                 (Some(incoming), Some(expected)) => {
-                                    // Reject stale audio - question_id doesn't match
+                                    // Reject stale audio - question_id predates the reset
                                     debug!(
                                         "🚫 Filtering out stale audio from {} (question_id={}, expected={})",
                                         participant_id, incoming, expected
@@ -496,7 +512,12 @@ impl AudioPlayerBridge {
                         // Send audio_complete signal back to text-segmenter
                         // This allows the next segment to be released
                         // CRITICAL: This must be sent for every audio chunk to keep the pipeline flowing
-                        if let Err(e) = Self::send_audio_complete(node, input_id, &event_meta) {
+                        if let Err(e) = Self::send_audio_complete(
+                            node,
+                            input_id,
+                            &event_meta,
+                            audio_data.duration_secs(),
+                        ) {
                             warn!("Failed to send audio_complete: {}", e);
                         } else {
                             debug!(
@@ -508,7 +529,7 @@ impl AudioPlayerBridge {
                     }
                 }
             }
-            Event::Stop(_) => {
+            BridgeEvent::Stop => {
                 info!("Received stop event from dora");
             }
             _ => {}
@@ -522,9 +543,10 @@ impl AudioPlayerBridge {
     /// Send audio_complete signal to notify text-segmenter that audio was received
     /// Matches conference-dashboard's implementation for compatibility
     fn send_audio_complete(
-        node: &mut DoraNode,
+        node: &mut dyn NodeTransport,
         input_id: &str,
         metadata: &EventMetadata,
+        audio_seconds: f32,
     ) -> BridgeResult<()> {
         use std::collections::BTreeMap;
 
@@ -537,6 +559,11 @@ impl AudioPlayerBridge {
             "participant".to_string(),
             Parameter::String(participant.to_string()),
         );
+        // Played duration, so the controller can balance actual speaking time
+        params.insert(
+            "audio_seconds".to_string(),
+            Parameter::Float(audio_seconds as f64),
+        );
 
         // Include question_id if present in incoming metadata
         if let Some(qid) = metadata.get("question_id") {
@@ -556,7 +583,6 @@ impl AudioPlayerBridge {
 
         // Use vec!["received"] format to match conference-dashboard
         let data = vec!["received".to_string()].into_arrow();
-        let output_id: DataId = "audio_complete".to_string().into();
 
         debug!(
             "Sending audio_complete for participant: {} (question_id={:?}, session_status={:?})",
@@ -565,14 +591,13 @@ impl AudioPlayerBridge {
             metadata.get("session_status")
         );
 
-        node.send_output(output_id, params, data)
-            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+        node.send_output("audio_complete", params, Arc::new(data))
     }
 
     /// Send session_start signal to notify conference-controller that audio playback has begun
     /// This is critical for the controller to advance to the next speaker
     fn send_session_start(
-        node: &mut DoraNode,
+        node: &mut dyn NodeTransport,
         input_id: &str,
         metadata: &EventMetadata,
     ) -> BridgeResult<()> {
@@ -612,7 +637,6 @@ impl AudioPlayerBridge {
 
         // Use vec!["audio_started"] format to match conference-dashboard
         let data = vec!["audio_started".to_string()].into_arrow();
-        let output_id: DataId = "session_start".to_string().into();
 
         info!(
             "Sending session_start for participant: {} (question_id={:?})",
@@ -620,20 +644,15 @@ impl AudioPlayerBridge {
             metadata.get("question_id")
         );
 
-        node.send_output(output_id, params, data)
-            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+        node.send_output("session_start", params, Arc::new(data))
     }
 
     /// Extract audio data from dora arrow data
     /// Handles multiple formats: Float32, Float64, Int16, ListArray, LargeListArray
-    fn extract_audio(
-        data: &dora_node_api::ArrowData,
-        metadata: &EventMetadata,
-    ) -> Option<AudioData> {
+    fn extract_audio(array: &ArrayRef, metadata: &EventMetadata) -> Option<AudioData> {
         use arrow::array::{Float32Array, Float64Array, Int16Array, LargeListArray, ListArray};
         use arrow::datatypes::DataType;
 
-        let array = &data.0;
         if array.is_empty() {
             return None;
         }
@@ -741,11 +760,9 @@ impl AudioPlayerBridge {
     }
 
     /// Send buffer status to dora
-    fn send_buffer_status_to_dora(node: &mut DoraNode, status: f64) -> BridgeResult<()> {
+    fn send_buffer_status_to_dora(node: &mut dyn NodeTransport, status: f64) -> BridgeResult<()> {
         let data = vec![status].into_arrow();
-        let output_id: DataId = "buffer_status".to_string().into();
-        node.send_output(output_id, Default::default(), data)
-            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+        node.send_output("buffer_status", Default::default(), Arc::new(data))
     }
 }
 
@@ -771,10 +788,18 @@ impl DoraBridge for AudioPlayerBridge {
         let node_id = self.node_id.clone();
         let state = Arc::clone(&self.state);
         let shared_state = self.shared_state.clone();
+        let transport = Arc::clone(&self.transport);
         let buffer_receiver = self.buffer_status_receiver.clone();
 
         let handle = thread::spawn(move || {
-            Self::run_event_loop(node_id, state, shared_state, buffer_receiver, stop_rx);
+            Self::run_event_loop(
+                node_id,
+                state,
+                shared_state,
+                transport,
+                buffer_receiver,
+                stop_rx,
+            );
         });
 
         self.worker_handle = Some(handle);
@@ -866,3 +891,124 @@ impl Drop for AudioPlayerBridge {
         let _ = self.disconnect();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MockDoraRuntime;
+    use arrow::array::{Float32Array, StringArray};
+
+    fn audio(participant: &str, question_id: &str) -> BridgeEvent {
+        BridgeEvent::input(
+            format!("audio_{}", participant),
+            Float32Array::from(vec![0.1, 0.2, 0.3]),
+            &[("question_id", question_id), ("sample_rate", "24000")],
+        )
+    }
+
+    fn reset(question_id: &str) -> BridgeEvent {
+        BridgeEvent::input(
+            "reset",
+            StringArray::from(vec!["reset"]),
+            &[("question_id", question_id)],
+        )
+    }
+
+    #[test]
+    fn test_smart_reset_filters_stale_audio() {
+        let runtime = MockDoraRuntime::new();
+        let handle = runtime.node("mofa-audio-player");
+        let mut transport = handle.transport();
+        let state = SharedDoraState::new();
+        let mut session = PlaybackSession::default();
+
+        let mut feed = |event| {
+            AudioPlayerBridge::handle_dora_event(event, &mut transport, Some(&state), &mut session)
+        };
+
+        feed(audio("tutor", "1"));
+        assert_eq!(state.audio.drain().len(), 1);
+
+        feed(reset("2"));
+        assert!(state.audio.take_clear_signal());
+
+        // Stale chunk from the interrupted question is dropped without an ack
+        handle.take_sent();
+        feed(audio("tutor", "1"));
+        assert!(state.audio.drain().is_empty());
+        assert!(handle.sent().is_empty());
+
+        // First chunk for the new question ends filtering
+        feed(audio("student1", "2"));
+        feed(audio("student1", "1"));
+        let played = state.audio.drain();
+        assert_eq!(played.len(), 2);
+        assert_eq!(played[0].participant_id.as_deref(), Some("student1"));
+        assert_eq!(played[0].question_id.as_deref(), Some("2"));
+        assert_eq!(played[0].sample_rate, 24000);
+    }
+
+    #[test]
+    fn test_smart_reset_compares_turn_generations() {
+        let runtime = MockDoraRuntime::new();
+        let handle = runtime.node("mofa-audio-player");
+        let mut transport = handle.transport();
+        let state = SharedDoraState::new();
+        let mut session = PlaybackSession::default();
+
+        let mut feed = |event| {
+            AudioPlayerBridge::handle_dora_event(event, &mut transport, Some(&state), &mut session)
+        };
+
+        feed(audio("tutor", "t1:100:4:0:tutor"));
+        assert_eq!(state.audio.drain().len(), 1);
+
+        // Human interrupt: reset carries the next generation, no speaker yet
+        feed(reset("t1:100:5:1:"));
+        assert!(state.audio.take_clear_signal());
+
+        // Later rounds of the interrupted generation are still stale
+        feed(audio("student1", "t1:100:5:0:student1"));
+        assert!(state.audio.drain().is_empty());
+
+        // The new generation's first turn ends filtering, though its
+        // speaker differs from the reset's
+        feed(audio("tutor", "t1:100:5:1:tutor"));
+        feed(audio("student1", "t1:100:6:1:student1"));
+        let played = state.audio.drain();
+        assert_eq!(played.len(), 2);
+        assert_eq!(played[0].question_id.as_deref(), Some("t1:100:5:1:tutor"));
+    }
+
+    #[test]
+    fn test_session_start_sent_once_per_question() {
+        let runtime = MockDoraRuntime::new();
+        let handle = runtime.node("mofa-audio-player");
+        let mut transport = handle.transport();
+        let state = SharedDoraState::new();
+        let mut session = PlaybackSession::default();
+
+        for _ in 0..3 {
+            AudioPlayerBridge::handle_dora_event(
+                audio("tutor", "5"),
+                &mut transport,
+                Some(&state),
+                &mut session,
+            );
+        }
+
+        let starts = handle.sent_on("session_start");
+        assert_eq!(starts.len(), 1);
+        assert_eq!(starts[0].string_param("question_id"), Some("5"));
+        assert_eq!(starts[0].string_param("participant"), Some("tutor"));
+
+        let acks = handle.sent_on("audio_complete");
+        assert_eq!(acks.len(), 3);
+        assert_eq!(acks[0].string_param("participant"), Some("tutor"));
+        // 3 mono samples at 24 kHz
+        match acks[0].parameters.get("audio_seconds") {
+            Some(Parameter::Float(secs)) => assert!((secs - 3.0 / 24000.0).abs() < 1e-9),
+            other => panic!("unexpected audio_seconds: {:?}", other),
+        }
+    }
+}
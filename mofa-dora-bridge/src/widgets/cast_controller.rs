@@ -7,9 +7,10 @@ use crate::bridge::{BridgeState, DoraBridge};
 use crate::data::{AudioData, DoraData, EventMetadata, LogEntry, LogLevel};
 use crate::error::{BridgeError, BridgeResult};
 use crate::shared_state::SharedDoraState;
-use arrow::array::Array;
+use crate::transport::{BridgeEvent, DoraTransportFactory, NodeTransport, TransportFactory};
+use arrow::array::{Array, ArrayRef};
 use crossbeam_channel::{bounded, Receiver, Sender};
-use dora_node_api::{DoraNode, Event, IntoArrow, dora_core::config::DataId, dora_core::config::NodeId};
+use dora_node_api::IntoArrow;
 use parking_lot::RwLock;
 use std::sync::Arc;
 use std::thread;
@@ -26,6 +27,8 @@ pub struct CastControllerBridge {
     state: Arc<RwLock<BridgeState>>,
     /// Shared state for direct UI communication
     shared_state: Option<Arc<SharedDoraState>>,
+    /// Connects the worker thread to dora
+    transport: Arc<dyn TransportFactory>,
     /// Text segment sender from widget
     text_sender: Sender<String>,
     /// Text segment receiver for dora
@@ -46,12 +49,18 @@ impl CastControllerBridge {
 
     /// Create a new cast controller bridge with shared state
     pub fn with_shared_state(node_id: &str, shared_state: Option<Arc<SharedDoraState>>) -> Self {
+        Self::with_transport(node_id, shared_state, DoraTransportFactory::shared())
+    }
+
+    /// Create a new cast controller bridge with a custom transport (e.g. mock runtime)
+    pub fn with_transport(node_id: &str, shared_state: Option<Arc<SharedDoraState>>, transport: Arc<dyn TransportFactory>) -> Self {
         let (text_tx, text_rx) = bounded(100);
 
         Self {
             node_id: node_id.to_string(),
             state: Arc::new(RwLock::new(BridgeState::Disconnected)),
             shared_state,
+            transport,
             text_sender: text_tx,
             text_receiver: text_rx,
             stop_sender: None,
@@ -72,6 +81,7 @@ impl CastControllerBridge {
         node_id: String,
         state: Arc<RwLock<BridgeState>>,
         shared_state: Option<Arc<SharedDoraState>>,
+        transport: Arc<dyn TransportFactory>,
         text_receiver: Receiver<String>,
         stop_receiver: Option<Receiver<()>>,
         current_speaker: Arc<RwLock<Option<String>>>,
@@ -79,7 +89,7 @@ impl CastControllerBridge {
         info!("Starting cast controller bridge event loop for {}", node_id);
 
         // Initialize dora node
-        let (mut node, mut events) = match DoraNode::init_from_node_id(NodeId::from(node_id.clone())) {
+        let mut node = match transport.connect(&node_id) {
             Ok(n) => {
                 info!("Dora node initialized successfully for {}", node_id);
                 n
@@ -104,8 +114,9 @@ impl CastControllerBridge {
             ss.status.set(status);
         }
 
-        // Event loop
+        // Event loop (ends in Error if dora closes the event stream)
         info!("Cast controller bridge event loop starting");
+        let mut exit_state = BridgeState::Disconnected;
         loop {
             // Check for stop signal
             if let Some(ref rx) = stop_receiver {
@@ -128,7 +139,7 @@ impl CastControllerBridge {
                 }
 
                 info!("Sending text segment to dora ({} chars)", text.len());
-                if let Err(e) = Self::send_text_to_dora(&mut node, &text) {
+                if let Err(e) = Self::send_text_to_dora(node.as_mut(), &text) {
                     error!("Failed to send text: {}", e);
                 } else {
                     info!("Text segment sent successfully");
@@ -143,7 +154,12 @@ impl CastControllerBridge {
             }
 
             // Receive dora events with timeout (100ms to avoid excessive timeout errors)
-            match events.recv_timeout(std::time::Duration::from_millis(100)) {
+            match node.recv_timeout(std::time::Duration::from_millis(100)) {
+                Some(BridgeEvent::Closed) => {
+                    warn!("{} event stream closed", node_id);
+                    exit_state = BridgeState::Error;
+                    break;
+                }
                 Some(event) => {
                     Self::handle_dora_event(event, shared_state.as_ref(), current_speaker.clone());
                 }
@@ -155,7 +171,7 @@ impl CastControllerBridge {
         }
 
         info!("Cast controller bridge event loop ended for {}", node_id);
-        *state.write() = BridgeState::Disconnected;
+        *state.write() = exit_state;
         if let Some(ref ss) = shared_state {
             let mut status = ss.status.read();
             status.active_bridges.retain(|id| id != &node_id);
@@ -164,36 +180,22 @@ impl CastControllerBridge {
     }
 
     /// Send text segment to dora node
-    fn send_text_to_dora(node: &mut DoraNode, text: &str) -> Result<(), String> {
+    fn send_text_to_dora(node: &mut dyn NodeTransport, text: &str) -> Result<(), String> {
         debug!("Sending text to dora: {} chars", text.len());
 
         // Send text data
         let data = text.to_string().into_arrow();
-        let output_id: DataId = "text".to_string().into();
-        node.send_output(output_id, Default::default(), data)
+        node.send_output("text", Default::default(), Arc::new(data))
             .map_err(|e| format!("Failed to send text: {}", e))
     }
 
     /// Handle incoming event from dora
-    fn handle_dora_event(event: Event, shared_state: Option<&Arc<SharedDoraState>>, current_speaker: Arc<RwLock<Option<String>>>) {
+    fn handle_dora_event(event: BridgeEvent, shared_state: Option<&Arc<SharedDoraState>>, current_speaker: Arc<RwLock<Option<String>>>) {
         match event {
-            Event::Input { id, data, metadata } => {
+            BridgeEvent::Input { id, data, metadata: event_meta } => {
                 let input_id = id.as_str();
                 info!("Received input: id={}", input_id);
 
-                // Extract metadata
-                let mut event_meta = EventMetadata::default();
-                for (key, value) in metadata.parameters.iter() {
-                    let string_value = match value {
-                        dora_node_api::Parameter::String(s) => s.clone(),
-                        dora_node_api::Parameter::Integer(i) => i.to_string(),
-                        dora_node_api::Parameter::Float(f) => f.to_string(),
-                        dora_node_api::Parameter::Bool(b) => b.to_string(),
-                        _ => "".to_string(),
-                    };
-                    event_meta.values.insert(key.clone(), string_value);
-                }
-
                 // Parse input data
                 match input_id {
                     input_id if input_id.starts_with("audio") || input_id == "audio" => {
@@ -235,10 +237,10 @@ impl CastControllerBridge {
                                     ));
                                 }
                                 None => {
-                                    warn!("Failed to extract log text, data_type: {:?}", data.0.data_type());
+                                    warn!("Failed to extract log text, data_type: {:?}", data.data_type());
                                     // Try to print raw data for debugging
-                                    if data.0.len() > 0 {
-                                        warn!("Log data length: {}", data.0.len());
+                                    if data.len() > 0 {
+                                        warn!("Log data length: {}", data.len());
                                     }
                                 }
                             }
@@ -249,23 +251,22 @@ impl CastControllerBridge {
                     }
                 }
             }
-            Event::Stop { .. } => {
+            BridgeEvent::Stop => {
                 info!("Dora node stopped");
                 // Status update is handled by run_event_loop
             }
             _ => {
-                // Ignore all other events (including Event::Error, Event::InputClosed)
+                // Ignore all other events (including BridgeEvent::Error, BridgeEvent::InputClosed)
                 // Match PromptInputBridge behavior - these errors are typically transient
             }
         }
     }
 
     /// Extract audio data from dora arrow data
-    fn extract_audio(data: &dora_node_api::ArrowData, metadata: &EventMetadata, speaker: Option<String>) -> Option<AudioData> {
-        use arrow::array::{Float32Array, Float64Array, Int16Array, Array};
+    fn extract_audio(array: &ArrayRef, metadata: &EventMetadata, speaker: Option<String>) -> Option<AudioData> {
+        use arrow::array::{Float32Array, Float64Array, Int16Array};
         use arrow::datatypes::DataType;
 
-        let array = &data.0;
         if array.is_empty() {
             return None;
         }
@@ -356,22 +357,22 @@ impl CastControllerBridge {
     }
 
     /// Extract text data from arrow array
-    fn extract_text(data: &dora_node_api::ArrowData) -> Option<String> {
-        match data.0.data_type() {
+    fn extract_text(data: &ArrayRef) -> Option<String> {
+        match data.data_type() {
             arrow::datatypes::DataType::Utf8 => {
-                let array = data.0.as_any().downcast_ref::<arrow::array::StringArray>()?;
+                let array = data.as_any().downcast_ref::<arrow::array::StringArray>()?;
                 if array.len() > 0 {
                     return Some(array.value(0).to_string());
                 }
             }
             arrow::datatypes::DataType::LargeUtf8 => {
-                let array = data.0.as_any().downcast_ref::<arrow::array::LargeStringArray>()?;
+                let array = data.as_any().downcast_ref::<arrow::array::LargeStringArray>()?;
                 if array.len() > 0 {
                     return Some(array.value(0).to_string());
                 }
             }
             _ => {
-                debug!("Unsupported text data type: {:?}", data.0.data_type());
+                debug!("Unsupported text data type: {:?}", data.data_type());
             }
         }
         None
@@ -414,11 +415,12 @@ impl DoraBridge for CastControllerBridge {
         let node_id = self.node_id.clone();
         let state = Arc::clone(&self.state);
         let shared_state = self.shared_state.clone();
+        let transport = Arc::clone(&self.transport);
         let text_receiver = self.text_receiver.clone();
         let current_speaker = Arc::clone(&self.current_speaker);
 
         let handle = thread::spawn(move || {
-            Self::run_event_loop(node_id, state, shared_state, text_receiver, Some(stop_rx), current_speaker);
+            Self::run_event_loop(node_id, state, shared_state, transport, text_receiver, Some(stop_rx), current_speaker);
         });
 
         self.worker_handle = Some(handle);
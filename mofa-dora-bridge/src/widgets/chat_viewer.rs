@@ -0,0 +1,398 @@
+//! Chat viewer bridge
+//!
+//! Connects to dora as `mofa-chat-viewer` dynamic node.
+//! Read-only counterpart of the prompt input bridge: it receives text from
+//! any number of LLM outputs and pushes them to `SharedDoraState.chat`,
+//! without wiring up prompt sending.
+//!
+//! # Sender Resolution
+//!
+//! 1. `sender`, `participant` or `speaker` metadata, if present
+//! 2. Input ID with the `text`/`response` part stripped (`tutor_text` → "Tutor")
+//! 3. "Assistant" for bare `text` / `response` inputs
+//!
+//! # Streaming
+//!
+//! A terminal stream status (`ended`, `error`, `cancelled` or `reset`, see
+//! [`mofa_stream::is_complete`]) finishes a message; anything else keeps it
+//! streaming so [`ChatState`](crate::ChatState) accumulates chunks.
+//! Chunks are grouped by `question_id` (or `session_id`) metadata, falling back
+//! to the input ID so consecutive streams from one LLM still consolidate.
+
+use crate::bridge::{BridgeState, DoraBridge};
+use crate::data::{current_timestamp, ChatMessage, DoraData, EventMetadata, MessageRole};
+use crate::error::{BridgeError, BridgeResult};
+use crate::shared_state::SharedDoraState;
+use crate::transport::{BridgeEvent, DoraTransportFactory, TransportFactory};
+use arrow::array::{Array, ArrayRef};
+use crossbeam_channel::{bounded, Receiver, Sender};
+use parking_lot::RwLock;
+use std::sync::Arc;
+use std::thread;
+use tracing::{debug, error, info, warn};
+
+/// Metadata keys checked (in order) for the message sender
+const SENDER_KEYS: [&str; 3] = ["sender", "participant", "speaker"];
+
+/// Chat viewer bridge - displays LLM text outputs as a conversation
+///
+/// Status updates (connected/disconnected/error) are communicated via SharedDoraState.
+/// Chat messages are pushed directly to SharedDoraState.chat for UI consumption.
+pub struct ChatViewerBridge {
+    /// Node ID (e.g., "mofa-chat-viewer")
+    node_id: String,
+    /// Current state
+    state: Arc<RwLock<BridgeState>>,
+    /// Shared state for direct UI communication
+    shared_state: Option<Arc<SharedDoraState>>,
+    /// Connects the worker thread to dora
+    transport: Arc<dyn TransportFactory>,
+    /// Stop signal
+    stop_sender: Option<Sender<()>>,
+    /// Worker thread handle
+    worker_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl ChatViewerBridge {
+    /// Create a new chat viewer bridge (legacy - without shared state)
+    pub fn new(node_id: &str) -> Self {
+        Self::with_shared_state(node_id, None)
+    }
+
+    /// Create a new chat viewer bridge with shared state
+    pub fn with_shared_state(node_id: &str, shared_state: Option<Arc<SharedDoraState>>) -> Self {
+        Self::with_transport(node_id, shared_state, DoraTransportFactory::shared())
+    }
+
+    /// Create a new chat viewer bridge with a custom transport (e.g. mock runtime)
+    pub fn with_transport(
+        node_id: &str,
+        shared_state: Option<Arc<SharedDoraState>>,
+        transport: Arc<dyn TransportFactory>,
+    ) -> Self {
+        Self {
+            node_id: node_id.to_string(),
+            state: Arc::new(RwLock::new(BridgeState::Disconnected)),
+            shared_state,
+            transport,
+            stop_sender: None,
+            worker_handle: None,
+        }
+    }
+
+    /// Run the dora event loop in background thread
+    fn run_event_loop(
+        node_id: String,
+        state: Arc<RwLock<BridgeState>>,
+        shared_state: Option<Arc<SharedDoraState>>,
+        transport: Arc<dyn TransportFactory>,
+        stop_receiver: Receiver<()>,
+    ) {
+        info!("Starting chat viewer bridge event loop for {}", node_id);
+
+        // Initialize dora node
+        let mut node = match transport.connect(&node_id) {
+            Ok(n) => n,
+            Err(e) => {
+                error!("Failed to init dora node {}: {}", node_id, e);
+                *state.write() = BridgeState::Error;
+                if let Some(ref ss) = shared_state {
+                    ss.set_error(Some(format!("Init failed: {}", e)));
+                }
+                return;
+            }
+        };
+
+        *state.write() = BridgeState::Connected;
+        if let Some(ref ss) = shared_state {
+            ss.add_bridge(node_id.clone());
+        }
+
+        // Event loop (ends in Error if dora closes the event stream)
+        let mut exit_state = BridgeState::Disconnected;
+        loop {
+            // Check for stop signal
+            if stop_receiver.try_recv().is_ok() {
+                info!("Chat viewer bridge received stop signal");
+                break;
+            }
+
+            // Receive dora events with timeout
+            match node.recv_timeout(std::time::Duration::from_millis(100)) {
+                Some(BridgeEvent::Closed) => {
+                    warn!("{} event stream closed", node_id);
+                    exit_state = BridgeState::Error;
+                    break;
+                }
+                Some(event) => Self::handle_dora_event(event, shared_state.as_ref()),
+                None => {}
+            }
+        }
+
+        *state.write() = exit_state;
+        if let Some(ref ss) = shared_state {
+            ss.remove_bridge(&node_id);
+        }
+        info!("Chat viewer bridge event loop ended");
+    }
+
+    /// Handle a dora event
+    fn handle_dora_event(event: BridgeEvent, shared_state: Option<&Arc<SharedDoraState>>) {
+        match event {
+            BridgeEvent::Input { id, data, metadata } => {
+                let Some(msg) = Self::extract_message(&id, &data, &metadata) else {
+                    debug!("Ignoring non-text input on chat viewer: {}", id);
+                    return;
+                };
+
+                // ChatState.push() handles streaming consolidation internally
+                if let Some(ss) = shared_state {
+                    ss.chat.push(msg);
+                }
+            }
+            BridgeEvent::Stop => {
+                info!("Received stop event from dora");
+            }
+            _ => {}
+        }
+    }
+
+    /// Build a chat message from an input event
+    fn extract_message(
+        input_id: &str,
+        data: &ArrayRef,
+        metadata: &EventMetadata,
+    ) -> Option<ChatMessage> {
+        let content = Self::extract_string(data)?;
+
+        let sender = SENDER_KEYS
+            .iter()
+            .find_map(|key| metadata.get(key))
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| Self::sender_from_input_id(input_id));
+
+        let session_id = metadata
+            .get("question_id")
+            .or_else(|| metadata.get("session_id"))
+            .unwrap_or(input_id)
+            .to_string();
+
+        // Any terminal status (ended, error, cancelled, reset) finishes the message
+        let is_complete = mofa_stream::is_complete(&metadata.values);
+
+        Some(ChatMessage {
+            content,
+            sender,
+            role: MessageRole::Assistant,
+            timestamp: current_timestamp(),
+            is_streaming: !is_complete,
+            session_id: Some(session_id),
+        })
+    }
+
+    /// Derive a display name from the input ID (e.g., "student1_text" -> "Student1")
+    fn sender_from_input_id(input_id: &str) -> String {
+        let name = input_id
+            .split('_')
+            .filter(|part| !matches!(*part, "text" | "response"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => "Assistant".to_string(),
+        }
+    }
+
+    /// Extract string from arrow data
+    fn extract_string(data: &ArrayRef) -> Option<String> {
+        match data.data_type() {
+            arrow::datatypes::DataType::Utf8 => {
+                let array = data.as_any().downcast_ref::<arrow::array::StringArray>()?;
+                if array.len() > 0 {
+                    return Some(array.value(0).to_string());
+                }
+            }
+            arrow::datatypes::DataType::LargeUtf8 => {
+                let array = data
+                    .as_any()
+                    .downcast_ref::<arrow::array::LargeStringArray>()?;
+                if array.len() > 0 {
+                    return Some(array.value(0).to_string());
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+impl DoraBridge for ChatViewerBridge {
+    fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    fn state(&self) -> BridgeState {
+        *self.state.read()
+    }
+
+    fn connect(&mut self) -> BridgeResult<()> {
+        if self.is_connected() {
+            return Err(BridgeError::AlreadyConnected);
+        }
+
+        *self.state.write() = BridgeState::Connecting;
+
+        let (stop_tx, stop_rx) = bounded(1);
+        self.stop_sender = Some(stop_tx);
+
+        let node_id = self.node_id.clone();
+        let state = Arc::clone(&self.state);
+        let shared_state = self.shared_state.clone();
+        let transport = Arc::clone(&self.transport);
+
+        let handle = thread::spawn(move || {
+            Self::run_event_loop(node_id, state, shared_state, transport, stop_rx);
+        });
+
+        self.worker_handle = Some(handle);
+
+        // Wait briefly for connection
+        std::thread::sleep(std::time::Duration::from_millis(200));
+
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> BridgeResult<()> {
+        if let Some(stop_tx) = self.stop_sender.take() {
+            let _ = stop_tx.send(());
+        }
+
+        if let Some(handle) = self.worker_handle.take() {
+            // Wait with timeout to avoid blocking indefinitely
+            let timeout = std::time::Duration::from_secs(2);
+            let start = std::time::Instant::now();
+
+            loop {
+                if start.elapsed() > timeout {
+                    warn!("Chat viewer bridge disconnect timeout after {:?}", timeout);
+                    break;
+                }
+
+                if handle.is_finished() {
+                    let _ = handle.join();
+                    break;
+                }
+
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+        }
+
+        *self.state.write() = BridgeState::Disconnected;
+        Ok(())
+    }
+
+    fn send(&self, _output_id: &str, _data: DoraData) -> BridgeResult<()> {
+        // Chat viewer is read-only
+        Ok(())
+    }
+
+    fn expected_inputs(&self) -> Vec<String> {
+        // Any text input is accepted; these are just the conventional names
+        vec!["text".to_string(), "response".to_string()]
+    }
+
+    fn expected_outputs(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+impl Drop for ChatViewerBridge {
+    fn drop(&mut self) {
+        let _ = self.disconnect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MockDoraRuntime;
+    use arrow::array::{Float32Array, StringArray};
+    use std::time::Duration;
+
+    #[test]
+    fn test_sender_resolution() {
+        assert_eq!(
+            ChatViewerBridge::sender_from_input_id("tutor_text"),
+            "Tutor"
+        );
+        assert_eq!(
+            ChatViewerBridge::sender_from_input_id("llm1_response"),
+            "Llm1"
+        );
+        assert_eq!(ChatViewerBridge::sender_from_input_id("text"), "Assistant");
+
+        let data: ArrayRef = Arc::new(StringArray::from(vec!["hi"]));
+        let mut metadata = EventMetadata::default();
+        metadata
+            .values
+            .insert("participant".to_string(), "Judge".to_string());
+        let msg = ChatViewerBridge::extract_message("llm2_text", &data, &metadata).unwrap();
+        assert_eq!(msg.sender, "Judge");
+        assert_eq!(msg.session_id.as_deref(), Some("llm2_text"));
+        assert!(msg.is_streaming);
+
+        let audio: ArrayRef = Arc::new(Float32Array::from(vec![0.1]));
+        assert!(ChatViewerBridge::extract_message("text", &audio, &metadata).is_none());
+    }
+
+    #[test]
+    fn test_streams_from_mock_runtime() {
+        let runtime = MockDoraRuntime::new();
+        let state = SharedDoraState::new();
+        let changes = state.subscribe();
+        let mut bridge = ChatViewerBridge::with_transport(
+            "mofa-chat-viewer",
+            Some(state.clone()),
+            runtime.clone(),
+        );
+        bridge.connect().unwrap();
+        assert!(bridge.is_connected());
+
+        let node = runtime.node("mofa-chat-viewer");
+        for (input, text, status) in [
+            ("tutor_text", "Good ", "started"),
+            ("student1_text", "Why?", "ended"),
+            ("tutor_text", "morning", "ended"),
+            ("student2_text", "Because", "error"),
+        ] {
+            node.inject_input(
+                input,
+                StringArray::from(vec![text]),
+                &[("question_id", "3"), ("session_status", status)],
+            );
+        }
+
+        // Wait until the worker has pushed the final chunk
+        let mut seen = 0;
+        while seen < 4 {
+            match changes.recv_timeout(Duration::from_secs(5)) {
+                Some(crate::StateChange::Chat(_)) => seen += 1,
+                Some(_) => {}
+                None => panic!("chat viewer did not deliver messages"),
+            }
+        }
+        bridge.disconnect().unwrap();
+
+        let messages = state.chat.read_all();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].sender, "Tutor");
+        assert_eq!(messages[0].content, "Good morning");
+        assert!(!messages[0].is_streaming);
+        assert_eq!(messages[1].sender, "Student1");
+        assert_eq!(messages[2].sender, "Student2");
+        assert!(!messages[2].is_streaming);
+        assert!(node.sent().is_empty());
+    }
+}
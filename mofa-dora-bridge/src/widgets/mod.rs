@@ -4,19 +4,25 @@
 //! - `mofa-audio-player`: Receives audio, forwards to UI for playback
 //! - `mofa-system-log`: Receives logs from multiple nodes
 //! - `mofa-prompt-input`: Sends user prompts to LLM
+//! - `mofa-chat-viewer`: Displays LLM text outputs (read-only)
 //! - `mofa-aec-input`: Captures mic audio with AEC, sends to ASR
 //!
+//! Bridges open their dora connection through a [`TransportFactory`](crate::transport::TransportFactory),
+//! so event handling can be exercised against [`MockDoraRuntime`](crate::transport::MockDoraRuntime).
+//!
 //! Note: LED visualization is calculated in screen.rs from output waveform
 //! (more accurate since it reflects what's actually being played)
 
 mod aec_input;
 mod audio_player;
+mod chat_viewer;
 mod prompt_input;
 mod system_log;
 mod cast_controller;
 
 pub use aec_input::{AecControlCommand, AecInputBridge};
 pub use audio_player::AudioPlayerBridge;
+pub use chat_viewer::ChatViewerBridge;
 pub use prompt_input::PromptInputBridge;
 pub use system_log::SystemLogBridge;
 pub use cast_controller::CastControllerBridge;
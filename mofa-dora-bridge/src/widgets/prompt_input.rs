@@ -6,15 +6,13 @@
 //! - Status updates
 
 use crate::bridge::{BridgeState, DoraBridge};
-use crate::data::{ChatMessage, ControlCommand, DoraData, EventMetadata, MessageRole};
+use crate::data::{ChatMessage, ControlMessage, DoraData, MessageRole};
 use crate::error::{BridgeError, BridgeResult};
 use crate::shared_state::SharedDoraState;
-use arrow::array::Array;
+use crate::transport::{BridgeEvent, DoraTransportFactory, NodeTransport, TransportFactory};
+use arrow::array::{Array, ArrayRef};
 use crossbeam_channel::{bounded, Receiver, Sender};
-use dora_node_api::{
-    dora_core::config::{DataId, NodeId},
-    DoraNode, Event, IntoArrow, Parameter,
-};
+use dora_node_api::IntoArrow;
 use parking_lot::RwLock;
 use std::sync::Arc;
 use std::thread;
@@ -31,14 +29,16 @@ pub struct PromptInputBridge {
     state: Arc<RwLock<BridgeState>>,
     /// Shared state for direct UI communication
     shared_state: Option<Arc<SharedDoraState>>,
+    /// Connects the worker thread to dora
+    transport: Arc<dyn TransportFactory>,
     /// Prompt sender from widget
     prompt_sender: Sender<String>,
     /// Prompt receiver for dora
     prompt_receiver: Receiver<String>,
     /// Control command sender from widget
-    control_sender: Sender<ControlCommand>,
+    control_sender: Sender<ControlMessage>,
     /// Control command receiver for dora
-    control_receiver: Receiver<ControlCommand>,
+    control_receiver: Receiver<ControlMessage>,
     /// Stop signal
     stop_sender: Option<Sender<()>>,
     /// Worker thread handle
@@ -53,6 +53,15 @@ impl PromptInputBridge {
 
     /// Create a new prompt input bridge with shared state
     pub fn with_shared_state(node_id: &str, shared_state: Option<Arc<SharedDoraState>>) -> Self {
+        Self::with_transport(node_id, shared_state, DoraTransportFactory::shared())
+    }
+
+    /// Create a new prompt input bridge with a custom transport (e.g. mock runtime)
+    pub fn with_transport(
+        node_id: &str,
+        shared_state: Option<Arc<SharedDoraState>>,
+        transport: Arc<dyn TransportFactory>,
+    ) -> Self {
         let (prompt_tx, prompt_rx) = bounded(10);
         let (control_tx, control_rx) = bounded(10);
 
@@ -60,6 +69,7 @@ impl PromptInputBridge {
             node_id: node_id.to_string(),
             state: Arc::new(RwLock::new(BridgeState::Disconnected)),
             shared_state,
+            transport,
             prompt_sender: prompt_tx,
             prompt_receiver: prompt_rx,
             control_sender: control_tx,
@@ -77,7 +87,7 @@ impl PromptInputBridge {
     }
 
     /// Send a control command to dora (widget calls this)
-    pub fn send_control(&self, command: ControlCommand) -> BridgeResult<()> {
+    pub fn send_control(&self, command: ControlMessage) -> BridgeResult<()> {
         self.control_sender
             .send(command)
             .map_err(|_| BridgeError::ChannelSendError)
@@ -88,32 +98,33 @@ impl PromptInputBridge {
         node_id: String,
         state: Arc<RwLock<BridgeState>>,
         shared_state: Option<Arc<SharedDoraState>>,
+        transport: Arc<dyn TransportFactory>,
         prompt_receiver: Receiver<String>,
-        control_receiver: Receiver<ControlCommand>,
+        control_receiver: Receiver<ControlMessage>,
         stop_receiver: Receiver<()>,
     ) {
         info!("Starting prompt input bridge event loop for {}", node_id);
 
         // Initialize dora node
-        let (mut node, mut events) =
-            match DoraNode::init_from_node_id(NodeId::from(node_id.clone())) {
-                Ok(n) => n,
-                Err(e) => {
-                    error!("Failed to init dora node {}: {}", node_id, e);
-                    *state.write() = BridgeState::Error;
-                    if let Some(ref ss) = shared_state {
-                        ss.set_error(Some(format!("Init failed: {}", e)));
-                    }
-                    return;
+        let mut node = match transport.connect(&node_id) {
+            Ok(n) => n,
+            Err(e) => {
+                error!("Failed to init dora node {}: {}", node_id, e);
+                *state.write() = BridgeState::Error;
+                if let Some(ref ss) = shared_state {
+                    ss.set_error(Some(format!("Init failed: {}", e)));
                 }
-            };
+                return;
+            }
+        };
 
         *state.write() = BridgeState::Connected;
         if let Some(ref ss) = shared_state {
             ss.add_bridge(node_id.clone());
         }
 
-        // Event loop
+        // Event loop (ends in Error if dora closes the event stream)
+        let mut exit_state = BridgeState::Disconnected;
         loop {
             // Check for stop signal
             if stop_receiver.try_recv().is_ok() {
@@ -123,20 +134,25 @@ impl PromptInputBridge {
 
             // Check for prompts to send
             while let Ok(prompt) = prompt_receiver.try_recv() {
-                if let Err(e) = Self::send_prompt_to_dora(&mut node, &prompt) {
+                if let Err(e) = Self::send_prompt_to_dora(node.as_mut(), &prompt) {
                     warn!("Failed to send prompt: {}", e);
                 }
             }
 
             // Check for control commands to send
             while let Ok(cmd) = control_receiver.try_recv() {
-                if let Err(e) = Self::send_control_to_dora(&mut node, &cmd) {
+                if let Err(e) = Self::send_control_to_dora(node.as_mut(), &cmd) {
                     warn!("Failed to send control: {}", e);
                 }
             }
 
             // Receive dora events with timeout
-            match events.recv_timeout(std::time::Duration::from_millis(100)) {
+            match node.recv_timeout(std::time::Duration::from_millis(100)) {
+                Some(BridgeEvent::Closed) => {
+                    warn!("{} event stream closed", node_id);
+                    exit_state = BridgeState::Error;
+                    break;
+                }
                 Some(event) => {
                     Self::handle_dora_event(event, shared_state.as_ref());
                 }
@@ -146,7 +162,7 @@ impl PromptInputBridge {
             }
         }
 
-        *state.write() = BridgeState::Disconnected;
+        *state.write() = exit_state;
         if let Some(ref ss) = shared_state {
             ss.remove_bridge(&node_id);
         }
@@ -154,26 +170,15 @@ impl PromptInputBridge {
     }
 
     /// Handle a dora event
-    fn handle_dora_event(event: Event, shared_state: Option<&Arc<SharedDoraState>>) {
+    fn handle_dora_event(event: BridgeEvent, shared_state: Option<&Arc<SharedDoraState>>) {
         match event {
-            Event::Input { id, data, metadata } => {
+            BridgeEvent::Input {
+                id,
+                data,
+                metadata: event_meta,
+            } => {
                 let input_id = id.as_str();
 
-                // Extract metadata (handle all parameter types like conference-dashboard)
-                let mut event_meta = EventMetadata::default();
-                for (key, value) in metadata.parameters.iter() {
-                    let string_value = match value {
-                        Parameter::String(s) => s.clone(),
-                        Parameter::Integer(i) => i.to_string(),
-                        Parameter::Float(f) => f.to_string(),
-                        Parameter::Bool(b) => b.to_string(),
-                        Parameter::ListInt(l) => format!("{:?}", l),
-                        Parameter::ListFloat(l) => format!("{:?}", l),
-                        Parameter::ListString(l) => format!("{:?}", l),
-                    };
-                    event_meta.values.insert(key.clone(), string_value);
-                }
-
                 // Handle text inputs (responses from LLM)
                 if input_id.contains("text") || input_id.contains("response") {
                     if let Some(text) = Self::extract_string(&data) {
@@ -207,7 +212,7 @@ impl PromptInputBridge {
                     }
                 }
             }
-            Event::Stop(_) => {
+            BridgeEvent::Stop => {
                 info!("Received stop event from dora");
             }
             _ => {}
@@ -228,20 +233,16 @@ impl PromptInputBridge {
     }
 
     /// Extract string from arrow data
-    fn extract_string(data: &dora_node_api::ArrowData) -> Option<String> {
-        match data.0.data_type() {
+    fn extract_string(data: &ArrayRef) -> Option<String> {
+        match data.data_type() {
             arrow::datatypes::DataType::Utf8 => {
-                let array = data
-                    .0
-                    .as_any()
-                    .downcast_ref::<arrow::array::StringArray>()?;
+                let array = data.as_any().downcast_ref::<arrow::array::StringArray>()?;
                 if array.len() > 0 {
                     return Some(array.value(0).to_string());
                 }
             }
             arrow::datatypes::DataType::LargeUtf8 => {
                 let array = data
-                    .0
                     .as_any()
                     .downcast_ref::<arrow::array::LargeStringArray>()?;
                 if array.len() > 0 {
@@ -249,41 +250,30 @@ impl PromptInputBridge {
                 }
             }
             arrow::datatypes::DataType::UInt8 => {
-                let array = data.0.as_any().downcast_ref::<arrow::array::UInt8Array>()?;
+                let array = data.as_any().downcast_ref::<arrow::array::UInt8Array>()?;
                 let bytes: Vec<u8> = array.values().to_vec();
                 return String::from_utf8(bytes).ok();
             }
             _ => {
-                warn!("Unsupported text data type: {:?}", data.0.data_type());
+                warn!("Unsupported text data type: {:?}", data.data_type());
             }
         }
         None
     }
 
     /// Send prompt to dora via control output
-    /// The conference-controller expects JSON with "prompt" field
-    fn send_prompt_to_dora(node: &mut DoraNode, prompt: &str) -> BridgeResult<()> {
-        // Create JSON payload that conference-controller expects
-        let payload = serde_json::json!({
-            "prompt": prompt
-        });
-
+    fn send_prompt_to_dora(node: &mut dyn NodeTransport, prompt: &str) -> BridgeResult<()> {
         info!("Sending prompt to dora: {}", prompt);
-        let data = payload.to_string().into_arrow();
-        let output_id: DataId = "control".to_string().into(); // Use control output
-        node.send_output(output_id, Default::default(), data)
-            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+        Self::send_control_to_dora(node, &ControlMessage::prompt(prompt))
     }
 
-    /// Send control command to dora
-    fn send_control_to_dora(node: &mut DoraNode, cmd: &ControlCommand) -> BridgeResult<()> {
-        let payload =
-            serde_json::to_string(cmd).map_err(|e| BridgeError::SendFailed(e.to_string()))?;
-
-        let data = payload.into_arrow();
-        let output_id: DataId = "control".to_string().into();
-        node.send_output(output_id, Default::default(), data)
-            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+    /// Send control message to dora
+    fn send_control_to_dora(
+        node: &mut dyn NodeTransport,
+        message: &ControlMessage,
+    ) -> BridgeResult<()> {
+        let data = message.encode().into_arrow();
+        node.send_output("control", Default::default(), Arc::new(data))
     }
 }
 
@@ -309,6 +299,7 @@ impl DoraBridge for PromptInputBridge {
         let node_id = self.node_id.clone();
         let state = Arc::clone(&self.state);
         let shared_state = self.shared_state.clone();
+        let transport = Arc::clone(&self.transport);
         let prompt_receiver = self.prompt_receiver.clone();
         let control_receiver = self.control_receiver.clone();
 
@@ -317,6 +308,7 @@ impl DoraBridge for PromptInputBridge {
                 node_id,
                 state,
                 shared_state,
+                transport,
                 prompt_receiver,
                 control_receiver,
                 stop_rx,
@@ -406,3 +398,78 @@ impl Drop for PromptInputBridge {
         let _ = self.disconnect();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MockDoraRuntime;
+    use arrow::array::StringArray;
+
+    fn chunk(input_id: &str, text: &str, question_id: &str, status: &str) -> BridgeEvent {
+        BridgeEvent::input(
+            input_id,
+            StringArray::from(vec![text]),
+            &[("question_id", question_id), ("session_status", status)],
+        )
+    }
+
+    #[test]
+    fn test_streaming_chunks_consolidate() {
+        let state = SharedDoraState::new();
+
+        for event in [
+            chunk("llm1_text", "Hello", "1", "streaming"),
+            chunk("llm2_text", "Hi", "1", "streaming"),
+            chunk("llm1_text", ", world", "1", "streaming"),
+            chunk("llm1_text", "!", "1", "ended"),
+            chunk("llm1_text", "Again", "2", "streaming"),
+        ] {
+            PromptInputBridge::handle_dora_event(event, Some(&state));
+        }
+
+        let messages = state.chat.read_all();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].sender, "Student 1");
+        assert_eq!(messages[0].content, "Hello, world!");
+        assert!(!messages[0].is_streaming);
+        assert_eq!(messages[1].sender, "Student 2");
+        assert!(messages[1].is_streaming);
+        assert_eq!(messages[2].content, "Again");
+        assert_eq!(messages[2].session_id.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn test_non_text_inputs_ignored() {
+        let state = SharedDoraState::new();
+        PromptInputBridge::handle_dora_event(chunk("control", "reset", "1", "ended"), Some(&state));
+        assert!(state.chat.is_empty());
+    }
+
+    #[test]
+    fn test_prompts_and_commands_sent_as_control_messages() {
+        let runtime = MockDoraRuntime::new();
+        let handle = runtime.node("mofa-prompt-input");
+        let mut transport = handle.transport();
+
+        PromptInputBridge::send_prompt_to_dora(&mut transport, "Hello").unwrap();
+        PromptInputBridge::send_control_to_dora(&mut transport, &ControlMessage::Reset).unwrap();
+
+        let sent: Vec<ControlMessage> = handle
+            .sent_on("control")
+            .iter()
+            .map(|output| {
+                let text = output
+                    .data
+                    .as_any()
+                    .downcast_ref::<StringArray>()
+                    .unwrap()
+                    .value(0);
+                ControlMessage::parse(text).unwrap()
+            })
+            .collect();
+        assert_eq!(
+            sent,
+            vec![ControlMessage::prompt("Hello"), ControlMessage::Reset]
+        );
+    }
+}
@@ -9,9 +9,9 @@ use crate::bridge::{BridgeState, DoraBridge};
 use crate::data::{current_timestamp, DoraData, EventMetadata, LogEntry, LogLevel};
 use crate::error::{BridgeError, BridgeResult};
 use crate::shared_state::SharedDoraState;
-use arrow::array::Array;
+use crate::transport::{BridgeEvent, DoraTransportFactory, TransportFactory};
+use arrow::array::{Array, ArrayRef};
 use crossbeam_channel::{bounded, Receiver, Sender};
-use dora_node_api::{dora_core::config::NodeId, DoraNode, Event, Parameter};
 use parking_lot::RwLock;
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -29,6 +29,8 @@ pub struct SystemLogBridge {
     state: Arc<RwLock<BridgeState>>,
     /// Shared state for direct UI communication
     shared_state: Option<Arc<SharedDoraState>>,
+    /// Connects the worker thread to dora
+    transport: Arc<dyn TransportFactory>,
     /// Known log sources
     log_sources: Arc<RwLock<HashSet<String>>>,
     /// Minimum log level filter
@@ -47,10 +49,20 @@ impl SystemLogBridge {
 
     /// Create a new system log bridge with shared state
     pub fn with_shared_state(node_id: &str, shared_state: Option<Arc<SharedDoraState>>) -> Self {
+        Self::with_transport(node_id, shared_state, DoraTransportFactory::shared())
+    }
+
+    /// Create a new system log bridge with a custom transport (e.g. mock runtime)
+    pub fn with_transport(
+        node_id: &str,
+        shared_state: Option<Arc<SharedDoraState>>,
+        transport: Arc<dyn TransportFactory>,
+    ) -> Self {
         Self {
             node_id: node_id.to_string(),
             state: Arc::new(RwLock::new(BridgeState::Disconnected)),
             shared_state,
+            transport,
             log_sources: Arc::new(RwLock::new(HashSet::new())),
             min_level: Arc::new(RwLock::new(LogLevel::Info)),
             stop_sender: None,
@@ -73,6 +85,7 @@ impl SystemLogBridge {
         node_id: String,
         state: Arc<RwLock<BridgeState>>,
         shared_state: Option<Arc<SharedDoraState>>,
+        transport: Arc<dyn TransportFactory>,
         log_sources: Arc<RwLock<HashSet<String>>>,
         min_level: Arc<RwLock<LogLevel>>,
         stop_receiver: Receiver<()>,
@@ -80,7 +93,7 @@ impl SystemLogBridge {
         info!("Starting system log bridge event loop for {}", node_id);
 
         // Initialize dora node
-        let (_node, mut events) = match DoraNode::init_from_node_id(NodeId::from(node_id.clone())) {
+        let mut node = match transport.connect(&node_id) {
             Ok(n) => n,
             Err(e) => {
                 error!("Failed to init dora node {}: {}", node_id, e);
@@ -97,7 +110,8 @@ impl SystemLogBridge {
             ss.add_bridge(node_id.clone());
         }
 
-        // Event loop
+        // Event loop (ends in Error if dora closes the event stream)
+        let mut exit_state = BridgeState::Disconnected;
         loop {
             // Check for stop signal
             if stop_receiver.try_recv().is_ok() {
@@ -106,7 +120,12 @@ impl SystemLogBridge {
             }
 
             // Receive dora events with timeout
-            match events.recv_timeout(std::time::Duration::from_millis(100)) {
+            match node.recv_timeout(std::time::Duration::from_millis(100)) {
+                Some(BridgeEvent::Closed) => {
+                    warn!("{} event stream closed", node_id);
+                    exit_state = BridgeState::Error;
+                    break;
+                }
                 Some(event) => {
                     Self::handle_dora_event(event, shared_state.as_ref(), &log_sources, &min_level);
                 }
@@ -116,7 +135,7 @@ impl SystemLogBridge {
             }
         }
 
-        *state.write() = BridgeState::Disconnected;
+        *state.write() = exit_state;
         if let Some(ref ss) = shared_state {
             ss.remove_bridge(&node_id);
         }
@@ -125,13 +144,17 @@ impl SystemLogBridge {
 
     /// Handle a dora event
     fn handle_dora_event(
-        event: Event,
+        event: BridgeEvent,
         shared_state: Option<&Arc<SharedDoraState>>,
         log_sources: &Arc<RwLock<HashSet<String>>>,
         min_level: &Arc<RwLock<LogLevel>>,
     ) {
         match event {
-            Event::Input { id, data, metadata } => {
+            BridgeEvent::Input {
+                id,
+                data,
+                metadata: event_meta,
+            } => {
                 let input_id = id.as_str();
 
                 // Extract source node from input ID (e.g., "tts_log" -> "tts")
@@ -143,21 +166,6 @@ impl SystemLogBridge {
                 // Track log source
                 log_sources.write().insert(source_node.to_string());
 
-                // Extract metadata (handle all parameter types like conference-dashboard)
-                let mut event_meta = EventMetadata::default();
-                for (key, value) in metadata.parameters.iter() {
-                    let string_value = match value {
-                        Parameter::String(s) => s.clone(),
-                        Parameter::Integer(i) => i.to_string(),
-                        Parameter::Float(f) => f.to_string(),
-                        Parameter::Bool(b) => b.to_string(),
-                        Parameter::ListInt(l) => format!("{:?}", l),
-                        Parameter::ListFloat(l) => format!("{:?}", l),
-                        Parameter::ListString(l) => format!("{:?}", l),
-                    };
-                    event_meta.values.insert(key.clone(), string_value);
-                }
-
                 // Try to parse log entry
                 if let Some(log_entry) = Self::extract_log_entry(&data, source_node, &event_meta) {
                     // Filter by level
@@ -175,7 +183,7 @@ impl SystemLogBridge {
                     }
                 }
             }
-            Event::Stop(_) => {
+            BridgeEvent::Stop => {
                 info!("Received stop event from dora");
             }
             _ => {}
@@ -184,7 +192,7 @@ impl SystemLogBridge {
 
     /// Extract log entry from dora data
     fn extract_log_entry(
-        data: &dora_node_api::ArrowData,
+        data: &ArrayRef,
         source_node: &str,
         _metadata: &EventMetadata,
     ) -> Option<LogEntry> {
@@ -230,20 +238,16 @@ impl SystemLogBridge {
     }
 
     /// Extract string from arrow data
-    fn extract_string(data: &dora_node_api::ArrowData) -> Option<String> {
-        match data.0.data_type() {
+    fn extract_string(data: &ArrayRef) -> Option<String> {
+        match data.data_type() {
             arrow::datatypes::DataType::Utf8 => {
-                let array = data
-                    .0
-                    .as_any()
-                    .downcast_ref::<arrow::array::StringArray>()?;
+                let array = data.as_any().downcast_ref::<arrow::array::StringArray>()?;
                 if array.len() > 0 {
                     return Some(array.value(0).to_string());
                 }
             }
             arrow::datatypes::DataType::LargeUtf8 => {
                 let array = data
-                    .0
                     .as_any()
                     .downcast_ref::<arrow::array::LargeStringArray>()?;
                 if array.len() > 0 {
@@ -251,12 +255,12 @@ impl SystemLogBridge {
                 }
             }
             arrow::datatypes::DataType::UInt8 => {
-                let array = data.0.as_any().downcast_ref::<arrow::array::UInt8Array>()?;
+                let array = data.as_any().downcast_ref::<arrow::array::UInt8Array>()?;
                 let bytes: Vec<u8> = array.values().to_vec();
                 return String::from_utf8(bytes).ok();
             }
             _ => {
-                warn!("Unsupported log data type: {:?}", data.0.data_type());
+                warn!("Unsupported log data type: {:?}", data.data_type());
             }
         }
         None
@@ -285,6 +289,7 @@ impl DoraBridge for SystemLogBridge {
         let node_id = self.node_id.clone();
         let state = Arc::clone(&self.state);
         let shared_state = self.shared_state.clone();
+        let transport = Arc::clone(&self.transport);
         let log_sources = Arc::clone(&self.log_sources);
         let min_level = Arc::clone(&self.min_level);
 
@@ -293,6 +298,7 @@ impl DoraBridge for SystemLogBridge {
                 node_id,
                 state,
                 shared_state,
+                transport,
                 log_sources,
                 min_level,
                 stop_rx,
@@ -357,3 +363,81 @@ impl Drop for SystemLogBridge {
         let _ = self.disconnect();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::StringArray;
+
+    fn handle(
+        input_id: &str,
+        text: &str,
+        state: &Arc<SharedDoraState>,
+        sources: &Arc<RwLock<HashSet<String>>>,
+        min_level: &Arc<RwLock<LogLevel>>,
+    ) {
+        SystemLogBridge::handle_dora_event(
+            BridgeEvent::input(input_id, StringArray::from(vec![text]), &[]),
+            Some(state),
+            sources,
+            min_level,
+        );
+    }
+
+    #[test]
+    fn test_json_and_plain_log_extraction() {
+        let state = SharedDoraState::new();
+        let sources = Arc::new(RwLock::new(HashSet::new()));
+        let min_level = Arc::new(RwLock::new(LogLevel::Info));
+
+        handle(
+            "tts_log",
+            r#"{"level": "WARNING", "message": "slow model", "node": "primespeech", "timestamp": 42}"#,
+            &state,
+            &sources,
+            &min_level,
+        );
+        handle("asr_status", "ready", &state, &sources, &min_level);
+
+        let logs = state.logs.read_all();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].level, LogLevel::Warning);
+        assert_eq!(logs[0].message, "slow model");
+        assert_eq!(logs[0].node_id, "primespeech");
+        assert_eq!(logs[0].timestamp, 42);
+        assert_eq!(logs[1].level, LogLevel::Info);
+        assert_eq!(logs[1].message, "ready");
+        assert_eq!(logs[1].node_id, "asr");
+
+        let mut known: Vec<String> = sources.read().iter().cloned().collect();
+        known.sort();
+        assert_eq!(known, vec!["asr".to_string(), "tts".to_string()]);
+    }
+
+    #[test]
+    fn test_min_level_filter() {
+        let state = SharedDoraState::new();
+        let sources = Arc::new(RwLock::new(HashSet::new()));
+        let min_level = Arc::new(RwLock::new(LogLevel::Error));
+
+        handle(
+            "llm1_log",
+            r#"{"level": "INFO", "message": "hi"}"#,
+            &state,
+            &sources,
+            &min_level,
+        );
+        handle(
+            "llm1_log",
+            r#"{"level": "ERROR", "message": "boom"}"#,
+            &state,
+            &sources,
+            &min_level,
+        );
+
+        let logs = state.logs.read_all();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].message, "boom");
+        assert_eq!(logs[0].node_id, "llm1");
+    }
+}
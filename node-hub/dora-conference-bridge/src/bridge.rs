@@ -0,0 +1,627 @@
+// Conference bridge state machine
+//
+// Collects participant replies and forwards them as one bundle when the
+// controller resumes this bridge. Inputs are handled one at a time and the
+// outputs to send (text, status and logs) are queued; the dora node in
+// main.rs only translates events and actions, so the scenario tests drive
+// exactly the same code.
+
+use mofa_control::ControlMessage;
+use mofa_roster::Roster;
+use mofa_sim::{Action, Input, LogLevel, Node, Output};
+use mofa_stream::{keys, MetadataRead, QuestionId, StreamStatus};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::Instant;
+
+#[derive(Debug, Clone, PartialEq)]
+enum SignalType {
+    ResetSignal,      // status: "reset" - control signal, drop silently
+    CancelledSignal,  // status: "cancelled" - control signal, drop silently
+    TechnicalError,   // status: "error" - actual error, may need notification
+    ContentError,     // Text-based errors like "Error:" - forward template if configured
+    NormalContent,    // Regular content - forward as-is
+}
+
+/// Classify the type of signal from metadata and text content
+fn classify_signal(metadata: &BTreeMap<String, String>, text: &str) -> SignalType {
+    // First check the stream status in metadata
+    match mofa_stream::status(metadata) {
+        Some(StreamStatus::Reset) => return SignalType::ResetSignal,
+        Some(StreamStatus::Cancelled) => return SignalType::CancelledSignal,
+        Some(StreamStatus::Error) => return SignalType::TechnicalError,
+        _ => {} // Fall through to text-based detection
+    }
+
+    // Check for text-based error patterns
+    if text.starts_with("Error:") || text.starts_with("error:") {
+        SignalType::ContentError
+    } else {
+        // Default to normal content
+        SignalType::NormalContent
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BundledMessage {
+    participant: String,
+    content: String,
+}
+
+#[derive(Debug, Clone)]
+enum MessageState {
+    Streaming {
+        chunks: Vec<String>,
+    },
+    Complete {
+        content: String,
+    },
+}
+
+impl MessageState {
+    fn new_streaming() -> Self {
+        MessageState::Streaming { chunks: Vec::new() }
+    }
+
+    fn add_chunk(&mut self, chunk: String) {
+        if let MessageState::Streaming { chunks } = self {
+            chunks.push(chunk);
+        }
+    }
+
+    fn complete(&mut self) {
+        if let MessageState::Streaming { chunks } = self {
+            *self = MessageState::Complete {
+                content: chunks.join(""),
+            };
+        }
+    }
+
+    fn get_content(&self) -> String {
+        match self {
+            MessageState::Streaming { chunks } => chunks.join(""),
+            MessageState::Complete { content } => content.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct InputPort {
+    port_name: String,
+    is_streaming: bool,  // Explicitly configured
+    message_state: Option<MessageState>,
+    ready: bool,
+    draining: bool,
+    was_already_ready: bool, // Track if we already logged this as ready
+    signal_type: Option<SignalType>,  // Type of signal detected, if any
+    should_forward: bool,  // Whether this input should be forwarded (false for control signals)
+}
+
+impl InputPort {
+    fn new(port_name: String, is_streaming: bool) -> Self {
+        Self {
+            port_name,
+            is_streaming,
+            message_state: None,
+            ready: false,
+            draining: false,
+            was_already_ready: false,
+            signal_type: None,
+            should_forward: true,  // Default to forwarding
+        }
+    }
+
+    fn handle_input(&mut self, text: String, metadata: &BTreeMap<String, String>) -> bool {
+        // Classify the signal type from metadata and text
+        let signal_type = classify_signal(metadata, &text);
+        self.signal_type = Some(signal_type.clone());
+
+        // For control signals (reset/cancelled), discard completely - don't accumulate or mark ready
+        // These are notifications, not content to be forwarded
+        if matches!(signal_type, SignalType::ResetSignal | SignalType::CancelledSignal) {
+            // Clear any accumulated state - this input port is now empty
+            self.message_state = None;
+            self.ready = false;
+            self.should_forward = false;
+            return false;  // Not ready, nothing accumulated
+        }
+
+        // Errors forward the template message, normal content is forwarded as-is
+        self.should_forward = true;
+
+        // Check if this is the start of a new message (status: "started")
+        if mofa_stream::status(metadata) == Some(StreamStatus::Started) {
+            self.message_state = Some(MessageState::new_streaming());
+            self.ready = false;
+            self.was_already_ready = false;
+        }
+
+        // ended = normal completion, error = error occurred
+        let is_complete = mofa_stream::is_complete(metadata);
+
+        if self.is_streaming {
+            // Streaming input - accumulate chunks, except an empty ending signal
+            let state = self.message_state.get_or_insert_with(MessageState::new_streaming);
+            if !(text.trim().is_empty() && is_complete) {
+                state.add_chunk(text);
+            }
+
+            if is_complete {
+                state.complete();
+                self.ready = true;
+                return true;  // Ready to forward (will be filtered in forward_bundle)
+            }
+        } else {
+            // Non-streaming input - complete immediately
+            self.message_state = Some(MessageState::Complete { content: text });
+            self.ready = true;
+            return true;  // Ready to forward (will be filtered in forward_bundle)
+        }
+
+        false  // Not ready yet
+    }
+
+    fn get_bundled_message(&self) -> Option<BundledMessage> {
+        self.message_state.as_ref().map(|state| BundledMessage {
+            participant: self.port_name.clone(),
+            content: state.get_content(),
+        })
+    }
+
+    fn reset(&mut self) {
+        self.message_state = None;
+        self.ready = false;
+        self.draining = false;
+        self.was_already_ready = false;
+        self.signal_type = None;
+        self.should_forward = true;
+    }
+
+    fn is_streaming_active(&self) -> bool {
+        matches!(self.message_state, Some(MessageState::Streaming { .. }))
+    }
+}
+
+/// Bridge configuration, read from the environment by the node
+#[derive(Debug, Clone, Default)]
+pub struct BridgeConfig {
+    /// Ports whose replies arrive in chunks (STREAMING_PORTS)
+    pub streaming_ports: HashSet<String>,
+    /// Ports registered up front (the node's inputs except `control`)
+    pub expected_ports: HashSet<String>,
+    /// Forwarded in place of a failed reply; `{participant}` becomes its display name
+    pub error_message_template: Option<String>,
+    /// Display names of participants by bridge port
+    pub roster: Option<Roster>,
+    /// Without a roster, capitalize study role names (DORA_STUDY_MODE)
+    pub study_mode: bool,
+}
+
+pub struct ConferenceBridge {
+    inputs: HashMap<String, InputPort>,
+    streaming_ports: HashSet<String>,
+    arrival_queue: VecDeque<String>,
+    current_question_id: Option<String>,
+    controller_question_id: Option<String>,  // Track controller's question_id (turn id)
+    has_controller_input: bool,           // Flag if controller provided question_id
+    last_status: String,  // Track last status to avoid duplicate logs
+    resume_mode: bool,     // Track if bridge is in resume mode
+    error_message_template: Option<String>,  // Template for error messages, {participant} will be replaced
+    roster: Option<Roster>,
+    study_mode: bool,
+    actions: Vec<Action>,
+}
+
+impl ConferenceBridge {
+    /// Create a bridge; its startup logs and initial "waiting" status are
+    /// the first actions taken
+    pub fn new(config: BridgeConfig) -> Self {
+        let mut bridge = Self {
+            inputs: HashMap::new(),
+            streaming_ports: config.streaming_ports,
+            arrival_queue: VecDeque::new(),
+            current_question_id: None,
+            controller_question_id: None,
+            has_controller_input: false,
+            last_status: String::new(),
+            resume_mode: false,  // Start in paused mode
+            error_message_template: config.error_message_template,
+            roster: config.roster,
+            study_mode: config.study_mode,
+            actions: Vec::new(),
+        };
+
+        for port in config.expected_ports {
+            if !port.trim().is_empty() {
+                bridge.register_input(port);
+            }
+        }
+
+        bridge.log(LogLevel::Info, "Conference bridge initialized - forwarding controlled by controller");
+        if let Some(ids) = bridge.roster.as_ref().map(|roster| format!("{:?}", roster.ids())) {
+            bridge.log(LogLevel::Info, format!("Participant roster: {}", ids));
+        }
+        if !bridge.streaming_ports.is_empty() {
+            let ports = format!("Streaming ports: {:?}", bridge.streaming_ports);
+            bridge.log(LogLevel::Info, ports);
+        } else {
+            bridge.log(LogLevel::Info, "No streaming ports - all inputs treated as non-streaming");
+        }
+        bridge.send_status("waiting");
+
+        bridge
+    }
+
+    fn log(&mut self, level: LogLevel, message: impl Into<String>) {
+        self.actions.push(Action::log(level, message));
+    }
+
+    fn register_input(&mut self, port_name: String) {
+        if !self.inputs.contains_key(&port_name) {
+            let is_streaming = self.streaming_ports.contains(&port_name);
+            self.inputs.insert(port_name.clone(), InputPort::new(port_name, is_streaming));
+        }
+    }
+
+    /// Name used for the participant whose text arrives on `port_name`
+    fn get_participant_display_name(&self, port_name: &str) -> String {
+        if let Some(participant) = self.roster.as_ref().and_then(|roster| roster.by_port(port_name)) {
+            return participant.display_name.clone();
+        }
+
+        // No roster: derive it from the port name
+        if self.study_mode {
+            port_name
+                .replace("student1", "Student1")
+                .replace("student2", "Student2")
+                .replace("tutor", "Tutor")
+        } else {
+            port_name
+                .replace("llm1", "LLM1")
+                .replace("llm2", "LLM2")
+                .replace("judge", "Judge")
+        }
+    }
+
+    /// Handle control input from conference controller
+    fn handle_control_input(&mut self, message: &ControlMessage, metadata: &BTreeMap<String, String>) {
+        if *message == ControlMessage::Resume {
+            // Extract question_id from controller's resume command
+            if let Some(qid) = metadata.get_str(keys::QUESTION_ID) {
+                // Forwarded verbatim; turn ids and legacy numbers are both accepted
+                if let QuestionId::Other(value) = QuestionId::parse(&qid) {
+                    self.log(LogLevel::Warn, format!("⚠️ Unrecognized question_id format: {}", value));
+                }
+                self.log(LogLevel::Info, format!("▶️ Bridge using controller question_id: {}", qid));
+                self.controller_question_id = Some(qid.into_owned());
+                self.has_controller_input = true;
+                self.resume_mode = true;
+            } else {
+                self.log(LogLevel::Warn, "⚠️ Resume command without question_id - using default behavior");
+                self.has_controller_input = false;
+            }
+        } else if *message == ControlMessage::Reset {
+            // Reset doesn't affect question_id - controller will provide new one in next resume
+            self.controller_question_id = None;
+            self.has_controller_input = false;
+            self.resume_mode = false;
+        }
+    }
+
+    /// Send status output, but only if it has changed from last time (deduplication)
+    fn send_status(&mut self, status: &str) {
+        if self.last_status != status {
+            self.actions.push(Action::Send(Output::new("status", status)));
+            self.last_status = status.to_string();
+        }
+    }
+
+    fn handle_input(&mut self, port_name: &str, text: String, metadata: &BTreeMap<String, String>) -> bool {
+        // Register input if not known
+        self.register_input(port_name.to_string());
+
+        // Track arrival order in FIFO queue (only add if not already present)
+        if !self.arrival_queue.iter().any(|p| p == port_name) {
+            self.arrival_queue.push_back(port_name.to_string());
+        }
+
+        // Extract question_id from metadata (use first arrival's question_id)
+        if self.current_question_id.is_none() {
+            self.current_question_id = metadata.get(keys::QUESTION_ID).cloned();
+        }
+
+        match self.inputs.get_mut(port_name) {
+            Some(input) => input.handle_input(text, metadata),
+            None => false,
+        }
+    }
+
+    fn get_ready_inputs(&self) -> HashSet<String> {
+        self.inputs.iter()
+            .filter(|(_, input)| input.ready)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    fn any_streaming(&self) -> bool {
+        self.inputs.values().any(InputPort::is_streaming_active)
+    }
+
+    fn handle_drain(
+        &mut self,
+        port_name: &str,
+        is_starting: bool,
+        is_complete: bool,
+        has_session_status: bool,
+    ) -> bool {
+        if let Some(input) = self.inputs.get_mut(port_name) {
+            if input.draining {
+                if is_complete {
+                    input.draining = false;
+                    return true;
+                }
+
+                if is_starting || !has_session_status {
+                    input.draining = false;
+                    return false;
+                }
+
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn finalize_cycle(&mut self, status: &str) {
+        for input in self.inputs.values_mut() {
+            input.reset();
+        }
+
+        self.arrival_queue.clear();
+        self.current_question_id = None;
+
+        self.send_status(status);
+    }
+
+    fn reset_state(&mut self) {
+        // Force clear all inputs EXCEPT human input - don't drain, just reset immediately
+        // Any in-flight streaming chunks will be dropped
+        // Preserve human input (from ASR) so it can be forwarded after reset
+        let mut messages = Vec::new();
+        for (port_name, input) in self.inputs.iter_mut() {
+            // Human input typically comes from ASR (asr/transcription source)
+            if port_name.to_lowercase().contains("human") {
+                messages.push(format!("🔄 PRESERVING human input during reset: {}", port_name));
+                // Don't reset human input - keep it for forwarding
+                continue;
+            }
+
+            if input.is_streaming_active() {
+                messages.push(format!("🔄 Force clearing active streaming input: {}", port_name));
+            }
+            input.reset();  // Force clear, don't drain
+        }
+        for message in messages {
+            self.log(LogLevel::Info, message);
+        }
+
+        // Don't clear arrival_queue completely - remove non-human entries but keep human
+        self.arrival_queue.retain(|port_name| port_name.to_lowercase().contains("human"));
+
+        self.current_question_id = None;
+        self.resume_mode = false;  // Reset to pause mode
+
+        self.log(LogLevel::Info,
+            "✅ Bridge reset complete - all inputs cleared (human input preserved), ready for new conversation");
+        self.send_status("reset");
+    }
+
+    /// Forward the ready inputs if nothing is still streaming
+    fn forward_if_ready(&mut self) -> bool {
+        let any_streaming = self.any_streaming();
+        let ready_inputs = self.get_ready_inputs();
+
+        if ready_inputs.is_empty() || any_streaming {
+            self.log(LogLevel::Debug,
+                format!("⏳ Waiting for inputs (ready={}, streaming={})", ready_inputs.len(), any_streaming));
+            return false;
+        }
+
+        self.log(LogLevel::Info, format!("🚀 Forwarding {} ready inputs", ready_inputs.len()));
+        self.forward_bundle();
+        self.resume_mode = false;
+        self.log(LogLevel::Debug, "✅ Forward complete");
+        true
+    }
+
+    fn forward_bundle(&mut self) {
+        self.log(LogLevel::Debug,
+            format!("🚀 FORWARDING BUNDLE - queue: {:?}, {} ready inputs", self.arrival_queue, self.get_ready_inputs().len()));
+
+        // Step 1: Collect messages in FIFO order and concatenate
+        let mut concatenated_content = String::new();
+        let mut forwarded_count = 0;
+        let mut logs = Vec::new();
+
+        // Iterate in FIFO queue order (not arbitrary HashMap order)
+        for port_name in &self.arrival_queue {
+            let Some(input) = self.inputs.get(port_name) else { continue };
+            if !input.ready {
+                continue; // Skip if not ready (cold start case)
+            }
+
+            // Handle signals based on type - either drop silently or forward template message
+            if !input.should_forward {
+                logs.push((LogLevel::Debug,
+                    format!("🚫 Dropping {:?} signal from {}", input.signal_type, port_name)));
+                continue;  // Skip control signals (reset, cancelled)
+            }
+
+            // Handle error signals that should be forwarded with template message
+            if matches!(input.signal_type, Some(SignalType::TechnicalError | SignalType::ContentError)) {
+                if let Some(template) = &self.error_message_template {
+                    let participant_name = self.get_participant_display_name(port_name);
+                    let error_message = template.replace("{participant}", &participant_name);
+                    logs.push((LogLevel::Warn,
+                        format!("📢 {} had an error - sending notification: {}", port_name, error_message)));
+
+                    if !concatenated_content.is_empty() {
+                        concatenated_content.push('\n');
+                    }
+                    concatenated_content.push_str(&error_message);
+                    forwarded_count += 1;
+                } else {
+                    logs.push((LogLevel::Warn,
+                        format!("❌ Dropping error input from {} - no error message template", port_name)));
+                }
+                continue;
+            }
+
+            if let Some(message) = input.get_bundled_message() {
+                // Skip empty messages (completion signals with no content)
+                if message.content.trim().is_empty() {
+                    continue;
+                }
+
+                // Add content with newline separator
+                if !concatenated_content.is_empty() {
+                    concatenated_content.push('\n');
+                }
+                concatenated_content.push_str(&message.content);
+                forwarded_count += 1;
+
+                logs.push((LogLevel::Debug,
+                    format!("📦 Adding {} to bundle: {} chars", message.participant, message.content.len())));
+            }
+        }
+        for (level, message) in logs {
+            self.log(level, message);
+        }
+
+        if forwarded_count == 0 {
+            self.log(LogLevel::Debug, "No messages ready to forward");
+            return;
+        }
+
+        // Clear the arrival queue and reset input states after forwarding
+        self.arrival_queue.clear();
+        for input in self.inputs.values_mut() {
+            input.reset();
+        }
+
+        // Use controller's question_id if provided, otherwise generate default
+        let output_question_id = match &self.controller_question_id {
+            Some(controller_qid) => controller_qid.clone(),
+            None => "1".to_string(),  // Simple fallback for standalone usage
+        };
+
+        self.log(LogLevel::Debug,
+            format!("📤 Forwarding with question_id: {} ({})",
+                output_question_id,
+                if self.has_controller_input { "controller" } else { "fallback" }));
+        self.log(LogLevel::Debug,
+            format!("📤 Sending {} chars from {} inputs", concatenated_content.len(), forwarded_count));
+
+        // Step 3: Send concatenated output with metadata
+        self.actions.push(Action::Send(
+            Output::new("text", concatenated_content)
+                .with_metadata(keys::QUESTION_ID, output_question_id),
+        ));
+
+        // Step 4: Update state - the controller manages question_id
+        if self.has_controller_input {
+            self.current_question_id = self.controller_question_id.clone();
+        }
+
+        self.finalize_cycle("forwarded");
+    }
+
+    fn handle_control(&mut self, payload: &str, metadata: &BTreeMap<String, String>) {
+        let command = match ControlMessage::parse(payload) {
+            Ok(message) => message,
+            Err(e) => {
+                self.log(LogLevel::Warn, e.to_string());
+                return;
+            }
+        };
+
+        match command {
+            ControlMessage::Reset => {
+                self.handle_control_input(&ControlMessage::Reset, metadata);
+                self.reset_state();
+                self.log(LogLevel::Info, "🔄 Reset command received");
+            }
+            ControlMessage::Resume => {
+                // Forward if there are ready inputs AND no ongoing streaming
+                self.handle_control_input(&ControlMessage::Resume, metadata);
+                self.forward_if_ready();
+            }
+            other => self.log(LogLevel::Warn, format!("Unsupported command: {}", other)),
+        }
+    }
+
+    fn handle_participant(&mut self, port_name: &str, text: String, metadata: &BTreeMap<String, String>) {
+        self.register_input(port_name.to_string());
+
+        let completion_signal = mofa_stream::is_complete(metadata);
+        let stream_status = mofa_stream::status(metadata);
+        let is_starting = stream_status == Some(StreamStatus::Started);
+        let has_session_status = stream_status.is_some();
+
+        if self.handle_drain(port_name, is_starting, completion_signal, has_session_status) {
+            return;
+        }
+
+        if text.trim().is_empty() && !completion_signal {
+            return;
+        }
+
+        // CRITICAL: status "reset" from a participant is the LAST message
+        // from the old conversation - discard ALL accumulated inputs
+        if stream_status == Some(StreamStatus::Reset) {
+            self.log(LogLevel::Info,
+                format!("🔄 RESET SIGNAL from {} - discarding ALL queued inputs", port_name));
+            self.reset_state();
+            return;  // Skip further processing, wait for new conversation
+        }
+
+        let input_ready = self.handle_input(port_name, text, metadata);
+
+        if input_ready {
+            // Mark as ready (deduplication); log state changes only for errors
+            let completed_with_error = self.inputs.get_mut(port_name).is_some_and(|input| {
+                let first = !input.was_already_ready;
+                input.was_already_ready = true;
+                first && matches!(input.signal_type, Some(SignalType::TechnicalError | SignalType::ContentError))
+            });
+            if completed_with_error {
+                self.log(LogLevel::Warn, format!("❌ Input {} completed with ERROR", port_name));
+            }
+        }
+
+        // If input completed and bridge is in resume mode, check if we can forward
+        if self.resume_mode && input_ready {
+            self.forward_if_ready();
+        }
+
+        if self.resume_mode {
+            self.send_status("resume");
+        } else {
+            self.send_status("waiting");
+        }
+    }
+}
+
+impl Node for ConferenceBridge {
+    fn handle(&mut self, input: &Input, _now: Instant) {
+        if input.id == "control" {
+            self.handle_control(&input.text, &input.metadata);
+        } else {
+            self.handle_participant(&input.id, input.text.clone(), &input.metadata);
+        }
+    }
+
+    fn take_actions(&mut self) -> Vec<Action> {
+        std::mem::take(&mut self.actions)
+    }
+}
@@ -0,0 +1,4 @@
+// Library exports for dora-conference-bridge
+// This allows the bridge state machine to be tested as a library
+
+pub mod bridge;
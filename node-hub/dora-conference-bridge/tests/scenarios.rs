@@ -0,0 +1,73 @@
+// Replays the scripted timelines in tests/scenarios against the bridge
+//
+// Each YAML file is one scenario; its `setup` section configures the bridge
+// the way the dataflow environment would.
+
+use dora_conference_bridge::bridge::{BridgeConfig, ConferenceBridge};
+use mofa_roster::Roster;
+use mofa_sim::Scenario;
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Instant;
+
+/// Bridge configuration of a scenario
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Setup {
+    /// STREAMING_PORTS
+    streaming_ports: Vec<String>,
+    /// The node's inputs other than `control`; defaults to the streaming ports
+    inputs: Vec<String>,
+    /// ERROR_MESSAGE_TEMPLATE
+    error_message_template: Option<String>,
+    /// Contents of the DORA_ROSTER file
+    roster: Option<String>,
+    /// DORA_STUDY_MODE
+    study_mode: bool,
+}
+
+impl Setup {
+    fn bridge(&self) -> ConferenceBridge {
+        let streaming_ports = self.streaming_ports.iter().cloned().collect();
+        let inputs = if self.inputs.is_empty() { &self.streaming_ports } else { &self.inputs };
+        ConferenceBridge::new(BridgeConfig {
+            streaming_ports,
+            expected_ports: inputs.iter().cloned().collect(),
+            error_message_template: self.error_message_template.clone(),
+            roster: self
+                .roster
+                .as_deref()
+                .map(|roster| roster.parse::<Roster>().unwrap()),
+            study_mode: self.study_mode,
+        })
+    }
+}
+
+#[test]
+fn test_scenarios() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/scenarios");
+    let mut paths: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "yml"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no scenarios in {}", dir.display());
+
+    let mut failures = Vec::new();
+    for path in &paths {
+        let scenario = Scenario::<Setup>::load(path).unwrap();
+        let mut bridge = scenario.setup.bridge();
+        match scenario.run(&mut bridge, Instant::now()) {
+            Ok(trace) => {
+                // Every bridge announces itself as waiting before any input
+                let first = trace.startup.iter().find_map(|action| action.as_output());
+                if first.map(|output| output.data.as_str()) != Some("waiting") {
+                    failures.push(format!("{}: did not start with status waiting", scenario.name));
+                }
+            }
+            Err(e) => failures.push(e.to_string()),
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
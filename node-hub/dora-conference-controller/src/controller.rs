@@ -0,0 +1,754 @@
+// Conference controller state machine
+//
+// Takes the controller's inputs one at a time and queues the outputs to send
+// (resume/reset commands, prompts, statistics and logs). The dora node in
+// main.rs only translates events and actions, so the scenario tests drive
+// exactly the same code with a scripted clock.
+
+use crate::policies::bid::parse_bid;
+use crate::policies::{Policy, PolicyConfig, PolicyKind, PolicySignal};
+use crate::session::{EndReason, SessionConfig, SessionTracker};
+use eyre::Result;
+use mofa_control::ControlMessage;
+use mofa_roster::Roster;
+use mofa_sim::{Action, Input, LogLevel, Node, Output};
+use mofa_stream::{keys, QuestionId, StreamStatus, TurnId};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+struct StreamingAccumulator {
+    accumulated_text: String,
+    accumulated_words: usize,
+}
+
+#[derive(Debug)]
+enum ControllerState {
+    Waiting,
+    Processing,  // A participant holds the floor
+}
+
+pub struct ConferenceController {
+    state: ControllerState,
+    policy: Box<dyn Policy>,
+    policy_kind: PolicyKind,
+    participant_inputs: HashSet<String>,  // Participants heard from since the last reset
+    streaming_accumulators: HashMap<String, StreamingAccumulator>,
+    reset_pending: bool,  // Track if reset is in progress - ignore incoming "reset" status
+    roster: Roster,  // Participant IDs, aliases and control outputs
+    current_question_id: TurnId,  // Turn ID sent as question_id with every resume/reset
+
+    // New session-start based resume control
+    waiting_for_session_start: Option<TurnId>,  // Turn we're waiting for
+    pending_next_speaker: bool,              // Flag that next speaker should be determined after session_start
+
+    // Session limits and closing protocol
+    session: SessionTracker,
+    closer: Option<String>,  // Participant behind judge_prompt
+    closing_prompt: String,  // Empty: end without a closing statement
+    closing_timeout: Duration,
+
+    // Clock: the epoch of the first turn ID and when the controller started;
+    // `now` is the time of the input being handled
+    start_epoch: u64,
+    started: Instant,
+    now: Instant,
+
+    actions: Vec<Action>,
+}
+
+impl ConferenceController {
+    /// Create a controller started at `now`; `epoch` (the current Unix time in
+    /// the node) is the epoch of the first turn ID
+    pub fn new(
+        config: PolicyConfig,
+        roster: Option<Roster>,
+        session_config: SessionConfig,
+        epoch: u64,
+        now: Instant,
+    ) -> Result<Self> {
+        let policy = config.build()
+            .map_err(|e| eyre::eyre!("Failed to configure {} policy from pattern: {}", config.kind, e))?;
+
+        // Every policy participant needs a control output; without a roster
+        // file the built-in debate/study role mapping is used
+        let participants = policy.get_participants();
+        let roster = match roster {
+            Some(roster) => {
+                roster.check_covers(&participants)?;
+                roster
+            }
+            None => Roster::legacy(&participants)?,
+        };
+
+        // The session moderator watches for end keywords: configured, else the
+        // policy's moderator, else judge/tutor, else the first participant
+        let moderator = session_config.moderator.as_deref()
+            .or(config.moderator.as_deref())
+            .or(roster.resolve("judge"))
+            .or(participants.first().map(String::as_str))
+            .map(|name| roster.resolve(name).unwrap_or(name).to_string());
+        // The closing prompt goes out on judge_prompt, so only the judge can
+        // give the closing statement
+        let closer = roster.resolve("judge").map(str::to_string);
+        let limits = session_config.limits;
+        let session = SessionTracker::new(limits.clone(), moderator.clone(), now);
+
+        // Start a turn-ID session; the first speaker is filled in on the first resume
+        let initial_turn = TurnId::new(epoch);
+
+        let mut controller = Self {
+            state: ControllerState::Waiting,
+            policy,
+            policy_kind: config.kind,
+            participant_inputs: HashSet::new(),
+            streaming_accumulators: HashMap::new(),
+            reset_pending: false,
+            roster,
+            current_question_id: initial_turn,
+            waiting_for_session_start: None,  // Cold start - no waiting initially
+            pending_next_speaker: false,
+            session,
+            closer,
+            closing_prompt: session_config.closing_prompt,
+            closing_timeout: session_config.closing_timeout,
+            start_epoch: epoch,
+            started: now,
+            now,
+            actions: Vec::new(),
+        };
+
+        controller.log(LogLevel::Info, format!("✅ Policy configured with participants: {:?}", participants));
+        let stats = controller.policy.get_stats();
+        controller.log(LogLevel::Info, format!("📊 Policy configuration:\n{}", serde_json::to_string_pretty(&stats)?));
+
+        let participant_logs: Vec<String> = controller.roster.participants().iter()
+            .map(|participant| format!("🔄 Participant {} ({}) → {}, aliases: {:?}",
+                participant.id, participant.display_name, participant.control_output, participant.aliases))
+            .collect();
+        for message in participant_logs {
+            controller.log(LogLevel::Info, message);
+        }
+
+        if limits.is_unlimited() {
+            controller.log(LogLevel::Info, "♾️ No session limits - running until reset or stop");
+        } else {
+            controller.log(LogLevel::Info,
+                format!("⏱️ Session limits: {:?}, moderator: {:?}, closer: {:?}",
+                    limits, moderator, controller.closer));
+        }
+
+        controller.log(LogLevel::Info,
+            format!("🏷️ Starting with turn id: {}", controller.current_question_id));
+
+        // Log the ready message after all initialization is complete
+        controller.log(LogLevel::Info, "🚀 all nodes are ready, starting dataflow");
+
+        Ok(controller)
+    }
+
+    /// Turn ID of the current (or next) turn
+    pub fn current_question_id(&self) -> &TurnId {
+        &self.current_question_id
+    }
+
+    fn log(&mut self, level: LogLevel, message: impl Into<String>) {
+        self.actions.push(Action::log(level, message));
+    }
+
+    fn send(&mut self, output: Output) {
+        self.actions.push(Action::Send(output));
+    }
+
+    /// An output carrying the current turn ID
+    fn turn_output(&self, id: &str, data: impl Into<String>) -> Output {
+        Output::new(id, data).with_metadata(keys::QUESTION_ID, self.current_question_id.to_string())
+    }
+
+    /// A control command on `id`, sent as its wire name
+    fn control_output(id: &str, message: ControlMessage) -> Output {
+        Output::new(id, message.command())
+    }
+
+    /// A `command` output carrying the current turn ID
+    fn command_output(&self, id: &str, message: ControlMessage) -> Output {
+        let command = message.command();
+        self.turn_output(id, command).with_metadata("command", command)
+    }
+
+    /// Current time in seconds, the epoch of a new turn-ID session
+    fn epoch_now(&self) -> u64 {
+        self.start_epoch + self.now.saturating_duration_since(self.started).as_secs()
+    }
+
+    /// Check if the stream status indicates the message is complete
+    fn is_message_complete(metadata: &BTreeMap<String, String>) -> bool {
+        // ended = normal completion
+        // error/cancelled/reset = abnormal completion (also triggers next speaker)
+        // Default to complete if no stream status (non-streaming)
+        mofa_stream::status(metadata).into_iter().all(StreamStatus::is_terminal)
+    }
+
+    /// Accumulate streaming chunk and return whether message is now complete
+    fn accumulate_streaming_input(
+        &mut self,
+        participant_id: &str,
+        text: String,
+        metadata: &BTreeMap<String, String>,
+    ) -> (String, usize, bool) {
+        let is_complete = Self::is_message_complete(metadata);
+        let word_count = text.split_whitespace().count();
+
+        if !is_complete || self.streaming_accumulators.contains_key(participant_id) {
+            // Streaming in progress or we have previous chunks
+            let accumulator = self.streaming_accumulators.entry(participant_id.to_string())
+                .or_insert_with(|| StreamingAccumulator {
+                    accumulated_text: String::new(),
+                    accumulated_words: 0,
+                });
+
+            if !accumulator.accumulated_text.is_empty() {
+                accumulator.accumulated_text.push(' ');
+            }
+            accumulator.accumulated_text.push_str(&text);
+            accumulator.accumulated_words += word_count;
+
+            if is_complete {
+                let complete_text = accumulator.accumulated_text.clone();
+                let complete_words = accumulator.accumulated_words;
+                self.streaming_accumulators.remove(participant_id);
+                (complete_text, complete_words, true)
+            } else {
+                (accumulator.accumulated_text.clone(), accumulator.accumulated_words, false)
+            }
+        } else {
+            // Non-streaming or first complete message
+            (text, word_count, true)
+        }
+    }
+
+    fn handle_participant_input(&mut self, participant_id: &str, text: String, metadata: &BTreeMap<String, String>) {
+        // Special handling for human input (non-streaming)
+        // Human input always arrives with session_status="ended" (single shot from ASR)
+        if participant_id == "human" {
+            return self.handle_human_input(&text);
+        }
+
+        // Check the stream status to understand the input type
+        let stream_status = mofa_stream::status(metadata);
+
+        // CRITICAL: Check if this is a reset signal from participant output
+        // status: "reset" from LLM output = LAST message from old debate
+        if stream_status == Some(StreamStatus::Reset) {
+            self.log(LogLevel::Info,
+                format!("🔄 RESET SIGNAL from {} - discarding ALL inputs", participant_id));
+            self.participant_inputs.clear();
+            self.streaming_accumulators.clear();
+            self.state = ControllerState::Waiting;
+            self.reset_pending = true;
+            return;
+        }
+
+        // If reset_pending is true, ignore ALL inputs EXCEPT "started" status
+        if self.reset_pending {
+            if stream_status == Some(StreamStatus::Started) {
+                self.log(LogLevel::Info,
+                    format!("🎬 New debate starting from {}", participant_id));
+                self.reset_pending = false;
+            } else {
+                return;  // Ignore stale inputs while reset_pending
+            }
+        }
+
+        // Check if this is an error status
+        let is_error = matches!(stream_status, Some(StreamStatus::Error | StreamStatus::Cancelled));
+
+        if is_error {
+            self.log(LogLevel::Warn,
+                format!("❌ {} had an error - proceeding to next speaker", participant_id));
+
+            // Clear any accumulated streaming data for this participant
+            self.streaming_accumulators.remove(participant_id);
+
+            // A failed closing statement still ends the session
+            if self.is_closing_reply(participant_id) {
+                return self.finish_session();
+            }
+
+            // Proceed to next speaker immediately
+            return self.process_next_speaker();
+        }
+
+        // Accumulate streaming chunks and check if message is complete
+        let (complete_text, word_count, is_complete) =
+            self.accumulate_streaming_input(participant_id, text, metadata);
+
+        self.participant_inputs.insert(participant_id.to_string());
+
+        // Always accumulate word counts, but only process when complete
+        self.policy.update_word_count(participant_id, word_count);
+
+        if is_complete {
+            self.log(LogLevel::Info,
+                format!("📥 {} completed ({} words)", participant_id, word_count));
+
+            let speaker = self.roster.resolve(participant_id).unwrap_or(participant_id).to_string();
+            self.session.record_reply(&speaker, &complete_text, word_count);
+            if self.is_closing_reply(participant_id) {
+                return self.finish_session();
+            }
+
+            // Process next speaker (will wait for session_start if needed)
+            self.process_next_speaker();
+        }
+    }
+
+    /// Whether input from `participant_id` (an ID or alias) is the awaited
+    /// closing statement
+    fn is_closing_reply(&self, participant_id: &str) -> bool {
+        let speaker = self.roster.resolve(participant_id).unwrap_or(participant_id);
+        self.session.is_closing_reply(speaker)
+    }
+
+    /// Handle session_start signals from audio player
+    fn handle_session_start(&mut self, question_id: QuestionId) {
+        self.log(LogLevel::Info, format!("🎬 Session start: {}", question_id));
+
+        // Check if this is the session_start we're waiting for
+        if self.waiting_for_session_start.is_some()
+            && self.waiting_for_session_start.as_ref() == question_id.as_turn()
+        {
+            self.log(LogLevel::Info, "✅ Participant audio started - ready for next speaker");
+
+            // Clear waiting state
+            self.waiting_for_session_start = None;
+
+            // If we have a pending next speaker request, process it now
+            if self.pending_next_speaker {
+                self.log(LogLevel::Info, "🔄 Processing pending next speaker");
+                self.pending_next_speaker = false;
+                self.process_next_speaker();
+            }
+        } else {
+            self.log(LogLevel::Debug,
+                format!("📝 Session start for question_id={} (not waiting for this one)", question_id));
+        }
+    }
+
+    fn process_next_speaker(&mut self) {
+        // A closing or ended session resumes nobody
+        if !self.session.is_running() {
+            self.log(LogLevel::Debug,
+                format!("🏁 Session {} - not resuming participants", self.session.phase().as_str()));
+            return;
+        }
+
+        // Limits are checked between turns, never cutting a reply short
+        if let Some(reason) = self.session.check(self.now) {
+            return self.close_session(reason);
+        }
+
+        // Check if we're waiting for a session_start
+        if self.waiting_for_session_start.is_some() {
+            self.log(LogLevel::Debug, "⏳ Waiting for session_start - marking pending");
+            // Mark that we need to process next speaker after session_start arrives
+            self.pending_next_speaker = true;
+            return;
+        }
+
+        // Cold start or session_start already received - proceed immediately
+        let Some(next_speaker) = self.policy.determine_next_speaker() else {
+            self.log(LogLevel::Warn, "⚠️ No next speaker");
+            return self.close_session(EndReason::PolicyExhausted);
+        };
+
+        // Map the participant ID to the correct control output
+        let control_output = self.get_control_output(&next_speaker).to_string();
+
+        // Only start a NEW round if cycle > 0 (normal operation)
+        // If cycle == 0, it means we just reset and the round was already set;
+        // the turn is just handed to the chosen speaker
+        let cycle = self.policy.get_current_cycle();
+        self.current_question_id = if cycle > 0 {
+            self.current_question_id.next_round(next_speaker.as_str())
+        } else {
+            self.current_question_id.with_speaker(next_speaker.as_str())
+        };
+
+        // Increment cycle counter
+        self.policy.increment_cycle();
+        self.session.record_turn();
+        self.state = ControllerState::Processing;
+
+        self.log(LogLevel::Info,
+            format!("🎯 Resume: {} → {} (question_id: {}, cycle: {})",
+                next_speaker, control_output, self.current_question_id, cycle));
+
+        // Send resume WITH controller's question_id
+        self.send(self.turn_output(&control_output, ControlMessage::Resume.command()));
+
+        // Now wait for this participant's session_start before next resume
+        self.waiting_for_session_start = Some(self.current_question_id.clone());
+
+        self.log(LogLevel::Debug,
+            format!("⏳ Now waiting for session_start for question_id={}", self.current_question_id));
+
+        // Send policy statistics
+        self.send(Output::new("status", self.policy.get_stats().to_string()));
+    }
+
+    /// Stop resuming participants and ask the judge for a closing statement
+    fn close_session(&mut self, reason: EndReason) {
+        let closer = self.closer.clone()
+            .filter(|_| !self.closing_prompt.trim().is_empty());
+        self.log(LogLevel::Info,
+            format!("🏁 Session limit reached ({}) after {} rounds, {} words",
+                reason, self.session.rounds(), self.session.words()));
+        self.session.begin_closing(reason, closer.clone(), self.now);
+        self.waiting_for_session_start = None;
+        self.pending_next_speaker = false;
+
+        let Some(closer) = closer else {
+            return self.emit_session_ended();
+        };
+
+        // The closing statement is its own turn, prompted like a user prompt
+        self.current_question_id = self.current_question_id.next_round(closer.as_str());
+        let prompt = ControlMessage::prompt(self.closing_prompt.clone()).encode();
+        self.send(self.turn_output("judge_prompt", prompt));
+
+        self.log(LogLevel::Info,
+            format!("🧑‍⚖️ Asked {} for the closing statement (question_id: {})", closer, self.current_question_id));
+    }
+
+    /// End a closing session whose closing statement never arrived
+    fn check_closing_timeout(&mut self) {
+        if self.session.closing_timed_out(self.now, self.closing_timeout) {
+            self.log(LogLevel::Warn,
+                format!("⏰ No closing statement after {:?} - ending the session", self.closing_timeout));
+            self.finish_session();
+        }
+    }
+
+    /// The closing statement arrived (or failed): end the session
+    fn finish_session(&mut self) {
+        self.session.finish();
+        self.emit_session_ended();
+    }
+
+    fn emit_session_ended(&mut self) {
+        let stats = self.get_stats().to_string();
+        self.send(Output::new("session_ended", stats.clone()));
+        self.send(Output::new("status", stats));
+
+        self.log(LogLevel::Info, "🏁 Session ended - send reset to start a new one");
+    }
+
+    /// Handle input from human speaker (via ASR)
+    /// Human input is non-streaming - always arrives complete with session_status="ended"
+    /// When human speaks, interrupt all AI participants and reset system to initial state
+    fn handle_human_input(&mut self, text: &str) {
+        self.log(LogLevel::Info,
+            format!("👤 Human input received: '{}'", text.chars().take(100).collect::<String>()));
+
+        // Human input is always complete (non-streaming ASR output)
+        // ASR modification ensures session_status="ended" is always present
+        // So we immediately trigger interrupt sequence
+
+        // 1. Store current question_id for logging
+        let old_question_id = self.current_question_id.clone();
+
+        // 2. START A NEW interrupt generation (CRITICAL!)
+        // Everything tagged with an older generation is now stale for the
+        // audio player; the speaker is filled in by the first resume
+        self.current_question_id = old_question_id.interrupted();
+
+        self.log(LogLevel::Info,
+            format!("📈 New turn id after interrupt: {} → {}", old_question_id, self.current_question_id));
+
+        // 3. Cancel all LLMs with NEW question_id
+        // LLMs will abort streaming and propagate question_id to downstream
+        self.send_cancel_to_all_llms();
+
+        // 4. Reset all bridges with NEW question_id
+        // Bridges will clear buffered messages
+        self.send_reset_to_all_bridges();
+
+        // 5. Reset audio pipeline (text-segmenter + audio-player) with NEW question_id
+        // Text-segmenter: discards segments with old question_id, keeps new
+        // Audio-player: discards audio with old question_id, keeps new
+        self.send_reset_to_audio_pipeline();
+
+        // 6. Reset controller state to initial (tutor speaks first, cycle=0)
+        self.reset_to_initial_state();
+
+        self.log(LogLevel::Info, "✅ System reset complete - ready for new round");
+    }
+
+    /// Get control output name for a participant
+    fn get_control_output(&self, participant: &str) -> &str {
+        self.roster.get(participant)
+            .map(|p| p.control_output.as_str())
+            .unwrap_or("control_llm1")
+    }
+
+    /// Send cancel signal to all LLM participants with NEW question_id
+    fn send_cancel_to_all_llms(&mut self) {
+        // Send to student1 and student2 via llm_control
+        self.send(self.command_output("llm_control", ControlMessage::Cancel));
+
+        // Send to tutor via judge_prompt
+        self.send(self.command_output("judge_prompt", ControlMessage::Cancel));
+
+        self.log(LogLevel::Debug,
+            format!("🛑 Sent cancel to all LLMs with question_id={}", self.current_question_id));
+    }
+
+    /// Send reset signal to all bridges with NEW question_id
+    fn send_reset_to_all_bridges(&mut self) {
+        // Send reset to all bridge control outputs
+        let control_outputs: Vec<String> = self.roster.control_outputs().into_iter()
+            .map(str::to_string)
+            .collect();
+        for control_output in control_outputs {
+            self.send(self.command_output(&control_output, ControlMessage::Reset));
+        }
+
+        self.log(LogLevel::Debug,
+            format!("🔄 Sent reset to all bridges with question_id={}", self.current_question_id));
+    }
+
+    /// Send reset signal to audio pipeline (text-segmenter + audio-player) with NEW question_id
+    fn send_reset_to_audio_pipeline(&mut self) {
+        // Send reset to llm_control (will reach text-segmenter)
+        // Text-segmenter will discard segments with question_id != current_question_id
+        // Audio-player will receive reset via its reset input (configured in YAML)
+        self.send(self.command_output("llm_control", ControlMessage::Reset));
+
+        self.log(LogLevel::Debug,
+            format!("🔄 Sent reset to audio pipeline with question_id={}", self.current_question_id));
+    }
+
+    /// Reset controller to initial state (tutor speaks first, cycle=0)
+    fn reset_to_initial_state(&mut self) {
+        self.log(LogLevel::Info, "🔄 Resetting controller to initial state");
+
+        // 1. Clear all accumulated inputs
+        self.participant_inputs.clear();
+
+        // 2. Clear streaming accumulators
+        self.streaming_accumulators.clear();
+
+        // 3. Reset state
+        self.state = ControllerState::Waiting;
+        self.reset_pending = false;
+        self.waiting_for_session_start = None;
+        self.pending_next_speaker = false;
+
+        // 4. Reset policy to initial state
+        self.policy.reset_counts();
+
+        // 5. Set last_speaker to "human" to avoid cold start logic
+        // This ensures tutor (priority) responds after human speaks,
+        // instead of student1 winning the ratio calculation during cold start.
+        self.policy.set_last_speaker(Some("human".to_string()));
+
+        self.log(LogLevel::Info,
+            format!("✅ Reset complete - ready to start with question_id={}", self.current_question_id));
+
+        // 6. Trigger initial speaker (tutor via priority)
+        // Use existing logic to process first speaker
+        self.process_next_speaker();
+    }
+
+    /// Start a new conversation (`reset` control command)
+    fn reset(&mut self) {
+        // Start a new turn-ID epoch for the fresh conversation
+        self.current_question_id = self.current_question_id.next_epoch(self.epoch_now());
+
+        self.log(LogLevel::Info, "🔄 Resetting controller");
+        self.reset_pending = true;
+
+        // Send reset to all bridges with NEW question_id (same as human speaker reset)
+        self.send_reset_to_all_bridges();
+
+        // Send reset to audio pipeline (text-segmenter + audio-player) with NEW question_id
+        self.send_reset_to_audio_pipeline();
+
+        // Send reset to LLMs and judge
+        self.send(Self::control_output("llm_control", ControlMessage::Reset));
+        self.send(Self::control_output("judge_prompt", ControlMessage::Reset));
+
+        // Reset internal state
+        self.participant_inputs.clear();
+        self.streaming_accumulators.clear();
+        self.waiting_for_session_start = None;
+        self.pending_next_speaker = false;
+        self.policy.reset_counts();
+        self.policy.reset_round_tracking();
+        self.state = ControllerState::Waiting;
+        self.session.restart(self.now);
+
+        self.log(LogLevel::Info, "✅ Reset complete");
+    }
+
+    fn handle_control(&mut self, text: &str) {
+        match ControlMessage::parse(text) {
+            Ok(ControlMessage::Prompt { prompt }) => {
+                // Forward prompt to judge with question_id metadata
+                self.log(LogLevel::Info,
+                    format!("📤 Forwarding user prompt to judge with question_id={}: {}",
+                        self.current_question_id, prompt));
+                self.send(self.turn_output("judge_prompt", ControlMessage::prompt(prompt).encode()));
+            }
+            Ok(ControlMessage::Reset) => self.reset(),
+            Ok(ControlMessage::Cancel) => {
+                // Forward cancel to LLM1/LLM2 and the judge
+                self.send(Self::control_output("llm_control", ControlMessage::Cancel));
+                self.send(Self::control_output("judge_prompt", ControlMessage::Cancel));
+                self.log(LogLevel::Info, "🛑 Sent cancel command to all LLMs");
+            }
+            Ok(ControlMessage::Ready) => self.send(Output::new("status", "ready")),
+            Ok(ControlMessage::Stats) => self.send(Output::new("status", self.get_stats().to_string())),
+            Ok(other) => self.log(LogLevel::Warn, format!("Unsupported control command: {}", other)),
+            Err(e) => self.log(LogLevel::Warn, e.to_string()),
+        }
+    }
+
+    /// IMMEDIATE interrupt when human starts speaking
+    ///
+    /// Doesn't wait for the ASR transcription - cancels everything now.
+    fn handle_human_speaking(&mut self) {
+        self.log(LogLevel::Info, "🎤 Human speaking detected - IMMEDIATE INTERRUPT");
+
+        // Send cancel to all LLMs immediately
+        self.send(Self::control_output("llm_control", ControlMessage::Cancel));
+        self.send(Self::control_output("judge_prompt", ControlMessage::Cancel));
+
+        // Send cancel to text segmenter to clear pending text, using the next
+        // interrupt generation to ensure all old segments are cleared (the
+        // same turn id the ASR transcription will start)
+        let interrupt_qid = self.current_question_id.interrupted();
+        let cancel = ControlMessage::Cancel.command();
+        self.send(Output::new("segmenter_control", cancel)
+            .with_metadata("command", cancel)
+            .with_metadata(keys::QUESTION_ID, interrupt_qid.to_string()));
+
+        self.log(LogLevel::Info, "🔇 Sent immediate cancel to all LLMs and text segmenter");
+    }
+
+    /// Question ended signal - prolonged silence after speech
+    ///
+    /// The user has finished their question; ASR has already sent the
+    /// transcription on `human`. Sends a final reset so the audio player's
+    /// buffer is clear of anything from before the interrupt.
+    fn handle_question_ended(&mut self, metadata: &BTreeMap<String, String>) {
+        let question_id = QuestionId::read(metadata)
+            .map_or_else(|| "none".to_string(), |qid| qid.to_string());
+
+        self.log(LogLevel::Info,
+            format!("⏱️ QUESTION_ENDED received (question_id={}) - user finished speaking", question_id));
+
+        self.send(self.command_output("llm_control", ControlMessage::Reset));
+
+        self.log(LogLevel::Info, "📤 Sent reset to audio pipeline (question_ended confirmation)");
+    }
+
+    /// Forward played audio duration from the audio player to the policy
+    fn handle_audio_complete(&mut self, metadata: &BTreeMap<String, String>) {
+        let Some(participant) = metadata.get("participant") else {
+            return;
+        };
+        let Some(seconds) = metadata.get("audio_seconds").and_then(|s| s.parse::<f64>().ok()) else {
+            return;
+        };
+        let speaker = self.roster.resolve(participant).unwrap_or(participant.as_str()).to_string();
+        self.policy.observe(PolicySignal::AudioPlayed { speaker: &speaker, seconds });
+    }
+
+    /// Forward a participant's bid for the floor to the policy
+    fn handle_bid(&mut self, text: &str) {
+        match parse_bid(text) {
+            Some((participant, strength)) => {
+                let speaker = self.roster.resolve(&participant).unwrap_or(participant.as_str()).to_string();
+                self.log(LogLevel::Debug, format!("🙋 Bid from {} (strength {})", speaker, strength));
+                self.policy.observe(PolicySignal::Bid { speaker: &speaker, strength });
+            }
+            None => {
+                self.log(LogLevel::Warn, format!("⚠️ Ignoring malformed bid: {}", text));
+            }
+        }
+    }
+
+    /// Forward the moderator's structured reply to the policy
+    fn handle_moderator_reply(&mut self, text: &str) {
+        match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(reply) => {
+                self.log(LogLevel::Debug, format!("🧑‍⚖️ Moderator reply: {}", reply));
+                self.policy.observe(PolicySignal::ModeratorReply { reply: &reply });
+                self.session.record_moderator_text(text);
+            }
+            Err(e) => {
+                self.log(LogLevel::Warn, format!("⚠️ Moderator reply is not JSON ({}): {}", e, text));
+            }
+        }
+    }
+
+    pub fn get_stats(&self) -> serde_json::Value {
+        let mut stats = self.policy.get_stats();
+
+        if let serde_json::Value::Object(ref mut map) = stats {
+            map.insert("policy".to_string(), serde_json::Value::String(self.policy_kind.to_string()));
+            map.insert("session".to_string(), self.session.to_json(self.now));
+            map.insert("input_count".to_string(), serde_json::Value::Number(self.participant_inputs.len().into()));
+            map.insert(
+                "controller_state".to_string(),
+                serde_json::Value::String(format!("{:?}", self.state))
+            );
+        }
+
+        stats
+    }
+}
+
+impl Node for ConferenceController {
+    fn handle(&mut self, input: &Input, now: Instant) {
+        self.now = now;
+        self.log(LogLevel::Debug, format!("📨 Received event from input: '{}'", input.id));
+
+        // Any input (e.g. buffer_status) advances the closing timeout
+        self.check_closing_timeout();
+
+        match input.id.as_str() {
+            "control" => self.handle_control(input.text.trim()),
+            "session_start" => {
+                // When we receive session_start for the turn we handed out,
+                // the next speaker may be resumed
+                self.log(LogLevel::Info, "🎬 Received session_start input from audio player");
+                match QuestionId::read(&input.metadata) {
+                    Some(qid @ QuestionId::Turn(_)) => self.handle_session_start(qid),
+                    Some(other) => self.log(LogLevel::Warn,
+                        format!("⚠️ session_start question_id '{}' is not a turn id - ignoring", other)),
+                    None => self.log(LogLevel::Warn, "⚠️ Session start signal missing question_id metadata"),
+                }
+            }
+            // Buffer status from audio player - not used anymore
+            "buffer_status" => self.log(LogLevel::Debug, "📊 Received buffer_status (ignored)"),
+            "human_speaking" => self.handle_human_speaking(),
+            "question_ended" => self.handle_question_ended(&input.metadata),
+            // Played audio duration per participant (fair_audio policy)
+            "audio_complete" => self.handle_audio_complete(&input.metadata),
+            // Policy side channels: bids for the floor, moderator's structured reply
+            "bid" => self.handle_bid(&input.text),
+            "moderator" => self.handle_moderator_reply(&input.text),
+            participant => {
+                self.log(LogLevel::Debug, format!("📨 Processing input from {}", participant));
+                self.handle_participant_input(participant, input.text.clone(), &input.metadata);
+            }
+        }
+    }
+
+    fn take_actions(&mut self) -> Vec<Action> {
+        std::mem::take(&mut self.actions)
+    }
+}
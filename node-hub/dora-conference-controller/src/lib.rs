@@ -1,4 +1,7 @@
 // Library exports for dora-conference-controller
-// This allows the policy module to be tested as a library
+// This allows the controller state machine, policy and session modules to be
+// tested as a library
 
+pub mod controller;
 pub mod policies;
+pub mod session;
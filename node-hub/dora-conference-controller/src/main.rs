@@ -1,70 +1,20 @@
 use dora_node_api::{self, DoraNode, Event, Parameter};
-use dora_node_api::arrow::array::{StringArray, AsArray};
-use dora_node_api::arrow;
-use dora_conference_controller::policies::{Policy, UnifiedRatioPolicy};
+use dora_node_api::arrow::array::{AsArray, StringArray};
+use dora_conference_controller::controller::ConferenceController;
+use dora_conference_controller::policies::{PolicyConfig, PolicyKind};
+use dora_conference_controller::session::{SessionConfig, SessionLimits};
 use dora_core::config::DataId;
 use eyre::Result;
-use std::collections::HashMap;
+use mofa_roster::Roster;
+use mofa_sim::{Action, Input, LogLevel, Node};
+use mofa_stream::MetadataRead;
+use std::collections::BTreeMap;
 use std::env;
+use std::time::{Duration, Instant};
 
-// Enhanced Question ID (16-bit: 8-4-4 layout)
-// Bits 15-8: Round number (0-255)
-// Bits 7-4: Total participants (1-16, stored as total-1)
-// Bits 3-0: Current participant (0-15)
-fn encode_enhanced_question_id(round: u8, participant: u8, total_participants: u8) -> u16 {
-    let round_bits = (round as u16) << 8;
-    let total_bits = ((total_participants - 1) as u16) << 4;
-    let participant_bits = participant as u16;
-
-    round_bits | total_bits | participant_bits
-}
-
-fn decode_enhanced_question_id(question_id: u16) -> (u8, u8, u8, bool) {
-    let round = (question_id >> 8) as u8;
-    let total_participants = ((question_id >> 4) & 0xF) + 1;
-    let participant = (question_id & 0xF) as u8;
-    let is_last_participant = participant + 1 == total_participants as u8;
-
-    (round, participant, total_participants as u8, is_last_participant)
-}
-
-fn enhanced_id_debug_string(question_id: u16) -> String {
-    let (round, participant, total, is_last) = decode_enhanced_question_id(question_id);
-    format!("R{}P{}/{}{}", round + 1, participant + 1, total, if is_last {"[LAST]"} else {""})
-}
-
-fn is_last_participant(question_id: u16) -> bool {
-    let (_, _, _, is_last) = decode_enhanced_question_id(question_id);
-    is_last
-}
-
-fn get_round_number(question_id: u16) -> u8 {
-    (question_id >> 8) as u8 + 1
-}
-
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum LogLevel {
-    Error = 0,
-    Warn = 1,
-    Info = 2,
-    Debug = 3,
-}
-
-impl LogLevel {
-    fn parse(value: &str) -> Option<Self> {
-        match value.to_ascii_lowercase().as_str() {
-            "error" => Some(LogLevel::Error),
-            "warn" | "warning" => Some(LogLevel::Warn),
-            "info" => Some(LogLevel::Info),
-            "debug" => Some(LogLevel::Debug),
-            _ => None,
-        }
-    }
-
-    fn allows(self, other: LogLevel) -> bool {
-        other as i32 <= self as i32
-    }
+/// Current time in seconds, the epoch of the first turn-ID session
+fn epoch_now() -> u64 {
+    chrono::Utc::now().timestamp().max(0) as u64
 }
 
 fn send_log(node: &mut DoraNode, level: LogLevel, config_level: LogLevel, message: &str) {
@@ -94,673 +44,137 @@ fn send_log(node: &mut DoraNode, level: LogLevel, config_level: LogLevel, messag
     }
 }
 
-#[derive(Debug, Clone)]
-struct ParticipantInput {
-    id: String,
-    text: String,
-    timestamp: i64,
-    word_count: usize,
-    is_complete: bool,
-}
-
-#[derive(Debug, Clone)]
-struct StreamingAccumulator {
-    accumulated_text: String,
-    accumulated_words: usize,
-}
-
-#[derive(Debug)]
-enum ControllerState {
-    Waiting,
-    Processing,
-}
-
-struct ConferenceController {
-    state: ControllerState,
-    policy: UnifiedRatioPolicy,
-    participant_inputs: HashMap<String, ParticipantInput>,
-    streaming_accumulators: HashMap<String, StreamingAccumulator>,
-    pattern: String,
-    log_level: LogLevel,
-    reset_pending: bool,  // Track if reset is in progress - ignore incoming "reset" status
-    participant_name_map: HashMap<String, String>, // Maps role -> participant ID (e.g., "judge" -> "tutor")
-    participant_index_map: HashMap<String, u8>,  // Maps participant ID -> index (0-based)
-    current_question_id: u16,  // Track current conversation question ID (enhanced 16-bit format)
-
-    // New session-start based resume control
-    waiting_for_session_start: Option<u16>,  // Question ID we're waiting for
-    pending_next_speaker: bool,              // Flag that next speaker should be determined after session_start
-
-    // Human interrupt control
-    system_paused: bool,  // True when human is speaking or processing human input
-}
-
-impl ConferenceController {
-    fn new(pattern: String, node: &mut DoraNode, log_level: LogLevel) -> Result<Self> {
-        let mut policy = UnifiedRatioPolicy::new();
-        policy.configure(&pattern)
-            .map_err(|e| eyre::eyre!("Failed to configure policy from pattern: {}", e))?;
-
-        send_log(node, LogLevel::Info, log_level, &format!("✅ Policy configured with participants: {:?}", policy.get_participants()));
-        let stats = policy.get_stats();
-        send_log(node, LogLevel::Info, log_level, &format!("📊 Policy configuration:\n{}", serde_json::to_string_pretty(&stats).unwrap()));
-
-        // Initialize participant name mapping
-        let participants = policy.get_participants();
-        let mut participant_name_map = HashMap::new();
-
-        // Create mapping from role to participant ID
-        // This allows the controller to work with different naming schemes
-        for participant_id in &participants {
-            match participant_id.as_str() {
-                "llm1" | "student1" => {
-                    participant_name_map.insert("llm1".to_string(), participant_id.clone());
-                    participant_name_map.insert("student1".to_string(), participant_id.clone());
-                },
-                "llm2" | "student2" => {
-                    participant_name_map.insert("llm2".to_string(), participant_id.clone());
-                    participant_name_map.insert("student2".to_string(), participant_id.clone());
-                },
-                "judge" | "tutor" => {
-                    participant_name_map.insert("judge".to_string(), participant_id.clone());
-                    participant_name_map.insert("tutor".to_string(), participant_id.clone());
-                },
-                _ => {
-                    participant_name_map.insert(participant_id.clone(), participant_id.clone());
-                }
-            }
-        }
-
-        send_log(node, LogLevel::Info, log_level, &format!("🔄 Participant name mapping: {:?}", participant_name_map));
-
-        // Initialize participant index mapping (0-based)
-        let mut participant_index_map = HashMap::new();
-        for (index, participant_id) in participants.iter().enumerate() {
-            participant_index_map.insert(participant_id.clone(), index as u8);
-            send_log(node, LogLevel::Debug, log_level,
-                &format!("📍 Participant index mapping: {} -> {}", participant_id, index));
-        }
-
-        // Initialize enhanced question_id for round 1
-        let initial_round = 0; // 0-based for encoding
-        let total_participants = participants.len() as u8;
-        // Start with first participant (index 0)
-        let initial_enhanced_id = encode_enhanced_question_id(initial_round, 0, total_participants);
-
-        send_log(node, LogLevel::Info, log_level,
-            &format!("🏷️ Starting with enhanced question_id: {} ({})",
-                initial_enhanced_id, enhanced_id_debug_string(initial_enhanced_id)));
-
-        // Log the ready message after all initialization is complete
-        send_log(node, LogLevel::Info, log_level, "🚀 all nodes are ready, starting dataflow");
-
-        Ok(Self {
-            state: ControllerState::Waiting,
-            policy,
-            participant_inputs: HashMap::new(),
-            streaming_accumulators: HashMap::new(),
-            pattern,
-            log_level,
-            reset_pending: false,
-            participant_name_map,
-            participant_index_map,
-            current_question_id: initial_enhanced_id,
-            waiting_for_session_start: None,  // Cold start - no waiting initially
-            pending_next_speaker: false,
-            system_paused: false,  // Initialize as not paused
-        })
-    }
-
-    /// Check if metadata indicates the message is complete
-    fn is_message_complete(&self, metadata: &dora_node_api::Metadata) -> bool {
-        if let Some(Parameter::String(status)) = metadata.parameters.get("session_status") {
-            // ended/complete = normal completion
-            // error/cancelled/reset = abnormal completion (also triggers next speaker)
-            return status == "ended" || status == "complete"
-                || status == "error" || status == "cancelled" || status == "reset";
-        }
-
-        // Default to complete if no metadata (non-streaming)
-        true
-    }
-
-    /// Check if metadata indicates an error occurred
-    fn is_error_status(&self, metadata: &dora_node_api::Metadata) -> bool {
-        if let Some(Parameter::String(status)) = metadata.parameters.get("session_status") {
-            return status == "error" || status == "cancelled" || status == "reset";
-        }
-        false
-    }
-
-    /// Accumulate streaming chunk and return whether message is now complete
-    fn accumulate_streaming_input(
-        &mut self,
-        participant_id: &str,
-        text: String,
-        metadata: &dora_node_api::Metadata,
-    ) -> (String, usize, bool) {
-        let is_complete = self.is_message_complete(metadata);
-        let word_count = text.split_whitespace().count();
-
-        if !is_complete || self.streaming_accumulators.contains_key(participant_id) {
-            // Streaming in progress or we have previous chunks
-            let accumulator = self.streaming_accumulators.entry(participant_id.to_string())
-                .or_insert_with(|| StreamingAccumulator {
-                    accumulated_text: String::new(),
-                    accumulated_words: 0,
-                });
-
-            if !accumulator.accumulated_text.is_empty() {
-                accumulator.accumulated_text.push(' ');
+/// Send the controller's queued outputs and logs
+fn perform(node: &mut DoraNode, actions: Vec<Action>, log_level: LogLevel) -> Result<()> {
+    for action in actions {
+        match action {
+            Action::Send(output) => {
+                let metadata: BTreeMap<String, Parameter> = output.metadata.into_iter()
+                    .map(|(key, value)| (key, Parameter::String(value)))
+                    .collect();
+                node.send_output(
+                    DataId::from(output.id),
+                    metadata,
+                    StringArray::from(vec![output.data.as_str()]),
+                )?;
             }
-            accumulator.accumulated_text.push_str(&text);
-            accumulator.accumulated_words += word_count;
-
-            if is_complete {
-                let complete_text = accumulator.accumulated_text.clone();
-                let complete_words = accumulator.accumulated_words;
-                self.streaming_accumulators.remove(participant_id);
-                (complete_text, complete_words, true)
-            } else {
-                (accumulator.accumulated_text.clone(), accumulator.accumulated_words, false)
-            }
-        } else {
-            // Non-streaming or first complete message
-            (text, word_count, true)
+            Action::Log { level, message } => send_log(node, level, log_level, &message),
         }
     }
-
-    fn handle_participant_input(
-        &mut self,
-        participant_id: &str,
-        text: String,
-        metadata: &dora_node_api::Metadata,
-        node: &mut DoraNode,
-    ) -> Result<()> {
-        // NEW: Special handling for human input (non-streaming)
-        // Human input always arrives with session_status="ended" (single shot from ASR)
-        if participant_id == "human" {
-            return self.handle_human_input(participant_id, text, metadata, node);
-        }
-
-        // Check session_status to understand the input type
-        let session_status = metadata.parameters.get("session_status")
-            .and_then(|p| match p { Parameter::String(s) => Some(s.as_str()), _ => None });
-
-        // CRITICAL: Check if this is a reset signal from participant output
-        // session_status: "reset" from LLM output = LAST message from old debate
-        if session_status == Some("reset") {
-            send_log(node, LogLevel::Info, self.log_level,
-                &format!("🔄 RESET SIGNAL from {} - discarding ALL inputs", participant_id));
-            self.participant_inputs.clear();
-            self.streaming_accumulators.clear();
-            self.state = ControllerState::Waiting;
-            self.reset_pending = true;
-            return Ok(());
-        }
-
-        // If reset_pending is true, ignore ALL inputs EXCEPT "started" status
-        if self.reset_pending {
-            if session_status == Some("started") {
-                send_log(node, LogLevel::Info, self.log_level,
-                    &format!("🎬 New debate starting from {}", participant_id));
-                self.reset_pending = false;
-            } else {
-                return Ok(());  // Ignore stale inputs while reset_pending
-            }
-        }
-
-        // Check if this is an error status
-        let is_error = session_status == Some("error") || session_status == Some("cancelled");
-
-        if is_error {
-            send_log(node, LogLevel::Warn, self.log_level,
-                &format!("❌ {} had an error - proceeding to next speaker", participant_id));
-
-            // Clear any accumulated streaming data for this participant
-            self.streaming_accumulators.remove(participant_id);
-
-            // Proceed to next speaker immediately
-            self.process_next_speaker(node)?;
-            return Ok(());
-        }
-
-        // Accumulate streaming chunks and check if message is complete
-        let (complete_text, word_count, is_complete) =
-            self.accumulate_streaming_input(participant_id, text, metadata);
-
-        // Store the input
-        let input = ParticipantInput {
-            id: participant_id.to_string(),
-            text: complete_text.clone(),
-            timestamp: chrono::Utc::now().timestamp(),
-            word_count,
-            is_complete,
-        };
-        self.participant_inputs.insert(participant_id.to_string(), input);
-
-        // Always accumulate word counts, but only process when complete
-        self.policy.update_word_count(participant_id, word_count);
-
-        if is_complete {
-            send_log(node, LogLevel::Info, self.log_level,
-                &format!("📥 {} completed ({} words)", participant_id, word_count));
-
-            // Process next speaker (will wait for session_start if needed)
-            self.process_next_speaker(node)?;
-        }
-
-        Ok(())
-    }
-
-    /// Generate new question_id for next conversation round
-    /// Handle session_start signals from audio player
-    fn handle_session_start(&mut self, question_id: u16, node: &mut DoraNode, log_level: LogLevel) -> Result<()> {
-        let (cycle, participant, total, _) = decode_enhanced_question_id(question_id);
-
-        send_log(node, LogLevel::Info, log_level,
-            &format!("🎬 Session start: {} ({})",
-                question_id, enhanced_id_debug_string(question_id)));
-
-        // Check if this is the session_start we're waiting for
-        if self.waiting_for_session_start == Some(question_id) {
-            send_log(node, LogLevel::Info, log_level,
-                "✅ Participant audio started - ready for next speaker");
-
-            // Clear waiting state
-            self.waiting_for_session_start = None;
-
-            // If we have a pending next speaker request, process it now
-            if self.pending_next_speaker {
-                send_log(node, LogLevel::Info, log_level,
-                    "🔄 Processing pending next speaker");
-                self.pending_next_speaker = false;
-                self.process_next_speaker(node)?;
-            }
-        } else {
-            send_log(node, LogLevel::Debug, log_level,
-                &format!("📝 Session start for question_id={} (not waiting for this one)", question_id));
-        }
-
-        Ok(())
-    }
-
-    fn process_next_speaker(&mut self, node: &mut DoraNode) -> Result<()> {
-        // Check if we're waiting for a session_start
-        if self.waiting_for_session_start.is_some() {
-            send_log(node, LogLevel::Debug, self.log_level,
-                "⏳ Waiting for session_start - marking pending");
-            // Mark that we need to process next speaker after session_start arrives
-            self.pending_next_speaker = true;
-            return Ok(());
-        }
-
-        // Cold start or session_start already received - proceed immediately
-        if let Some(next_speaker) = self.policy.determine_next_speaker() {
-            // Map the participant ID to the correct control output (convert to owned String)
-            let control_output = self.get_control_output(&next_speaker).to_string();
-
-            // Only generate NEW question_id if cycle > 0 (normal operation)
-            // If cycle == 0, it means we just reset and question_id was already set
-            let cycle = self.policy.get_current_cycle() as u8;
-            if cycle > 0 {
-                // Normal operation: generate question_id for this participant
-                let participant_index = self.get_participant_index(&next_speaker);
-                let total_participants = self.policy.get_participants().len() as u8;
-                self.current_question_id = encode_enhanced_question_id(cycle, participant_index, total_participants);
-            }
-            // else: cycle == 0 means we just reset, use existing question_id from reset_to_initial_state()
-
-            // Increment cycle counter
-            self.policy.increment_cycle();
-
-            // Prepare metadata with enhanced question_id
-            let mut metadata = std::collections::BTreeMap::new();
-            metadata.insert("question_id".to_string(),
-                dora_node_api::Parameter::String(self.current_question_id.to_string()));
-
-            send_log(node, LogLevel::Info, self.log_level,
-                &format!("🎯 Resume: {} → {} (question_id: {}, cycle: {})",
-                    next_speaker, control_output, self.current_question_id, cycle));
-
-            // Send resume WITH controller's question_id
-            node.send_output(
-                DataId::from(control_output.to_string()),
-                metadata,
-                StringArray::from(vec!["resume"]),
-            )?;
-
-            // Now wait for this participant's session_start before next resume
-            self.waiting_for_session_start = Some(self.current_question_id);
-
-            send_log(node, LogLevel::Debug, self.log_level,
-                &format!("⏳ Now waiting for session_start for question_id={}", self.current_question_id));
-        } else {
-            send_log(node, LogLevel::Warn, self.log_level, "⚠️ No next speaker");
-        }
-
-        // Send policy statistics
-        node.send_output(
-            DataId::from("status".to_string()),
-            Default::default(),
-            StringArray::from(vec![serde_json::to_string(&self.policy.get_stats())?.as_str()]),
-        )?;
-
-        Ok(())
-    }
-
-    /// Handle input from human speaker (via ASR)
-    /// Human input is non-streaming - always arrives complete with session_status="ended"
-    /// When human speaks, interrupt all AI participants and reset system to initial state
-    fn handle_human_input(
-        &mut self,
-        participant_id: &str,
-        text: String,
-        metadata: &dora_node_api::Metadata,
-        node: &mut DoraNode,
-    ) -> Result<()> {
-        send_log(node, LogLevel::Info, self.log_level,
-            &format!("👤 Human input received: '{}'",
-                     text.chars().take(100).collect::<String>()));
-
-        // Human input is always complete (non-streaming ASR output)
-        // ASR modification ensures session_status="ended" is always present
-        // So we immediately trigger interrupt sequence
-
-        // 1. Mark system as paused
-        self.system_paused = true;
-
-        // 2. Store current question_id for logging
-        let old_question_id = self.current_question_id;
-
-        // 3. ENCODE NEW question_id for next round (CRITICAL!)
-        // Question ID uses 16-bit encoding (8-4-4 layout):
-        //   Bits 15-8: Round number (0-255)
-        //   Bits 7-4: Total participants - 1 (0-15)
-        //   Bits 3-0: Current participant index (0-15)
-        // We increment the ROUND number and reset to first participant (tutor)
-        let (current_round, _, _, _) = decode_enhanced_question_id(old_question_id);
-        let new_round = current_round.wrapping_add(1);  // Increment round number
-        let total_participants = self.policy.get_participants().len() as u8;
-
-        // Encode new question_id: new round, participant 0 (will be tutor after reset)
-        self.current_question_id = encode_enhanced_question_id(
-            new_round,
-            0,  // Start from first participant (tutor speaks first)
-            total_participants
-        );
-
-        send_log(node, LogLevel::Info, self.log_level,
-            &format!("📈 Encoded new question_id: {} → {} ({})",
-                     old_question_id,
-                     self.current_question_id,
-                     enhanced_id_debug_string(self.current_question_id)));
-
-        // 4. Cancel all LLMs with NEW question_id
-        // LLMs will abort streaming and propagate question_id to downstream
-        self.send_cancel_to_all_llms(node)?;
-
-        // 5. Reset all bridges with NEW question_id
-        // Bridges will clear buffered messages
-        self.send_reset_to_all_bridges(node)?;
-
-        // 6. Reset audio pipeline (text-segmenter + audio-player) with NEW question_id
-        // Text-segmenter: discards segments with old question_id, keeps new
-        // Audio-player: discards audio with old question_id, keeps new
-        self.send_reset_to_audio_pipeline(node)?;
-
-        // 7. Reset controller state to initial (tutor speaks first, cycle=0)
-        self.reset_to_initial_state(node)?;
-
-        // 8. Resume system
-        self.system_paused = false;
-
-        send_log(node, LogLevel::Info, self.log_level,
-            "✅ System reset complete - ready for new round");
-
-        Ok(())
-    }
-
-    /// Get control output name for a participant
-    fn get_control_output(&self, participant: &str) -> &str {
-        if self.participant_name_map.contains_key("judge") &&
-           self.participant_name_map.get("judge") == Some(&participant.to_string()) {
-            "control_judge"
-        } else if self.participant_name_map.contains_key("llm2") &&
-                  self.participant_name_map.get("llm2") == Some(&participant.to_string()) {
-            "control_llm2"
-        } else if self.participant_name_map.contains_key("llm1") &&
-                  self.participant_name_map.get("llm1") == Some(&participant.to_string()) {
-            "control_llm1"
-        } else {
-            // Fallback: try to guess based on naming patterns
-            if participant.contains("judge") || participant.contains("tutor") {
-                "control_judge"
-            } else if participant.contains("llm2") || participant.contains("student2") {
-                "control_llm2"
-            } else {
-                "control_llm1"
-            }
-        }
-    }
-
-    /// Get participant index (0-based) for question_id encoding
-    fn get_participant_index(&self, participant: &str) -> u8 {
-        *self.participant_index_map.get(participant).unwrap_or(&0)
-    }
-
-    /// Send cancel signal to all LLM participants with NEW question_id
-    fn send_cancel_to_all_llms(&self, node: &mut DoraNode) -> Result<()> {
-        use std::collections::BTreeMap;
-
-        // Create metadata with NEW question_id
-        let mut cancel_metadata = BTreeMap::new();
-        cancel_metadata.insert(
-            "command".to_string(),
-            Parameter::String("cancel".to_string())
-        );
-        cancel_metadata.insert(
-            "question_id".to_string(),
-            Parameter::String(self.current_question_id.to_string())
-        );
-
-        // Send to student1 and student2 via llm_control
-        node.send_output(
-            DataId::from("llm_control".to_string()),
-            cancel_metadata.clone(),
-            StringArray::from(vec!["cancel"]),
-        )?;
-
-        // Send to tutor via judge_prompt
-        node.send_output(
-            DataId::from("judge_prompt".to_string()),
-            cancel_metadata.clone(),
-            StringArray::from(vec!["cancel"]),
-        )?;
-
-        send_log(node, LogLevel::Debug, self.log_level,
-            &format!("🛑 Sent cancel to all LLMs with question_id={}",
-                     self.current_question_id));
-
-        Ok(())
-    }
-
-    /// Send reset signal to all bridges with NEW question_id
-    fn send_reset_to_all_bridges(&self, node: &mut DoraNode) -> Result<()> {
-        use std::collections::BTreeMap;
-
-        // Create metadata with NEW question_id
-        let mut reset_metadata = BTreeMap::new();
-        reset_metadata.insert(
-            "command".to_string(),
-            Parameter::String("reset".to_string())
-        );
-        reset_metadata.insert(
-            "question_id".to_string(),
-            Parameter::String(self.current_question_id.to_string())
-        );
-
-        // Send reset to all bridge control outputs
-        node.send_output(
-            DataId::from("control_judge".to_string()),
-            reset_metadata.clone(),
-            StringArray::from(vec!["reset"]),
-        )?;
-
-        node.send_output(
-            DataId::from("control_llm1".to_string()),
-            reset_metadata.clone(),
-            StringArray::from(vec!["reset"]),
-        )?;
-
-        node.send_output(
-            DataId::from("control_llm2".to_string()),
-            reset_metadata.clone(),
-            StringArray::from(vec!["reset"]),
-        )?;
-
-        send_log(node, LogLevel::Debug, self.log_level,
-            &format!("🔄 Sent reset to all bridges with question_id={}",
-                     self.current_question_id));
-
-        Ok(())
-    }
-
-    /// Send reset signal to audio pipeline (text-segmenter + audio-player) with NEW question_id
-    fn send_reset_to_audio_pipeline(&self, node: &mut DoraNode) -> Result<()> {
-        use std::collections::BTreeMap;
-
-        // Create metadata with NEW question_id
-        let mut reset_metadata = BTreeMap::new();
-        reset_metadata.insert(
-            "command".to_string(),
-            Parameter::String("reset".to_string())
-        );
-        reset_metadata.insert(
-            "question_id".to_string(),
-            Parameter::String(self.current_question_id.to_string())
-        );
-
-        // Send reset to llm_control (will reach text-segmenter)
-        // Text-segmenter will discard segments with question_id != current_question_id
-        // Audio-player will receive reset via its reset input (configured in YAML)
-        node.send_output(
-            DataId::from("llm_control".to_string()),
-            reset_metadata.clone(),
-            StringArray::from(vec!["reset"]),
-        )?;
-
-        send_log(node, LogLevel::Debug, self.log_level,
-            &format!("🔄 Sent reset to audio pipeline with question_id={}",
-                     self.current_question_id));
-
-        Ok(())
-    }
-
-    /// Reset controller to initial state (tutor speaks first, cycle=0)
-    fn reset_to_initial_state(&mut self, node: &mut DoraNode) -> Result<()> {
-        send_log(node, LogLevel::Info, self.log_level,
-            "🔄 Resetting controller to initial state");
-
-        // 1. Clear all accumulated inputs
-        self.participant_inputs.clear();
-
-        // 2. Clear streaming accumulators
-        self.streaming_accumulators.clear();
-
-        // 3. Reset state
-        self.state = ControllerState::Waiting;
-        self.reset_pending = false;
-        self.waiting_for_session_start = None;
-        self.pending_next_speaker = false;
-
-        // 4. Reset policy to initial state
-        self.policy.reset_counts();
-
-        // 5. Set last_speaker to "human" to avoid cold start logic
-        // This ensures tutor (priority) responds after human speaks,
-        // instead of student1 winning the ratio calculation during cold start.
-        self.policy.set_last_speaker(Some("human".to_string()));
-
-        send_log(node, LogLevel::Info, self.log_level,
-            &format!("✅ Reset complete - ready to start with question_id={} ({})",
-                     self.current_question_id,
-                     enhanced_id_debug_string(self.current_question_id)));
-
-        // 6. Trigger initial speaker (tutor via priority)
-        // Use existing logic to process first speaker
-        self.process_next_speaker(node)?;
-
-        Ok(())
-    }
-
-    /// Advance to next round after session start of first participant
-    fn reset(&mut self, node: &mut DoraNode) -> Result<()> {
-        // Generate new question_id for fresh conversation - start with round 0, participant 0
-        self.current_question_id = encode_enhanced_question_id(0, 0, 1);
-
-        send_log(node, LogLevel::Info, self.log_level, "🔄 Resetting controller");
-        self.reset_pending = true;
-
-        // Send reset to all bridges with NEW question_id (same as human speaker reset)
-        self.send_reset_to_all_bridges(node)?;
-
-        // Send reset to audio pipeline (text-segmenter + audio-player) with NEW question_id
-        self.send_reset_to_audio_pipeline(node)?;
-
-        // Send reset to LLMs and judge
-        node.send_output(DataId::from("llm_control".to_string()), Default::default(), StringArray::from(vec!["reset"]))?;
-        node.send_output(DataId::from("judge_prompt".to_string()), Default::default(), StringArray::from(vec!["reset"]))?;
-
-        // Reset internal state
-        self.participant_inputs.clear();
-        self.streaming_accumulators.clear();
-        self.waiting_for_session_start = None;
-        self.pending_next_speaker = false;
-        self.policy.reset_counts();
-        self.policy.reset_round_tracking();
-        self.state = ControllerState::Waiting;
-
-        send_log(node, LogLevel::Info, self.log_level, "✅ Reset complete");
-        Ok(())
-    }
-
-    fn get_stats(&self) -> serde_json::Value {
-        let mut stats = self.policy.get_stats();
-
-        if let serde_json::Value::Object(ref mut map) = stats {
-            map.insert("input_count".to_string(), serde_json::Value::Number(self.participant_inputs.len().into()));
-            map.insert(
-                "controller_state".to_string(),
-                serde_json::Value::String(format!("{:?}", self.state))
-            );
-        }
-
-        stats
-    }
+    Ok(())
 }
 
 /// Parse command line arguments and YAML configuration
-fn load_pattern_from_env() -> Result<String> {
+///
+/// An explicit pattern wins over the one derived from the roster
+fn load_pattern_from_env(roster: Option<&Roster>) -> Result<String> {
     if let Ok(pattern) = env::var("DORA_POLICY_PATTERN") {
         return Ok(pattern);
     }
     if let Ok(pattern) = env::var("PATTERN") {
         return Ok(pattern);
     }
+    if let Some(roster) = roster {
+        return Ok(roster.pattern());
+    }
     Ok("[Judge → Defense → Prosecution]".to_string())
 }
 
+/// Load the policy selection: DORA_POLICY picks the policy (default
+/// unified_ratio), DORA_POLICY_MODERATOR names the moderated policy's moderator
+fn load_policy_config_from_env(roster: Option<&Roster>) -> Result<PolicyConfig> {
+    let pattern = load_pattern_from_env(roster)?;
+    let kind = match env::var("DORA_POLICY") {
+        Ok(kind) => kind.parse::<PolicyKind>().map_err(|e| eyre::eyre!(e))?,
+        Err(_) => PolicyKind::default(),
+    };
+    let mut config = PolicyConfig::new(kind, pattern);
+    if let Ok(moderator) = env::var("DORA_POLICY_MODERATOR") {
+        config = config.with_moderator(moderator);
+    }
+    if let Ok(loop_forever) = env::var("DORA_POLICY_LOOP") {
+        let loop_forever = loop_forever.trim().parse::<bool>()
+            .map_err(|_| eyre::eyre!("DORA_POLICY_LOOP must be true or false, got '{}'", loop_forever))?;
+        config = config.with_loop(loop_forever);
+    }
+    Ok(config)
+}
+
+/// Parse an optional numeric environment variable
+fn env_number<T: std::str::FromStr>(key: &str) -> Result<Option<T>> {
+    match env::var(key) {
+        Ok(value) if !value.trim().is_empty() => value.trim().parse().map(Some)
+            .map_err(|_| eyre::eyre!("{} must be a number, got '{}'", key, value)),
+        _ => Ok(None),
+    }
+}
+
+/// Load session limits: DORA_SESSION_MAX_ROUNDS, DORA_SESSION_MAX_SECONDS,
+/// DORA_SESSION_MAX_WORDS and DORA_SESSION_END_KEYWORDS (comma-separated),
+/// plus the moderator, closing prompt and DORA_SESSION_CLOSING_TIMEOUT_SECONDS
+/// used when a limit is reached
+fn load_session_config_from_env() -> Result<SessionConfig> {
+    let max_duration = match env_number::<f64>("DORA_SESSION_MAX_SECONDS")? {
+        Some(seconds) if seconds > 0.0 && seconds.is_finite() => Some(Duration::from_secs_f64(seconds)),
+        Some(seconds) => return Err(eyre::eyre!("DORA_SESSION_MAX_SECONDS must be positive, got {}", seconds)),
+        None => None,
+    };
+    let end_keywords = env::var("DORA_SESSION_END_KEYWORDS").unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|keyword| !keyword.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let mut config = SessionConfig {
+        limits: SessionLimits {
+            max_rounds: env_number("DORA_SESSION_MAX_ROUNDS")?,
+            max_duration,
+            max_words: env_number("DORA_SESSION_MAX_WORDS")?,
+            end_keywords,
+        },
+        moderator: env::var("DORA_SESSION_MODERATOR").ok().filter(|m| !m.trim().is_empty()),
+        ..SessionConfig::default()
+    };
+    if let Ok(prompt) = env::var("DORA_SESSION_CLOSING_PROMPT") {
+        config.closing_prompt = prompt;
+    }
+    match env_number::<f64>("DORA_SESSION_CLOSING_TIMEOUT_SECONDS")? {
+        Some(seconds) if seconds > 0.0 && seconds.is_finite() => {
+            config.closing_timeout = Duration::from_secs_f64(seconds);
+        }
+        Some(seconds) => return Err(eyre::eyre!(
+            "DORA_SESSION_CLOSING_TIMEOUT_SECONDS must be positive, got {}", seconds)),
+        None => {}
+    }
+    Ok(config)
+}
+
 fn main() -> Result<()> {
-    let pattern = load_pattern_from_env()?;
+    // DORA_ROSTER names the participant roster file (optional)
+    let roster = Roster::from_env()?;
+    let config = load_policy_config_from_env(roster.as_ref())?;
+    let session_config = load_session_config_from_env()?;
     let (mut node, events) = DoraNode::init_from_env()?;
 
     let log_level = env::var("LOG_LEVEL").ok()
         .and_then(|s| LogLevel::parse(&s))
         .unwrap_or(LogLevel::Info);
 
-    send_log(&mut node, LogLevel::Info, log_level, &format!("🚀 Controller started with pattern: {}", pattern));
-    let mut controller = ConferenceController::new(pattern, &mut node, log_level)?;
+    send_log(&mut node, LogLevel::Info, log_level,
+        &format!("🚀 Controller started with {} policy and pattern: {}", config.kind, config.pattern));
+    let connected: Vec<String> = node.node_config().inputs.keys()
+        .map(|data_id| data_id.to_string())
+        .collect();
+    for input in config.kind.required_inputs() {
+        if !connected.iter().any(|id| id == input) {
+            send_log(&mut node, LogLevel::Warn, log_level,
+                &format!("⚠️ The {} policy needs the '{}' input, which is not connected in the dataflow",
+                    config.kind, input));
+        }
+    }
+    let mut controller = ConferenceController::new(config, roster, session_config, epoch_now(), Instant::now())?;
+    perform(&mut node, controller.take_actions(), log_level)?;
 
     let mut events = futures::executor::block_on_stream(events);
 
@@ -773,245 +187,21 @@ fn main() -> Result<()> {
                 data,
                 ..
             }) => {
-                // Debug: Log all incoming event IDs
-                send_log(&mut node, LogLevel::Debug, log_level, &format!("📨 Received event from input: '{}'", id.as_str()));
-
-                if id.as_str() == "control" {
-                    // Extract text from control input
-                    let control_array = data.as_string::<i32>();
-                    let control_text = control_array
-                        .iter()
-                        .filter_map(|s| s)
-                        .collect::<Vec<_>>()
-                        .join(" ");
-                    let control_text = control_text.trim();
-
-                    // Try to parse as JSON first
-                    let parsed_json: Option<serde_json::Value> = serde_json::from_str(control_text).ok();
-
-                    if let Some(json) = &parsed_json {
-                        // Handle JSON control input
-                        if let Some(prompt) = json.get("prompt").and_then(|v| v.as_str()) {
-                            // Forward prompt to judge via llm_control with question_id metadata
-                            send_log(&mut node, LogLevel::Info, log_level,
-                                &format!("📤 Forwarding user prompt to judge with question_id={} ({}): {}",
-                                    controller.current_question_id,
-                                    enhanced_id_debug_string(controller.current_question_id),
-                                    prompt));
-
-                            // Create metadata with question_id
-                            let mut metadata = std::collections::BTreeMap::new();
-                            metadata.insert(
-                                "question_id".to_string(),
-                                Parameter::String(controller.current_question_id.to_string())
-                            );
-
-                            node.send_output(
-                                DataId::from("judge_prompt".to_string()),
-                                metadata,
-                                StringArray::from(vec![control_text]),  // Forward the full JSON
-                            )?;
-                        } else if let Some(command) = json.get("command").and_then(|v| v.as_str()) {
-                            match command.to_lowercase().as_str() {
-                                "reset" => controller.reset(&mut node)?,
-                                "cancel" => {
-                                    // Forward cancel to LLM1/LLM2
-                                    node.send_output(
-                                        DataId::from("llm_control".to_string()),
-                                        Default::default(),
-                                        StringArray::from(vec!["cancel"]),
-                                    )?;
-                                    // Forward cancel to judge
-                                    node.send_output(
-                                        DataId::from("judge_prompt".to_string()),
-                                        Default::default(),
-                                        StringArray::from(vec!["cancel"]),
-                                    )?;
-                                    send_log(&mut node, LogLevel::Info, log_level, "🛑 Sent cancel command to all LLMs");
-                                }
-                                "stats" => {
-                                    let stats = controller.get_stats();
-                                    node.send_output(
-                                        DataId::from("status".to_string()),
-                                        Default::default(),
-                                        StringArray::from(vec![serde_json::to_string(&stats)?.as_str()]),
-                                    )?;
-                                }
-                                _ => {
-                                    send_log(&mut node, LogLevel::Warn, log_level, &format!("Unknown JSON command: {}", command));
-                                }
-                            }
-                        }
-                    } else {
-                        // Plain text command (backward compatibility)
-                        match control_text.to_lowercase().as_str() {
-                            "reset" => {
-                                controller.reset(&mut node)?;
-                            }
-                            "cancel" => {
-                                node.send_output(
-                                    DataId::from("llm_control".to_string()),
-                                    Default::default(),
-                                    StringArray::from(vec!["cancel"]),
-                                )?;
-                                node.send_output(
-                                    DataId::from("judge_prompt".to_string()),
-                                    Default::default(),
-                                    StringArray::from(vec!["cancel"]),
-                                )?;
-                                send_log(&mut node, LogLevel::Info, log_level, "🛑 Sent cancel command to all LLMs");
-                            }
-                            "ready" => {
-                                node.send_output(
-                                    DataId::from("status".to_string()),
-                                    Default::default(),
-                                    StringArray::from(vec!["ready"]),
-                                )?;
-                            }
-                            "stats" => {
-                                let stats = controller.get_stats();
-                                node.send_output(
-                                    DataId::from("status".to_string()),
-                                    Default::default(),
-                                    StringArray::from(vec![serde_json::to_string(&stats)?.as_str()]),
-                                )?;
-                            }
-                            _ => {
-                                send_log(&mut node, LogLevel::Warn, log_level, &format!("Unknown control command: {}", control_text));
-                            }
-                        }
-                    }
-                } else if id.as_str() == "session_start" {
-                    // Handle session start signal from audio player
-                    // When we receive session_start for first participant of a round,
-                    // it means we can advance to that round
-                    send_log(&mut node, LogLevel::Info, log_level, "🎬 Received session_start input from audio player");
-
-                    // Read the data (session_status string) - we don't use it but need to consume it
-                    let _session_status_array = data.as_string::<i32>();
-
-                    // Get question_id from metadata
-                    let question_id = if let Some(Parameter::String(qid_str)) = metadata.parameters.get("question_id") {
-                        match qid_str.parse::<u16>() {
-                            Ok(qid) => {
-                                if qid == 0 {
-                                    send_log(&mut node, LogLevel::Error, log_level, "❌ Invalid question_id=0 in session_start signal - ignoring");
-                                    continue;
-                                }
-                                qid
-                            }
-                            Err(e) => {
-                                send_log(&mut node, LogLevel::Error, log_level, &format!("❌ Failed to parse question_id '{}' in session_start signal: {}", qid_str, e));
-                                continue;
-                            }
-                        }
-                    } else {
-                        send_log(&mut node, LogLevel::Warn, log_level, "⚠️ Session start signal missing question_id metadata");
-                        continue;
-                    };
-
-                    // Handle session start for round advancement
-                    if let Err(e) = controller.handle_session_start(question_id, &mut node, log_level) {
-                        send_log(&mut node, LogLevel::Error, log_level, &format!("❌ Error handling session start: {}", e));
-                    }
-                } else if id.as_str() == "buffer_status" {
-                    // Buffer status from audio player - we don't use this anymore
-                    // Just consume it to avoid crashes
-                    let _buffer_data = data.as_primitive::<arrow::datatypes::Float64Type>();
-                    send_log(&mut node, LogLevel::Debug, log_level, "📊 Received buffer_status (ignored)");
-                } else if id.as_str() == "human_speaking" {
-                    // IMMEDIATE interrupt when human starts speaking
-                    // Don't wait for ASR transcription - cancel everything NOW
-                    send_log(&mut node, LogLevel::Info, log_level, "🎤 Human speaking detected - IMMEDIATE INTERRUPT");
-
-                    // Send cancel to all LLMs immediately
-                    node.send_output(
-                        DataId::from("llm_control".to_string()),
-                        Default::default(),
-                        StringArray::from(vec!["cancel"]),
-                    )?;
-                    node.send_output(
-                        DataId::from("judge_prompt".to_string()),
-                        Default::default(),
-                        StringArray::from(vec!["cancel"]),
-                    )?;
-
-                    // Send cancel to text segmenter to clear pending text
-                    // Create metadata with cancel command and new question_id
-                    let mut cancel_metadata = std::collections::BTreeMap::new();
-                    cancel_metadata.insert(
-                        "command".to_string(),
-                        Parameter::String("cancel".to_string())
-                    );
-                    // Use a high question_id to ensure all old segments are cleared
-                    let interrupt_qid = controller.current_question_id.saturating_add(256);
-                    cancel_metadata.insert(
-                        "question_id".to_string(),
-                        Parameter::String(interrupt_qid.to_string())
-                    );
-                    node.send_output(
-                        DataId::from("segmenter_control".to_string()),
-                        cancel_metadata,
-                        StringArray::from(vec!["cancel"]),
-                    )?;
-
-                    send_log(&mut node, LogLevel::Info, log_level, "🔇 Sent immediate cancel to all LLMs and text segmenter");
-                } else if id.as_str() == "question_ended" {
-                    // Question ended signal - prolonged silence after speech
-                    // This means the user has finished their question/statement
-                    // ASR should have already sent the transcription via "human" input
-
-                    // Get question_id from metadata
-                    let question_id = metadata.parameters.get("question_id")
-                        .and_then(|p| match p {
-                            Parameter::Integer(i) => Some(*i as u32),
-                            Parameter::String(s) => s.parse().ok(),
-                            _ => None
-                        })
-                        .unwrap_or(0);
-
-                    send_log(&mut node, LogLevel::Info, log_level,
-                        &format!("⏱️ QUESTION_ENDED received (question_id={}) - user finished speaking", question_id));
-
-                    // The question_ended signal confirms the user has stopped speaking
-                    // Most handling is done via "human" (ASR transcription) input
-                    // But this signal can be used to:
-                    // 1. Confirm the cancel commands had time to propagate
-                    // 2. Clear any lingering state from the interrupt
-
-                    // Send a final clear to audio player to ensure buffer is empty
-                    let mut reset_metadata = std::collections::BTreeMap::new();
-                    reset_metadata.insert(
-                        "command".to_string(),
-                        Parameter::String("reset".to_string())
-                    );
-                    reset_metadata.insert(
-                        "question_id".to_string(),
-                        Parameter::String(controller.current_question_id.to_string())
-                    );
-                    node.send_output(
-                        DataId::from("llm_control".to_string()),
-                        reset_metadata,
-                        StringArray::from(vec!["reset"]),
-                    )?;
-
-                    send_log(&mut node, LogLevel::Info, log_level,
-                        "📤 Sent reset to audio pipeline (question_ended confirmation)");
-                } else {
-                    // Participant input - extract text
-                    let text_array = data.as_string::<i32>();
-                    let text = text_array
-                        .iter()
-                        .filter_map(|s| s)
-                        .collect::<Vec<_>>()
-                        .join(" ");
-
-                    send_log(&mut node, LogLevel::Debug, log_level, &format!("📨 Processing input from {}", id));
-
-                    if let Err(e) = controller.handle_participant_input(id.as_str(), text, &metadata, &mut node) {
-                        send_log(&mut node, LogLevel::Error, log_level, &format!("❌ Error handling input: {}", e));
-                    }
-                }
+                // buffer_status carries numbers; every other input is text
+                let text = match data.as_string_opt::<i32>() {
+                    Some(array) => array.iter().flatten().collect::<Vec<_>>().join(" "),
+                    None => String::new(),
+                };
+                let metadata = metadata.parameters.keys()
+                    .filter_map(|key| {
+                        let value = metadata.parameters.get_str(key)?;
+                        Some((key.clone(), value.into_owned()))
+                    })
+                    .collect();
+                let input = Input { id: id.to_string(), text, metadata };
+
+                controller.handle(&input, Instant::now());
+                perform(&mut node, controller.take_actions(), log_level)?;
             }
             Some(Event::Stop(_cause)) => {
                 send_log(&mut node, LogLevel::Info, log_level, "🛑 Received stop event, shutting down");
@@ -1025,4 +215,4 @@ fn main() -> Result<()> {
     }
 
     Ok(())
-}
\ No newline at end of file
+}
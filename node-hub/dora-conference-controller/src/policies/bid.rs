@@ -0,0 +1,129 @@
+use super::{Policy, PolicySignal, TurnTracker};
+use std::collections::HashMap;
+
+/// Bid policy
+///
+/// Participants ask for the floor through a side channel. The highest
+/// outstanding bid from anyone but the last speaker wins and is consumed; ties
+/// go to whoever has waited longest. Without bids the floor rotates to the
+/// participant who has waited longest.
+pub struct BidPolicy {
+    participants: Vec<String>,
+    bids: HashMap<String, f64>,
+    turns: TurnTracker,
+}
+
+impl BidPolicy {
+    pub fn new(participants: Vec<String>) -> Result<Self, String> {
+        if participants.is_empty() {
+            return Err("Need participants".to_string());
+        }
+        Ok(Self { participants, bids: HashMap::new(), turns: TurnTracker::new() })
+    }
+
+    /// Outstanding bid strength for a participant (0 if none)
+    pub fn bid(&self, speaker: &str) -> f64 {
+        self.bids.get(speaker).copied().unwrap_or(0.0)
+    }
+}
+
+/// Parse a bid message
+///
+/// Accepts `{"participant": "...", "strength": 0.8}` (strength defaults to 1)
+/// or a bare participant name.
+pub fn parse_bid(text: &str) -> Option<(String, f64)> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(serde_json::Value::Object(map)) => {
+            let participant = map.get("participant").and_then(|v| v.as_str())?;
+            let strength = map.get("strength").and_then(|v| v.as_f64()).unwrap_or(1.0);
+            Some((participant.to_string(), strength))
+        }
+        Ok(serde_json::Value::String(name)) => Some((name, 1.0)),
+        Ok(_) => None,
+        Err(_) => Some((text.to_string(), 1.0)),
+    }
+}
+
+impl Policy for BidPolicy {
+    fn update_word_count(&mut self, speaker: &str, _word_count: usize) {
+        self.turns.spoke(speaker);
+    }
+
+    fn determine_next_speaker(&mut self) -> Option<String> {
+        let candidates = self.turns.candidates(&self.participants);
+        let best_bid = candidates.iter()
+            .map(|c| self.bid(c))
+            .fold(0.0_f64, f64::max);
+        let speaker = if best_bid > 0.0 {
+            let bidders: Vec<&String> = candidates.into_iter()
+                .filter(|c| self.bid(c) == best_bid)
+                .collect();
+            self.turns.least_recent(&bidders)?.clone()
+        } else {
+            self.turns.least_recent(&candidates)?.clone()
+        };
+        self.bids.remove(&speaker);
+        self.turns.assign(&speaker);
+        Some(speaker)
+    }
+
+    fn all_participants_completed(&self) -> bool {
+        self.turns.all_spoke(&self.participants)
+    }
+
+    fn reset_round_tracking(&mut self) {
+        self.turns.reset_round();
+    }
+
+    fn increment_cycle(&mut self) {
+        self.turns.increment_cycle();
+    }
+
+    fn get_current_cycle(&self) -> usize {
+        self.turns.cycle()
+    }
+
+    fn get_participants(&self) -> Vec<String> {
+        self.participants.clone()
+    }
+
+    fn reset_counts(&mut self) {
+        self.bids.clear();
+        self.turns.reset();
+    }
+
+    fn set_last_speaker(&mut self, speaker: Option<String>) {
+        self.turns.set_last_speaker(speaker);
+    }
+
+    fn get_stats(&self) -> serde_json::Value {
+        let mut stats = serde_json::json!({
+            "mode": "bid",
+            "participants": self.participants,
+            "bids": self.bids,
+            "cycle": self.turns.cycle(),
+        });
+        if let Some(last) = self.turns.last_speaker() {
+            stats["current_speaker"] = serde_json::Value::String(last.to_string());
+        }
+        stats
+    }
+
+    fn observe(&mut self, signal: PolicySignal<'_>) {
+        if let PolicySignal::Bid { speaker, strength } = signal {
+            if !self.participants.iter().any(|p| p == speaker) {
+                return;
+            }
+            if strength.is_finite() && strength > 0.0 {
+                self.bids.insert(speaker.to_string(), strength);
+            } else {
+                // A zero or negative bid withdraws the request
+                self.bids.remove(speaker);
+            }
+        }
+    }
+}
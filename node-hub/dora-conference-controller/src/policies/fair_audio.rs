@@ -0,0 +1,145 @@
+use super::{Policy, PolicyPattern, PolicySignal, TurnTracker, Weight};
+use std::collections::HashMap;
+
+/// Audio fairness policy
+///
+/// Like the ratio mode of `UnifiedRatioPolicy`, but balances the seconds of
+/// audio actually played for each participant instead of word counts, so
+/// slow or verbose voices don't skew the split. The participant furthest
+/// below their share goes next; never the last speaker.
+pub struct FairAudioPolicy {
+    participants: Vec<String>,
+    ratios: Vec<f64>,
+    seconds: HashMap<String, f64>,
+    turns: TurnTracker,
+}
+
+impl FairAudioPolicy {
+    /// Build from a pattern; sequential patterns get equal shares
+    pub fn from_pattern(pattern: &PolicyPattern) -> Result<Self, String> {
+        let (participants, ratios) = match pattern {
+            PolicyPattern::RatioPriority { participants, weights } => {
+                let ratios = weights.iter().map(|w| match w {
+                    Weight::Ratio(r) => Ok(*r),
+                    Weight::Priority => Err("fair_audio policy does not support priority (*) weights".to_string()),
+                }).collect::<Result<Vec<_>, _>>()?;
+                (participants.clone(), ratios)
+            }
+            PolicyPattern::Sequential { participants, .. } => {
+                (participants.clone(), vec![1.0; participants.len()])
+            }
+        };
+        Self::new(participants, ratios)
+    }
+
+    pub fn new(participants: Vec<String>, ratios: Vec<f64>) -> Result<Self, String> {
+        if participants.is_empty() {
+            return Err("Need participants".to_string());
+        }
+        if participants.len() != ratios.len() {
+            return Err("Mismatched lengths".to_string());
+        }
+        if ratios.iter().any(|r| *r <= 0.0) {
+            return Err("Positive ratios only".to_string());
+        }
+        let seconds = participants.iter().map(|p| (p.clone(), 0.0)).collect();
+        Ok(Self { participants, ratios, seconds, turns: TurnTracker::new() })
+    }
+
+    /// Seconds of audio played for a participant
+    pub fn audio_seconds(&self, speaker: &str) -> f64 {
+        self.seconds.get(speaker).copied().unwrap_or(0.0)
+    }
+
+    /// How far a participant is below their share, normalised by weight
+    fn deficit(&self, index: usize, total_seconds: f64, total_ratio: f64) -> f64 {
+        let ratio = self.ratios[index];
+        let target = ratio / total_ratio * total_seconds;
+        (target - self.audio_seconds(&self.participants[index])) / ratio
+    }
+}
+
+impl Policy for FairAudioPolicy {
+    fn update_word_count(&mut self, speaker: &str, _word_count: usize) {
+        self.turns.spoke(speaker);
+    }
+
+    fn determine_next_speaker(&mut self) -> Option<String> {
+        let total_seconds: f64 = self.seconds.values().sum();
+        let total_ratio: f64 = self.ratios.iter().sum();
+        let candidates = self.turns.candidates(&self.participants);
+
+        let mut best: Vec<&String> = Vec::new();
+        let mut best_deficit = f64::MIN;
+        for candidate in candidates {
+            let index = self.participants.iter().position(|p| p == candidate)?;
+            let deficit = self.deficit(index, total_seconds, total_ratio);
+            if deficit > best_deficit + f64::EPSILON {
+                best_deficit = deficit;
+                best = vec![candidate];
+            } else if (deficit - best_deficit).abs() <= f64::EPSILON {
+                best.push(candidate);
+            }
+        }
+        let speaker = self.turns.least_recent(&best)?.clone();
+        self.turns.assign(&speaker);
+        Some(speaker)
+    }
+
+    fn all_participants_completed(&self) -> bool {
+        self.turns.all_spoke(&self.participants)
+    }
+
+    fn reset_round_tracking(&mut self) {
+        self.turns.reset_round();
+    }
+
+    fn increment_cycle(&mut self) {
+        self.turns.increment_cycle();
+    }
+
+    fn get_current_cycle(&self) -> usize {
+        self.turns.cycle()
+    }
+
+    fn get_participants(&self) -> Vec<String> {
+        self.participants.clone()
+    }
+
+    fn reset_counts(&mut self) {
+        for seconds in self.seconds.values_mut() { *seconds = 0.0; }
+        self.turns.reset();
+    }
+
+    fn set_last_speaker(&mut self, speaker: Option<String>) {
+        self.turns.set_last_speaker(speaker);
+    }
+
+    fn get_stats(&self) -> serde_json::Value {
+        let weights: Vec<_> = self.participants.iter().zip(&self.ratios)
+            .map(|(p, r)| serde_json::json!({ "name": p, "weight": r }))
+            .collect();
+        let mut stats = serde_json::json!({
+            "mode": "fair_audio",
+            "participants": self.participants,
+            "weights": weights,
+            "audio_seconds": self.seconds,
+            "cycle": self.turns.cycle(),
+        });
+        if let Some(last) = self.turns.last_speaker() {
+            stats["current_speaker"] = serde_json::Value::String(last.to_string());
+        }
+        stats
+    }
+
+    fn observe(&mut self, signal: PolicySignal<'_>) {
+        if let PolicySignal::AudioPlayed { speaker, seconds } = signal {
+            if !seconds.is_finite() || seconds <= 0.0 {
+                return;
+            }
+            if let Some(total) = self.seconds.get_mut(speaker) {
+                *total += seconds;
+            }
+        }
+    }
+}
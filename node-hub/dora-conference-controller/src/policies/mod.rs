@@ -1,10 +1,21 @@
 // Policy module for conference controller
 // This module defines traits and implementations for customizable control policies
 
+pub mod bid;
+pub mod fair_audio;
+pub mod moderated;
+pub mod turns;
 pub mod unified_ratio;
 
+pub use bid::BidPolicy;
+pub use fair_audio::FairAudioPolicy;
+pub use moderated::ModeratedPolicy;
+pub use turns::TurnTracker;
 pub use unified_ratio::{PatternParser, PolicyPattern, UnifiedRatioPolicy, Weight};
 
+use std::fmt;
+use std::str::FromStr;
+
 /// Core trait that defines the interface for all control policies
 ///
 /// Implementations of this trait determine which participant should speak next
@@ -41,9 +52,169 @@ pub trait Policy: Send + Sync {
     ///
     /// Returns the current cycle based on policy mode
     fn get_current_cycle(&self) -> usize;
+
+    /// Get the configured participants, in pattern order
+    fn get_participants(&self) -> Vec<String>;
+
+    /// Reset counts and turn state for a fresh conversation
+    fn reset_counts(&mut self);
+
+    /// Set the last speaker
+    ///
+    /// Used after human input so the policy responds to the human instead of
+    /// treating the next turn as a cold start
+    fn set_last_speaker(&mut self, speaker: Option<String>);
+
+    /// Get statistics for the `status` output
+    fn get_stats(&self) -> serde_json::Value;
+
+    /// Feed a side-channel signal to the policy
+    ///
+    /// Policies ignore signals they have no use for, so the controller can
+    /// forward every signal it receives regardless of the configured policy
+    fn observe(&mut self, _signal: PolicySignal<'_>) {}
+}
+
+/// Side-channel information a policy may use besides word counts
+#[derive(Debug, Clone, Copy)]
+pub enum PolicySignal<'a> {
+    /// The audio player finished playing `seconds` of a participant's speech
+    AudioPlayed { speaker: &'a str, seconds: f64 },
+    /// A participant asked for the floor; higher strength wins
+    Bid { speaker: &'a str, strength: f64 },
+    /// The moderator's structured reply (e.g. `{"next_speaker": "..."}`)
+    ModeratorReply { reply: &'a serde_json::Value },
+}
+
+/// Which policy the controller runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PolicyKind {
+    /// Sequential / ratio / priority, inferred from the pattern syntax
+    #[default]
+    UnifiedRatio,
+    /// A moderator's structured reply names the next speaker
+    Moderated,
+    /// Participants bid for the floor through a side channel
+    Bid,
+    /// Balance spoken audio seconds instead of word counts
+    FairAudio,
+}
+
+impl PolicyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PolicyKind::UnifiedRatio => "unified_ratio",
+            PolicyKind::Moderated => "moderated",
+            PolicyKind::Bid => "bid",
+            PolicyKind::FairAudio => "fair_audio",
+        }
+    }
+
+    /// Side channel inputs the policy needs wired in the dataflow
+    pub fn required_inputs(&self) -> &'static [&'static str] {
+        match self {
+            PolicyKind::UnifiedRatio => &[],
+            PolicyKind::Moderated => &["moderator"],
+            PolicyKind::Bid => &["bid"],
+            PolicyKind::FairAudio => &["audio_complete"],
+        }
+    }
+}
+
+impl fmt::Display for PolicyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PolicyKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "unified_ratio" | "ratio" | "pattern" => Ok(PolicyKind::UnifiedRatio),
+            "moderated" | "moderator" => Ok(PolicyKind::Moderated),
+            "bid" | "bidding" => Ok(PolicyKind::Bid),
+            "fair_audio" | "audio" => Ok(PolicyKind::FairAudio),
+            other => Err(format!(
+                "Unknown policy '{}' (expected unified_ratio, moderated, bid or fair_audio)",
+                other
+            )),
+        }
+    }
+}
+
+/// Policy selection: which policy to run, over which pattern
+#[derive(Debug, Clone)]
+pub struct PolicyConfig {
+    pub kind: PolicyKind,
+    pub pattern: String,
+    /// Moderator for the moderated policy; defaults to the first participant
+    pub moderator: Option<String>,
+    /// Whether a sequential pattern starts over after its last participant
+    pub loop_forever: bool,
+}
+
+impl PolicyConfig {
+    pub fn new(kind: PolicyKind, pattern: impl Into<String>) -> Self {
+        Self { kind, pattern: pattern.into(), moderator: None, loop_forever: true }
+    }
+
+    pub fn with_moderator(mut self, moderator: impl Into<String>) -> Self {
+        self.moderator = Some(moderator.into());
+        self
+    }
+
+    /// Stop a sequential pattern after one pass instead of looping
+    pub fn with_loop(mut self, loop_forever: bool) -> Self {
+        self.loop_forever = loop_forever;
+        self
+    }
+
+    /// Build the configured policy
+    ///
+    /// Every policy takes its participants from the pattern. Only the unified
+    /// ratio and fair audio policies use the pattern's weights.
+    pub fn build(&self) -> Result<Box<dyn Policy>, String> {
+        match self.kind {
+            PolicyKind::UnifiedRatio => {
+                let mut policy = UnifiedRatioPolicy::new();
+                policy.configure(&self.pattern)?;
+                policy.set_loop_forever(self.loop_forever);
+                Ok(Box::new(policy))
+            }
+            PolicyKind::Moderated => {
+                let participants = pattern_participants(&self.pattern)?;
+                let moderator = self.moderator.clone()
+                    .unwrap_or_else(|| participants[0].clone());
+                Ok(Box::new(ModeratedPolicy::new(participants, moderator)?))
+            }
+            PolicyKind::Bid => Ok(Box::new(BidPolicy::new(pattern_participants(&self.pattern)?)?)),
+            PolicyKind::FairAudio => {
+                let pattern = PatternParser::parse(&self.pattern)?;
+                PatternParser::validate(&pattern)?;
+                Ok(Box::new(FairAudioPolicy::from_pattern(&pattern)?))
+            }
+        }
+    }
+}
+
+/// Parse and validate a pattern, returning only its participants
+fn pattern_participants(pattern: &str) -> Result<Vec<String>, String> {
+    let pattern = PatternParser::parse(pattern)?;
+    PatternParser::validate(&pattern)?;
+    match pattern {
+        PolicyPattern::RatioPriority { participants, .. } | PolicyPattern::Sequential { participants, .. } => {
+            Ok(participants)
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    mod bid_test;
+    mod fair_audio_test;
+    mod moderated_test;
+    mod policy_config_test;
     mod unified_ratio_test;
 }
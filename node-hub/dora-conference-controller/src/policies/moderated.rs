@@ -0,0 +1,126 @@
+use super::{Policy, PolicySignal, TurnTracker};
+
+/// Field of the moderator's structured reply that names the next speaker
+pub const NEXT_SPEAKER_FIELD: &str = "next_speaker";
+
+/// LLM-moderated policy
+///
+/// The moderator speaks first and after every other participant. When the
+/// moderator finishes, the participant named in its structured reply
+/// (`{"next_speaker": "..."}`) is next. A missing or invalid choice falls back
+/// to the participant who has waited longest.
+pub struct ModeratedPolicy {
+    participants: Vec<String>,
+    moderator: String,
+    choice: Option<String>,
+    turns: TurnTracker,
+}
+
+impl ModeratedPolicy {
+    pub fn new(participants: Vec<String>, moderator: impl Into<String>) -> Result<Self, String> {
+        let moderator = moderator.into();
+        if !participants.contains(&moderator) {
+            return Err(format!("Moderator '{}' is not a participant", moderator));
+        }
+        if participants.len() < 2 {
+            return Err("Moderated policy needs at least one participant besides the moderator".to_string());
+        }
+        Ok(Self {
+            participants,
+            moderator,
+            choice: None,
+            turns: TurnTracker::new(),
+        })
+    }
+
+    pub fn moderator(&self) -> &str {
+        &self.moderator
+    }
+
+    /// Accept a named speaker if it is a participant other than the moderator
+    fn choose(&mut self, name: &str) {
+        let name = name.trim();
+        if let Some(participant) = self.participants.iter()
+            .find(|p| *p != &self.moderator && p.eq_ignore_ascii_case(name))
+        {
+            self.choice = Some(participant.clone());
+        }
+    }
+}
+
+impl Policy for ModeratedPolicy {
+    fn update_word_count(&mut self, speaker: &str, _word_count: usize) {
+        self.turns.spoke(speaker);
+    }
+
+    fn determine_next_speaker(&mut self) -> Option<String> {
+        let speaker = if self.turns.last_speaker() == Some(self.moderator.as_str()) {
+            match self.choice.take() {
+                Some(choice) => choice,
+                None => {
+                    let others: Vec<&String> = self.participants.iter()
+                        .filter(|p| *p != &self.moderator)
+                        .collect();
+                    self.turns.least_recent(&others)?.clone()
+                }
+            }
+        } else {
+            // A stale choice must not outlive the moderator's next turn
+            self.choice = None;
+            self.moderator.clone()
+        };
+        self.turns.assign(&speaker);
+        Some(speaker)
+    }
+
+    fn all_participants_completed(&self) -> bool {
+        self.turns.all_spoke(&self.participants)
+    }
+
+    fn reset_round_tracking(&mut self) {
+        self.turns.reset_round();
+    }
+
+    fn increment_cycle(&mut self) {
+        self.turns.increment_cycle();
+    }
+
+    fn get_current_cycle(&self) -> usize {
+        self.turns.cycle()
+    }
+
+    fn get_participants(&self) -> Vec<String> {
+        self.participants.clone()
+    }
+
+    fn reset_counts(&mut self) {
+        self.choice = None;
+        self.turns.reset();
+    }
+
+    fn set_last_speaker(&mut self, speaker: Option<String>) {
+        self.turns.set_last_speaker(speaker);
+    }
+
+    fn get_stats(&self) -> serde_json::Value {
+        let mut stats = serde_json::json!({
+            "mode": "moderated",
+            "participants": self.participants,
+            "moderator": self.moderator,
+            "pending_choice": self.choice,
+            "cycle": self.turns.cycle(),
+        });
+        if let Some(last) = self.turns.last_speaker() {
+            stats["current_speaker"] = serde_json::Value::String(last.to_string());
+        }
+        stats
+    }
+
+    fn observe(&mut self, signal: PolicySignal<'_>) {
+        if let PolicySignal::ModeratorReply { reply } = signal {
+            if let Some(name) = reply.get(NEXT_SPEAKER_FIELD).and_then(|v| v.as_str()) {
+                self.choose(name);
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::policies::bid::parse_bid;
+use crate::policies::{BidPolicy, Policy, PolicySignal};
+
+fn participants() -> Vec<String> {
+    vec!["alex".to_string(), "bob".to_string(), "charlie".to_string()]
+}
+
+fn bid(policy: &mut BidPolicy, speaker: &str, strength: f64) {
+    policy.observe(PolicySignal::Bid { speaker, strength });
+}
+
+#[test]
+fn test_parse_bid() {
+    assert_eq!(parse_bid(r#"{"participant": "bob", "strength": 0.5}"#), Some(("bob".to_string(), 0.5)));
+    assert_eq!(parse_bid(r#"{"participant": "bob"}"#), Some(("bob".to_string(), 1.0)));
+    assert_eq!(parse_bid(r#""bob""#), Some(("bob".to_string(), 1.0)));
+    assert_eq!(parse_bid("bob"), Some(("bob".to_string(), 1.0)));
+    assert_eq!(parse_bid(r#"{"strength": 2}"#), None);
+    assert_eq!(parse_bid("  "), None);
+}
+
+#[test]
+fn test_rotation_without_bids() {
+    let mut policy = BidPolicy::new(participants()).unwrap();
+
+    for expected in ["alex", "bob", "charlie", "alex"] {
+        let speaker = policy.determine_next_speaker().unwrap();
+        assert_eq!(speaker, expected);
+        policy.update_word_count(&speaker, 50);
+    }
+}
+
+#[test]
+fn test_highest_bid_wins_and_is_consumed() {
+    let mut policy = BidPolicy::new(participants()).unwrap();
+    assert_eq!(policy.determine_next_speaker(), Some("alex".to_string()));
+    policy.update_word_count("alex", 50);
+
+    bid(&mut policy, "bob", 0.3);
+    bid(&mut policy, "charlie", 0.9);
+    assert_eq!(policy.determine_next_speaker(), Some("charlie".to_string()));
+    assert_eq!(policy.bid("charlie"), 0.0);
+    policy.update_word_count("charlie", 50);
+
+    // bob's bid is still outstanding
+    assert_eq!(policy.determine_next_speaker(), Some("bob".to_string()));
+}
+
+#[test]
+fn test_last_speaker_cannot_win_twice() {
+    let mut policy = BidPolicy::new(participants()).unwrap();
+    assert_eq!(policy.determine_next_speaker(), Some("alex".to_string()));
+    policy.update_word_count("alex", 50);
+
+    bid(&mut policy, "alex", 5.0);
+    bid(&mut policy, "charlie", 0.1);
+    assert_eq!(policy.determine_next_speaker(), Some("charlie".to_string()));
+    policy.update_word_count("charlie", 50);
+
+    // alex's bid carries over to the next turn
+    assert_eq!(policy.determine_next_speaker(), Some("alex".to_string()));
+}
+
+#[test]
+fn test_tied_bids_go_to_longest_waiting() {
+    let mut policy = BidPolicy::new(participants()).unwrap();
+    for _ in 0..3 {
+        let speaker = policy.determine_next_speaker().unwrap();
+        policy.update_word_count(&speaker, 50);
+    }
+    // Order so far: alex, bob, charlie
+
+    bid(&mut policy, "bob", 1.0);
+    bid(&mut policy, "alex", 1.0);
+    assert_eq!(policy.determine_next_speaker(), Some("alex".to_string()));
+}
+
+#[test]
+fn test_withdrawn_and_unknown_bids() {
+    let mut policy = BidPolicy::new(participants()).unwrap();
+    assert_eq!(policy.determine_next_speaker(), Some("alex".to_string()));
+    policy.update_word_count("alex", 50);
+
+    bid(&mut policy, "charlie", 1.0);
+    bid(&mut policy, "charlie", 0.0);
+    bid(&mut policy, "mallory", 10.0);
+    assert_eq!(policy.get_stats()["bids"], serde_json::json!({}));
+    assert_eq!(policy.determine_next_speaker(), Some("bob".to_string()));
+}
+
+#[test]
+fn test_bid_reset() {
+    let mut policy = BidPolicy::new(participants()).unwrap();
+    bid(&mut policy, "charlie", 1.0);
+    policy.determine_next_speaker();
+    policy.increment_cycle();
+
+    policy.reset_counts();
+    assert_eq!(policy.get_current_cycle(), 0);
+    assert_eq!(policy.bid("charlie"), 0.0);
+    assert_eq!(policy.get_stats()["mode"], "bid");
+}
@@ -0,0 +1,100 @@
+use crate::policies::{FairAudioPolicy, PatternParser, Policy, PolicySignal};
+
+fn policy(pattern: &str) -> FairAudioPolicy {
+    FairAudioPolicy::from_pattern(&PatternParser::parse(pattern).unwrap()).unwrap()
+}
+
+fn played(policy: &mut FairAudioPolicy, speaker: &str, seconds: f64) {
+    policy.observe(PolicySignal::AudioPlayed { speaker, seconds });
+}
+
+#[test]
+fn test_priority_weights_rejected() {
+    let pattern = PatternParser::parse("[(judge, *), (defense, 1)]").unwrap();
+    assert!(FairAudioPolicy::from_pattern(&pattern).is_err());
+}
+
+#[test]
+fn test_cold_start_picks_first_participant() {
+    let mut policy = policy("[A, B, C]");
+    assert_eq!(policy.determine_next_speaker(), Some("A".to_string()));
+}
+
+#[test]
+fn test_balances_audio_not_words() {
+    let mut policy = policy("[A, B, C]");
+    assert_eq!(policy.determine_next_speaker(), Some("A".to_string()));
+    policy.update_word_count("A", 10);
+    played(&mut policy, "A", 30.0);
+
+    assert_eq!(policy.determine_next_speaker(), Some("B".to_string()));
+    // B says many words quickly; only the audio seconds count
+    policy.update_word_count("B", 500);
+    played(&mut policy, "B", 5.0);
+
+    assert_eq!(policy.determine_next_speaker(), Some("C".to_string()));
+    policy.update_word_count("C", 10);
+    played(&mut policy, "C", 20.0);
+
+    // B is furthest below an equal share
+    assert_eq!(policy.determine_next_speaker(), Some("B".to_string()));
+}
+
+#[test]
+fn test_weighted_shares() {
+    let mut policy = policy("[(A, 2), (B, 1)]");
+    for _ in 0..12 {
+        let speaker = policy.determine_next_speaker().unwrap();
+        policy.update_word_count(&speaker, 10);
+        played(&mut policy, &speaker, 10.0);
+    }
+    // Alternation is forced (no back-to-back turns), so A cannot reach 2:1,
+    // but must never fall behind B
+    assert!(policy.audio_seconds("A") >= policy.audio_seconds("B"));
+
+    let mut policy = policy_with_three();
+    for _ in 0..30 {
+        let speaker = policy.determine_next_speaker().unwrap();
+        policy.update_word_count(&speaker, 10);
+        played(&mut policy, &speaker, 10.0);
+    }
+    assert!(policy.audio_seconds("A") > policy.audio_seconds("B"));
+    assert!(policy.audio_seconds("A") > policy.audio_seconds("C"));
+}
+
+fn policy_with_three() -> FairAudioPolicy {
+    policy("[(A, 2), (B, 1), (C, 1)]")
+}
+
+#[test]
+fn test_never_same_speaker_twice() {
+    let mut policy = policy("[A, B]");
+    let first = policy.determine_next_speaker().unwrap();
+    played(&mut policy, &first, 1.0);
+    policy.update_word_count(&first, 1);
+    let second = policy.determine_next_speaker().unwrap();
+    assert_ne!(first, second);
+}
+
+#[test]
+fn test_ignores_invalid_audio() {
+    let mut policy = policy("[A, B]");
+    played(&mut policy, "A", -3.0);
+    played(&mut policy, "A", f64::NAN);
+    played(&mut policy, "Z", 10.0);
+    assert_eq!(policy.audio_seconds("A"), 0.0);
+    assert!(policy.get_stats()["audio_seconds"].get("Z").is_none());
+}
+
+#[test]
+fn test_fair_audio_stats_and_reset() {
+    let mut policy = policy("[(A, 2), (B, 1)]");
+    played(&mut policy, "A", 12.5);
+    let stats = policy.get_stats();
+    assert_eq!(stats["mode"], "fair_audio");
+    assert_eq!(stats["audio_seconds"]["A"], 12.5);
+    assert_eq!(stats["weights"].as_array().unwrap().len(), 2);
+
+    policy.reset_counts();
+    assert_eq!(policy.audio_seconds("A"), 0.0);
+}
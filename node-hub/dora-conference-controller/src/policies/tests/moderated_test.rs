@@ -0,0 +1,103 @@
+use crate::policies::{ModeratedPolicy, Policy, PolicySignal};
+use serde_json::json;
+
+fn participants() -> Vec<String> {
+    vec!["judge".to_string(), "defense".to_string(), "prosecution".to_string()]
+}
+
+fn reply(policy: &mut ModeratedPolicy, value: serde_json::Value) {
+    policy.observe(PolicySignal::ModeratorReply { reply: &value });
+}
+
+#[test]
+fn test_moderator_must_be_participant() {
+    assert!(ModeratedPolicy::new(participants(), "clerk").is_err());
+    assert!(ModeratedPolicy::new(vec!["judge".to_string()], "judge").is_err());
+    assert!(ModeratedPolicy::new(participants(), "judge").is_ok());
+}
+
+#[test]
+fn test_moderator_opens() {
+    let mut policy = ModeratedPolicy::new(participants(), "judge").unwrap();
+    assert_eq!(policy.determine_next_speaker(), Some("judge".to_string()));
+}
+
+#[test]
+fn test_moderator_choice_is_followed() {
+    let mut policy = ModeratedPolicy::new(participants(), "judge").unwrap();
+    assert_eq!(policy.determine_next_speaker(), Some("judge".to_string()));
+
+    reply(&mut policy, json!({"next_speaker": "prosecution", "reason": "rebuttal"}));
+    policy.update_word_count("judge", 40);
+    assert_eq!(policy.determine_next_speaker(), Some("prosecution".to_string()));
+
+    // After any other participant, the floor returns to the moderator
+    policy.update_word_count("prosecution", 120);
+    assert_eq!(policy.determine_next_speaker(), Some("judge".to_string()));
+
+    reply(&mut policy, json!({"next_speaker": "Defense"}));
+    policy.update_word_count("judge", 30);
+    assert_eq!(policy.determine_next_speaker(), Some("defense".to_string()));
+}
+
+#[test]
+fn test_invalid_choice_falls_back_to_longest_waiting() {
+    let mut policy = ModeratedPolicy::new(participants(), "judge").unwrap();
+    policy.determine_next_speaker();
+
+    // The moderator cannot pick itself or an unknown name
+    reply(&mut policy, json!({"next_speaker": "judge"}));
+    reply(&mut policy, json!({"next_speaker": "bailiff"}));
+    policy.update_word_count("judge", 40);
+    assert_eq!(policy.determine_next_speaker(), Some("defense".to_string()));
+
+    policy.update_word_count("defense", 80);
+    assert_eq!(policy.determine_next_speaker(), Some("judge".to_string()));
+
+    // No reply at all: prosecution has waited longest
+    policy.update_word_count("judge", 40);
+    assert_eq!(policy.determine_next_speaker(), Some("prosecution".to_string()));
+}
+
+#[test]
+fn test_stale_choice_is_discarded() {
+    let mut policy = ModeratedPolicy::new(participants(), "judge").unwrap();
+    policy.determine_next_speaker();
+
+    // A reply that arrives while someone else holds the floor is dropped
+    // when the moderator is handed the next turn
+    policy.update_word_count("judge", 40);
+    reply(&mut policy, json!({"next_speaker": "prosecution"}));
+    assert_eq!(policy.determine_next_speaker(), Some("prosecution".to_string()));
+    reply(&mut policy, json!({"next_speaker": "prosecution"}));
+    policy.update_word_count("prosecution", 50);
+    assert_eq!(policy.determine_next_speaker(), Some("judge".to_string()));
+    policy.update_word_count("judge", 40);
+    assert_eq!(policy.determine_next_speaker(), Some("defense".to_string()));
+}
+
+#[test]
+fn test_human_interrupt_returns_floor_to_moderator() {
+    let mut policy = ModeratedPolicy::new(participants(), "judge").unwrap();
+    policy.determine_next_speaker();
+    policy.update_word_count("judge", 40);
+    assert_eq!(policy.determine_next_speaker(), Some("defense".to_string()));
+
+    policy.reset_counts();
+    policy.set_last_speaker(Some("human".to_string()));
+    assert_eq!(policy.determine_next_speaker(), Some("judge".to_string()));
+}
+
+#[test]
+fn test_moderated_stats() {
+    let mut policy = ModeratedPolicy::new(participants(), "judge").unwrap();
+    policy.determine_next_speaker();
+    reply(&mut policy, json!({"next_speaker": "defense"}));
+
+    let stats = policy.get_stats();
+    assert_eq!(stats["mode"], "moderated");
+    assert_eq!(stats["moderator"], "judge");
+    assert_eq!(stats["pending_choice"], "defense");
+    assert_eq!(stats["current_speaker"], "judge");
+    assert_eq!(stats["participants"].as_array().unwrap().len(), 3);
+}
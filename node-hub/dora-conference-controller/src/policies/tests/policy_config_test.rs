@@ -0,0 +1,68 @@
+use crate::policies::{PolicyConfig, PolicyKind};
+
+#[test]
+fn test_parse_policy_kind() {
+    assert_eq!("unified_ratio".parse::<PolicyKind>(), Ok(PolicyKind::UnifiedRatio));
+    assert_eq!("ratio".parse::<PolicyKind>(), Ok(PolicyKind::UnifiedRatio));
+    assert_eq!("Moderated".parse::<PolicyKind>(), Ok(PolicyKind::Moderated));
+    assert_eq!("bid".parse::<PolicyKind>(), Ok(PolicyKind::Bid));
+    assert_eq!(" fair_audio ".parse::<PolicyKind>(), Ok(PolicyKind::FairAudio));
+    assert!("round_robin".parse::<PolicyKind>().is_err());
+    assert_eq!(PolicyKind::default(), PolicyKind::UnifiedRatio);
+}
+
+#[test]
+fn test_required_inputs() {
+    assert!(PolicyKind::UnifiedRatio.required_inputs().is_empty());
+    assert_eq!(PolicyKind::Moderated.required_inputs(), ["moderator"]);
+    assert_eq!(PolicyKind::Bid.required_inputs(), ["bid"]);
+    assert_eq!(PolicyKind::FairAudio.required_inputs(), ["audio_complete"]);
+}
+
+#[test]
+fn test_build_each_kind() {
+    let pattern = "[(judge, 1), (defense, 1), (prosecution, 1)]";
+    for (kind, mode) in [
+        (PolicyKind::UnifiedRatio, "ratio_priority"),
+        (PolicyKind::Moderated, "moderated"),
+        (PolicyKind::Bid, "bid"),
+        (PolicyKind::FairAudio, "fair_audio"),
+    ] {
+        let policy = PolicyConfig::new(kind, pattern).build().unwrap();
+        assert_eq!(policy.get_stats()["mode"], mode);
+        assert_eq!(policy.get_participants(), vec!["judge", "defense", "prosecution"]);
+    }
+}
+
+#[test]
+fn test_moderator_defaults_to_first_participant() {
+    let mut policy = PolicyConfig::new(PolicyKind::Moderated, "[tutor → student1 → student2]")
+        .build()
+        .unwrap();
+    assert_eq!(policy.get_stats()["moderator"], "tutor");
+    assert_eq!(policy.determine_next_speaker(), Some("tutor".to_string()));
+
+    let policy = PolicyConfig::new(PolicyKind::Moderated, "[tutor, student1, student2]")
+        .with_moderator("student2")
+        .build()
+        .unwrap();
+    assert_eq!(policy.get_stats()["moderator"], "student2");
+}
+
+#[test]
+fn test_build_errors() {
+    assert!(PolicyConfig::new(PolicyKind::Bid, "[]").build().is_err());
+    assert!(PolicyConfig::new(PolicyKind::FairAudio, "[(judge, *), (defense, 1)]").build().is_err());
+    assert!(PolicyConfig::new(PolicyKind::Moderated, "[A, B]").with_moderator("C").build().is_err());
+}
+
+#[test]
+fn test_loop_setting() {
+    let config = PolicyConfig::new(PolicyKind::UnifiedRatio, "[judge → defense]");
+    assert!(config.loop_forever);
+
+    let mut policy = config.with_loop(false).build().unwrap();
+    assert_eq!(policy.determine_next_speaker(), Some("judge".to_string()));
+    assert_eq!(policy.determine_next_speaker(), Some("defense".to_string()));
+    assert_eq!(policy.determine_next_speaker(), None);
+}
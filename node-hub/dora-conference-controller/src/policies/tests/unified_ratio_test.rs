@@ -135,6 +135,24 @@ fn test_policy_sequential() {
     assert_eq!(policy.determine_next_speaker(), Some("B".to_string()));
 }
 
+#[test]
+fn test_policy_sequential_without_loop() {
+    let mut policy = UnifiedRatioPolicy::new();
+    policy.configure("[A → B]").unwrap();
+    policy.set_loop_forever(false);
+
+    // One pass, then no more speakers
+    assert_eq!(policy.determine_next_speaker(), Some("A".to_string()));
+    assert_eq!(policy.determine_next_speaker(), Some("B".to_string()));
+    assert_eq!(policy.determine_next_speaker(), None);
+    assert_eq!(policy.get_stats()["loop_forever"], false);
+    assert!(policy.get_stats().get("next_speaker").is_none());
+
+    // A reset starts the sequence over
+    policy.reset_counts();
+    assert_eq!(policy.determine_next_speaker(), Some("A".to_string()));
+}
+
 #[test]
 fn test_policy_ratio_priority_with_priority() {
     let mut policy = UnifiedRatioPolicy::new();
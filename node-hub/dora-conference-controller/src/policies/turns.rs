@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+/// Turn bookkeeping shared by the side-channel policies
+///
+/// Tracks the last speaker, who has spoken in the current round, the cycle
+/// counter the controller uses for question ids, and when each participant
+/// last held the floor so ties can go to whoever has waited longest.
+#[derive(Debug, Clone, Default)]
+pub struct TurnTracker {
+    last_speaker: Option<String>,
+    round_speakers: Vec<String>,
+    cycle: usize,
+    turn: u64,
+    last_turn: HashMap<String, u64>,
+}
+
+impl TurnTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn last_speaker(&self) -> Option<&str> {
+        self.last_speaker.as_deref()
+    }
+
+    pub fn set_last_speaker(&mut self, speaker: Option<String>) {
+        self.last_speaker = speaker;
+    }
+
+    /// Record that `speaker` produced output
+    pub fn spoke(&mut self, speaker: &str) {
+        self.last_speaker = Some(speaker.to_string());
+        if !self.round_speakers.iter().any(|s| s == speaker) {
+            self.round_speakers.push(speaker.to_string());
+        }
+    }
+
+    /// Record that `speaker` was handed the floor
+    pub fn assign(&mut self, speaker: &str) {
+        self.turn += 1;
+        self.last_turn.insert(speaker.to_string(), self.turn);
+        self.last_speaker = Some(speaker.to_string());
+    }
+
+    /// Candidates for the next turn: everyone except the last speaker,
+    /// unless that would leave nobody
+    pub fn candidates<'a>(&self, participants: &'a [String]) -> Vec<&'a String> {
+        let others: Vec<&String> = participants
+            .iter()
+            .filter(|p| self.last_speaker.as_deref() != Some(p.as_str()))
+            .collect();
+        if others.is_empty() { participants.iter().collect() } else { others }
+    }
+
+    /// Turn number at which `speaker` last held the floor (0 = never)
+    pub fn last_turn(&self, speaker: &str) -> u64 {
+        self.last_turn.get(speaker).copied().unwrap_or(0)
+    }
+
+    /// The candidate who has waited longest; earlier entries win ties
+    pub fn least_recent<'a>(&self, candidates: &[&'a String]) -> Option<&'a String> {
+        candidates.iter().copied().min_by_key(|c| self.last_turn(c))
+    }
+
+    pub fn all_spoke(&self, participants: &[String]) -> bool {
+        participants.iter().all(|p| self.round_speakers.contains(p))
+    }
+
+    pub fn reset_round(&mut self) {
+        self.round_speakers.clear();
+    }
+
+    pub fn increment_cycle(&mut self) {
+        self.cycle += 1;
+    }
+
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    /// Forget everything, as at startup
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
@@ -205,63 +205,13 @@ impl UnifiedRatioPolicy {
         Ok(())
     }
 
-    /// Get participants
-    pub fn get_participants(&self) -> Vec<String> {
-        match &self.pattern {
-            PolicyPattern::RatioPriority { participants, .. } | PolicyPattern::Sequential { participants, .. } => participants.clone(),
+    /// Whether a sequential pattern loops; without looping the policy
+    /// returns no speaker once everyone has spoken
+    pub fn set_loop_forever(&mut self, loop_forever: bool) {
+        if let PolicyPattern::Sequential { loop_forever: current, .. } = &mut self.pattern {
+            *current = loop_forever;
         }
     }
-
-    /// Reset word counts
-    pub fn reset_counts(&mut self) {
-        for count in self.word_counts.values_mut() { *count = 0; }
-        self.position = 0;
-        self.last_speaker = None;
-        self.sequential_cycle = 0;
-        self.ratio_priority_cycle = 0;
-    }
-
-    /// Set last speaker (used after human input to avoid cold start)
-    /// When human speaks, we want priority speakers (tutor) to respond,
-    /// not trigger cold start logic which skips priority speakers.
-    pub fn set_last_speaker(&mut self, speaker: Option<String>) {
-        self.last_speaker = speaker;
-    }
-
-    /// Get statistics
-    pub fn get_stats(&self) -> serde_json::Value {
-        let mut stats = serde_json::Map::new();
-        match &self.pattern {
-            PolicyPattern::RatioPriority { participants, weights } => {
-                stats.insert("mode".to_string(), serde_json::Value::String("ratio_priority".to_string()));
-                stats.insert("participants".to_string(), serde_json::Value::Array(participants.iter().map(|p| serde_json::Value::String(p.clone())).collect()));
-                let weight_objects: Vec<_> = participants.iter().zip(weights).map(|(p,w)| {
-                    let mut obj = serde_json::Map::new();
-                    obj.insert("name".to_string(), serde_json::Value::String(p.clone()));
-                    match w { Weight::Priority => obj.insert("weight".to_string(), serde_json::Value::String("*".to_string())), Weight::Ratio(r) => obj.insert("weight".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(*r).unwrap())) };
-                    serde_json::Value::Object(obj)
-                }).collect();
-                stats.insert("weights".to_string(), serde_json::Value::Array(weight_objects));
-            }
-            PolicyPattern::Sequential { participants, loop_forever } => {
-                stats.insert("mode".to_string(), serde_json::Value::String("sequential".to_string()));
-                stats.insert("sequence".to_string(), serde_json::Value::Array(participants.iter().map(|p| serde_json::Value::String(p.clone())).collect()));
-                stats.insert("loop_forever".to_string(), serde_json::Value::Bool(*loop_forever));
-            }
-        }
-        let word_count_obj: serde_json::Map<String, serde_json::Value> = self.word_counts.iter().map(|(k,v)| (k.clone(), serde_json::Value::Number(serde_json::Number::from(*v)))).collect();
-        stats.insert("word_counts".to_string(), serde_json::Value::Object(word_count_obj));
-        // Compute next speaker from sequence and position
-        if let PolicyPattern::Sequential { participants, .. } = &self.pattern {
-            if !participants.is_empty() {
-                let next_speaker = &participants[self.position % participants.len()];
-                stats.insert("next_speaker".to_string(), serde_json::Value::String(next_speaker.clone()));
-            }
-        }
-        stats.insert("cycle".to_string(), serde_json::Value::Number(self.get_current_cycle().into()));
-        if let Some(last) = &self.last_speaker { stats.insert("current_speaker".to_string(), serde_json::Value::String(last.clone())); }
-        serde_json::Value::Object(stats)
-    }
 }
 
 impl Policy for UnifiedRatioPolicy {
@@ -317,6 +267,64 @@ impl Policy for UnifiedRatioPolicy {
     fn reset_round_tracking(&mut self) {
         self.round_speakers.clear();
     }
+
+    /// Get participants
+    fn get_participants(&self) -> Vec<String> {
+        match &self.pattern {
+            PolicyPattern::RatioPriority { participants, .. } | PolicyPattern::Sequential { participants, .. } => participants.clone(),
+        }
+    }
+
+    /// Reset word counts
+    fn reset_counts(&mut self) {
+        for count in self.word_counts.values_mut() { *count = 0; }
+        self.position = 0;
+        self.last_speaker = None;
+        self.sequential_cycle = 0;
+        self.ratio_priority_cycle = 0;
+    }
+
+    /// Set last speaker (used after human input to avoid cold start)
+    /// When human speaks, we want priority speakers (tutor) to respond,
+    /// not trigger cold start logic which skips priority speakers.
+    fn set_last_speaker(&mut self, speaker: Option<String>) {
+        self.last_speaker = speaker;
+    }
+
+    /// Get statistics
+    fn get_stats(&self) -> serde_json::Value {
+        let mut stats = serde_json::Map::new();
+        match &self.pattern {
+            PolicyPattern::RatioPriority { participants, weights } => {
+                stats.insert("mode".to_string(), serde_json::Value::String("ratio_priority".to_string()));
+                stats.insert("participants".to_string(), serde_json::Value::Array(participants.iter().map(|p| serde_json::Value::String(p.clone())).collect()));
+                let weight_objects: Vec<_> = participants.iter().zip(weights).map(|(p,w)| {
+                    let mut obj = serde_json::Map::new();
+                    obj.insert("name".to_string(), serde_json::Value::String(p.clone()));
+                    match w { Weight::Priority => obj.insert("weight".to_string(), serde_json::Value::String("*".to_string())), Weight::Ratio(r) => obj.insert("weight".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(*r).unwrap())) };
+                    serde_json::Value::Object(obj)
+                }).collect();
+                stats.insert("weights".to_string(), serde_json::Value::Array(weight_objects));
+            }
+            PolicyPattern::Sequential { participants, loop_forever } => {
+                stats.insert("mode".to_string(), serde_json::Value::String("sequential".to_string()));
+                stats.insert("sequence".to_string(), serde_json::Value::Array(participants.iter().map(|p| serde_json::Value::String(p.clone())).collect()));
+                stats.insert("loop_forever".to_string(), serde_json::Value::Bool(*loop_forever));
+            }
+        }
+        let word_count_obj: serde_json::Map<String, serde_json::Value> = self.word_counts.iter().map(|(k,v)| (k.clone(), serde_json::Value::Number(serde_json::Number::from(*v)))).collect();
+        stats.insert("word_counts".to_string(), serde_json::Value::Object(word_count_obj));
+        // Compute next speaker from sequence and position
+        if let PolicyPattern::Sequential { participants, .. } = &self.pattern {
+            // A finished non-looping sequence has no next speaker
+            if let Some(next_speaker) = participants.get(self.position) {
+                stats.insert("next_speaker".to_string(), serde_json::Value::String(next_speaker.clone()));
+            }
+        }
+        stats.insert("cycle".to_string(), serde_json::Value::Number(self.get_current_cycle().into()));
+        if let Some(last) = &self.last_speaker { stats.insert("current_speaker".to_string(), serde_json::Value::String(last.clone())); }
+        serde_json::Value::Object(stats)
+    }
 }
 
 impl UnifiedRatioPolicy {
@@ -366,12 +374,12 @@ impl UnifiedRatioPolicy {
 
     /// Sequential selection
     fn determine_sequential_speaker(&mut self, participants: &[String], loop_forever: bool) -> Option<String> {
-        if participants.is_empty() { return None; }
+        if participants.is_empty() || self.position >= participants.len() { return None; }
         let speaker = participants[self.position].clone();
         self.position += 1;
-        if self.position >= participants.len() {
-            if loop_forever { self.position = 0; self.sequential_cycle += 1; }
-            else { self.position = participants.len() - 1; }
+        if self.position >= participants.len() && loop_forever {
+            self.position = 0;
+            self.sequential_cycle += 1;
         }
         self.last_speaker = Some(speaker.clone());
         Some(speaker)
@@ -0,0 +1,409 @@
+// Session limits and closing protocol
+//
+// A session runs until one of its limits is reached. The controller then
+// asks the closer (judge or tutor) for a final summary or verdict, waits for
+// that reply (at most the closing timeout), emits `session_ended` and stops
+// resuming participants.
+
+use serde_json::{json, Value};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Prompt sent to the closer when no closing prompt is configured
+pub const DEFAULT_CLOSING_PROMPT: &str =
+    "The session is over. Please give your final summary or verdict of the discussion.";
+
+/// How long to wait for the closing statement before ending without it
+pub const DEFAULT_CLOSING_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Stop conditions of a session; `None` means no limit
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionLimits {
+    /// Speaking turns handed out (the `round` of the turn id)
+    pub max_rounds: Option<u64>,
+    /// Wall-clock time since the session started
+    pub max_duration: Option<Duration>,
+    /// Words spoken by all participants together
+    pub max_words: Option<usize>,
+    /// Phrases that end the session when the moderator says them
+    /// (case-insensitive)
+    pub end_keywords: Vec<String>,
+}
+
+impl SessionLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_rounds.is_none()
+            && self.max_duration.is_none()
+            && self.max_words.is_none()
+            && self.end_keywords.is_empty()
+    }
+
+    /// The first end keyword contained in `text`
+    fn find_keyword(&self, text: &str) -> Option<&str> {
+        let text = text.to_lowercase();
+        self.end_keywords
+            .iter()
+            .map(|keyword| keyword.trim())
+            .find(|keyword| !keyword.is_empty() && text.contains(&keyword.to_lowercase()))
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "max_rounds": self.max_rounds,
+            "max_seconds": self.max_duration.map(|d| d.as_secs_f64()),
+            "max_words": self.max_words,
+            "end_keywords": self.end_keywords,
+        })
+    }
+}
+
+/// Session settings of the controller
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionConfig {
+    pub limits: SessionLimits,
+    /// Participant whose replies may contain end keywords; the controller
+    /// picks a default when unset. The closing statement always comes from
+    /// the judge, the participant behind `judge_prompt`
+    pub moderator: Option<String>,
+    /// Prompt asking the closer for the closing statement; empty ends the
+    /// session without one
+    pub closing_prompt: String,
+    /// How long to wait for the closing statement
+    pub closing_timeout: Duration,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            limits: SessionLimits::default(),
+            moderator: None,
+            closing_prompt: DEFAULT_CLOSING_PROMPT.to_string(),
+            closing_timeout: DEFAULT_CLOSING_TIMEOUT,
+        }
+    }
+}
+
+/// Why a session ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EndReason {
+    MaxRounds,
+    MaxDuration,
+    MaxWords,
+    /// The moderator said an end keyword
+    Keyword(String),
+    /// The policy has nobody left to call on (e.g. a non-looping sequence)
+    PolicyExhausted,
+}
+
+impl EndReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EndReason::MaxRounds => "max_rounds",
+            EndReason::MaxDuration => "max_duration",
+            EndReason::MaxWords => "max_words",
+            EndReason::Keyword(_) => "keyword",
+            EndReason::PolicyExhausted => "policy_exhausted",
+        }
+    }
+}
+
+impl fmt::Display for EndReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EndReason::Keyword(keyword) => write!(f, "keyword '{}'", keyword),
+            other => f.write_str(other.as_str()),
+        }
+    }
+}
+
+/// Where a session is in its life cycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionPhase {
+    Running,
+    /// Waiting for the closer's final statement
+    Closing { reason: EndReason, closer: String },
+    Ended { reason: EndReason },
+}
+
+impl SessionPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionPhase::Running => "running",
+            SessionPhase::Closing { .. } => "closing",
+            SessionPhase::Ended { .. } => "ended",
+        }
+    }
+}
+
+/// Tracks one session against its limits
+///
+/// Time is passed in by the caller so the tracker can be driven by a
+/// simulated clock.
+#[derive(Debug, Clone)]
+pub struct SessionTracker {
+    limits: SessionLimits,
+    moderator: Option<String>,
+    started: Instant,
+    rounds: u64,
+    words: usize,
+    keyword: Option<String>,
+    phase: SessionPhase,
+    /// When the closing statement was asked for
+    closing_since: Option<Instant>,
+}
+
+impl SessionTracker {
+    /// Start a session; `moderator` is whose replies are checked for end
+    /// keywords
+    pub fn new(limits: SessionLimits, moderator: Option<String>, now: Instant) -> Self {
+        Self {
+            limits,
+            moderator,
+            started: now,
+            rounds: 0,
+            words: 0,
+            keyword: None,
+            phase: SessionPhase::Running,
+            closing_since: None,
+        }
+    }
+
+    /// Start a fresh session with the same limits
+    pub fn restart(&mut self, now: Instant) {
+        *self = Self::new(self.limits.clone(), self.moderator.take(), now);
+    }
+
+    pub fn limits(&self) -> &SessionLimits {
+        &self.limits
+    }
+
+    pub fn moderator(&self) -> Option<&str> {
+        self.moderator.as_deref()
+    }
+
+    pub fn phase(&self) -> &SessionPhase {
+        &self.phase
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.phase == SessionPhase::Running
+    }
+
+    pub fn rounds(&self) -> u64 {
+        self.rounds
+    }
+
+    pub fn words(&self) -> usize {
+        self.words
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.started)
+    }
+
+    /// A turn was handed to a participant
+    pub fn record_turn(&mut self) {
+        self.rounds += 1;
+    }
+
+    /// A participant finished a reply
+    pub fn record_reply(&mut self, speaker: &str, text: &str, words: usize) {
+        self.words += words;
+        if self.moderator.as_deref() == Some(speaker) {
+            self.record_moderator_text(text);
+        }
+    }
+
+    /// Text from the moderator, e.g. its structured reply
+    pub fn record_moderator_text(&mut self, text: &str) {
+        if self.keyword.is_none() && self.is_running() {
+            self.keyword = self.limits.find_keyword(text).map(str::to_string);
+        }
+    }
+
+    /// The limit reached by a running session, if any
+    ///
+    /// The round limit is reached once that many turns have been handed
+    /// out, so it is checked before handing out the next one.
+    pub fn check(&self, now: Instant) -> Option<EndReason> {
+        if !self.is_running() {
+            return None;
+        }
+        if let Some(keyword) = &self.keyword {
+            return Some(EndReason::Keyword(keyword.clone()));
+        }
+        if self.limits.max_rounds.is_some_and(|max| self.rounds >= max) {
+            return Some(EndReason::MaxRounds);
+        }
+        if self.limits.max_words.is_some_and(|max| self.words >= max) {
+            return Some(EndReason::MaxWords);
+        }
+        if self.limits.max_duration.is_some_and(|max| self.elapsed(now) >= max) {
+            return Some(EndReason::MaxDuration);
+        }
+        None
+    }
+
+    /// Stop the session; with a closer it waits for the closing statement,
+    /// without one it ends right away
+    pub fn begin_closing(&mut self, reason: EndReason, closer: Option<String>, now: Instant) {
+        self.closing_since = closer.as_ref().map(|_| now);
+        self.phase = match closer {
+            Some(closer) => SessionPhase::Closing { reason, closer },
+            None => SessionPhase::Ended { reason },
+        };
+    }
+
+    /// Whether a reply from `speaker` is the awaited closing statement
+    pub fn is_closing_reply(&self, speaker: &str) -> bool {
+        matches!(&self.phase, SessionPhase::Closing { closer, .. } if closer == speaker)
+    }
+
+    /// Whether a closing session has waited `timeout` for its closing statement
+    pub fn closing_timed_out(&self, now: Instant, timeout: Duration) -> bool {
+        matches!(self.phase, SessionPhase::Closing { .. })
+            && self
+                .closing_since
+                .is_some_and(|since| now.saturating_duration_since(since) >= timeout)
+    }
+
+    /// End a closing session; returns why it ended
+    pub fn finish(&mut self) -> Option<EndReason> {
+        match &self.phase {
+            SessionPhase::Closing { reason, .. } => {
+                let reason = reason.clone();
+                self.phase = SessionPhase::Ended { reason: reason.clone() };
+                Some(reason)
+            }
+            SessionPhase::Ended { reason } => Some(reason.clone()),
+            SessionPhase::Running => None,
+        }
+    }
+
+    /// Session section of the controller statistics
+    pub fn to_json(&self, now: Instant) -> Value {
+        let mut stats = json!({
+            "phase": self.phase.as_str(),
+            "rounds": self.rounds,
+            "words": self.words,
+            "elapsed_seconds": self.elapsed(now).as_secs_f64(),
+            "limits": self.limits.to_json(),
+        });
+        match &self.phase {
+            SessionPhase::Closing { reason, closer } => {
+                stats["end_reason"] = json!(reason.as_str());
+                stats["closer"] = json!(closer);
+            }
+            SessionPhase::Ended { reason } => stats["end_reason"] = json!(reason.as_str()),
+            SessionPhase::Running => {}
+        }
+        if let Some(keyword) = &self.keyword {
+            stats["keyword"] = json!(keyword);
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> SessionLimits {
+        SessionLimits {
+            max_rounds: Some(3),
+            max_duration: Some(Duration::from_secs(60)),
+            max_words: Some(100),
+            end_keywords: vec!["[END SESSION]".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_unlimited_session_never_ends() {
+        let start = Instant::now();
+        let mut session = SessionTracker::new(SessionLimits::default(), None, start);
+        for _ in 0..1000 {
+            session.record_turn();
+            session.record_reply("tutor", "[END SESSION]", 50);
+        }
+        assert!(SessionLimits::default().is_unlimited());
+        assert_eq!(session.check(start + Duration::from_secs(86_400)), None);
+    }
+
+    #[test]
+    fn test_each_limit() {
+        let start = Instant::now();
+
+        let mut session = SessionTracker::new(limits(), None, start);
+        session.record_turn();
+        session.record_turn();
+        assert_eq!(session.check(start), None);
+        session.record_turn();
+        assert_eq!(session.check(start), Some(EndReason::MaxRounds));
+
+        let mut session = SessionTracker::new(limits(), None, start);
+        session.record_reply("student1", "", 99);
+        assert_eq!(session.check(start), None);
+        session.record_reply("student2", "", 1);
+        assert_eq!(session.check(start), Some(EndReason::MaxWords));
+
+        let session = SessionTracker::new(limits(), None, start);
+        assert_eq!(session.check(start + Duration::from_secs(59)), None);
+        assert_eq!(
+            session.check(start + Duration::from_secs(60)),
+            Some(EndReason::MaxDuration)
+        );
+    }
+
+    #[test]
+    fn test_keyword_only_counts_from_moderator() {
+        let start = Instant::now();
+        let mut session = SessionTracker::new(limits(), Some("tutor".to_string()), start);
+
+        session.record_reply("student1", "Shall we [end session]?", 4);
+        assert_eq!(session.check(start), None);
+
+        session.record_reply("tutor", "Thank you all. [End Session]", 4);
+        assert_eq!(
+            session.check(start),
+            Some(EndReason::Keyword("[END SESSION]".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_closing_protocol() {
+        let start = Instant::now();
+        let mut session = SessionTracker::new(limits(), Some("tutor".to_string()), start);
+        assert_eq!(session.finish(), None);
+
+        session.begin_closing(EndReason::MaxRounds, Some("tutor".to_string()), start);
+        assert_eq!(session.phase().as_str(), "closing");
+        assert!(!session.closing_timed_out(start + Duration::from_secs(59), Duration::from_secs(60)));
+        assert!(session.closing_timed_out(start + Duration::from_secs(60), Duration::from_secs(60)));
+        assert!(!session.is_running());
+        assert!(session.is_closing_reply("tutor"));
+        assert!(!session.is_closing_reply("student1"));
+        // No further limits are reported once closing
+        session.record_turn();
+        session.record_turn();
+        session.record_turn();
+        assert_eq!(session.check(start), None);
+
+        assert_eq!(session.finish(), Some(EndReason::MaxRounds));
+        assert!(!session.is_closing_reply("tutor"));
+        assert!(!session.closing_timed_out(start + Duration::from_secs(60), Duration::from_secs(60)));
+        let stats = session.to_json(start + Duration::from_secs(5));
+        assert_eq!(stats["phase"], "ended");
+        assert_eq!(stats["end_reason"], "max_rounds");
+        assert_eq!(stats["elapsed_seconds"], 5.0);
+
+        // Without a closer the session ends right away
+        session.restart(start);
+        assert!(session.is_running());
+        assert_eq!(session.rounds(), 0);
+        session.begin_closing(EndReason::PolicyExhausted, None, start);
+        assert_eq!(
+            session.phase(),
+            &SessionPhase::Ended { reason: EndReason::PolicyExhausted }
+        );
+    }
+}
@@ -0,0 +1,108 @@
+// Replays the scripted timelines in tests/scenarios against the controller
+//
+// Each YAML file is one scenario; its `setup` section configures the
+// controller the way the dataflow environment would.
+
+use dora_conference_controller::controller::ConferenceController;
+use dora_conference_controller::policies::{PolicyConfig, PolicyKind};
+use dora_conference_controller::session::{SessionConfig, SessionLimits};
+use mofa_roster::Roster;
+use mofa_sim::Scenario;
+use serde::Deserialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Controller configuration of a scenario
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Setup {
+    /// DORA_POLICY_PATTERN; defaults to the roster's pattern
+    pattern: Option<String>,
+    /// DORA_POLICY (default unified_ratio)
+    policy: Option<String>,
+    /// DORA_POLICY_MODERATOR
+    moderator: Option<String>,
+    /// DORA_POLICY_LOOP
+    #[serde(rename = "loop")]
+    loop_forever: Option<bool>,
+    /// Contents of the DORA_ROSTER file
+    roster: Option<String>,
+    /// Unix time the controller starts at (default 1000)
+    epoch: Option<u64>,
+    max_rounds: Option<u64>,
+    max_seconds: Option<f64>,
+    max_words: Option<usize>,
+    end_keywords: Vec<String>,
+    session_moderator: Option<String>,
+    closing_prompt: Option<String>,
+    closing_timeout: Option<f64>,
+}
+
+impl Setup {
+    fn controller(&self, start: Instant) -> ConferenceController {
+        let kind = match &self.policy {
+            Some(kind) => kind.parse::<PolicyKind>().unwrap(),
+            None => PolicyKind::default(),
+        };
+        let roster = self
+            .roster
+            .as_deref()
+            .map(|roster| roster.parse::<Roster>().unwrap());
+        let pattern = match (&self.pattern, &roster) {
+            (Some(pattern), _) => pattern.clone(),
+            (None, Some(roster)) => roster.pattern(),
+            (None, None) => panic!("a scenario needs a pattern or a roster"),
+        };
+
+        let mut config = PolicyConfig::new(kind, pattern);
+        if let Some(moderator) = &self.moderator {
+            config = config.with_moderator(moderator.clone());
+        }
+        if let Some(loop_forever) = self.loop_forever {
+            config = config.with_loop(loop_forever);
+        }
+
+        let mut session = SessionConfig {
+            limits: SessionLimits {
+                max_rounds: self.max_rounds,
+                max_duration: self.max_seconds.map(Duration::from_secs_f64),
+                max_words: self.max_words,
+                end_keywords: self.end_keywords.clone(),
+            },
+            moderator: self.session_moderator.clone(),
+            ..SessionConfig::default()
+        };
+        if let Some(prompt) = &self.closing_prompt {
+            session.closing_prompt = prompt.clone();
+        }
+        if let Some(seconds) = self.closing_timeout {
+            session.closing_timeout = Duration::from_secs_f64(seconds);
+        }
+
+        ConferenceController::new(config, roster, session, self.epoch.unwrap_or(1000), start)
+            .unwrap()
+    }
+}
+
+#[test]
+fn test_scenarios() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/scenarios");
+    let mut paths: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "yml"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no scenarios in {}", dir.display());
+
+    let mut failures = Vec::new();
+    for path in &paths {
+        let scenario = Scenario::<Setup>::load(path).unwrap();
+        let start = Instant::now();
+        let mut controller = scenario.setup.controller(start);
+        if let Err(e) = scenario.run(&mut controller, start) {
+            failures.push(e.to_string());
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
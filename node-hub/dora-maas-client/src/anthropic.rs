@@ -0,0 +1,765 @@
+//! Native Anthropic Messages API support
+//!
+//! Translates OpenAI-style chat completion requests into Anthropic
+//! `/v1/messages` bodies and Anthropic responses back into
+//! [`CreateChatCompletionResponse`], so [`AnthropicClient`] fits the same
+//! [`ChatClient`] interface as the other providers.
+//!
+//! | OpenAI | Anthropic |
+//! |--------|-----------|
+//! | `system` / `developer` messages | top-level `system` |
+//! | `user` message | `user` message with a `text` block |
+//! | `assistant` message, `tool_calls` | `assistant` message with `text` / `tool_use` blocks |
+//! | `tool` message | `user` message with a `tool_result` block |
+//! | `tools` | `tools[]` with `input_schema` |
+//!
+//! Anthropic requires alternating roles, so consecutive messages of the same
+//! role (e.g. several tool results) are merged into one message. It also
+//! requires `max_tokens`; requests without one get the provider default.
+//!
+//! [`AnthropicClient`]: crate::client::AnthropicClient
+//! [`ChatClient`]: crate::client::ChatClient
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use bytes::Bytes;
+use eyre::{Result, eyre};
+use futures::StreamExt;
+use outfox_openai::spec::{
+    ChatCompletionMessageToolCall, ChatCompletionToolType, CreateChatCompletionRequest,
+    CreateChatCompletionResponse, FunctionCall,
+};
+use reqwest_eventsource::{Event, EventSource};
+use serde_json::{Map, Value, json};
+use tokio_util::sync::CancellationToken;
+
+use crate::failover::ProviderError;
+use crate::streaming::sse_error;
+use crate::usage::Usage;
+use crate::wire::{content_text, unix_timestamp};
+
+/// Value of the `anthropic-version` header
+pub const API_VERSION: &str = "2023-06-01";
+
+/// Anthropic endpoint URL for the Messages API
+pub fn endpoint(api_url: &str) -> String {
+    format!("{}/messages", api_url.trim_end_matches('/'))
+}
+
+/// Build an Anthropic request body from an OpenAI-style request.
+///
+/// Returns the model name and the `/messages` body; `max_tokens` is used
+/// when the request sets no token limit.
+pub fn to_anthropic_request(
+    request: &CreateChatCompletionRequest,
+    max_tokens: u32,
+) -> Result<(String, Value)> {
+    let request = serde_json::to_value(request)?;
+    let model = request["model"]
+        .as_str()
+        .ok_or_else(|| eyre!("Request has no model"))?
+        .to_string();
+
+    let mut system = Vec::new();
+    let mut messages = Vec::new();
+
+    for message in request["messages"].as_array().into_iter().flatten() {
+        let text = content_text(&message["content"]);
+        match message["role"].as_str().unwrap_or("user") {
+            "system" | "developer" => {
+                if !text.is_empty() {
+                    system.push(text);
+                }
+            }
+            "assistant" => {
+                let mut blocks = Vec::new();
+                if !text.is_empty() {
+                    blocks.push(json!({ "type": "text", "text": text }));
+                }
+                for call in message["tool_calls"].as_array().into_iter().flatten() {
+                    let input = call["function"]["arguments"]
+                        .as_str()
+                        .and_then(|args| serde_json::from_str::<Value>(args).ok())
+                        .unwrap_or_else(|| json!({}));
+                    blocks.push(json!({
+                        "type": "tool_use",
+                        "id": call["id"],
+                        "name": call["function"]["name"],
+                        "input": input,
+                    }));
+                }
+                push_message(&mut messages, "assistant", blocks);
+            }
+            "tool" => {
+                let call_id = message["tool_call_id"]
+                    .as_str()
+                    .ok_or_else(|| eyre!("Tool result without tool_call_id"))?;
+                push_message(
+                    &mut messages,
+                    "user",
+                    vec![json!({ "type": "tool_result", "tool_use_id": call_id, "content": text })],
+                );
+            }
+            _ => push_message(
+                &mut messages,
+                "user",
+                vec![json!({ "type": "text", "text": text })],
+            ),
+        }
+    }
+
+    let max_tokens = request["max_completion_tokens"]
+        .as_u64()
+        .or_else(|| request["max_tokens"].as_u64())
+        .unwrap_or(max_tokens as u64);
+
+    let mut body = Map::new();
+    body.insert("model".to_string(), json!(model));
+    body.insert("max_tokens".to_string(), json!(max_tokens));
+    body.insert("messages".to_string(), Value::Array(messages));
+    if !system.is_empty() {
+        body.insert("system".to_string(), json!(system.join("\n\n")));
+    }
+
+    let tools: Vec<Value> = request["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|tool| {
+            let function = &tool["function"];
+            let mut declaration = json!({
+                "name": function["name"],
+                "input_schema": if function["parameters"].is_null() {
+                    json!({ "type": "object" })
+                } else {
+                    function["parameters"].clone()
+                },
+            });
+            if let Some(description) = function["description"].as_str() {
+                declaration["description"] = json!(description);
+            }
+            declaration
+        })
+        .collect();
+    if !tools.is_empty() {
+        body.insert("tools".to_string(), Value::Array(tools));
+    }
+    if let Some(tool_choice) = tool_choice(&request["tool_choice"]) {
+        body.insert("tool_choice".to_string(), tool_choice);
+    }
+
+    if let Some(temperature) = request["temperature"].as_f64() {
+        body.insert("temperature".to_string(), json!(temperature));
+    }
+    if let Some(top_p) = request["top_p"].as_f64() {
+        body.insert("top_p".to_string(), json!(top_p));
+    }
+    match &request["stop"] {
+        Value::String(stop) => {
+            body.insert("stop_sequences".to_string(), json!([stop]));
+        }
+        Value::Array(stops) => {
+            body.insert("stop_sequences".to_string(), json!(stops));
+        }
+        _ => {}
+    }
+
+    Ok((model, Value::Object(body)))
+}
+
+/// Convert an Anthropic `/messages` response into a chat completion response
+pub fn from_anthropic_response(
+    response: &Value,
+    model: &str,
+) -> Result<CreateChatCompletionResponse> {
+    if response["type"] == "error" {
+        return Err(eyre!("Anthropic error: {}", response["error"]));
+    }
+    let blocks = response["content"]
+        .as_array()
+        .ok_or_else(|| eyre!("Anthropic response has no content: {}", response))?;
+
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    for block in blocks {
+        match block["type"].as_str() {
+            Some("text") => text.push_str(block["text"].as_str().unwrap_or_default()),
+            Some("tool_use") => tool_calls.push(tool_call(
+                block["id"].as_str().unwrap_or_default(),
+                block["name"].as_str().unwrap_or_default(),
+                block.get("input").unwrap_or(&json!({})).to_string(),
+            )),
+            // Thinking blocks aren't part of the answer
+            _ => {}
+        }
+    }
+
+    let content = if text.is_empty() && !tool_calls.is_empty() {
+        Value::Null
+    } else {
+        Value::String(text)
+    };
+    let mut message = json!({ "role": "assistant", "content": content });
+    if !tool_calls.is_empty() {
+        message["tool_calls"] = serde_json::to_value(&tool_calls)?;
+    }
+
+    let mut completion = json!({
+        "id": response["id"].as_str().unwrap_or("anthropic"),
+        "object": "chat.completion",
+        "created": unix_timestamp(),
+        "model": response["model"].as_str().unwrap_or(model),
+        "choices": [{
+            "index": 0,
+            "message": message,
+            "finish_reason": openai_finish_reason(response["stop_reason"].as_str()),
+        }],
+    });
+    if let Some(usage) = usage(&response["usage"]) {
+        completion["usage"] = serde_json::to_value(usage)?;
+    }
+
+    serde_json::from_value(completion)
+        .map_err(|e| eyre!("Failed to convert Anthropic response: {}", e))
+}
+
+/// Stream a `/messages` request (SSE), calling `on_chunk` for each text delta.
+///
+/// Tool calls arrive as a `tool_use` block start followed by
+/// `input_json_delta` fragments; they are assembled per block index and
+/// returned with the accumulated text once `message_stop` arrives. `timeout`
+/// bounds the wait for each event.
+pub async fn stream_messages<F>(
+    client: &reqwest::Client,
+    url: String,
+    api_key: String,
+    mut request_body: Value,
+    cancellation_token: Option<CancellationToken>,
+    timeout: Option<Duration>,
+    mut on_chunk: F,
+) -> Result<(
+    String,
+    Option<Vec<ChatCompletionMessageToolCall>>,
+    Option<Usage>,
+)>
+where
+    F: FnMut(String) -> Result<()>,
+{
+    request_body["stream"] = json!(true);
+    let body_bytes: Bytes = serde_json::to_vec(&request_body)?.into();
+    let request = client
+        .post(url)
+        .header("x-api-key", api_key)
+        .header("anthropic-version", API_VERSION)
+        .header("Content-Type", "application/json")
+        .body(body_bytes);
+
+    let mut event_source = EventSource::new(request)?;
+    let cancellation_token = cancellation_token.unwrap_or_default();
+    let mut accumulated_content = String::new();
+    // Tool calls by content block index: (id, name, partial input JSON)
+    let mut tool_blocks: BTreeMap<u64, (String, String, String)> = BTreeMap::new();
+    let mut input_tokens = None;
+    let mut output_tokens = None;
+
+    loop {
+        let idle_timeout = async {
+            match timeout {
+                Some(duration) => tokio::time::sleep(duration).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            event = event_source.next() => {
+                match event {
+                    Some(Ok(Event::Open)) => {}
+                    Some(Ok(Event::Message(msg))) => {
+                        let data: Value = match serde_json::from_str(&msg.data) {
+                            Ok(data) => data,
+                            Err(_) => continue,
+                        };
+                        // The payload repeats the SSE event name as `type`
+                        match data["type"].as_str().unwrap_or(msg.event.as_str()) {
+                            "message_start" => {
+                                let usage = &data["message"]["usage"];
+                                input_tokens = usage["input_tokens"].as_u64();
+                                output_tokens = usage["output_tokens"].as_u64();
+                            }
+                            "content_block_start" => {
+                                let block = &data["content_block"];
+                                if block["type"] == "tool_use" {
+                                    tool_blocks.insert(
+                                        data["index"].as_u64().unwrap_or_default(),
+                                        (
+                                            block["id"].as_str().unwrap_or_default().to_string(),
+                                            block["name"].as_str().unwrap_or_default().to_string(),
+                                            String::new(),
+                                        ),
+                                    );
+                                }
+                            }
+                            "content_block_delta" => {
+                                let delta = &data["delta"];
+                                match delta["type"].as_str() {
+                                    Some("text_delta") => {
+                                        let text = delta["text"].as_str().unwrap_or_default();
+                                        if !text.is_empty() {
+                                            accumulated_content.push_str(text);
+                                            on_chunk(text.to_string())?;
+                                        }
+                                    }
+                                    Some("input_json_delta") => {
+                                        let index = data["index"].as_u64().unwrap_or_default();
+                                        if let Some((_, _, input)) = tool_blocks.get_mut(&index) {
+                                            input.push_str(
+                                                delta["partial_json"].as_str().unwrap_or_default(),
+                                            );
+                                        }
+                                    }
+                                    _ => {}
+                                }
+                            }
+                            "message_delta" => {
+                                // Cumulative output count for the whole message
+                                if let Some(tokens) = data["usage"]["output_tokens"].as_u64() {
+                                    output_tokens = Some(tokens);
+                                }
+                            }
+                            "message_stop" => break,
+                            "error" => {
+                                event_source.close();
+                                return Err(stream_error(&data["error"]).into());
+                            }
+                            // ping, content_block_stop
+                            _ => {}
+                        }
+                    }
+                    // Closed without `message_stop`: the response is incomplete
+                    Some(Err(reqwest_eventsource::Error::StreamEnded)) | None => {
+                        event_source.close();
+                        if cancellation_token.is_cancelled() {
+                            return Err(ProviderError::Cancelled.into());
+                        }
+                        return Err(ProviderError::StreamEnded.into());
+                    }
+                    Some(Err(e)) => {
+                        event_source.close();
+                        if cancellation_token.is_cancelled() {
+                            return Err(ProviderError::Cancelled.into());
+                        }
+                        return Err(sse_error(e).await);
+                    }
+                }
+            }
+            _ = cancellation_token.cancelled() => {
+                event_source.close();
+                return Err(ProviderError::Cancelled.into());
+            }
+            _ = idle_timeout => {
+                event_source.close();
+                return Err(ProviderError::StreamTimeout(timeout.unwrap_or_default()).into());
+            }
+        }
+    }
+    event_source.close();
+
+    let tool_calls: Vec<_> = tool_blocks
+        .into_values()
+        .map(|(id, name, input)| {
+            // A tool without parameters streams no input fragments
+            let arguments = if input.trim().is_empty() {
+                "{}".to_string()
+            } else {
+                input
+            };
+            tool_call(&id, &name, arguments)
+        })
+        .collect();
+    let tool_calls = if tool_calls.is_empty() {
+        None
+    } else {
+        Some(tool_calls)
+    };
+    let usage = input_tokens.map(|input| Usage::new(input, output_tokens.unwrap_or(0)));
+    Ok((accumulated_content, tool_calls, usage))
+}
+
+/// Append content blocks, merging consecutive messages of the same role
+fn push_message(messages: &mut Vec<Value>, role: &str, blocks: Vec<Value>) {
+    if blocks.is_empty() {
+        return;
+    }
+    if let Some(last) = messages.last_mut() {
+        if last["role"] == role {
+            if let Some(existing) = last["content"].as_array_mut() {
+                existing.extend(blocks);
+                return;
+            }
+        }
+    }
+    messages.push(json!({ "role": role, "content": blocks }));
+}
+
+/// Anthropic `tool_choice` for an OpenAI `tool_choice`
+fn tool_choice(tool_choice: &Value) -> Option<Value> {
+    match tool_choice {
+        Value::String(choice) => match choice.as_str() {
+            "none" => Some(json!({ "type": "none" })),
+            "auto" => Some(json!({ "type": "auto" })),
+            "required" => Some(json!({ "type": "any" })),
+            _ => None,
+        },
+        Value::Object(_) => match tool_choice["function"]["name"].as_str() {
+            Some(name) => Some(json!({ "type": "tool", "name": name })),
+            None => Some(json!({ "type": "any" })),
+        },
+        _ => None,
+    }
+}
+
+fn tool_call(id: &str, name: &str, arguments: String) -> ChatCompletionMessageToolCall {
+    ChatCompletionMessageToolCall {
+        id: id.to_string(),
+        kind: ChatCompletionToolType::Function,
+        function: FunctionCall {
+            name: name.to_string(),
+            arguments,
+        },
+    }
+}
+
+/// OpenAI `finish_reason` for an Anthropic `stop_reason`
+fn openai_finish_reason(reason: Option<&str>) -> &'static str {
+    match reason {
+        Some("max_tokens") => "length",
+        Some("tool_use") => "tool_calls",
+        Some("refusal") => "content_filter",
+        _ => "stop",
+    }
+}
+
+/// Usage from an Anthropic `usage` object
+fn usage(usage: &Value) -> Option<Usage> {
+    let input_tokens = usage["input_tokens"].as_u64()?;
+    Some(Usage::new(
+        input_tokens,
+        usage["output_tokens"].as_u64().unwrap_or(0),
+    ))
+}
+
+/// Provider error for an in-stream `error` event, with the HTTP status the
+/// same error would have had before streaming started (so failover can retry
+/// overloads)
+fn stream_error(error: &Value) -> ProviderError {
+    let status = match error["type"].as_str() {
+        Some("overloaded_error") => 529,
+        Some("rate_limit_error") => 429,
+        Some("api_error") => 500,
+        _ => 400,
+    };
+    ProviderError::Status {
+        status,
+        body: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{AnthropicClient, ChatClient};
+    use crate::config::AnthropicConfig;
+    use crate::failover::Failure;
+    use crate::mock_server::{MockResponse, MockServer};
+    use tokio::sync::mpsc;
+
+    fn request(body: Value) -> CreateChatCompletionRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    fn client(server: &MockServer) -> AnthropicClient {
+        AnthropicClient::new(&AnthropicConfig {
+            id: "anthropic".to_string(),
+            api_key: "test-key".to_string(),
+            api_url: server.url().to_string(),
+            max_tokens: 1024,
+            proxy: false,
+        })
+    }
+
+    fn chat_request() -> CreateChatCompletionRequest {
+        request(json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [
+                { "role": "system", "content": "You are a tutor." },
+                { "role": "user", "content": "What's the weather in Paris?" },
+            ],
+        }))
+    }
+
+    fn event(data: Value) -> (String, Value) {
+        (data["type"].as_str().unwrap().to_string(), data)
+    }
+
+    #[test]
+    fn test_request_translation() {
+        let (model, body) = to_anthropic_request(
+            &request(json!({
+                "model": "claude-sonnet-4-5",
+                "temperature": 0.2,
+                "stop": "END",
+                "messages": [
+                    { "role": "system", "content": "You are a tutor." },
+                    { "role": "developer", "content": "Be brief." },
+                    { "role": "user", "content": "Weather in Paris and Rome?" },
+                    { "role": "assistant", "content": "Checking.", "tool_calls": [
+                        { "id": "toolu_1", "type": "function",
+                          "function": { "name": "get_weather", "arguments": "{\"city\":\"Paris\"}" } },
+                        { "id": "toolu_2", "type": "function",
+                          "function": { "name": "get_weather", "arguments": "{\"city\":\"Rome\"}" } },
+                    ]},
+                    { "role": "tool", "tool_call_id": "toolu_1", "content": "sunny" },
+                    { "role": "tool", "tool_call_id": "toolu_2", "content": "rainy" },
+                ],
+                "tools": [{ "type": "function", "function": {
+                    "name": "get_weather",
+                    "description": "Current weather",
+                    "parameters": { "type": "object", "properties": { "city": { "type": "string" } } },
+                }}],
+                "tool_choice": { "type": "function", "function": { "name": "get_weather" } },
+            })),
+            1024,
+        )
+        .unwrap();
+
+        assert_eq!(model, "claude-sonnet-4-5");
+        assert_eq!(body["system"], "You are a tutor.\n\nBe brief.");
+        assert_eq!(body["max_tokens"], 1024);
+        assert_eq!(body["temperature"], 0.2);
+        assert_eq!(body["stop_sequences"], json!(["END"]));
+
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0]["role"], "user");
+        assert_eq!(messages[1]["role"], "assistant");
+        assert_eq!(
+            messages[1]["content"][0],
+            json!({ "type": "text", "text": "Checking." })
+        );
+        assert_eq!(messages[1]["content"][1]["type"], "tool_use");
+        assert_eq!(messages[1]["content"][1]["id"], "toolu_1");
+        assert_eq!(
+            messages[1]["content"][1]["input"],
+            json!({ "city": "Paris" })
+        );
+        // Both results share one user message
+        assert_eq!(messages[2]["role"], "user");
+        assert_eq!(
+            messages[2]["content"],
+            json!([
+                { "type": "tool_result", "tool_use_id": "toolu_1", "content": "sunny" },
+                { "type": "tool_result", "tool_use_id": "toolu_2", "content": "rainy" },
+            ])
+        );
+
+        assert_eq!(body["tools"][0]["name"], "get_weather");
+        assert_eq!(
+            body["tools"][0]["input_schema"]["properties"]["city"]["type"],
+            "string"
+        );
+        assert_eq!(
+            body["tool_choice"],
+            json!({ "type": "tool", "name": "get_weather" })
+        );
+    }
+
+    #[test]
+    fn test_request_max_tokens_override() {
+        let (_, body) = to_anthropic_request(
+            &request(json!({
+                "model": "claude-sonnet-4-5",
+                "max_tokens": 64,
+                "messages": [{ "role": "user", "content": "Hi" }],
+            })),
+            1024,
+        )
+        .unwrap();
+        assert_eq!(body["max_tokens"], 64);
+        assert!(body.get("system").is_none());
+    }
+
+    #[test]
+    fn test_response_translation() {
+        let response = from_anthropic_response(
+            &json!({
+                "id": "msg_1",
+                "type": "message",
+                "role": "assistant",
+                "model": "claude-sonnet-4-5-20250929",
+                "content": [
+                    { "type": "text", "text": "Let me check." },
+                    { "type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": { "city": "Paris" } },
+                ],
+                "stop_reason": "tool_use",
+                "usage": { "input_tokens": 12, "output_tokens": 5 },
+            }),
+            "claude-sonnet-4-5",
+        )
+        .unwrap();
+
+        let value = serde_json::to_value(&response).unwrap();
+        let choice = &value["choices"][0];
+        assert_eq!(choice["finish_reason"], "tool_calls");
+        assert_eq!(choice["message"]["content"], "Let me check.");
+        let call = &choice["message"]["tool_calls"][0];
+        assert_eq!(call["id"], "toolu_1");
+        assert_eq!(call["function"]["name"], "get_weather");
+        assert_eq!(call["function"]["arguments"], r#"{"city":"Paris"}"#);
+        assert_eq!(Usage::from_response(&response), Some(Usage::new(12, 5)));
+        assert_eq!(value["model"], "claude-sonnet-4-5-20250929");
+    }
+
+    #[tokio::test]
+    async fn test_complete_against_mock_server() {
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::json(
+            200,
+            json!({
+                "id": "msg_1",
+                "type": "message",
+                "content": [{ "type": "text", "text": "Sunny, 24°C." }],
+                "stop_reason": "end_turn",
+                "usage": { "input_tokens": 20, "output_tokens": 6 },
+            }),
+        ));
+
+        let response = client(&server).complete(chat_request()).await.unwrap();
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(value["choices"][0]["message"]["content"], "Sunny, 24°C.");
+        assert_eq!(value["choices"][0]["finish_reason"], "stop");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/messages");
+        assert_eq!(requests[0].headers["x-api-key"], "test-key");
+        assert_eq!(requests[0].headers["anthropic-version"], API_VERSION);
+        let body = requests[0].json();
+        assert_eq!(body["system"], "You are a tutor.");
+        assert_eq!(body["max_tokens"], 1024);
+        assert!(body.get("stream").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_streaming_against_mock_server() {
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::sse_events([
+            event(json!({ "type": "message_start", "message": {
+                "id": "msg_1", "type": "message", "role": "assistant", "content": [],
+                "usage": { "input_tokens": 25, "output_tokens": 1 },
+            }})),
+            event(json!({ "type": "content_block_start", "index": 0,
+                "content_block": { "type": "text", "text": "" } })),
+            event(json!({ "type": "ping" })),
+            event(json!({ "type": "content_block_delta", "index": 0,
+                "delta": { "type": "text_delta", "text": "Checking" } })),
+            event(json!({ "type": "content_block_delta", "index": 0,
+                "delta": { "type": "text_delta", "text": " now." } })),
+            event(json!({ "type": "content_block_stop", "index": 0 })),
+            event(json!({ "type": "content_block_start", "index": 1,
+                "content_block": { "type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": {} } })),
+            event(json!({ "type": "content_block_delta", "index": 1,
+                "delta": { "type": "input_json_delta", "partial_json": "{\"city\": " } })),
+            event(json!({ "type": "content_block_delta", "index": 1,
+                "delta": { "type": "input_json_delta", "partial_json": "\"Paris\"}" } })),
+            event(json!({ "type": "content_block_stop", "index": 1 })),
+            event(json!({ "type": "message_delta",
+                "delta": { "stop_reason": "tool_use" }, "usage": { "output_tokens": 9 } })),
+            event(json!({ "type": "message_stop" })),
+        ]));
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (text, tool_calls, usage) = client(&server)
+            .complete_streaming_with_cancellation(
+                chat_request(),
+                tx,
+                CancellationToken::new(),
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap();
+
+        let mut chunks = Vec::new();
+        while let Ok(chunk) = rx.try_recv() {
+            chunks.push(chunk);
+        }
+        assert_eq!(chunks, vec!["Checking", " now."]);
+        assert_eq!(text, "Checking now.");
+
+        let tool_calls = tool_calls.unwrap();
+        assert_eq!(tool_calls.len(), 1);
+        assert_eq!(tool_calls[0].id, "toolu_1");
+        assert_eq!(tool_calls[0].function.name, "get_weather");
+        assert_eq!(tool_calls[0].function.arguments, r#"{"city": "Paris"}"#);
+        assert_eq!(usage, Some(Usage::new(25, 9)));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/messages");
+        assert_eq!(requests[0].json()["stream"], true);
+    }
+
+    #[tokio::test]
+    async fn test_stream_error_event_is_retryable() {
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::sse_events([
+            event(
+                json!({ "type": "message_start", "message": { "usage": { "input_tokens": 5 } } }),
+            ),
+            event(json!({ "type": "error",
+                "error": { "type": "overloaded_error", "message": "Overloaded" } })),
+        ]));
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let err = client(&server)
+            .complete_streaming(chat_request(), tx)
+            .await
+            .unwrap_err();
+        assert_eq!(Failure::classify(&err), Some(Failure::Status(529)));
+    }
+
+    #[tokio::test]
+    async fn test_truncated_stream_is_an_error() {
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::sse_events([event(json!({
+            "type": "content_block_delta", "index": 0,
+            "delta": { "type": "text_delta", "text": "Check" },
+        }))]));
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let err = client(&server)
+            .complete_streaming(chat_request(), tx)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProviderError>(),
+            Some(ProviderError::StreamEnded)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_stream_cancellation() {
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::sse_events([]));
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let err = client(&server)
+            .complete_streaming_with_cancellation(chat_request(), tx, token, Duration::from_secs(5))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProviderError>(),
+            Some(ProviderError::Cancelled)
+        ));
+    }
+}
@@ -0,0 +1,822 @@
+//! Built-in tools that run inside the node
+//!
+//! These need no MCP server or network access. Enable them by name in the
+//! config; they share the local tool-call loop with MCP tools:
+//!
+//! ```toml
+//! enable_tools = true
+//! enable_local_mcp = true
+//! builtin_tools = ["calculator", "datetime", "anchor_lookup", "scratchpad"]
+//! ```
+//!
+//! `anchor_lookup` searches the `[A0]..[An]` sections of the configured
+//! `anchor_context` file; `scratchpad` keeps notes per session.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use eyre::{Result, eyre};
+use rmcp::model::{CallToolResult, Content};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::config::load_anchor_context;
+use crate::tool::{Tool, ToolSet};
+
+/// Names accepted in `builtin_tools`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltinTool {
+    Calculator,
+    Datetime,
+    AnchorLookup,
+    Scratchpad,
+}
+
+/// Add the configured built-in tools to a tool set.
+///
+/// `anchor_context` is the path of the anchor context file; `anchor_lookup`
+/// is skipped with a warning if it is missing or unreadable.
+pub fn register(tool_set: &mut ToolSet, tools: &[BuiltinTool], anchor_context: Option<&str>) {
+    for tool in tools {
+        match tool {
+            BuiltinTool::Calculator => tool_set.add_tool(CalculatorTool),
+            BuiltinTool::Datetime => tool_set.add_tool(DateTimeTool),
+            BuiltinTool::AnchorLookup => {
+                let Some(path) = anchor_context else {
+                    eprintln!("Warning: anchor_lookup needs anchor_context to be set");
+                    continue;
+                };
+                match load_anchor_context(path) {
+                    Ok(markdown) => tool_set.add_tool(AnchorLookupTool::new(&markdown)),
+                    Err(e) => {
+                        eprintln!("Warning: anchor_lookup disabled: {}", e);
+                        continue;
+                    }
+                }
+            }
+            BuiltinTool::Scratchpad => tool_set.add_tool(ScratchpadTool::default()),
+        }
+        eprintln!("  - Registered built-in tool: {:?}", tool);
+    }
+}
+
+fn text_result(text: impl Into<String>) -> CallToolResult {
+    CallToolResult::success(vec![Content::text(text.into())])
+}
+
+fn string_arg<'a>(args: &'a Value, name: &str) -> Option<&'a str> {
+    args.get(name)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+/// Arithmetic on `+ - * / % ^`, parentheses, `pi`, `e` and one-argument
+/// functions (`sqrt`, `abs`, `ln`, `log`, `exp`, `sin`, `cos`, `tan`,
+/// `floor`, `ceil`, `round`)
+pub struct CalculatorTool;
+
+#[async_trait::async_trait]
+impl Tool for CalculatorTool {
+    fn name(&self) -> String {
+        "calculator".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Evaluate an arithmetic expression, e.g. \"(3 + 4) * 2 ^ 10 / sqrt(2)\". \
+         Supports + - * / % ^, parentheses, pi, e and sqrt, abs, ln, log, exp, sin, cos, \
+         tan, floor, ceil, round."
+            .to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "expression": { "type": "string", "description": "Expression to evaluate" }
+            },
+            "required": ["expression"]
+        })
+    }
+
+    async fn call(&self, args: Value) -> Result<CallToolResult> {
+        let expression =
+            string_arg(&args, "expression").ok_or_else(|| eyre!("Missing 'expression'"))?;
+        let value = evaluate(expression)?;
+        Ok(text_result(format_number(value)))
+    }
+}
+
+/// Longest expression evaluated, in characters
+const MAX_EXPRESSION_CHARS: usize = 1000;
+/// Deepest nesting of parentheses, signs and exponents
+const MAX_EXPRESSION_DEPTH: usize = 64;
+
+/// Evaluate an arithmetic expression
+///
+/// The expression comes from the model, so its length and nesting are
+/// limited to keep the recursive parser off the end of the stack.
+pub fn evaluate(expression: &str) -> Result<f64> {
+    if expression.chars().count() > MAX_EXPRESSION_CHARS {
+        return Err(eyre!(
+            "Expression is longer than {} characters",
+            MAX_EXPRESSION_CHARS
+        ));
+    }
+    let tokens = tokenize(expression)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.expression()?;
+    if let Some(token) = parser.tokens.get(parser.pos) {
+        return Err(eyre!("Unexpected {:?} in expression", token));
+    }
+    if !value.is_finite() {
+        return Err(eyre!("Result is not a finite number"));
+    }
+    Ok(value)
+}
+
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        // Drop float noise such as 0.30000000000000004
+        let rounded: f64 = format!("{:.12}", value).parse().unwrap_or(value);
+        rounded.to_string()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    Open,
+    Close,
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '0'..='9' | '.' => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() || c == '.' || c == '_' {
+                        if c != '_' {
+                            number.push(c);
+                        }
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let value = number
+                    .parse()
+                    .map_err(|_| eyre!("Invalid number '{}'", number))?;
+                tokens.push(Token::Number(value));
+            }
+            c if c.is_ascii_alphabetic() => {
+                let mut ident = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_alphanumeric() {
+                        ident.push(c.to_ascii_lowercase());
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' => {
+                tokens.push(Token::Op(c));
+                chars.next();
+            }
+            '×' => {
+                tokens.push(Token::Op('*'));
+                chars.next();
+            }
+            '÷' => {
+                tokens.push(Token::Op('/'));
+                chars.next();
+            }
+            '(' => {
+                tokens.push(Token::Open);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                chars.next();
+            }
+            c => return Err(eyre!("Unexpected character '{}'", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Current `unary` recursion depth; every nesting passes through it
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    // expression := term (('+' | '-') term)*
+    fn expression(&mut self) -> Result<f64> {
+        let mut value = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.pos += 1;
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Ok(value)
+    }
+
+    // term := unary (('*' | '/' | '%') unary)*
+    fn term(&mut self) -> Result<f64> {
+        let mut value = self.unary()?;
+        while let Some(Token::Op(op @ ('*' | '/' | '%'))) = self.peek().cloned() {
+            self.pos += 1;
+            let rhs = self.unary()?;
+            if op != '*' && rhs == 0.0 {
+                return Err(eyre!("Division by zero"));
+            }
+            value = match op {
+                '*' => value * rhs,
+                '/' => value / rhs,
+                _ => value % rhs,
+            };
+        }
+        Ok(value)
+    }
+
+    // unary := ('+' | '-') unary | power
+    fn unary(&mut self) -> Result<f64> {
+        if self.depth >= MAX_EXPRESSION_DEPTH {
+            return Err(eyre!(
+                "Expression is nested deeper than {} levels",
+                MAX_EXPRESSION_DEPTH
+            ));
+        }
+        self.depth += 1;
+        let value = self.signed();
+        self.depth -= 1;
+        value
+    }
+
+    fn signed(&mut self) -> Result<f64> {
+        match self.peek() {
+            Some(Token::Op('-')) => {
+                self.pos += 1;
+                Ok(-self.unary()?)
+            }
+            Some(Token::Op('+')) => {
+                self.pos += 1;
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    // power := atom ('^' unary)?, right-associative
+    fn power(&mut self) -> Result<f64> {
+        let base = self.atom()?;
+        if let Some(Token::Op('^')) = self.peek() {
+            self.pos += 1;
+            let exponent = self.unary()?;
+            return Ok(base.powf(exponent));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<f64> {
+        match self.advance() {
+            Some(Token::Number(value)) => Ok(value),
+            Some(Token::Open) => {
+                let value = self.expression()?;
+                self.expect_close()?;
+                Ok(value)
+            }
+            Some(Token::Ident(name)) => match name.as_str() {
+                "pi" => Ok(std::f64::consts::PI),
+                "e" => Ok(std::f64::consts::E),
+                _ => {
+                    if self.advance() != Some(Token::Open) {
+                        return Err(eyre!("Unknown constant '{}'", name));
+                    }
+                    let arg = self.expression()?;
+                    self.expect_close()?;
+                    apply_function(&name, arg)
+                }
+            },
+            Some(token) => Err(eyre!("Unexpected {:?} in expression", token)),
+            None => Err(eyre!("Unexpected end of expression")),
+        }
+    }
+
+    fn expect_close(&mut self) -> Result<()> {
+        match self.advance() {
+            Some(Token::Close) => Ok(()),
+            _ => Err(eyre!("Missing ')'")),
+        }
+    }
+}
+
+fn apply_function(name: &str, arg: f64) -> Result<f64> {
+    Ok(match name {
+        "sqrt" => arg.sqrt(),
+        "abs" => arg.abs(),
+        "ln" => arg.ln(),
+        "log" => arg.log10(),
+        "exp" => arg.exp(),
+        "sin" => arg.sin(),
+        "cos" => arg.cos(),
+        "tan" => arg.tan(),
+        "floor" => arg.floor(),
+        "ceil" => arg.ceil(),
+        "round" => arg.round(),
+        _ => return Err(eyre!("Unknown function '{}'", name)),
+    })
+}
+
+/// Current date and time, in local time or a given UTC offset
+pub struct DateTimeTool;
+
+#[async_trait::async_trait]
+impl Tool for DateTimeTool {
+    fn name(&self) -> String {
+        "current_datetime".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Get the current date, time and weekday. Optionally pass a UTC offset such as \
+         \"+08:00\"; defaults to the local time zone."
+            .to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "utc_offset": { "type": "string", "description": "UTC offset, e.g. \"+08:00\" or \"Z\"" }
+            }
+        })
+    }
+
+    async fn call(&self, args: Value) -> Result<CallToolResult> {
+        let offset = match string_arg(&args, "utc_offset") {
+            Some(offset) => parse_offset(offset)?,
+            None => *Local::now().offset(),
+        };
+        Ok(text_result(describe_time(Utc::now(), offset).to_string()))
+    }
+}
+
+fn parse_offset(offset: &str) -> Result<FixedOffset> {
+    if offset.eq_ignore_ascii_case("z") || offset.eq_ignore_ascii_case("utc") {
+        return Ok(FixedOffset::east_opt(0).expect("zero offset is valid"));
+    }
+    let (sign, rest) = match offset.split_at_checked(1) {
+        Some(("+", rest)) => (1, rest),
+        Some(("-", rest)) => (-1, rest),
+        _ => return Err(eyre!("Invalid UTC offset '{}'", offset)),
+    };
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let hours: i32 = hours
+        .parse()
+        .map_err(|_| eyre!("Invalid UTC offset '{}'", offset))?;
+    let minutes: i32 = minutes
+        .parse()
+        .map_err(|_| eyre!("Invalid UTC offset '{}'", offset))?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+        .ok_or_else(|| eyre!("UTC offset out of range: '{}'", offset))
+}
+
+fn describe_time(now: DateTime<Utc>, offset: FixedOffset) -> Value {
+    let local = now.with_timezone(&offset);
+    json!({
+        "datetime": local.to_rfc3339(),
+        "date": local.format("%Y-%m-%d").to_string(),
+        "time": local.format("%H:%M:%S").to_string(),
+        "weekday": local.format("%A").to_string(),
+        "utc_offset": local.format("%:z").to_string(),
+        "unix": now.timestamp(),
+    })
+}
+
+/// One `[An]` section of the anchor context
+#[derive(Clone, Debug)]
+struct AnchorSection {
+    anchor: String,
+    title: String,
+    body: String,
+}
+
+/// Search over the `[A0]..[An]` sections of the anchor context
+pub struct AnchorLookupTool {
+    sections: Vec<AnchorSection>,
+}
+
+/// Longest section text returned, in characters
+const MAX_SECTION_CHARS: usize = 2000;
+/// Sections returned for a keyword search
+const MAX_MATCHES: usize = 3;
+
+impl AnchorLookupTool {
+    pub fn new(markdown: &str) -> Self {
+        Self {
+            sections: parse_sections(markdown),
+        }
+    }
+
+    /// Section by anchor; `A3.1` resolves to its parent `A3`
+    fn by_anchor(&self, anchor: &str) -> Option<&AnchorSection> {
+        let anchor = anchor
+            .trim_matches(|c| c == '[' || c == ']')
+            .to_ascii_uppercase();
+        let top = anchor.split('.').next().unwrap_or_default();
+        self.sections.iter().find(|s| s.anchor == top)
+    }
+
+    /// Sections ranked by how often the query terms occur
+    fn search(&self, query: &str) -> Vec<&AnchorSection> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let mut scored: Vec<(usize, &AnchorSection)> = self
+            .sections
+            .iter()
+            .map(|section| {
+                let text = format!("{}\n{}", section.title, section.body).to_lowercase();
+                let score = terms.iter().map(|t| text.matches(t.as_str()).count()).sum();
+                (score, section)
+            })
+            .filter(|(score, _)| *score > 0)
+            .collect();
+        // Stable sort keeps document order among equal scores
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored
+            .into_iter()
+            .take(MAX_MATCHES)
+            .map(|(_, s)| s)
+            .collect()
+    }
+
+    fn index(&self) -> String {
+        self.sections
+            .iter()
+            .map(|s| format!("[{}] {}", s.anchor, s.title))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn format_section(section: &AnchorSection) -> String {
+    let mut body: String = section.body.chars().take(MAX_SECTION_CHARS).collect();
+    if body.len() < section.body.len() {
+        body.push_str(" …");
+    }
+    format!("[{}] {}\n\n{}", section.anchor, section.title, body)
+}
+
+fn parse_sections(markdown: &str) -> Vec<AnchorSection> {
+    let mut sections: Vec<AnchorSection> = Vec::new();
+    for line in markdown.lines() {
+        if let Some((anchor, title)) = parse_anchor_line(line) {
+            sections.push(AnchorSection {
+                anchor,
+                title,
+                body: String::new(),
+            });
+        } else if let Some(section) = sections.last_mut() {
+            section.body.push_str(line);
+            section.body.push('\n');
+        }
+    }
+    for section in &mut sections {
+        section.body = section
+            .body
+            .trim()
+            .trim_end_matches("---")
+            .trim()
+            .to_string();
+    }
+    sections
+}
+
+/// `[A12] Title` -> `("A12", "Title")`
+fn parse_anchor_line(line: &str) -> Option<(String, String)> {
+    let rest = line.trim_start().strip_prefix("[A")?;
+    let (digits, title) = rest.split_once(']')?;
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((format!("A{}", digits), title.trim().to_string()))
+}
+
+#[async_trait::async_trait]
+impl Tool for AnchorLookupTool {
+    fn name(&self) -> String {
+        "anchor_lookup".to_string()
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "Look up the study material by anchor (e.g. \"A3\") or by keywords. \
+             Call without arguments to list the sections. Sections:\n{}",
+            self.index()
+        )
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "anchor": { "type": "string", "description": "Anchor ID such as \"A3\"" },
+                "query": { "type": "string", "description": "Keywords to search for" }
+            }
+        })
+    }
+
+    async fn call(&self, args: Value) -> Result<CallToolResult> {
+        if let Some(anchor) = string_arg(&args, "anchor") {
+            let section = self
+                .by_anchor(anchor)
+                .ok_or_else(|| eyre!("No section [{}]", anchor))?;
+            return Ok(text_result(format_section(section)));
+        }
+        if let Some(query) = string_arg(&args, "query") {
+            let matches = self.search(query);
+            if matches.is_empty() {
+                return Ok(text_result(format!("No section mentions \"{}\"", query)));
+            }
+            let text = matches
+                .into_iter()
+                .map(format_section)
+                .collect::<Vec<_>>()
+                .join("\n\n---\n\n");
+            return Ok(text_result(text));
+        }
+        Ok(text_result(self.index()))
+    }
+}
+
+/// Per-session notes the model can write and read back
+#[derive(Default)]
+pub struct ScratchpadTool {
+    notes: Mutex<HashMap<String, Vec<String>>>,
+}
+
+#[async_trait::async_trait]
+impl Tool for ScratchpadTool {
+    fn name(&self) -> String {
+        "scratchpad".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Keep notes for this conversation. action \"add\" stores a note, \"list\" \
+         returns all notes, \"clear\" deletes them."
+            .to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": { "type": "string", "enum": ["add", "list", "clear"] },
+                "note": { "type": "string", "description": "Note text for \"add\"" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn call(&self, args: Value) -> Result<CallToolResult> {
+        self.call_in_session("default", args).await
+    }
+
+    async fn call_in_session(&self, session_id: &str, args: Value) -> Result<CallToolResult> {
+        let mut notes = self.notes.lock().unwrap_or_else(|e| e.into_inner());
+        let action = string_arg(&args, "action").unwrap_or("list");
+        let text = match action {
+            "add" => {
+                let note = string_arg(&args, "note").ok_or_else(|| eyre!("Missing 'note'"))?;
+                let session_notes = notes.entry(session_id.to_string()).or_default();
+                session_notes.push(note.to_string());
+                format!("Saved note {}", session_notes.len())
+            }
+            "list" => match notes.get(session_id) {
+                Some(session_notes) if !session_notes.is_empty() => session_notes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, note)| format!("{}. {}", i + 1, note))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                _ => "No notes yet".to_string(),
+            },
+            "clear" => {
+                let count = notes.remove(session_id).map_or(0, |n| n.len());
+                format!("Cleared {} note(s)", count)
+            }
+            other => return Err(eyre!("Unknown scratchpad action '{}'", other)),
+        };
+        Ok(text_result(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn text(result: CallToolResult) -> String {
+        result
+            .content
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    const MARKDOWN: &str = "[A0] 导言：跨学科的必要性\n\n生命问题与物理学。\n\n---\n\n\
+        [A1] Statistical physics\n\n### A1.1 Why are atoms small?\n\nOrder from disorder, \
+        statistical laws.\n\n---\n\n[A2] Entropy and order\n\nLife feeds on negative entropy. \
+        Entropy again.\n";
+
+    #[test]
+    fn test_evaluate() {
+        assert_eq!(evaluate("1 + 2 * 3").unwrap(), 7.0);
+        assert_eq!(evaluate("(1 + 2) * 3").unwrap(), 9.0);
+        assert_eq!(evaluate("2 ^ 3 ^ 2").unwrap(), 512.0);
+        assert_eq!(evaluate("-2 ^ 2").unwrap(), -4.0);
+        assert_eq!(evaluate("10 % 4 - -1").unwrap(), 3.0);
+        assert_eq!(evaluate("sqrt(16) + abs(-2) × 3").unwrap(), 10.0);
+        assert_eq!(evaluate("1_000 ÷ 8").unwrap(), 125.0);
+        assert!((evaluate("2 * pi").unwrap() - std::f64::consts::TAU).abs() < 1e-12);
+        assert_eq!(format_number(evaluate("0.1 + 0.2").unwrap()), "0.3");
+        assert_eq!(format_number(evaluate("2 ^ 10").unwrap()), "1024");
+
+        for bad in [
+            "1 / 0", "2 +", "(1 + 2", "foo(1)", "x", "1 2", "3 $ 4", "sqrt(-1)",
+        ] {
+            assert!(evaluate(bad).is_err(), "{} should fail", bad);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_limits() {
+        // Nesting within the limit still works
+        let nested = format!("{}1{}", "(".repeat(20), ")".repeat(20));
+        assert_eq!(evaluate(&nested).unwrap(), 1.0);
+
+        let nested = format!("{}1{}", "(".repeat(300), ")".repeat(300));
+        let err = evaluate(&nested).unwrap_err();
+        assert!(err.to_string().contains("nested"), "{}", err);
+        let err = evaluate(&format!("{}1", "-".repeat(300))).unwrap_err();
+        assert!(err.to_string().contains("nested"), "{}", err);
+        let err = evaluate(&"2^".repeat(300)).unwrap_err();
+        assert!(err.to_string().contains("nested"), "{}", err);
+
+        let long = vec!["1"; 600].join("+");
+        let err = evaluate(&long).unwrap_err();
+        assert!(err.to_string().contains("longer"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_calculator_tool() {
+        let result = CalculatorTool
+            .call(json!({ "expression": "(3 + 4) * 6" }))
+            .await
+            .unwrap();
+        assert_eq!(text(result), "42");
+        assert!(CalculatorTool.call(json!({})).await.is_err());
+    }
+
+    #[test]
+    fn test_describe_time() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 20, 30, 0).unwrap();
+        let value = describe_time(now, parse_offset("+08:00").unwrap());
+        assert_eq!(value["date"], "2026-10-19");
+        assert_eq!(value["time"], "04:30:00");
+        assert_eq!(value["weekday"], "Monday");
+        assert_eq!(value["utc_offset"], "+08:00");
+        assert_eq!(value["unix"], now.timestamp());
+
+        let value = describe_time(now, parse_offset("Z").unwrap());
+        assert_eq!(value["datetime"], "2026-10-18T20:30:00+00:00");
+        assert_eq!(parse_offset("-05:30").unwrap().local_minus_utc(), -19_800);
+        assert!(parse_offset("8").is_err());
+        assert!(parse_offset("+99:00").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_datetime_tool() {
+        let result = DateTimeTool
+            .call(json!({ "utc_offset": "+00:00" }))
+            .await
+            .unwrap();
+        let value: Value = serde_json::from_str(&text(result)).unwrap();
+        assert_eq!(value["utc_offset"], "+00:00");
+        assert!(value["unix"].as_i64().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_parse_sections() {
+        let sections = parse_sections(MARKDOWN);
+        let anchors: Vec<_> = sections.iter().map(|s| s.anchor.as_str()).collect();
+        assert_eq!(anchors, vec!["A0", "A1", "A2"]);
+        assert_eq!(sections[0].title, "导言：跨学科的必要性");
+        assert_eq!(sections[0].body, "生命问题与物理学。");
+        // Sub-headings stay in their section
+        assert!(sections[1].body.starts_with("### A1.1"));
+    }
+
+    #[tokio::test]
+    async fn test_anchor_lookup_tool() {
+        let tool = AnchorLookupTool::new(MARKDOWN);
+        assert!(tool.description().contains("[A2] Entropy and order"));
+
+        let result = tool.call(json!({ "anchor": "a1.1" })).await.unwrap();
+        assert!(text(result).starts_with("[A1] Statistical physics"));
+        assert!(tool.call(json!({ "anchor": "A9" })).await.is_err());
+
+        // Ranked by term count: A2 scores 4, A1 scores 2 ("order" in "disorder" counts)
+        let result = text(
+            tool.call(json!({ "query": "Entropy order" }))
+                .await
+                .unwrap(),
+        );
+        let a2 = result.find("[A2]").unwrap();
+        let a1 = result.find("[A1]").unwrap();
+        assert!(a2 < a1);
+        assert!(!result.contains("[A0]"));
+
+        let result = tool.call(json!({ "query": "物理学" })).await.unwrap();
+        assert!(text(result).starts_with("[A0]"));
+
+        let result = tool.call(json!({ "query": "quantum" })).await.unwrap();
+        assert!(text(result).starts_with("No section"));
+
+        let result = tool.call(json!({})).await.unwrap();
+        assert_eq!(text(result).lines().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_scratchpad_is_per_session() {
+        let tool = ScratchpadTool::default();
+        let add = |note: &str| json!({ "action": "add", "note": note });
+
+        tool.call_in_session("pro", add("Opening: entropy"))
+            .await
+            .unwrap();
+        let result = tool.call_in_session("pro", add("Rebut A3")).await.unwrap();
+        assert_eq!(text(result), "Saved note 2");
+        tool.call_in_session("con", add("Ask for sources"))
+            .await
+            .unwrap();
+
+        let list = json!({ "action": "list" });
+        let result = tool.call_in_session("pro", list.clone()).await.unwrap();
+        assert_eq!(text(result), "1. Opening: entropy\n2. Rebut A3");
+        let result = tool.call_in_session("con", list.clone()).await.unwrap();
+        assert_eq!(text(result), "1. Ask for sources");
+
+        let result = tool
+            .call_in_session("pro", json!({ "action": "clear" }))
+            .await
+            .unwrap();
+        assert_eq!(text(result), "Cleared 2 note(s)");
+        let result = tool.call_in_session("pro", list).await.unwrap();
+        assert_eq!(text(result), "No notes yet");
+
+        assert!(
+            tool.call_in_session("pro", json!({ "action": "add" }))
+                .await
+                .is_err()
+        );
+        assert!(
+            tool.call_in_session("pro", json!({ "action": "drop" }))
+                .await
+                .is_err()
+        );
+    }
+}
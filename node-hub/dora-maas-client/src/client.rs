@@ -7,7 +7,13 @@ use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 use std::time::Duration;
 
-use crate::config::{GeminiConfig, OpenaiConfig, get_env_or_value};
+use crate::anthropic;
+use crate::config::{AnthropicConfig, GeminiConfig, OllamaConfig, OpenaiConfig, get_env_or_value};
+use crate::failover::ProviderError;
+use crate::gemini;
+use crate::ollama;
+use crate::structured::ResponseFormat;
+use crate::usage::Usage;
 
 /// Trait for chat completion clients supporting multiple providers.
 ///
@@ -41,12 +47,12 @@ pub trait ChatClient: Send + Sync {
     /// * `chunk_sender` - Channel to send text chunks as they arrive
     ///
     /// # Returns
-    /// * `Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)>` - The complete accumulated response text and any tool calls
+    /// * `Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)>` - The complete accumulated response text, any tool calls, and token usage if the provider reported it
     async fn complete_streaming(
         &self,
         request: CreateChatCompletionRequest,
         chunk_sender: mpsc::UnboundedSender<String>,
-    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)>;
+    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)>;
 
     /// Send a streaming chat completion request with cancellation support.
     async fn complete_streaming_with_cancellation(
@@ -55,9 +61,44 @@ pub trait ChatClient: Send + Sync {
         chunk_sender: mpsc::UnboundedSender<String>,
         cancellation_token: CancellationToken,
         timeout_duration: Duration,
-    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)>;
+    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)>;
+
+    /// Send a non-streaming request whose reply should match `format`.
+    ///
+    /// Providers without native JSON-schema output ignore `format`; callers
+    /// validate the reply either way (see [`crate::structured::complete`]).
+    async fn complete_structured(
+        &self,
+        request: CreateChatCompletionRequest,
+        format: &ResponseFormat,
+    ) -> Result<CreateChatCompletionResponse> {
+        let _ = format;
+        self.complete(request).await
+    }
+
+    /// Whether [`complete_structured`](Self::complete_structured) constrains
+    /// the output to the schema itself
+    fn supports_response_format(&self) -> bool {
+        false
+    }
+}
+
+/// HTTP client shared by the provider clients; `proxy` honours the system
+/// proxy settings
+fn http_client(proxy: bool, timeout: Duration) -> HttpClient {
+    let builder = HttpClient::builder()
+        .timeout(timeout)
+        .connect_timeout(Duration::from_secs(10))
+        .pool_idle_timeout(Duration::from_secs(30));
+    let builder = if proxy { builder } else { builder.no_proxy() };
+    builder.build().unwrap_or_else(|_| HttpClient::new())
 }
 
+/// Google Gemini client using the native `generateContent` API.
+///
+/// Requests and responses are translated to and from the OpenAI format by
+/// the [`gemini`] module; `api_url` is the API base, e.g.
+/// `https://generativelanguage.googleapis.com/v1beta`.
 #[derive(Debug)]
 pub struct GeminiClient {
     id: String,
@@ -72,22 +113,7 @@ impl GeminiClient {
     }
 
     pub fn new_with_timeout(config: &GeminiConfig, timeout: Duration) -> Self {
-        let client = if config.proxy {
-            HttpClient::builder()
-                .timeout(timeout)
-                .connect_timeout(Duration::from_secs(10))
-                .pool_idle_timeout(Duration::from_secs(30))
-                .build()
-                .unwrap_or_else(|_| HttpClient::new())
-        } else {
-            HttpClient::builder()
-                .timeout(timeout)
-                .connect_timeout(Duration::from_secs(10))
-                .pool_idle_timeout(Duration::from_secs(30))
-                .no_proxy()
-                .build()
-                .unwrap_or_else(|_| HttpClient::new())
-        };
+        let client = http_client(config.proxy, timeout);
 
         Self {
             id: config.id.clone(),
@@ -104,12 +130,16 @@ impl ChatClient for GeminiClient {
         &self,
         request: CreateChatCompletionRequest,
     ) -> Result<CreateChatCompletionResponse> {
+        let (model, body) = gemini::to_gemini_request(&request)?;
+        let url = gemini::endpoint(&self.api_url, &model, "generateContent");
+        eprintln!("[{}] Sending request to: {}", self.id, url);
+
         let response = self
             .client
-            .post(&self.api_url)
-            .header("X-goog-api-key", self.api_key.clone())
+            .post(&url)
+            .header("x-goog-api-key", self.api_key.clone())
             .header("Content-Type", "application/json")
-            .json(&request)
+            .json(&body)
             .send()
             .await?;
 
@@ -119,54 +149,351 @@ impl ChatClient for GeminiClient {
         if !status.is_success() {
             let error_text = response.text().await?;
             eprintln!("[{}] Error response: {}", self.id, error_text);
-            return Err(eyre!("API Error: {}", error_text));
+            return Err(ProviderError::Status {
+                status: status.as_u16(),
+                body: error_text,
+            }
+            .into());
         }
         let text_data = response.text().await?;
-        // Debug log the response for troubleshooting
-        if text_data.is_empty() {
-            eprintln!("[{}] Empty response received!", self.id);
-        } else if text_data.len() < 1000 {
+        if text_data.len() < 1000 {
             eprintln!("[{}] Response: {}", self.id, text_data);
-        } else {
-            eprintln!(
-                "[{}] Response (first 1000 chars): {}",
-                self.id,
-                &text_data[..1000]
-            );
         }
-        let completion: CreateChatCompletionResponse =
-            serde_json::from_str(&text_data).map_err(|e| {
-                eyre!(
-                    "Failed to parse API response: {}. Response: {}",
-                    e,
-                    text_data
-                )
-            })?;
-        Ok(completion)
+        let value: serde_json::Value = serde_json::from_str(&text_data).map_err(|e| {
+            eyre!(
+                "Failed to parse API response: {}. Response: {}",
+                e,
+                text_data
+            )
+        })?;
+        gemini::from_gemini_response(&value, &model)
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)> {
+        let (model, body) = gemini::to_gemini_request(&request)?;
+        let url = gemini::endpoint(&self.api_url, &model, "streamGenerateContent");
+
+        gemini::stream_generate_content(
+            &self.client,
+            url,
+            self.api_key.clone(),
+            body,
+            None,
+            None,
+            |chunk| {
+                chunk_sender
+                    .send(chunk)
+                    .map_err(|e| eyre!("Failed to send chunk: {}", e))
+            },
+        )
+        .await
+    }
+
+    async fn complete_streaming_with_cancellation(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: CancellationToken,
+        timeout_duration: Duration,
+    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)> {
+        let (model, body) = gemini::to_gemini_request(&request)?;
+        let url = gemini::endpoint(&self.api_url, &model, "streamGenerateContent");
+
+        gemini::stream_generate_content(
+            &self.client,
+            url,
+            self.api_key.clone(),
+            body,
+            Some(cancellation_token),
+            Some(timeout_duration),
+            |chunk| {
+                chunk_sender
+                    .send(chunk)
+                    .map_err(|e| eyre!("Failed to send chunk: {}", e))
+            },
+        )
+        .await
+    }
+}
+
+/// Anthropic client using the native Messages API.
+///
+/// Requests and responses are translated to and from the OpenAI format by
+/// the [`anthropic`] module; `api_url` is the API base, e.g.
+/// `https://api.anthropic.com/v1`.
+#[derive(Debug)]
+pub struct AnthropicClient {
+    id: String,
+    api_key: String,
+    api_url: String,
+    max_tokens: u32,
+    client: HttpClient,
+}
+
+impl AnthropicClient {
+    pub fn new(config: &AnthropicConfig) -> Self {
+        Self::new_with_timeout(config, Duration::from_secs(30))
+    }
+
+    pub fn new_with_timeout(config: &AnthropicConfig, timeout: Duration) -> Self {
+        Self {
+            id: config.id.clone(),
+            api_key: get_env_or_value(&config.api_key),
+            api_url: get_env_or_value(&config.api_url),
+            max_tokens: config.max_tokens,
+            client: http_client(config.proxy, timeout),
+        }
+    }
+
+    async fn stream(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: Option<CancellationToken>,
+        timeout: Option<Duration>,
+    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)> {
+        let (_, body) = anthropic::to_anthropic_request(&request, self.max_tokens)?;
+
+        anthropic::stream_messages(
+            &self.client,
+            anthropic::endpoint(&self.api_url),
+            self.api_key.clone(),
+            body,
+            cancellation_token,
+            timeout,
+            |chunk| {
+                chunk_sender
+                    .send(chunk)
+                    .map_err(|e| eyre!("Failed to send chunk: {}", e))
+            },
+        )
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatClient for AnthropicClient {
+    async fn complete(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse> {
+        let (model, body) = anthropic::to_anthropic_request(&request, self.max_tokens)?;
+        let url = anthropic::endpoint(&self.api_url);
+        eprintln!("[{}] Sending request to: {}", self.id, url);
+
+        let response = self
+            .client
+            .post(&url)
+            .header("x-api-key", self.api_key.clone())
+            .header("anthropic-version", anthropic::API_VERSION)
+            .header("Content-Type", "application/json")
+            .json(&body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        eprintln!("[{}] Got response with status: {}", self.id, status);
+
+        if !status.is_success() {
+            let error_text = response.text().await?;
+            eprintln!("[{}] Error response: {}", self.id, error_text);
+            return Err(ProviderError::Status {
+                status: status.as_u16(),
+                body: error_text,
+            }
+            .into());
+        }
+        let text_data = response.text().await?;
+        if text_data.len() < 1000 {
+            eprintln!("[{}] Response: {}", self.id, text_data);
+        }
+        let value: serde_json::Value = serde_json::from_str(&text_data).map_err(|e| {
+            eyre!(
+                "Failed to parse API response: {}. Response: {}",
+                e,
+                text_data
+            )
+        })?;
+        anthropic::from_anthropic_response(&value, &model)
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)> {
+        self.stream(request, chunk_sender, None, None).await
+    }
+
+    async fn complete_streaming_with_cancellation(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: CancellationToken,
+        timeout_duration: Duration,
+    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)> {
+        self.stream(
+            request,
+            chunk_sender,
+            Some(cancellation_token),
+            Some(timeout_duration),
+        )
+        .await
+    }
+}
+
+/// Ollama client using the native `/api/chat` API, for fully local runs.
+///
+/// Requests and responses are translated to and from the OpenAI format by
+/// the [`ollama`] module; `api_url` is the server root, e.g.
+/// `http://localhost:11434`. No API key is sent.
+#[derive(Debug)]
+pub struct OllamaClient {
+    id: String,
+    api_url: String,
+    keep_alive: Option<String>,
+    client: HttpClient,
+}
+
+impl OllamaClient {
+    pub fn new(config: &OllamaConfig) -> Self {
+        // Local models can take minutes to load and generate
+        Self::new_with_timeout(config, Duration::from_secs(300))
+    }
+
+    pub fn new_with_timeout(config: &OllamaConfig, timeout: Duration) -> Self {
+        Self {
+            id: config.id.clone(),
+            api_url: get_env_or_value(&config.api_url),
+            keep_alive: config.keep_alive.clone(),
+            client: http_client(config.proxy, timeout),
+        }
+    }
+
+    fn request_body(
+        &self,
+        request: &CreateChatCompletionRequest,
+        stream: bool,
+    ) -> Result<(String, serde_json::Value)> {
+        let (model, mut body) = ollama::to_ollama_request(request, stream)?;
+        if let Some(keep_alive) = &self.keep_alive {
+            body["keep_alive"] = serde_json::json!(keep_alive);
+        }
+        Ok((model, body))
+    }
+
+    /// Post a non-streaming `/api/chat` body
+    async fn post(
+        &self,
+        model: &str,
+        body: serde_json::Value,
+    ) -> Result<CreateChatCompletionResponse> {
+        let url = ollama::endpoint(&self.api_url);
+        eprintln!("[{}] Sending request to: {}", self.id, url);
+
+        let response = self.client.post(&url).json(&body).send().await?;
+
+        let status = response.status();
+        eprintln!("[{}] Got response with status: {}", self.id, status);
+
+        if !status.is_success() {
+            let error_text = response.text().await?;
+            eprintln!("[{}] Error response: {}", self.id, error_text);
+            return Err(ProviderError::Status {
+                status: status.as_u16(),
+                body: error_text,
+            }
+            .into());
+        }
+        let text_data = response.text().await?;
+        if text_data.len() < 1000 {
+            eprintln!("[{}] Response: {}", self.id, text_data);
+        }
+        let value: serde_json::Value = serde_json::from_str(&text_data).map_err(|e| {
+            eyre!(
+                "Failed to parse API response: {}. Response: {}",
+                e,
+                text_data
+            )
+        })?;
+        ollama::from_ollama_response(&value, model)
+    }
+
+    async fn stream(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: Option<CancellationToken>,
+        timeout: Option<Duration>,
+    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)> {
+        let (_, body) = self.request_body(&request, true)?;
+
+        ollama::stream_chat(
+            &self.client,
+            ollama::endpoint(&self.api_url),
+            body,
+            cancellation_token,
+            timeout,
+            |chunk| {
+                chunk_sender
+                    .send(chunk)
+                    .map_err(|e| eyre!("Failed to send chunk: {}", e))
+            },
+        )
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatClient for OllamaClient {
+    async fn complete(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse> {
+        let (model, body) = self.request_body(&request, false)?;
+        self.post(&model, body).await
     }
 
     async fn complete_streaming(
         &self,
-        _request: CreateChatCompletionRequest,
-        _chunk_sender: mpsc::UnboundedSender<String>,
-    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)> {
-        Err(eyre!(
-            "Streaming not implemented for provider '{}'",
-            self.id
-        ))
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)> {
+        self.stream(request, chunk_sender, None, None).await
     }
 
     async fn complete_streaming_with_cancellation(
         &self,
-        _request: CreateChatCompletionRequest,
-        _chunk_sender: mpsc::UnboundedSender<String>,
-        _cancellation_token: CancellationToken,
-        _timeout_duration: Duration,
-    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)> {
-        Err(eyre!(
-            "Streaming with cancellation not implemented for provider '{}'",
-            self.id
-        ))
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: CancellationToken,
+        timeout_duration: Duration,
+    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)> {
+        self.stream(
+            request,
+            chunk_sender,
+            Some(cancellation_token),
+            Some(timeout_duration),
+        )
+        .await
+    }
+
+    async fn complete_structured(
+        &self,
+        request: CreateChatCompletionRequest,
+        format: &ResponseFormat,
+    ) -> Result<CreateChatCompletionResponse> {
+        let (model, mut body) = self.request_body(&request, false)?;
+        body["format"] = format.schema.clone();
+        self.post(&model, body).await
+    }
+
+    fn supports_response_format(&self) -> bool {
+        true
     }
 }
 
@@ -179,6 +506,7 @@ pub struct OpenaiClient {
     id: String,
     api_key: String,
     api_url: String,
+    json_schema: bool,
     client: HttpClient,
 }
 
@@ -188,50 +516,30 @@ impl OpenaiClient {
     }
 
     pub fn new_with_timeout(config: &OpenaiConfig, timeout: Duration) -> Self {
-        let client = if config.proxy {
-            HttpClient::builder()
-                .timeout(timeout)
-                .connect_timeout(Duration::from_secs(10))
-                .pool_idle_timeout(Duration::from_secs(30))
-                .build()
-                .unwrap_or_else(|_| HttpClient::new())
-        } else {
-            HttpClient::builder()
-                .timeout(timeout)
-                .connect_timeout(Duration::from_secs(10))
-                .pool_idle_timeout(Duration::from_secs(30))
-                .no_proxy()
-                .build()
-                .unwrap_or_else(|_| HttpClient::new())
-        };
+        let client = http_client(config.proxy, timeout);
 
         Self {
             id: config.id.clone(),
             api_key: get_env_or_value(&config.api_key),
             api_url: get_env_or_value(&config.api_url),
+            json_schema: config.json_schema,
             client,
         }
     }
-}
 
-#[async_trait::async_trait]
-impl ChatClient for OpenaiClient {
-    async fn complete(
-        &self,
-        request: CreateChatCompletionRequest,
-    ) -> Result<CreateChatCompletionResponse> {
+    /// Post a non-streaming `/chat/completions` body
+    async fn post(&self, body: serde_json::Value) -> Result<CreateChatCompletionResponse> {
         eprintln!(
             "[{}] Sending request to: {}/chat/completions",
             self.id, self.api_url
         );
-        eprintln!("[{}] Request model: {:?}", self.id, request.model);
 
         let response = self
             .client
             .post(format!("{}/chat/completions", self.api_url))
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
-            .json(&request)
+            .json(&body)
             .send()
             .await?;
 
@@ -241,7 +549,11 @@ impl ChatClient for OpenaiClient {
         if !status.is_success() {
             let error_text = response.text().await?;
             eprintln!("[{}] Error: {}", self.id, error_text);
-            return Err(eyre!("API Error: {}", error_text));
+            return Err(ProviderError::Status {
+                status: status.as_u16(),
+                body: error_text,
+            }
+            .into());
         }
 
         let text_data = response.text().await?;
@@ -258,25 +570,38 @@ impl ChatClient for OpenaiClient {
             })?;
         Ok(completion)
     }
+}
+
+#[async_trait::async_trait]
+impl ChatClient for OpenaiClient {
+    async fn complete(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse> {
+        eprintln!("[{}] Request model: {:?}", self.id, request.model);
+        self.post(serde_json::to_value(&request)?).await
+    }
 
     async fn complete_streaming(
         &self,
         mut request: CreateChatCompletionRequest,
         chunk_sender: mpsc::UnboundedSender<String>,
-    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)> {
+    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)> {
 
         // Force streaming mode
         request.stream = Some(true);
 
         // Convert request to JSON value to modify it
-        let request_json = serde_json::to_value(&request)?;
+        let mut request_json = serde_json::to_value(&request)?;
+        // Ask for token usage in the final chunk
+        request_json["stream_options"] = serde_json::json!({ "include_usage": true });
 
         let url = format!("{}/chat/completions", self.api_url);
 
         // Use the streaming module
         use crate::streaming::stream_completion;
 
-        let (accumulated, tool_calls) = stream_completion(
+        let (accumulated, tool_calls, usage) = stream_completion(
             &self.client,
             url,
             self.api_key.clone(),
@@ -289,8 +614,8 @@ impl ChatClient for OpenaiClient {
         )
         .await?;
 
-        // Return both text and tool calls
-        Ok((accumulated, tool_calls))
+        // Return text, tool calls and usage
+        Ok((accumulated, tool_calls, usage))
     }
 
     async fn complete_streaming_with_cancellation(
@@ -299,20 +624,22 @@ impl ChatClient for OpenaiClient {
         chunk_sender: mpsc::UnboundedSender<String>,
         cancellation_token: CancellationToken,
         timeout_duration: Duration,
-    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)> {
+    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)> {
 
         // Force streaming mode
         request.stream = Some(true);
 
         // Convert request to JSON value to modify it
-        let request_json = serde_json::to_value(&request)?;
+        let mut request_json = serde_json::to_value(&request)?;
+        // Ask for token usage in the final chunk
+        request_json["stream_options"] = serde_json::json!({ "include_usage": true });
 
         let url = format!("{}/chat/completions", self.api_url);
 
         // Use the streaming module with cancellation support
         use crate::streaming::stream_completion_with_cancellation;
 
-        let (accumulated, tool_calls) = stream_completion_with_cancellation(
+        let (accumulated, tool_calls, usage) = stream_completion_with_cancellation(
             &self.client,
             url,
             self.api_key.clone(),
@@ -327,7 +654,23 @@ impl ChatClient for OpenaiClient {
         )
         .await?;
 
-        // Return both text and tool calls
-        Ok((accumulated, tool_calls))
+        // Return text, tool calls and usage
+        Ok((accumulated, tool_calls, usage))
+    }
+
+    async fn complete_structured(
+        &self,
+        request: CreateChatCompletionRequest,
+        format: &ResponseFormat,
+    ) -> Result<CreateChatCompletionResponse> {
+        let mut request_json = serde_json::to_value(&request)?;
+        if self.json_schema {
+            request_json["response_format"] = format.openai_value();
+        }
+        self.post(request_json).await
+    }
+
+    fn supports_response_format(&self) -> bool {
+        self.json_schema
     }
 }
@@ -1,5 +1,5 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
 
@@ -10,8 +10,14 @@ use figment::{
 use rmcp::{RoleClient, ServiceExt, service::RunningService, transport::ConfigureCommandExt};
 use serde::Deserialize;
 
-use crate::client::{ChatClient, GeminiClient, OpenaiClient};
+use crate::builtin_tools::{self, BuiltinTool};
+use crate::client::{AnthropicClient, ChatClient, GeminiClient, OllamaClient, OpenaiClient};
+use crate::failover::{FailoverClient, RetryPolicy, Route};
+use crate::fixture::{RecordingClient, ReplayClient, ReplayFallback};
+use crate::history::{HistoryLimits, SummarizerConfig};
+use crate::structured::ResponseFormatConfig;
 use crate::tool::{Tool, ToolSet, get_mcp_tools};
+use crate::usage::Pricing;
 
 /// Main configuration structure for the MaaS client.
 ///
@@ -32,6 +38,8 @@ pub struct Config {
     #[serde(default)]
     pub enable_local_mcp: bool, // Enable local MCP host (false = pass through to client)
     pub mcp: Option<McpConfig>, // MCP server configurations
+    #[serde(default)]
+    pub builtin_tools: Vec<BuiltinTool>, // Built-in tools run alongside MCP tools
     // HTTP request cancellation settings
     #[serde(default = "default_request_timeout")]
     pub request_timeout_secs: u64,
@@ -41,6 +49,20 @@ pub struct Config {
     pub enable_cancellation: bool,
     // Anchor context settings
     pub anchor_context: Option<String>, // Path to anchor context markdown file
+    // Retry/failover policy for provider requests (per-model `retry` overrides it)
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    // Rolling summary of turns trimmed from the history
+    pub summarizer: Option<SummarizerConfig>,
+    // Directory for persisted sessions (disabled if unset)
+    pub session_dir: Option<String>,
+    // Reload automatically when the config file changes
+    #[serde(default)]
+    pub watch_config: bool,
+    #[serde(default = "default_watch_interval")]
+    pub watch_interval_secs: u64,
+    // JSON-schema constrained replies, sent on the `structured` output
+    pub response_format: Option<ResponseFormatConfig>,
 }
 
 fn default_log_level() -> String {
@@ -53,13 +75,19 @@ fn default_stream_timeout() -> u64 { 120 }
 
 fn default_enable_cancellation() -> bool { true }
 
+fn default_watch_interval() -> u64 { 2 }
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum ProviderConfig {
     Openai(OpenaiConfig),
     Gemini(GeminiConfig),
+    Anthropic(AnthropicConfig),
+    Ollama(OllamaConfig),
     Alicloud(AlicloudConfig),
     Deepseek(DeepseekConfig),
+    Record(RecordConfig),
+    Replay(ReplayConfig),
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -69,6 +97,14 @@ pub struct OpenaiConfig {
     pub api_url: String,
     #[serde(default)]
     pub proxy: bool,
+    /// Whether the endpoint accepts `response_format: json_schema`; turn off
+    /// for OpenAI-compatible servers that don't
+    #[serde(default = "default_json_schema")]
+    pub json_schema: bool,
+}
+
+fn default_json_schema() -> bool {
+    true
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -80,6 +116,43 @@ pub struct GeminiConfig {
     pub proxy: bool,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct AnthropicConfig {
+    pub id: String,
+    pub api_key: String,
+    #[serde(default = "default_anthropic_url")]
+    pub api_url: String,
+    /// `max_tokens` for requests that set no limit (Anthropic requires one)
+    #[serde(default = "default_anthropic_max_tokens")]
+    pub max_tokens: u32,
+    #[serde(default)]
+    pub proxy: bool,
+}
+
+fn default_anthropic_url() -> String {
+    "https://api.anthropic.com/v1".to_string()
+}
+
+fn default_anthropic_max_tokens() -> u32 {
+    4096
+}
+
+/// Local Ollama server
+#[derive(Clone, Debug, Deserialize)]
+pub struct OllamaConfig {
+    pub id: String,
+    #[serde(default = "default_ollama_url")]
+    pub api_url: String,
+    /// How long Ollama keeps the model loaded after a request, e.g. `"10m"`
+    pub keep_alive: Option<String>,
+    #[serde(default)]
+    pub proxy: bool,
+}
+
+fn default_ollama_url() -> String {
+    "http://localhost:11434".to_string()
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct AlicloudConfig {
     pub id: String,
@@ -103,16 +176,79 @@ fn default_deepseek_url() -> String {
     "https://api.deepseek.com/v1".to_string()
 }
 
+/// Records the exchanges of another provider as fixtures
+#[derive(Clone, Debug, Deserialize)]
+pub struct RecordConfig {
+    pub id: String,
+    /// ID of the provider to record
+    pub provider: String,
+    pub fixture_dir: String,
+}
+
+/// Serves recorded fixtures instead of calling a provider
+#[derive(Clone, Debug, Deserialize)]
+pub struct ReplayConfig {
+    pub id: String,
+    pub fixture_dir: String,
+    /// What to do when no fixture matches a request
+    #[serde(default)]
+    pub fallback: ReplayFallback,
+    /// Pace streamed chunks by their recorded delays
+    #[serde(default)]
+    pub realtime: bool,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct ModelConfig {
     pub id: String,
-    pub route: ModelRoute,
+    /// Routes in failover order; a single route or a list
+    #[serde(deserialize_with = "one_or_many")]
+    pub route: Vec<ModelRoute>,
+    pub retry: Option<RetryPolicy>,
+    /// Estimated token budget for the conversation history sent to this model
+    pub context_budget: Option<usize>,
+    /// Token prices, for cost accounting on the `metrics` output
+    pub pricing: Option<Pricing>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct ModelRoute {
     pub provider: String,
     pub model: Option<String>,
+    /// Token prices of this route, if they differ from the model's
+    pub pricing: Option<Pricing>,
+}
+
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<ModelRoute>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(ModelRoute),
+        Many(Vec<ModelRoute>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(route) => vec![route],
+        OneOrMany::Many(routes) => routes,
+    })
+}
+
+impl ProviderConfig {
+    pub fn id(&self) -> &str {
+        match self {
+            ProviderConfig::Openai(c) => &c.id,
+            ProviderConfig::Gemini(c) => &c.id,
+            ProviderConfig::Anthropic(c) => &c.id,
+            ProviderConfig::Ollama(c) => &c.id,
+            ProviderConfig::Alicloud(c) => &c.id,
+            ProviderConfig::Deepseek(c) => &c.id,
+            ProviderConfig::Record(c) => &c.id,
+            ProviderConfig::Replay(c) => &c.id,
+        }
+    }
 }
 
 impl Config {
@@ -121,15 +257,29 @@ impl Config {
     /// Supports TOML, YAML, and JSON formats based on file extension.
     /// Falls back to `maas_config.toml` if MAAS_CONFIG_PATH is not set.
     pub fn load() -> eyre::Result<Self> {
-        let config_file =
-            std::env::var("MAAS_CONFIG_PATH").unwrap_or_else(|_| "maas_config.toml".to_string());
-        let config_path = PathBuf::from(config_file);
+        let config_path = Self::path();
 
         if !config_path.exists() {
             eprintln!("Config file not found at: {}", config_path.display());
             std::process::exit(1);
         }
 
+        Self::load_from(&config_path)
+    }
+
+    /// Path of the configuration file (MAAS_CONFIG_PATH or `maas_config.toml`).
+    pub fn path() -> PathBuf {
+        let config_file =
+            std::env::var("MAAS_CONFIG_PATH").unwrap_or_else(|_| "maas_config.toml".to_string());
+        PathBuf::from(config_file)
+    }
+
+    /// Load configuration from `config_path`, with environment overrides.
+    pub fn load_from(config_path: &Path) -> eyre::Result<Self> {
+        if !config_path.exists() {
+            return Err(eyre::eyre!("Config file not found at: {}", config_path.display()));
+        }
+
         let figment = match config_path.extension().and_then(|s| s.to_str()) {
             Some("yaml") | Some("yml") => Figment::new().merge(Yaml::file(config_path)),
             Some("json") => Figment::new().merge(Json::file(config_path)),
@@ -145,6 +295,69 @@ impl Config {
         Ok(config)
     }
 
+    /// Check that the configuration can serve requests.
+    ///
+    /// Startup tolerates these problems (they surface as warnings or request
+    /// errors); a reload rejects them so a bad edit can't take down a running
+    /// node. All problems are reported in one error.
+    pub fn validate(&self) -> eyre::Result<()> {
+        let mut problems = Vec::new();
+
+        let mut provider_ids = HashSet::new();
+        for provider in &self.providers {
+            if !provider_ids.insert(provider.id()) {
+                problems.push(format!("duplicate provider id '{}'", provider.id()));
+            }
+        }
+        if self.providers.is_empty() {
+            problems.push("no providers configured".to_string());
+        }
+        for provider in &self.providers {
+            if let ProviderConfig::Record(record) = provider {
+                if !provider_ids.contains(record.provider.as_str()) {
+                    problems.push(format!(
+                        "record provider '{}' wraps unknown provider '{}'",
+                        record.id, record.provider
+                    ));
+                }
+            }
+        }
+
+        if !self.models.iter().any(|m| m.id == self.default_model) {
+            problems.push(format!("default_model '{}' is not in models", self.default_model));
+        }
+        for model in &self.models {
+            for (provider_id, _) in self.route_chain(&model.id) {
+                if !provider_ids.contains(provider_id.as_str()) {
+                    problems.push(format!(
+                        "model '{}' routes to unknown provider '{}'",
+                        model.id, provider_id
+                    ));
+                }
+            }
+        }
+        if let Some(summarizer) = &self.summarizer {
+            if !self.models.iter().any(|m| m.id == summarizer.model) {
+                problems.push(format!(
+                    "summarizer model '{}' is not in models",
+                    summarizer.model
+                ));
+            }
+        }
+
+        if let Some(format) = &self.response_format {
+            if let Err(e) = format.load() {
+                problems.push(e.to_string());
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre::eyre!("Invalid configuration: {}", problems.join("; ")))
+        }
+    }
+
     /// Create client instances for all configured providers.
     ///
     /// Returns a map from provider ID to client implementation.
@@ -155,6 +368,8 @@ impl Config {
             let client: Arc<dyn ChatClient> = match provider {
                 ProviderConfig::Openai(config) => Arc::new(OpenaiClient::new(config)),
                 ProviderConfig::Gemini(config) => Arc::new(GeminiClient::new(config)),
+                ProviderConfig::Anthropic(config) => Arc::new(AnthropicClient::new(config)),
+                ProviderConfig::Ollama(config) => Arc::new(OllamaClient::new(config)),
                 ProviderConfig::Alicloud(config) => {
                     // Alicloud uses OpenAI-compatible API, so we can reuse OpenaiClient
                     Arc::new(OpenaiClient::new(&OpenaiConfig {
@@ -162,6 +377,7 @@ impl Config {
                         api_key: config.api_key.clone(),
                         api_url: config.api_url.clone(),
                         proxy: config.proxy,
+                        json_schema: false,
                     }))
                 }
                 ProviderConfig::Deepseek(config) => {
@@ -171,18 +387,44 @@ impl Config {
                         api_key: config.api_key.clone(),
                         api_url: config.api_url.clone(),
                         proxy: config.proxy,
+                        json_schema: false,
                     }))
                 }
+                ProviderConfig::Replay(config) => {
+                    let replay =
+                        ReplayClient::open(&config.fixture_dir, config.fallback, config.realtime);
+                    match replay {
+                        Ok(client) => Arc::new(client),
+                        Err(e) => {
+                            eprintln!("Warning: Skipping replay provider '{}': {}", config.id, e);
+                            continue;
+                        }
+                    }
+                }
+                // Created below, once the provider they wrap exists
+                ProviderConfig::Record(_) => continue,
             };
 
-            let provider_id = match provider {
-                ProviderConfig::Openai(c) => &c.id,
-                ProviderConfig::Gemini(c) => &c.id,
-                ProviderConfig::Alicloud(c) => &c.id,
-                ProviderConfig::Deepseek(c) => &c.id,
-            };
+            clients.insert(provider.id().to_string(), client);
+        }
 
-            clients.insert(provider_id.clone(), client);
+        for provider in &self.providers {
+            let ProviderConfig::Record(config) = provider else {
+                continue;
+            };
+            let Some(inner) = clients.get(&config.provider).cloned() else {
+                eprintln!(
+                    "Warning: Skipping record provider '{}': unknown provider '{}'",
+                    config.id, config.provider
+                );
+                continue;
+            };
+            match RecordingClient::new(inner, &config.fixture_dir) {
+                Ok(client) => {
+                    clients.insert(config.id.clone(), Arc::new(client));
+                }
+                Err(e) => eprintln!("Warning: Skipping record provider '{}': {}", config.id, e),
+            }
         }
 
         clients
@@ -197,11 +439,108 @@ impl Config {
     /// * `Some((provider_id, model_name))` - Provider and actual model name
     /// * `None` - No routing found for the model ID
     pub fn route_model(&self, model_id: &str) -> Option<(String, String)> {
-        self.models.iter().find(|m| m.id == model_id).map(|m| {
-            let provider = m.route.provider.clone();
-            let model = m.route.model.clone().unwrap_or_else(|| m.id.clone());
-            (provider, model)
-        })
+        self.route_chain(model_id).into_iter().next()
+    }
+
+    /// All routes of a model ID in failover order, as `(provider_id, model_name)`.
+    pub fn route_chain(&self, model_id: &str) -> Vec<(String, String)> {
+        self.models
+            .iter()
+            .find(|m| m.id == model_id)
+            .map(|m| {
+                m.route
+                    .iter()
+                    .map(|route| {
+                        let model = route.model.clone().unwrap_or_else(|| m.id.clone());
+                        (route.provider.clone(), model)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// History limits for the default model.
+    ///
+    /// Combines `max_history_exchanges` with the model's `context_budget`.
+    pub fn history_limits(&self) -> HistoryLimits {
+        HistoryLimits {
+            max_messages: self.max_history_exchanges * 2,
+            token_budget: self
+                .models
+                .iter()
+                .find(|m| m.id == self.default_model)
+                .and_then(|m| m.context_budget),
+        }
+    }
+
+    /// Token prices of a model ID, if configured.
+    pub fn pricing(&self, model_id: &str) -> Option<&Pricing> {
+        self.models
+            .iter()
+            .find(|m| m.id == model_id)
+            .and_then(|m| m.pricing.as_ref())
+    }
+
+    /// Token prices of one route of a model.
+    ///
+    /// A route's own `pricing` wins. Otherwise the model's prices cover its
+    /// first route and routes to the same model ID; other routes use the
+    /// prices of the configured model they name.
+    fn route_pricing(
+        &self,
+        model: &ModelConfig,
+        index: usize,
+        model_name: &str,
+    ) -> Option<Pricing> {
+        if let Some(pricing) = model.route.get(index).and_then(|r| r.pricing.as_ref()) {
+            return Some(pricing.clone());
+        }
+        if index == 0 || model_name == model.id {
+            model.pricing.clone()
+        } else {
+            self.pricing(model_name).cloned()
+        }
+    }
+
+    /// Create a failover client for every configured model.
+    ///
+    /// Returns a map from model ID to client. Routes naming an unknown
+    /// provider are skipped with a warning. Retry and failover notices are
+    /// sent to `notices`.
+    pub fn create_routers(
+        &self,
+        clients: &HashMap<String, Arc<dyn ChatClient>>,
+        notices: &tokio::sync::mpsc::UnboundedSender<String>,
+    ) -> HashMap<String, Arc<FailoverClient>> {
+        let mut routers = HashMap::new();
+
+        for model in &self.models {
+            let mut routes = Vec::new();
+            for (index, (provider_id, model_name)) in
+                self.route_chain(&model.id).into_iter().enumerate()
+            {
+                let pricing = self.route_pricing(model, index, &model_name);
+                match clients.get(&provider_id) {
+                    Some(client) => routes.push(Route {
+                        provider_id,
+                        model: model_name,
+                        client: Arc::clone(client),
+                        pricing,
+                    }),
+                    None => eprintln!(
+                        "Warning: model '{}' routes to unknown provider '{}'",
+                        model.id, provider_id
+                    ),
+                }
+            }
+
+            let policy = model.retry.clone().unwrap_or_else(|| self.retry.clone());
+            let router = FailoverClient::new(model.id.clone(), routes, policy)
+                .with_notices(notices.clone());
+            routers.insert(model.id.clone(), Arc::new(router));
+        }
+
+        routers
     }
 }
 
@@ -291,14 +630,23 @@ impl McpServerTransportConfig {
 }
 
 impl Config {
-    /// Initialize the tool set with MCP tools
+    /// Initialize the tool set with built-in and MCP tools
     pub async fn init_tool_set(&self) -> eyre::Result<Option<ToolSet>> {
-        // Only initialize local MCP if both enable_tools and enable_local_mcp are true
-        if !self.enable_tools || !self.enable_local_mcp || self.mcp.is_none() {
+        // Only initialize local tools if both enable_tools and enable_local_mcp are true
+        if !self.enable_tools || !self.enable_local_mcp {
+            return Ok(None);
+        }
+        if self.mcp.is_none() && self.builtin_tools.is_empty() {
             return Ok(None);
         }
 
         let mut tool_set = ToolSet::default();
+        builtin_tools::register(
+            &mut tool_set,
+            &self.builtin_tools,
+            self.anchor_context.as_deref(),
+        );
+
         let mut mcp_clients = HashMap::new();
 
         // FIX: Made MCP server initialization graceful - if one server fails, others can still work
@@ -322,10 +670,8 @@ impl Config {
             }
         }
 
-        // If no clients were created successfully, return None
-        if mcp_clients.is_empty() {
+        if self.mcp.is_some() && mcp_clients.is_empty() {
             eprintln!("Warning: No MCP servers could be started");
-            return Ok(None);
         }
 
         // Load tools from successfully started servers
@@ -349,10 +695,10 @@ impl Config {
 
         let tool_count = tool_set.tools().len();
         if tool_count > 0 {
-            eprintln!("Initialized {} MCP tools", tool_count);
+            eprintln!("Initialized {} tools", tool_count);
             Ok(Some(tool_set))
         } else {
-            eprintln!("Warning: No tools were loaded from any MCP server");
+            eprintln!("Warning: No tools were loaded");
             Ok(None)
         }
     }
@@ -0,0 +1,695 @@
+//! Provider failover and retry
+//!
+//! A model's `route` can list several `(provider, model)` pairs. The
+//! [`FailoverClient`] built for it tries them in order:
+//!
+//! 1. A retryable failure (see [`RetryPolicy`]) is retried on the same route
+//!    with exponential backoff and jitter, up to `max_attempts`.
+//! 2. When a route is exhausted, the next route is tried.
+//! 3. A non-retryable failure (e.g. HTTP 400, user cancellation) is returned
+//!    immediately.
+//!
+//! Streaming requests only fail over before their first chunk, so listeners
+//! never receive two partial answers. Every retry and failover is sent as a
+//! notice that the node forwards to its `log` output.
+//!
+//! ```toml
+//! [retry]
+//! max_attempts = 2
+//! retryable_statuses = [429, 500, 502, 503, 504]
+//!
+//! [[models]]
+//! id = "tutor"
+//! route = [
+//!     { provider = "alicloud", model = "qwen-plus" },
+//!     { provider = "deepseek", model = "deepseek-chat" },
+//! ]
+//! ```
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use eyre::{Result, eyre};
+use outfox_openai::spec::{
+    ChatCompletionMessageToolCall, CreateChatCompletionRequest, CreateChatCompletionResponse,
+};
+use rand::Rng;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::client::ChatClient;
+use crate::structured::ResponseFormat;
+use crate::usage::{Pricing, Usage};
+
+/// Typed provider failures, so the retry policy can classify them
+#[derive(Debug, thiserror::Error)]
+pub enum ProviderError {
+    /// Non-success HTTP status
+    #[error("API Error ({status}): {body}")]
+    Status { status: u16, body: String },
+    /// The event stream closed before the response was complete
+    #[error("SSE error: stream ended before completion")]
+    StreamEnded,
+    /// No stream event within the stream timeout
+    #[error("Stream timed out after {0:?}")]
+    StreamTimeout(Duration),
+    /// Cancelled through the request's cancellation token
+    #[error("Stream cancelled by user")]
+    Cancelled,
+}
+
+/// Failure kinds a [`RetryPolicy`] can mark as retryable
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Request or stream timed out
+    Timeout,
+    /// Could not connect to the provider
+    Connect,
+    /// Response body or event stream broke off
+    Stream,
+}
+
+/// Classified provider failure
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Failure {
+    Status(u16),
+    Kind(ErrorKind),
+}
+
+impl Failure {
+    /// Classify an error returned by a [`ChatClient`]; `None` for errors
+    /// that are never retried (cancellation, bad requests, parse errors)
+    pub fn classify(err: &eyre::Report) -> Option<Self> {
+        if let Some(err) = err.downcast_ref::<ProviderError>() {
+            return match err {
+                ProviderError::Status { status, .. } => Some(Failure::Status(*status)),
+                ProviderError::StreamEnded => Some(Failure::Kind(ErrorKind::Stream)),
+                ProviderError::StreamTimeout(_) => Some(Failure::Kind(ErrorKind::Timeout)),
+                ProviderError::Cancelled => None,
+            };
+        }
+        if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+            if err.is_timeout() {
+                return Some(Failure::Kind(ErrorKind::Timeout));
+            }
+            if err.is_connect() || err.is_request() {
+                return Some(Failure::Kind(ErrorKind::Connect));
+            }
+            if let Some(status) = err.status() {
+                return Some(Failure::Status(status.as_u16()));
+            }
+            if err.is_body() || err.is_decode() {
+                return Some(Failure::Kind(ErrorKind::Stream));
+            }
+        }
+        None
+    }
+}
+
+/// When and how often failed provider requests are retried
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts per route before failing over (1 = no retries)
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_backoff_ms: u64,
+    /// Upper bound for the (doubling) delay
+    pub max_backoff_ms: u64,
+    /// Random spread applied to each delay, as a fraction (0.2 = ±20%)
+    pub jitter: f64,
+    /// HTTP statuses worth retrying
+    pub retryable_statuses: Vec<u16>,
+    /// Non-HTTP failures worth retrying
+    pub retryable_errors: Vec<ErrorKind>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 2,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 8_000,
+            jitter: 0.2,
+            retryable_statuses: vec![408, 429, 500, 502, 503, 504],
+            retryable_errors: vec![ErrorKind::Timeout, ErrorKind::Connect, ErrorKind::Stream],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether an error may be retried (on the same or the next route)
+    pub fn is_retryable(&self, err: &eyre::Report) -> bool {
+        match Failure::classify(err) {
+            Some(Failure::Status(status)) => self.retryable_statuses.contains(&status),
+            Some(Failure::Kind(kind)) => self.retryable_errors.contains(&kind),
+            None => false,
+        }
+    }
+
+    /// Delay before retrying after the given (1-based) failed attempt, without jitter
+    pub fn base_backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let delay = self.initial_backoff_ms.saturating_mul(1 << exponent);
+        Duration::from_millis(delay.min(self.max_backoff_ms))
+    }
+
+    /// Delay before retrying after the given (1-based) failed attempt
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let base = self.base_backoff(attempt).as_secs_f64();
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = if jitter > 0.0 {
+            rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter)
+        } else {
+            1.0
+        };
+        Duration::from_secs_f64(base * factor)
+    }
+}
+
+/// One `(provider, model)` pair of a route chain
+#[derive(Clone)]
+pub struct Route {
+    pub provider_id: String,
+    pub model: String,
+    pub client: Arc<dyn ChatClient>,
+    /// Token prices of this route, for the `metrics` output
+    pub pricing: Option<Pricing>,
+}
+
+/// [`ChatClient`] that walks a model's route chain with retries
+pub struct FailoverClient {
+    model_id: String,
+    routes: Vec<Route>,
+    policy: RetryPolicy,
+    notices: Option<mpsc::UnboundedSender<String>>,
+    /// Index of the route that served the last successful request
+    served: Mutex<Option<usize>>,
+}
+
+impl FailoverClient {
+    pub fn new(model_id: impl Into<String>, routes: Vec<Route>, policy: RetryPolicy) -> Self {
+        Self {
+            model_id: model_id.into(),
+            routes,
+            policy,
+            notices: None,
+            served: Mutex::new(None),
+        }
+    }
+
+    /// Send retry and failover notices to a channel (forwarded to the `log` output)
+    pub fn with_notices(mut self, notices: mpsc::UnboundedSender<String>) -> Self {
+        self.notices = Some(notices);
+        self
+    }
+
+    /// Route that served the last successful request, if any
+    pub fn served_route(&self) -> Option<&Route> {
+        let index = (*self.served.lock().unwrap())?;
+        self.routes.get(index)
+    }
+
+    fn notify(&self, message: String) {
+        eprintln!("[{}] {}", self.model_id, message);
+        if let Some(notices) = &self.notices {
+            let _ = notices.send(message);
+        }
+    }
+
+    /// Run `attempt` over the route chain.
+    ///
+    /// `attempt` returns the result and whether a failure may still be
+    /// retried (false once a streaming request produced output).
+    async fn run<T, F, Fut>(
+        &self,
+        request: CreateChatCompletionRequest,
+        cancellation_token: Option<&CancellationToken>,
+        mut attempt: F,
+    ) -> Result<T>
+    where
+        F: FnMut(Arc<dyn ChatClient>, CreateChatCompletionRequest) -> Fut,
+        Fut: Future<Output = (Result<T>, bool)>,
+    {
+        let max_attempts = self.policy.max_attempts.max(1);
+        let mut last_error = None;
+
+        for (index, route) in self.routes.iter().enumerate() {
+            for attempt_no in 1..=max_attempts {
+                let mut request = request.clone();
+                request.model = route.model.clone();
+
+                let (result, can_retry) = attempt(Arc::clone(&route.client), request).await;
+                let err = match result {
+                    Ok(value) => {
+                        if index > 0 || attempt_no > 1 {
+                            self.notify(format!(
+                                "Request succeeded via provider '{}' ({}) on attempt {}",
+                                route.provider_id, route.model, attempt_no
+                            ));
+                        }
+                        *self.served.lock().unwrap() = Some(index);
+                        return Ok(value);
+                    }
+                    Err(err) => err,
+                };
+                if !can_retry || !self.policy.is_retryable(&err) {
+                    return Err(err);
+                }
+
+                if attempt_no < max_attempts {
+                    let delay = self.policy.backoff(attempt_no);
+                    self.notify(format!(
+                        "Provider '{}' ({}) failed: {}; retrying in {:?} (attempt {}/{})",
+                        route.provider_id,
+                        route.model,
+                        err,
+                        delay,
+                        attempt_no + 1,
+                        max_attempts
+                    ));
+                    match cancellation_token {
+                        Some(token) => tokio::select! {
+                            _ = tokio::time::sleep(delay) => {}
+                            _ = token.cancelled() => return Err(ProviderError::Cancelled.into()),
+                        },
+                        None => tokio::time::sleep(delay).await,
+                    }
+                } else if let Some(next) = self.routes.get(index + 1) {
+                    self.notify(format!(
+                        "Provider '{}' ({}) failed: {}; failing over to '{}' ({})",
+                        route.provider_id, route.model, err, next.provider_id, next.model
+                    ));
+                }
+                last_error = Some(err);
+            }
+        }
+
+        Err(last_error
+            .unwrap_or_else(|| eyre!("No routes configured for model: {}", self.model_id)))
+    }
+}
+
+/// Forward chunks from one attempt, reporting whether any arrived
+async fn forward_chunks(
+    mut chunks: mpsc::UnboundedReceiver<String>,
+    sender: mpsc::UnboundedSender<String>,
+) -> bool {
+    let mut started = false;
+    while let Some(chunk) = chunks.recv().await {
+        started = true;
+        let _ = sender.send(chunk);
+    }
+    started
+}
+
+#[async_trait::async_trait]
+impl ChatClient for FailoverClient {
+    async fn complete(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse> {
+        self.run(request, None, |client, request| async move {
+            (client.complete(request).await, true)
+        })
+        .await
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)> {
+        self.run(request, None, |client, request| {
+            let sender = chunk_sender.clone();
+            async move {
+                let (tx, rx) = mpsc::unbounded_channel();
+                let (result, started) = tokio::join!(
+                    client.complete_streaming(request, tx),
+                    forward_chunks(rx, sender)
+                );
+                (result, !started)
+            }
+        })
+        .await
+    }
+
+    async fn complete_streaming_with_cancellation(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: CancellationToken,
+        timeout_duration: Duration,
+    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)> {
+        self.run(request, Some(&cancellation_token), |client, request| {
+            let sender = chunk_sender.clone();
+            let token = cancellation_token.clone();
+            async move {
+                let (tx, rx) = mpsc::unbounded_channel();
+                let (result, started) = tokio::join!(
+                    client.complete_streaming_with_cancellation(
+                        request,
+                        tx,
+                        token,
+                        timeout_duration
+                    ),
+                    forward_chunks(rx, sender)
+                );
+                (result, !started)
+            }
+        })
+        .await
+    }
+
+    async fn complete_structured(
+        &self,
+        request: CreateChatCompletionRequest,
+        format: &ResponseFormat,
+    ) -> Result<CreateChatCompletionResponse> {
+        self.run(request, None, |client, request| {
+            let format = format.clone();
+            async move { (client.complete_structured(request, &format).await, true) }
+        })
+        .await
+    }
+
+    /// Only if every route does, since any of them may serve the request
+    fn supports_response_format(&self) -> bool {
+        !self.routes.is_empty()
+            && self
+                .routes
+                .iter()
+                .all(|route| route.client.supports_response_format())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::OpenaiClient;
+    use crate::config::OpenaiConfig;
+    use crate::mock_server::{MockResponse, MockServer};
+    use serde_json::{Value, json};
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 4,
+            ..RetryPolicy::default()
+        }
+    }
+
+    fn route(provider_id: &str, model: &str, server: &MockServer) -> Route {
+        Route {
+            provider_id: provider_id.to_string(),
+            model: model.to_string(),
+            client: Arc::new(OpenaiClient::new(&OpenaiConfig {
+                id: provider_id.to_string(),
+                api_key: "test-key".to_string(),
+                api_url: server.url().to_string(),
+                proxy: false,
+                json_schema: true,
+            })),
+            pricing: None,
+        }
+    }
+
+    fn request() -> CreateChatCompletionRequest {
+        serde_json::from_value(json!({
+            "model": "tutor",
+            "messages": [{ "role": "user", "content": "Hello" }],
+        }))
+        .unwrap()
+    }
+
+    fn completion(content: &str) -> MockResponse {
+        MockResponse::json(
+            200,
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 0,
+                "model": "mock",
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": content },
+                    "finish_reason": "stop",
+                }],
+            }),
+        )
+    }
+
+    fn chunk(content: &str) -> Value {
+        json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion.chunk",
+            "created": 0,
+            "model": "mock",
+            "choices": [{ "index": 0, "delta": { "content": content }, "finish_reason": null }],
+        })
+    }
+
+    fn error(status: u16) -> MockResponse {
+        MockResponse::json(
+            status,
+            json!({ "error": { "message": format!("status {}", status) } }),
+        )
+    }
+
+    fn drain(rx: &mut mpsc::UnboundedReceiver<String>) -> Vec<String> {
+        let mut items = Vec::new();
+        while let Ok(item) = rx.try_recv() {
+            items.push(item);
+        }
+        items
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let policy = RetryPolicy {
+            initial_backoff_ms: 100,
+            max_backoff_ms: 350,
+            jitter: 0.0,
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+        assert_eq!(policy.backoff(40), Duration::from_millis(350));
+
+        let jittered = RetryPolicy {
+            jitter: 0.5,
+            ..policy
+        };
+        for _ in 0..100 {
+            let delay = jittered.backoff(2);
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(300));
+        }
+    }
+
+    #[test]
+    fn test_retryable_classification() {
+        let policy = RetryPolicy::default();
+        let status = |status| {
+            eyre::Report::from(ProviderError::Status {
+                status,
+                body: String::new(),
+            })
+        };
+        assert!(policy.is_retryable(&status(429)));
+        assert!(policy.is_retryable(&status(503)));
+        assert!(!policy.is_retryable(&status(400)));
+        assert!(policy.is_retryable(&ProviderError::StreamTimeout(Duration::from_secs(1)).into()));
+        assert!(!policy.is_retryable(&ProviderError::Cancelled.into()));
+        assert!(!policy.is_retryable(&eyre!("Failed to parse API response")));
+
+        let no_stream = RetryPolicy {
+            retryable_errors: vec![ErrorKind::Timeout],
+            ..RetryPolicy::default()
+        };
+        assert!(!no_stream.is_retryable(&ProviderError::StreamEnded.into()));
+    }
+
+    #[test]
+    fn test_route_accepts_one_or_many() {
+        use crate::config::ModelConfig;
+
+        let single: ModelConfig = serde_json::from_value(json!({
+            "id": "tutor",
+            "route": { "provider": "alicloud", "model": "qwen-plus" },
+        }))
+        .unwrap();
+        assert_eq!(single.route.len(), 1);
+        assert!(single.retry.is_none());
+
+        let chain: ModelConfig = serde_json::from_value(json!({
+            "id": "tutor",
+            "route": [
+                { "provider": "alicloud", "model": "qwen-plus" },
+                { "provider": "deepseek" },
+            ],
+            "retry": { "max_attempts": 4 },
+        }))
+        .unwrap();
+        assert_eq!(chain.route[1].provider, "deepseek");
+        assert!(chain.route[1].model.is_none());
+        let retry = chain.retry.unwrap();
+        assert_eq!(retry.max_attempts, 4);
+        assert_eq!(
+            retry.retryable_statuses,
+            RetryPolicy::default().retryable_statuses
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retries_then_fails_over() {
+        let (primary, fallback) = (MockServer::start().await, MockServer::start().await);
+        primary.enqueue(error(503));
+        primary.enqueue(error(429));
+        fallback.enqueue(completion("Hello from the fallback"));
+
+        let (tx, mut notices) = mpsc::unbounded_channel();
+        let client = FailoverClient::new(
+            "tutor",
+            vec![
+                route("dashscope", "qwen-plus", &primary),
+                route("deepseek", "deepseek-chat", &fallback),
+            ],
+            policy(2),
+        )
+        .with_notices(tx);
+        assert!(client.served_route().is_none());
+
+        let response = client.complete(request()).await.unwrap();
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(
+            value["choices"][0]["message"]["content"],
+            "Hello from the fallback"
+        );
+
+        assert_eq!(primary.requests().len(), 2);
+        assert_eq!(primary.requests()[0].json()["model"], "qwen-plus");
+        assert_eq!(fallback.requests().len(), 1);
+        assert_eq!(fallback.requests()[0].json()["model"], "deepseek-chat");
+        assert_eq!(client.served_route().unwrap().model, "deepseek-chat");
+
+        let notices = drain(&mut notices);
+        assert_eq!(notices.len(), 3);
+        assert!(notices[0].contains("retrying"));
+        assert!(notices[1].contains("failing over to 'deepseek'"));
+        assert!(notices[2].contains("succeeded via provider 'deepseek'"));
+    }
+
+    #[tokio::test]
+    async fn test_non_retryable_error_is_returned() {
+        let (primary, fallback) = (MockServer::start().await, MockServer::start().await);
+        primary.enqueue(error(400));
+
+        let client = FailoverClient::new(
+            "tutor",
+            vec![
+                route("dashscope", "qwen-plus", &primary),
+                route("deepseek", "deepseek-chat", &fallback),
+            ],
+            policy(3),
+        );
+
+        let err = client.complete(request()).await.unwrap_err();
+        assert!(matches!(
+            Failure::classify(&err),
+            Some(Failure::Status(400))
+        ));
+        assert_eq!(primary.requests().len(), 1);
+        assert!(fallback.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_all_routes_exhausted() {
+        let (primary, fallback) = (MockServer::start().await, MockServer::start().await);
+        primary.enqueue(error(500));
+        fallback.enqueue(error(502));
+
+        let client = FailoverClient::new(
+            "tutor",
+            vec![
+                route("dashscope", "qwen-plus", &primary),
+                route("deepseek", "deepseek-chat", &fallback),
+            ],
+            policy(1),
+        );
+
+        let err = client.complete(request()).await.unwrap_err();
+        assert!(matches!(
+            Failure::classify(&err),
+            Some(Failure::Status(502))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_fails_over_before_first_chunk() {
+        let (primary, fallback) = (MockServer::start().await, MockServer::start().await);
+        primary.enqueue(error(429));
+        fallback.enqueue(MockResponse::sse_raw([
+            chunk("Hello").to_string(),
+            chunk(" there").to_string(),
+            "[DONE]".to_string(),
+        ]));
+
+        let client = FailoverClient::new(
+            "tutor",
+            vec![
+                route("dashscope", "qwen-plus", &primary),
+                route("deepseek", "deepseek-chat", &fallback),
+            ],
+            policy(1),
+        );
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (text, _, _) = client
+            .complete_streaming_with_cancellation(
+                request(),
+                tx,
+                CancellationToken::new(),
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(text, "Hello there");
+        assert_eq!(drain(&mut rx), vec!["Hello", " there"]);
+        assert_eq!(primary.requests().len(), 1);
+        assert_eq!(fallback.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_does_not_fail_over_after_first_chunk() {
+        let (primary, fallback) = (MockServer::start().await, MockServer::start().await);
+        // Stream breaks off without [DONE]
+        primary.enqueue(MockResponse::sse(&[chunk("Hel")]));
+        fallback.enqueue(completion("unused"));
+
+        let client = FailoverClient::new(
+            "tutor",
+            vec![
+                route("dashscope", "qwen-plus", &primary),
+                route("deepseek", "deepseek-chat", &fallback),
+            ],
+            policy(2),
+        );
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let err = client.complete_streaming(request(), tx).await.unwrap_err();
+
+        assert!(matches!(
+            Failure::classify(&err),
+            Some(Failure::Kind(ErrorKind::Stream))
+        ));
+        assert_eq!(drain(&mut rx), vec!["Hel"]);
+        assert_eq!(primary.requests().len(), 1);
+        assert!(fallback.requests().is_empty());
+    }
+}
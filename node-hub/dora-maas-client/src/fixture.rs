@@ -0,0 +1,822 @@
+//! Record-and-replay LLM fixtures
+//!
+//! A `record` provider wraps a real provider and writes every successful
+//! exchange to `<fixture_dir>/<key>.json`. Streamed exchanges keep their
+//! chunks and the delay before each one. A `replay` provider serves those
+//! fixtures without network access, so dataflows can run offline (e.g. in CI)
+//! with reproducible text.
+//!
+//! ```toml
+//! # Recording run: talk to alicloud, keep what it says
+//! [[providers]]
+//! kind = "record"
+//! id = "tutor"
+//! provider = "alicloud"
+//! fixture_dir = "fixtures/tutor"
+//!
+//! # Offline run
+//! [[providers]]
+//! kind = "replay"
+//! id = "tutor"
+//! fixture_dir = "fixtures/tutor"
+//! fallback = "last_user"
+//! ```
+//!
+//! Requests are matched by a hash of the normalized message list (see
+//! [`normalize_messages`]: roles, text with collapsed whitespace, tool calls
+//! with positional IDs). The model name and sampling parameters are not part of the key. A
+//! key recorded several times is replayed in recorded order, repeating the
+//! last exchange once all have been served.
+
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use eyre::{Context, Result, eyre};
+use outfox_openai::spec::{
+    ChatCompletionMessageToolCall, ChatCompletionRequestMessage, CreateChatCompletionRequest,
+    CreateChatCompletionResponse,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::client::ChatClient;
+use crate::failover::ProviderError;
+use crate::structured::ResponseFormat;
+use crate::usage::Usage;
+
+/// Fixture file schema version
+pub const FIXTURE_VERSION: u32 = 1;
+
+/// What a replay provider does when no fixture matches a request
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayFallback {
+    /// Fail the request
+    #[default]
+    Error,
+    /// Match on the last user message only
+    LastUser,
+    /// Serve the oldest fixture not replayed yet
+    Sequential,
+}
+
+/// One recorded exchange
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Exchange {
+    /// Non-streaming response, as returned by the provider
+    Complete { response: Value },
+    /// Streamed text chunks and the final tool calls
+    Stream {
+        chunks: Vec<RecordedChunk>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tool_calls: Option<Vec<ChatCompletionMessageToolCall>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        usage: Option<Usage>,
+    },
+}
+
+/// Streamed chunk with the delay since the previous one (or the request)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedChunk {
+    pub delay_ms: u64,
+    pub text: String,
+}
+
+/// Fixture file: all exchanges recorded for one key
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Fixture {
+    pub version: u32,
+    pub key: String,
+    /// Key of the last user message, for the `last_user` fallback
+    pub last_user_key: Option<String>,
+    /// Recording order across all fixtures in the directory
+    pub sequence: u64,
+    /// Normalized messages the key was computed from (for humans)
+    pub messages: Vec<Value>,
+    pub exchanges: Vec<Exchange>,
+}
+
+/// Normalize a message list for matching
+pub fn normalize_messages(messages: &[ChatCompletionRequestMessage]) -> Vec<Value> {
+    let mut call_ids: HashMap<String, usize> = HashMap::new();
+    let mut normalized = Vec::with_capacity(messages.len());
+    for message in messages {
+        let value = serde_json::to_value(message).unwrap_or_default();
+        let mut entry = json!({
+            "role": value["role"],
+            "content": normalize_text(&value["content"]),
+        });
+        if let Some(calls) = value["tool_calls"].as_array() {
+            let calls: Vec<Value> = calls
+                .iter()
+                .map(|call| {
+                    let next = call_ids.len();
+                    let id = call["id"].as_str().unwrap_or_default().to_string();
+                    let index = *call_ids.entry(id).or_insert(next);
+                    json!({
+                        "id": index,
+                        "name": call["function"]["name"],
+                        "arguments": normalize_json_text(&call["function"]["arguments"]),
+                    })
+                })
+                .collect();
+            entry["tool_calls"] = Value::Array(calls);
+        }
+        if let Some(id) = value["tool_call_id"].as_str() {
+            let next = call_ids.len();
+            entry["tool_call_id"] = json!(*call_ids.entry(id.to_string()).or_insert(next));
+        }
+        normalized.push(entry);
+    }
+    normalized
+}
+
+fn normalize_text(content: &Value) -> String {
+    let text = match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part["text"].as_str())
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => String::new(),
+    };
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Re-serialize JSON arguments so formatting differences don't matter
+fn normalize_json_text(arguments: &Value) -> String {
+    let text = arguments.as_str().unwrap_or_default();
+    serde_json::from_str::<Value>(text)
+        .map(|value| value.to_string())
+        .unwrap_or_else(|_| text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn last_user_key(normalized: &[Value]) -> Option<String> {
+    normalized
+        .iter()
+        .rev()
+        .find(|m| m["role"] == "user")
+        .map(|m| hash_values(std::slice::from_ref(m)))
+}
+
+/// Stable key of normalized messages (FNV-1a over the JSON)
+fn hash_values(values: &[Value]) -> String {
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    let text = Value::Array(values.to_vec()).to_string();
+    let hash = text.bytes().fold(OFFSET, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    });
+    format!("{:016x}", hash)
+}
+
+fn fixture_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{}.json", key))
+}
+
+/// Build an OpenAI-style response from replayed text, tool calls and usage
+fn response_from_text(
+    text: &str,
+    tool_calls: Option<&Vec<ChatCompletionMessageToolCall>>,
+    usage: Option<&Usage>,
+) -> Result<CreateChatCompletionResponse> {
+    let finish_reason = if tool_calls.is_some() {
+        "tool_calls"
+    } else {
+        "stop"
+    };
+    let mut message = json!({ "role": "assistant", "content": text });
+    if let Some(calls) = tool_calls {
+        message["tool_calls"] = serde_json::to_value(calls)?;
+    }
+    let mut response = json!({
+        "id": "chatcmpl-replay",
+        "object": "chat.completion",
+        "created": 0,
+        "model": "replay",
+        "choices": [{ "index": 0, "message": message, "finish_reason": finish_reason }],
+    });
+    if let Some(usage) = usage {
+        response["usage"] = serde_json::to_value(usage)?;
+    }
+    serde_json::from_value(response).map_err(|e| eyre!("Failed to build replay response: {}", e))
+}
+
+/// [`ChatClient`] that records exchanges of another provider
+pub struct RecordingClient {
+    inner: Arc<dyn ChatClient>,
+    dir: PathBuf,
+    // Serializes read-modify-write of fixture files
+    lock: Mutex<()>,
+}
+
+impl RecordingClient {
+    pub fn new(inner: Arc<dyn ChatClient>, dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create fixture directory {}", dir.display()))?;
+        Ok(Self {
+            inner,
+            dir,
+            lock: Mutex::new(()),
+        })
+    }
+
+    fn record(&self, messages: &[ChatCompletionRequestMessage], exchange: Exchange) -> Result<()> {
+        let normalized = normalize_messages(messages);
+        let key = hash_values(&normalized);
+        let path = fixture_path(&self.dir, &key);
+
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut fixture = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| eyre!("Invalid fixture {}: {}", path.display(), e))?,
+            Err(_) => Fixture {
+                version: FIXTURE_VERSION,
+                last_user_key: last_user_key(&normalized),
+                sequence: chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64,
+                key,
+                messages: normalized,
+                exchanges: Vec::new(),
+            },
+        };
+        fixture.exchanges.push(exchange);
+        fs::write(&path, serde_json::to_string_pretty(&fixture)?)
+            .with_context(|| format!("Failed to write fixture {}", path.display()))?;
+        Ok(())
+    }
+
+    fn record_or_warn(&self, messages: &[ChatCompletionRequestMessage], exchange: Exchange) {
+        if let Err(e) = self.record(messages, exchange) {
+            eprintln!("Warning: Failed to record fixture: {}", e);
+        }
+    }
+
+    /// Forward chunks of a streaming call, recording their timing, and
+    /// record the exchange if the call succeeds
+    async fn record_stream_from<F>(
+        &self,
+        messages: Vec<ChatCompletionRequestMessage>,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        mut chunks: mpsc::UnboundedReceiver<String>,
+        call: F,
+    ) -> Result<(
+        String,
+        Option<Vec<ChatCompletionMessageToolCall>>,
+        Option<Usage>,
+    )>
+    where
+        F: Future<
+            Output = Result<(
+                String,
+                Option<Vec<ChatCompletionMessageToolCall>>,
+                Option<Usage>,
+            )>,
+        >,
+    {
+        let forward = async move {
+            let mut recorded = Vec::new();
+            let mut last = Instant::now();
+            while let Some(text) = chunks.recv().await {
+                let now = Instant::now();
+                recorded.push(RecordedChunk {
+                    delay_ms: now.duration_since(last).as_millis() as u64,
+                    text: text.clone(),
+                });
+                last = now;
+                let _ = chunk_sender.send(text);
+            }
+            recorded
+        };
+        let (result, recorded) = tokio::join!(call, forward);
+
+        if let Ok((_, tool_calls, usage)) = &result {
+            self.record_or_warn(
+                &messages,
+                Exchange::Stream {
+                    chunks: recorded,
+                    tool_calls: tool_calls.clone(),
+                    usage: *usage,
+                },
+            );
+        }
+        result
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatClient for RecordingClient {
+    async fn complete(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse> {
+        let messages = request.messages.clone();
+        let response = self.inner.complete(request).await?;
+        self.record_or_warn(
+            &messages,
+            Exchange::Complete {
+                response: serde_json::to_value(&response)?,
+            },
+        );
+        Ok(response)
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+    ) -> Result<(
+        String,
+        Option<Vec<ChatCompletionMessageToolCall>>,
+        Option<Usage>,
+    )> {
+        let messages = request.messages.clone();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let inner = Arc::clone(&self.inner);
+        self.record_stream_from(messages, chunk_sender, rx, async move {
+            inner.complete_streaming(request, tx).await
+        })
+        .await
+    }
+
+    async fn complete_streaming_with_cancellation(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: CancellationToken,
+        timeout_duration: Duration,
+    ) -> Result<(
+        String,
+        Option<Vec<ChatCompletionMessageToolCall>>,
+        Option<Usage>,
+    )> {
+        let messages = request.messages.clone();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let inner = Arc::clone(&self.inner);
+        self.record_stream_from(messages, chunk_sender, rx, async move {
+            inner
+                .complete_streaming_with_cancellation(
+                    request,
+                    tx,
+                    cancellation_token,
+                    timeout_duration,
+                )
+                .await
+        })
+        .await
+    }
+
+    async fn complete_structured(
+        &self,
+        request: CreateChatCompletionRequest,
+        format: &ResponseFormat,
+    ) -> Result<CreateChatCompletionResponse> {
+        let messages = request.messages.clone();
+        let response = self.inner.complete_structured(request, format).await?;
+        self.record_or_warn(
+            &messages,
+            Exchange::Complete {
+                response: serde_json::to_value(&response)?,
+            },
+        );
+        Ok(response)
+    }
+
+    fn supports_response_format(&self) -> bool {
+        self.inner.supports_response_format()
+    }
+}
+
+/// [`ChatClient`] that serves recorded fixtures
+pub struct ReplayClient {
+    fixtures: HashMap<String, Fixture>,
+    fallback: ReplayFallback,
+    realtime: bool,
+    // Exchanges served so far, per key
+    served: Mutex<HashMap<String, usize>>,
+}
+
+impl ReplayClient {
+    /// Load all fixtures from a directory.
+    ///
+    /// With `realtime`, streamed chunks are paced by their recorded delays.
+    pub fn open(dir: impl AsRef<Path>, fallback: ReplayFallback, realtime: bool) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut fixtures = HashMap::new();
+        let entries = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read fixture directory {}", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let text = fs::read_to_string(&path)?;
+            let fixture: Fixture = serde_json::from_str(&text)
+                .map_err(|e| eyre!("Invalid fixture {}: {}", path.display(), e))?;
+            if fixture.version > FIXTURE_VERSION {
+                return Err(eyre!(
+                    "Unsupported fixture version {} in {}",
+                    fixture.version,
+                    path.display()
+                ));
+            }
+            fixtures.insert(fixture.key.clone(), fixture);
+        }
+        Ok(Self {
+            fixtures,
+            fallback,
+            realtime,
+            served: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Pick the next exchange for a request
+    fn next_exchange(&self, messages: &[ChatCompletionRequestMessage]) -> Result<Exchange> {
+        let normalized = normalize_messages(messages);
+        let key = hash_values(&normalized);
+        let mut served = self.served.lock().unwrap_or_else(|e| e.into_inner());
+
+        let fixture = match self.fixtures.get(&key) {
+            Some(fixture) => fixture,
+            None => match self.fallback {
+                ReplayFallback::Error => None,
+                ReplayFallback::LastUser => last_user_key(&normalized).and_then(|user_key| {
+                    self.fixtures
+                        .values()
+                        .filter(|f| f.last_user_key.as_ref() == Some(&user_key))
+                        .min_by_key(|f| f.sequence)
+                }),
+                ReplayFallback::Sequential => self
+                    .fixtures
+                    .values()
+                    .filter(|f| served.get(&f.key).copied().unwrap_or(0) < f.exchanges.len())
+                    .min_by_key(|f| f.sequence),
+            }
+            .ok_or_else(|| eyre!("No fixture recorded for request (key {})", key))?,
+        };
+
+        let count = served.entry(fixture.key.clone()).or_insert(0);
+        let index = (*count).min(fixture.exchanges.len().saturating_sub(1));
+        *count += 1;
+        fixture
+            .exchanges
+            .get(index)
+            .cloned()
+            .ok_or_else(|| eyre!("Fixture {} has no exchanges", fixture.key))
+    }
+
+    async fn replay_stream(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: Option<CancellationToken>,
+    ) -> Result<(
+        String,
+        Option<Vec<ChatCompletionMessageToolCall>>,
+        Option<Usage>,
+    )> {
+        let (chunks, tool_calls, usage) = match self.next_exchange(&request.messages)? {
+            Exchange::Stream {
+                chunks,
+                tool_calls,
+                usage,
+            } => (chunks, tool_calls, usage),
+            Exchange::Complete { response } => {
+                let message = &response["choices"][0]["message"];
+                let text = message["content"].as_str().unwrap_or_default().to_string();
+                let tool_calls = serde_json::from_value(message["tool_calls"].clone()).ok();
+                let chunks = if text.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![RecordedChunk { delay_ms: 0, text }]
+                };
+                (chunks, tool_calls, Usage::from_value(&response["usage"]))
+            }
+        };
+
+        let token = cancellation_token.unwrap_or_else(CancellationToken::new);
+        let mut content = String::new();
+        for chunk in chunks {
+            if self.realtime && chunk.delay_ms > 0 {
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_millis(chunk.delay_ms)) => {}
+                    _ = token.cancelled() => return Err(ProviderError::Cancelled.into()),
+                }
+            }
+            if token.is_cancelled() {
+                return Err(ProviderError::Cancelled.into());
+            }
+            content.push_str(&chunk.text);
+            chunk_sender
+                .send(chunk.text)
+                .map_err(|e| eyre!("Failed to send chunk: {}", e))?;
+        }
+        Ok((content, tool_calls, usage))
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatClient for ReplayClient {
+    async fn complete(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse> {
+        match self.next_exchange(&request.messages)? {
+            Exchange::Complete { response } => serde_json::from_value(response)
+                .map_err(|e| eyre!("Invalid recorded response: {}", e)),
+            Exchange::Stream {
+                chunks,
+                tool_calls,
+                usage,
+            } => {
+                let text: String = chunks.iter().map(|c| c.text.as_str()).collect();
+                response_from_text(&text, tool_calls.as_ref(), usage.as_ref())
+            }
+        }
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+    ) -> Result<(
+        String,
+        Option<Vec<ChatCompletionMessageToolCall>>,
+        Option<Usage>,
+    )> {
+        self.replay_stream(request, chunk_sender, None).await
+    }
+
+    async fn complete_streaming_with_cancellation(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: CancellationToken,
+        _timeout_duration: Duration,
+    ) -> Result<(
+        String,
+        Option<Vec<ChatCompletionMessageToolCall>>,
+        Option<Usage>,
+    )> {
+        self.replay_stream(request, chunk_sender, Some(cancellation_token))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::OpenaiClient;
+    use crate::config::OpenaiConfig;
+    use crate::mock_server::{MockResponse, MockServer};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "maas-fixtures-{}-{}",
+            name,
+            uuid::Uuid::new_v4().simple()
+        ))
+    }
+
+    fn request(messages: Value) -> CreateChatCompletionRequest {
+        serde_json::from_value(json!({ "model": "tutor", "messages": messages })).unwrap()
+    }
+
+    fn ask(question: &str) -> CreateChatCompletionRequest {
+        request(json!([
+            { "role": "system", "content": "You are a tutor." },
+            { "role": "user", "content": question },
+        ]))
+    }
+
+    fn completion(content: &str) -> MockResponse {
+        MockResponse::json(
+            200,
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 0,
+                "model": "mock",
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": content },
+                    "finish_reason": "stop",
+                }],
+            }),
+        )
+    }
+
+    fn chunk(content: &str) -> String {
+        json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion.chunk",
+            "created": 0,
+            "model": "mock",
+            "choices": [{ "index": 0, "delta": { "content": content }, "finish_reason": null }],
+        })
+        .to_string()
+    }
+
+    fn recorder(server: &MockServer, dir: &Path) -> RecordingClient {
+        let inner = OpenaiClient::new(&OpenaiConfig {
+            id: "mock".to_string(),
+            api_key: "test-key".to_string(),
+            api_url: server.url().to_string(),
+            proxy: false,
+            json_schema: true,
+        });
+        RecordingClient::new(Arc::new(inner), dir).unwrap()
+    }
+
+    fn content(response: &CreateChatCompletionResponse) -> String {
+        let value = serde_json::to_value(response).unwrap();
+        value["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    async fn stream(
+        client: &dyn ChatClient,
+        request: CreateChatCompletionRequest,
+    ) -> (String, Vec<String>, Option<Usage>) {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (text, _, usage) = client.complete_streaming(request, tx).await.unwrap();
+        let mut chunks = Vec::new();
+        while let Ok(chunk) = rx.try_recv() {
+            chunks.push(chunk);
+        }
+        (text, chunks, usage)
+    }
+
+    #[test]
+    fn test_key_ignores_whitespace_and_call_ids() {
+        let key = |messages: Value| {
+            let request = request(messages);
+            hash_values(&normalize_messages(&request.messages))
+        };
+        let with_call = |id: &str, text: &str, args: &str| {
+            json!([
+                { "role": "user", "content": text },
+                { "role": "assistant", "tool_calls": [{
+                    "id": id,
+                    "type": "function",
+                    "function": { "name": "get_weather", "arguments": args },
+                }]},
+                { "role": "tool", "tool_call_id": id, "content": "Sunny" },
+            ])
+        };
+
+        assert_eq!(
+            key(with_call(
+                "call_a",
+                "Weather in  Paris?",
+                r#"{"city":"Paris"}"#
+            )),
+            key(with_call(
+                "call_b",
+                " Weather in Paris?\n",
+                r#"{ "city": "Paris" }"#
+            )),
+        );
+        assert_ne!(
+            key(with_call(
+                "call_a",
+                "Weather in Paris?",
+                r#"{"city":"Paris"}"#
+            )),
+            key(with_call(
+                "call_a",
+                "Weather in Rome?",
+                r#"{"city":"Rome"}"#
+            )),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay_complete() {
+        let dir = temp_dir("complete");
+        let server = MockServer::start().await;
+        server.enqueue(completion("Dora is a dataflow framework."));
+        server.enqueue(completion("Dora runs nodes."));
+
+        let recorder = recorder(&server, &dir);
+        recorder.complete(ask("What is dora?")).await.unwrap();
+        recorder.complete(ask("What is dora?")).await.unwrap();
+
+        let replay = ReplayClient::open(&dir, ReplayFallback::Error, false).unwrap();
+        let first = replay.complete(ask("What is  dora?")).await.unwrap();
+        let second = replay.complete(ask("What is dora?")).await.unwrap();
+        let third = replay.complete(ask("What is dora?")).await.unwrap();
+        assert_eq!(content(&first), "Dora is a dataflow framework.");
+        assert_eq!(content(&second), "Dora runs nodes.");
+        // Repeats the last exchange once all were served
+        assert_eq!(content(&third), "Dora runs nodes.");
+
+        // A complete exchange replays as a single chunk
+        let replay = ReplayClient::open(&dir, ReplayFallback::Error, false).unwrap();
+        let (text, chunks, _) = stream(&replay, ask("What is dora?")).await;
+        assert_eq!(text, "Dora is a dataflow framework.");
+        assert_eq!(chunks, vec!["Dora is a dataflow framework."]);
+
+        let err = replay.complete(ask("What is arrow?")).await.unwrap_err();
+        assert!(err.to_string().contains("No fixture recorded"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay_stream() {
+        let dir = temp_dir("stream");
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::sse_raw([
+            chunk("Dora is "),
+            chunk("a dataflow "),
+            chunk("framework."),
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion.chunk",
+                "created": 0,
+                "model": "mock",
+                "choices": [],
+                "usage": { "prompt_tokens": 14, "completion_tokens": 6, "total_tokens": 20 },
+            })
+            .to_string(),
+            "[DONE]".to_string(),
+        ]));
+
+        let recorder = recorder(&server, &dir);
+        let (recorded, forwarded, usage) = stream(&recorder, ask("What is dora?")).await;
+        assert_eq!(recorded, "Dora is a dataflow framework.");
+        assert_eq!(forwarded.len(), 3);
+        assert_eq!(usage, Some(Usage::new(14, 6)));
+        assert_eq!(
+            server.requests()[0].json()["stream_options"]["include_usage"],
+            true
+        );
+
+        let fixture: Fixture = {
+            let path = fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+            serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+        };
+        assert!(
+            matches!(&fixture.exchanges[0], Exchange::Stream { chunks, .. } if chunks.len() == 3)
+        );
+
+        let replay = ReplayClient::open(&dir, ReplayFallback::Error, true).unwrap();
+        let (text, chunks, replayed_usage) = stream(&replay, ask("What is dora?")).await;
+        assert_eq!(text, recorded);
+        assert_eq!(chunks, forwarded);
+        assert_eq!(replayed_usage, usage);
+
+        let response = replay.complete(ask("What is dora?")).await.unwrap();
+        assert_eq!(content(&response), recorded);
+        assert_eq!(Usage::from_response(&response), usage);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_fallbacks() {
+        let dir = temp_dir("fallback");
+        let server = MockServer::start().await;
+        server.enqueue(completion("First answer"));
+        server.enqueue(completion("Second answer"));
+
+        let recorder = recorder(&server, &dir);
+        recorder.complete(ask("First question")).await.unwrap();
+        recorder.complete(ask("Second question")).await.unwrap();
+
+        // Same last user message, different system prompt
+        let reworded = |question: &str| {
+            request(json!([
+                { "role": "system", "content": "You are a strict tutor." },
+                { "role": "user", "content": question },
+            ]))
+        };
+
+        let replay = ReplayClient::open(&dir, ReplayFallback::Error, false).unwrap();
+        assert!(replay.complete(reworded("Second question")).await.is_err());
+
+        let replay = ReplayClient::open(&dir, ReplayFallback::LastUser, false).unwrap();
+        let response = replay.complete(reworded("Second question")).await.unwrap();
+        assert_eq!(content(&response), "Second answer");
+
+        let replay = ReplayClient::open(&dir, ReplayFallback::Sequential, false).unwrap();
+        let first = replay.complete(ask("Unrelated")).await.unwrap();
+        let second = replay.complete(ask("Unrelated")).await.unwrap();
+        assert_eq!(content(&first), "First answer");
+        assert_eq!(content(&second), "Second answer");
+        assert!(replay.complete(ask("Unrelated")).await.is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
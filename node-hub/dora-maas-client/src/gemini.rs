@@ -0,0 +1,635 @@
+//! Native Gemini API support
+//!
+//! Translates OpenAI-style chat completion requests into Gemini
+//! `generateContent` / `streamGenerateContent` bodies and Gemini responses
+//! back into [`CreateChatCompletionResponse`], so [`GeminiClient`] fits the
+//! same [`ChatClient`] interface as the OpenAI-compatible providers.
+//!
+//! The translation works on the OpenAI wire format (the request serialized
+//! to JSON) rather than on the Rust types:
+//!
+//! | OpenAI | Gemini |
+//! |--------|--------|
+//! | `system` / `developer` messages | `systemInstruction` |
+//! | `user` message | `user` content with a `text` part |
+//! | `assistant` message, `tool_calls` | `model` content with `text` / `functionCall` parts |
+//! | `tool` message | `user` content with a `functionResponse` part |
+//! | `tools` | `tools[].functionDeclarations` |
+//!
+//! Gemini matches function responses by name, so tool messages are resolved
+//! to the function name of the assistant tool call with the same ID.
+//!
+//! [`GeminiClient`]: crate::client::GeminiClient
+//! [`ChatClient`]: crate::client::ChatClient
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bytes::Bytes;
+use eyre::{Result, eyre};
+use futures::StreamExt;
+use outfox_openai::spec::{
+    ChatCompletionMessageToolCall, ChatCompletionToolType, CreateChatCompletionRequest,
+    CreateChatCompletionResponse, FunctionCall,
+};
+use reqwest_eventsource::{Event, EventSource};
+use serde_json::{Map, Value, json};
+use tokio_util::sync::CancellationToken;
+
+use crate::failover::ProviderError;
+use crate::streaming::sse_error;
+use crate::usage::Usage;
+use crate::wire::{content_text, unix_timestamp};
+
+/// Gemini endpoint URL for a model and method (`generateContent`, ...)
+pub fn endpoint(api_url: &str, model: &str, method: &str) -> String {
+    let model = model.strip_prefix("models/").unwrap_or(model);
+    format!("{}/models/{}:{}", api_url.trim_end_matches('/'), model, method)
+}
+
+/// Build a Gemini request body from an OpenAI-style request.
+///
+/// Returns the model name and the `generateContent` body.
+pub fn to_gemini_request(request: &CreateChatCompletionRequest) -> Result<(String, Value)> {
+    let request = serde_json::to_value(request)?;
+    let model = request["model"]
+        .as_str()
+        .ok_or_else(|| eyre!("Request has no model"))?
+        .to_string();
+
+    let mut system_parts = Vec::new();
+    let mut contents = Vec::new();
+    let mut call_names: HashMap<String, String> = HashMap::new();
+
+    for message in request["messages"].as_array().into_iter().flatten() {
+        let text = content_text(&message["content"]);
+        match message["role"].as_str().unwrap_or("user") {
+            "system" | "developer" => {
+                if !text.is_empty() {
+                    system_parts.push(json!({ "text": text }));
+                }
+            }
+            "assistant" => {
+                let mut parts = Vec::new();
+                if !text.is_empty() {
+                    parts.push(json!({ "text": text }));
+                }
+                for call in message["tool_calls"].as_array().into_iter().flatten() {
+                    let name = call["function"]["name"].as_str().unwrap_or_default();
+                    let args = call["function"]["arguments"]
+                        .as_str()
+                        .and_then(|args| serde_json::from_str::<Value>(args).ok())
+                        .unwrap_or_else(|| json!({}));
+                    if let Some(id) = call["id"].as_str() {
+                        call_names.insert(id.to_string(), name.to_string());
+                    }
+                    parts.push(json!({ "functionCall": { "name": name, "args": args } }));
+                }
+                push_content(&mut contents, "model", parts);
+            }
+            "tool" => {
+                let call_id = message["tool_call_id"].as_str().unwrap_or_default();
+                let name = call_names
+                    .get(call_id)
+                    .ok_or_else(|| eyre!("Tool result for unknown tool call: {}", call_id))?;
+                // `response` must be an object; wrap plain-text tool output
+                let response = match serde_json::from_str::<Value>(&text) {
+                    Ok(value @ Value::Object(_)) => value,
+                    _ => json!({ "content": text }),
+                };
+                push_content(
+                    &mut contents,
+                    "user",
+                    vec![json!({ "functionResponse": { "name": name, "response": response } })],
+                );
+            }
+            _ => push_content(&mut contents, "user", vec![json!({ "text": text })]),
+        }
+    }
+
+    let mut body = Map::new();
+    body.insert("contents".to_string(), Value::Array(contents));
+    if !system_parts.is_empty() {
+        body.insert("systemInstruction".to_string(), json!({ "parts": system_parts }));
+    }
+
+    let declarations: Vec<Value> = request["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|tool| {
+            let function = &tool["function"];
+            let mut declaration = json!({ "name": function["name"] });
+            if let Some(description) = function["description"].as_str() {
+                declaration["description"] = json!(description);
+            }
+            if !function["parameters"].is_null() {
+                declaration["parameters"] = sanitize_schema(&function["parameters"]);
+            }
+            declaration
+        })
+        .collect();
+    if !declarations.is_empty() {
+        body.insert(
+            "tools".to_string(),
+            json!([{ "functionDeclarations": declarations }]),
+        );
+    }
+    if let Some(mode) = function_calling_mode(&request["tool_choice"]) {
+        body.insert(
+            "toolConfig".to_string(),
+            json!({ "functionCallingConfig": { "mode": mode } }),
+        );
+    }
+
+    let generation_config = generation_config(&request);
+    if !generation_config.is_empty() {
+        body.insert("generationConfig".to_string(), Value::Object(generation_config));
+    }
+
+    Ok((model, Value::Object(body)))
+}
+
+/// Convert a Gemini `generateContent` response into a chat completion response
+pub fn from_gemini_response(response: &Value, model: &str) -> Result<CreateChatCompletionResponse> {
+    let candidate = response["candidates"].get(0).ok_or_else(|| {
+        match response["promptFeedback"]["blockReason"].as_str() {
+            Some(reason) => eyre!("Gemini blocked the prompt: {}", reason),
+            None => eyre!("Gemini response has no candidates: {}", response),
+        }
+    })?;
+
+    let (text, tool_calls) = parse_parts(&candidate["content"]["parts"]);
+    let finish_reason = if tool_calls.is_empty() {
+        openai_finish_reason(candidate["finishReason"].as_str())
+    } else {
+        "tool_calls"
+    };
+
+    let content = if text.is_empty() && !tool_calls.is_empty() {
+        Value::Null
+    } else {
+        Value::String(text)
+    };
+    let mut message = json!({ "role": "assistant", "content": content });
+    if !tool_calls.is_empty() {
+        message["tool_calls"] = serde_json::to_value(&tool_calls)?;
+    }
+
+    let mut completion = json!({
+        "id": response["responseId"].as_str().unwrap_or("gemini"),
+        "object": "chat.completion",
+        "created": unix_timestamp(),
+        "model": response["modelVersion"].as_str().unwrap_or(model),
+        "choices": [{
+            "index": 0,
+            "message": message,
+            "finish_reason": finish_reason,
+        }],
+    });
+    if let Some(usage) = openai_usage(&response["usageMetadata"]) {
+        completion["usage"] = usage;
+    }
+
+    serde_json::from_value(completion)
+        .map_err(|e| eyre!("Failed to convert Gemini response: {}", e))
+}
+
+/// Stream a `streamGenerateContent` request (SSE), calling `on_chunk` for each text delta.
+///
+/// Gemini sends function calls whole rather than as argument deltas; they are
+/// returned with the accumulated text once the stream ends. `timeout` bounds
+/// the wait for each event.
+pub async fn stream_generate_content<F>(
+    client: &reqwest::Client,
+    url: String,
+    api_key: String,
+    request_body: Value,
+    cancellation_token: Option<CancellationToken>,
+    timeout: Option<Duration>,
+    mut on_chunk: F,
+) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)>
+where
+    F: FnMut(String) -> Result<()>,
+{
+    let body_bytes: Bytes = serde_json::to_vec(&request_body)?.into();
+    let request = client
+        .post(format!("{}?alt=sse", url))
+        .header("x-goog-api-key", api_key)
+        .header("Content-Type", "application/json")
+        .body(body_bytes);
+
+    let mut event_source = EventSource::new(request)?;
+    let cancellation_token = cancellation_token.unwrap_or_else(CancellationToken::new);
+    let mut accumulated_content = String::new();
+    let mut tool_calls = Vec::new();
+    let mut usage = None;
+
+    loop {
+        let idle_timeout = async {
+            match timeout {
+                Some(duration) => tokio::time::sleep(duration).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            event = event_source.next() => {
+                match event {
+                    Some(Ok(Event::Open)) => {}
+                    Some(Ok(Event::Message(msg))) => {
+                        let chunk: Value = match serde_json::from_str(&msg.data) {
+                            Ok(chunk) => chunk,
+                            // Skip unparseable chunks, like the OpenAI stream does
+                            Err(_) => continue,
+                        };
+                        // Every chunk carries the running usage; keep the latest
+                        if let Some(reported) = openai_usage(&chunk["usageMetadata"]) {
+                            usage = Usage::from_value(&reported);
+                        }
+                        if let Some(candidate) = chunk["candidates"].get(0) {
+                            let (text, calls) = parse_parts(&candidate["content"]["parts"]);
+                            if !text.is_empty() {
+                                accumulated_content.push_str(&text);
+                                on_chunk(text)?;
+                            }
+                            tool_calls.extend(calls);
+                        } else if let Some(reason) = chunk["promptFeedback"]["blockReason"].as_str() {
+                            event_source.close();
+                            return Err(eyre!("Gemini blocked the prompt: {}", reason));
+                        }
+                    }
+                    // Gemini ends the stream by closing it (no [DONE] marker)
+                    Some(Err(reqwest_eventsource::Error::StreamEnded)) | None => break,
+                    Some(Err(e)) => {
+                        event_source.close();
+                        if cancellation_token.is_cancelled() {
+                            return Err(ProviderError::Cancelled.into());
+                        }
+                        return Err(sse_error(e).await);
+                    }
+                }
+            }
+            _ = cancellation_token.cancelled() => {
+                event_source.close();
+                return Err(ProviderError::Cancelled.into());
+            }
+            _ = idle_timeout => {
+                event_source.close();
+                return Err(ProviderError::StreamTimeout(timeout.unwrap_or_default()).into());
+            }
+        }
+    }
+    event_source.close();
+
+    let tool_calls = if tool_calls.is_empty() {
+        None
+    } else {
+        Some(tool_calls)
+    };
+    Ok((accumulated_content, tool_calls, usage))
+}
+
+/// Append parts, merging consecutive contents of the same role
+fn push_content(contents: &mut Vec<Value>, role: &str, parts: Vec<Value>) {
+    if parts.is_empty() {
+        return;
+    }
+    if let Some(last) = contents.last_mut() {
+        if last["role"] == role {
+            if let Some(existing) = last["parts"].as_array_mut() {
+                existing.extend(parts);
+                return;
+            }
+        }
+    }
+    contents.push(json!({ "role": role, "parts": parts }));
+}
+
+/// Strip JSON Schema keywords Gemini's function declarations reject
+fn sanitize_schema(schema: &Value) -> Value {
+    match schema {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(key, _)| !matches!(key.as_str(), "$schema" | "additionalProperties"))
+                .map(|(key, value)| (key.clone(), sanitize_schema(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(sanitize_schema).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Gemini `functionCallingConfig.mode` for an OpenAI `tool_choice`
+fn function_calling_mode(tool_choice: &Value) -> Option<&'static str> {
+    match tool_choice {
+        Value::String(choice) => match choice.as_str() {
+            "none" => Some("NONE"),
+            "auto" => Some("AUTO"),
+            "required" => Some("ANY"),
+            _ => None,
+        },
+        Value::Object(_) => Some("ANY"),
+        _ => None,
+    }
+}
+
+/// Gemini `generationConfig` from OpenAI sampling parameters
+fn generation_config(request: &Value) -> Map<String, Value> {
+    let mut config = Map::new();
+    if let Some(temperature) = request["temperature"].as_f64() {
+        config.insert("temperature".to_string(), json!(temperature));
+    }
+    if let Some(top_p) = request["top_p"].as_f64() {
+        config.insert("topP".to_string(), json!(top_p));
+    }
+    if let Some(max_tokens) = request["max_completion_tokens"]
+        .as_u64()
+        .or_else(|| request["max_tokens"].as_u64())
+    {
+        config.insert("maxOutputTokens".to_string(), json!(max_tokens));
+    }
+    match &request["stop"] {
+        Value::String(stop) => {
+            config.insert("stopSequences".to_string(), json!([stop]));
+        }
+        Value::Array(stops) => {
+            config.insert("stopSequences".to_string(), json!(stops));
+        }
+        _ => {}
+    }
+    config
+}
+
+/// Text and function calls of a Gemini `content.parts` array
+fn parse_parts(parts: &Value) -> (String, Vec<ChatCompletionMessageToolCall>) {
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+
+    for part in parts.as_array().into_iter().flatten() {
+        // Thinking models interleave thought summaries; they aren't part of the answer
+        if part["thought"].as_bool() == Some(true) {
+            continue;
+        }
+        if let Some(delta) = part["text"].as_str() {
+            text.push_str(delta);
+        }
+        if let Some(call) = part.get("functionCall") {
+            let id = call["id"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple()));
+            tool_calls.push(ChatCompletionMessageToolCall {
+                id,
+                kind: ChatCompletionToolType::Function,
+                function: FunctionCall {
+                    name: call["name"].as_str().unwrap_or_default().to_string(),
+                    arguments: call.get("args").unwrap_or(&json!({})).to_string(),
+                },
+            });
+        }
+    }
+
+    (text, tool_calls)
+}
+
+/// OpenAI `finish_reason` for a Gemini `finishReason`
+fn openai_finish_reason(reason: Option<&str>) -> &'static str {
+    match reason {
+        Some("MAX_TOKENS") => "length",
+        Some("SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII") => {
+            "content_filter"
+        }
+        _ => "stop",
+    }
+}
+
+/// OpenAI `usage` from Gemini `usageMetadata`
+fn openai_usage(metadata: &Value) -> Option<Value> {
+    let prompt_tokens = metadata["promptTokenCount"].as_u64()?;
+    let completion_tokens = metadata["candidatesTokenCount"].as_u64().unwrap_or(0);
+    let total_tokens = metadata["totalTokenCount"]
+        .as_u64()
+        .unwrap_or(prompt_tokens + completion_tokens);
+    Some(json!({
+        "prompt_tokens": prompt_tokens,
+        "completion_tokens": completion_tokens,
+        "total_tokens": total_tokens,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ChatClient, GeminiClient};
+    use crate::config::GeminiConfig;
+    use crate::mock_server::{MockResponse, MockServer};
+    use tokio::sync::mpsc;
+
+    fn request(body: Value) -> CreateChatCompletionRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    fn client(server: &MockServer) -> GeminiClient {
+        GeminiClient::new(&GeminiConfig {
+            id: "gemini".to_string(),
+            api_key: "test-key".to_string(),
+            api_url: server.url().to_string(),
+            proxy: false,
+        })
+    }
+
+    fn chat_request() -> CreateChatCompletionRequest {
+        request(json!({
+            "model": "gemini-2.0-flash",
+            "messages": [
+                { "role": "system", "content": "You are a tutor." },
+                { "role": "user", "content": "What's the weather in Paris?" },
+            ],
+        }))
+    }
+
+    #[test]
+    fn test_request_translation() {
+        let (model, body) = to_gemini_request(&request(json!({
+            "model": "models/gemini-2.0-flash",
+            "temperature": 0.7,
+            "max_tokens": 256,
+            "messages": [
+                { "role": "system", "content": "You are a tutor." },
+                { "role": "user", "content": "Weather in Paris?" },
+                { "role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1", "type": "function",
+                    "function": { "name": "get_weather", "arguments": "{\"city\":\"Paris\"}" },
+                }]},
+                { "role": "tool", "tool_call_id": "call_1", "content": "sunny" },
+            ],
+            "tools": [{ "type": "function", "function": {
+                "name": "get_weather",
+                "description": "Current weather",
+                "parameters": {
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "properties": { "city": { "type": "string" } },
+                    "additionalProperties": false,
+                },
+            }}],
+        })))
+        .unwrap();
+
+        assert_eq!(model, "models/gemini-2.0-flash");
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "You are a tutor.");
+
+        let contents = body["contents"].as_array().unwrap();
+        assert_eq!(contents.len(), 3);
+        assert_eq!(contents[0]["role"], "user");
+        assert_eq!(contents[0]["parts"][0]["text"], "Weather in Paris?");
+        assert_eq!(contents[1]["role"], "model");
+        assert_eq!(contents[1]["parts"][0]["functionCall"]["name"], "get_weather");
+        assert_eq!(contents[1]["parts"][0]["functionCall"]["args"]["city"], "Paris");
+        assert_eq!(contents[2]["role"], "user");
+        let response = &contents[2]["parts"][0]["functionResponse"];
+        assert_eq!(response["name"], "get_weather");
+        assert_eq!(response["response"]["content"], "sunny");
+
+        let declaration = &body["tools"][0]["functionDeclarations"][0];
+        assert_eq!(declaration["name"], "get_weather");
+        assert_eq!(
+            declaration["parameters"],
+            json!({ "type": "object", "properties": { "city": { "type": "string" } } })
+        );
+        assert_eq!(body["generationConfig"]["temperature"], 0.7);
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 256);
+    }
+
+    #[test]
+    fn test_unknown_tool_call_rejected() {
+        let result = to_gemini_request(&request(json!({
+            "model": "gemini-2.0-flash",
+            "messages": [{ "role": "tool", "tool_call_id": "missing", "content": "x" }],
+        })));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_response_translation() {
+        let response = from_gemini_response(
+            &json!({
+                "candidates": [{
+                    "content": { "role": "model", "parts": [
+                        { "text": "Let me check." },
+                        { "functionCall": { "name": "get_weather", "args": { "city": "Paris" } } },
+                    ]},
+                    "finishReason": "STOP",
+                }],
+                "usageMetadata": { "promptTokenCount": 12, "candidatesTokenCount": 5, "totalTokenCount": 17 },
+            }),
+            "gemini-2.0-flash",
+        )
+        .unwrap();
+
+        let value = serde_json::to_value(&response).unwrap();
+        let choice = &value["choices"][0];
+        assert_eq!(choice["finish_reason"], "tool_calls");
+        assert_eq!(choice["message"]["content"], "Let me check.");
+        let call = &choice["message"]["tool_calls"][0];
+        assert_eq!(call["type"], "function");
+        assert_eq!(call["function"]["name"], "get_weather");
+        let args: Value = serde_json::from_str(call["function"]["arguments"].as_str().unwrap()).unwrap();
+        assert_eq!(args, json!({ "city": "Paris" }));
+        assert_eq!(value["usage"]["total_tokens"], 17);
+        assert_eq!(value["model"], "gemini-2.0-flash");
+    }
+
+    #[test]
+    fn test_blocked_prompt_is_an_error() {
+        let err = from_gemini_response(
+            &json!({ "promptFeedback": { "blockReason": "SAFETY" } }),
+            "gemini-2.0-flash",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("SAFETY"));
+    }
+
+    #[tokio::test]
+    async fn test_complete_against_mock_server() {
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::json(200, json!({
+            "candidates": [{
+                "content": { "role": "model", "parts": [{ "text": "Sunny, 24°C." }] },
+                "finishReason": "STOP",
+            }],
+        })));
+
+        let response = client(&server).complete(chat_request()).await.unwrap();
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(value["choices"][0]["message"]["content"], "Sunny, 24°C.");
+        assert_eq!(value["choices"][0]["finish_reason"], "stop");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/models/gemini-2.0-flash:generateContent");
+        assert_eq!(requests[0].headers["x-goog-api-key"], "test-key");
+        assert_eq!(
+            requests[0].json()["systemInstruction"]["parts"][0]["text"],
+            "You are a tutor."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_complete_reports_api_errors() {
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::json(400, json!({ "error": { "message": "bad key" } })));
+
+        let err = client(&server).complete(chat_request()).await.unwrap_err();
+        assert!(err.to_string().contains("bad key"));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_against_mock_server() {
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::sse(&[
+            json!({ "candidates": [{ "content": { "role": "model", "parts": [{ "text": "Checking" }] } }] }),
+            json!({ "candidates": [{ "content": { "role": "model", "parts": [{ "text": " now." }] } }] }),
+            json!({ "candidates": [{
+                "content": { "role": "model", "parts": [
+                    { "functionCall": { "name": "get_weather", "args": { "city": "Paris" } } },
+                ]},
+                "finishReason": "STOP",
+            }],
+            "usageMetadata": { "promptTokenCount": 12, "candidatesTokenCount": 5, "totalTokenCount": 17 },
+            }),
+        ]));
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (text, tool_calls, usage) = client(&server)
+            .complete_streaming_with_cancellation(
+                chat_request(),
+                tx,
+                CancellationToken::new(),
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap();
+
+        let mut chunks = Vec::new();
+        while let Ok(chunk) = rx.try_recv() {
+            chunks.push(chunk);
+        }
+        assert_eq!(chunks, vec!["Checking", " now."]);
+        assert_eq!(text, "Checking now.");
+
+        let tool_calls = tool_calls.unwrap();
+        assert_eq!(tool_calls.len(), 1);
+        assert_eq!(tool_calls[0].function.name, "get_weather");
+        assert_eq!(tool_calls[0].function.arguments, r#"{"city":"Paris"}"#);
+        assert_eq!(usage, Some(Usage::new(12, 5)));
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].path,
+            "/models/gemini-2.0-flash:streamGenerateContent?alt=sse"
+        );
+    }
+}
@@ -0,0 +1,412 @@
+//! Token-budget-aware conversation history
+//!
+//! Sessions are trimmed by exchange count (`max_history_exchanges`) and, when
+//! a model has a `context_budget`, by estimated tokens. Trimming removes whole
+//! turns from the front so an assistant tool call is never kept without its
+//! tool results (or the other way round). The system prompt and the rolling
+//! summary are always kept.
+//!
+//! With a `[summarizer]` configured, trimmed turns are folded into a rolling
+//! summary message by a separate model instead of being forgotten.
+
+use std::ops::Range;
+
+use eyre::{Result, eyre};
+use outfox_openai::spec::{ChatCompletionRequestMessage, CreateChatCompletionRequest};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::client::ChatClient;
+use crate::usage::Usage;
+
+/// Fixed per-message overhead (role, separators) added by chat templates
+const MESSAGE_OVERHEAD: usize = 4;
+
+/// Estimates how many tokens a piece of text takes up
+pub trait TokenEstimator: Send + Sync {
+    fn estimate(&self, text: &str) -> usize;
+
+    /// Estimate a whole message: text content, tool calls and overhead
+    fn estimate_message(&self, message: &ChatCompletionRequestMessage) -> usize {
+        MESSAGE_OVERHEAD + self.estimate(&message_text(message))
+    }
+
+    fn estimate_messages(&self, messages: &[ChatCompletionRequestMessage]) -> usize {
+        messages.iter().map(|m| self.estimate_message(m)).sum()
+    }
+}
+
+/// Tokenizer-free heuristic: one token per CJK character, one per four bytes
+/// of other text
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HeuristicEstimator;
+
+impl TokenEstimator for HeuristicEstimator {
+    fn estimate(&self, text: &str) -> usize {
+        let (cjk, other_bytes) = text.chars().fold((0, 0), |(cjk, other), c| {
+            if is_cjk(c) {
+                (cjk + 1, other)
+            } else {
+                (cjk, other + c.len_utf8())
+            }
+        });
+        cjk + other_bytes.div_ceil(4)
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // Hiragana, Katakana
+        | 0x3400..=0x4DBF   // CJK Extension A
+        | 0x4E00..=0x9FFF   // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF   // Hangul Syllables
+        | 0xF900..=0xFAFF   // CJK Compatibility Ideographs
+        | 0xFF00..=0xFFEF   // Full-width forms
+        | 0x20000..=0x2FFFF // CJK Extensions B+
+    )
+}
+
+/// Text of a message as the model sees it: content parts plus tool call
+/// names and arguments
+pub fn message_text(message: &ChatCompletionRequestMessage) -> String {
+    let value = serde_json::to_value(message).unwrap_or_default();
+    let mut text = match &value["content"] {
+        Value::String(content) => content.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    };
+    for call in value["tool_calls"].as_array().into_iter().flatten() {
+        for field in ["name", "arguments"] {
+            if let Some(s) = call["function"][field].as_str() {
+                text.push('\n');
+                text.push_str(s);
+            }
+        }
+    }
+    text
+}
+
+/// Wire-format role of a message (`user`, `assistant`, `tool`, ...)
+pub fn role(message: &ChatCompletionRequestMessage) -> String {
+    serde_json::to_value(message)
+        .ok()
+        .and_then(|value| value["role"].as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Split messages into turns that must be kept or dropped together.
+///
+/// Each user or assistant message starts a turn; tool (and legacy function)
+/// results belong to the turn of the assistant message that called them.
+pub fn turns(messages: &[ChatCompletionRequestMessage]) -> Vec<Range<usize>> {
+    let mut turns: Vec<Range<usize>> = Vec::new();
+    for (i, message) in messages.iter().enumerate() {
+        let is_result = matches!(role(message).as_str(), "tool" | "function");
+        match turns.last_mut() {
+            Some(turn) if is_result => turn.end = i + 1,
+            _ => turns.push(i..i + 1),
+        }
+    }
+    turns
+}
+
+/// Limits applied when trimming a history
+#[derive(Clone, Copy, Debug)]
+pub struct HistoryLimits {
+    /// Maximum number of trimmable messages (`max_history_exchanges * 2`)
+    pub max_messages: usize,
+    /// Maximum estimated tokens for the whole history, pinned messages included
+    pub token_budget: Option<usize>,
+}
+
+/// Trim `messages[pinned..]` from the front, whole turns at a time, until
+/// the limits are met. The most recent turn is always kept.
+///
+/// Returns the removed messages, oldest first.
+pub fn trim(
+    messages: &mut Vec<ChatCompletionRequestMessage>,
+    pinned: usize,
+    limits: HistoryLimits,
+    estimator: &dyn TokenEstimator,
+) -> Vec<ChatCompletionRequestMessage> {
+    let pinned = pinned.min(messages.len());
+    let turns = turns(&messages[pinned..]);
+    let mut tokens = estimator.estimate_messages(messages);
+
+    let over_limit = |count: usize, tokens: usize| {
+        count > limits.max_messages || limits.token_budget.is_some_and(|budget| tokens > budget)
+    };
+
+    let mut drop_until = 0;
+    for turn in &turns[..turns.len().saturating_sub(1)] {
+        if !over_limit(messages.len() - pinned - drop_until, tokens) {
+            break;
+        }
+        tokens -= estimator.estimate_messages(&messages[pinned + turn.start..pinned + turn.end]);
+        drop_until = turn.end;
+    }
+
+    messages.drain(pinned..pinned + drop_until).collect()
+}
+
+/// Rolling summary of trimmed turns
+#[derive(Clone, Debug, Deserialize)]
+pub struct SummarizerConfig {
+    /// Model ID (from `[[models]]`) used to write summaries
+    pub model: String,
+    /// Upper bound for the summary length
+    #[serde(default = "default_summary_tokens")]
+    pub max_tokens: u32,
+    /// Instructions for the summarizer
+    #[serde(default = "default_summary_prompt")]
+    pub prompt: String,
+}
+
+fn default_summary_tokens() -> u32 {
+    512
+}
+
+fn default_summary_prompt() -> String {
+    "Summarize the conversation below for the assistant that continues it. \
+     Keep names, facts, decisions and open questions; drop small talk. \
+     If a previous summary is given, merge it into the new one. \
+     Reply with the summary only."
+        .to_string()
+}
+
+/// A rolling summary and the usage of the call that produced it
+#[derive(Clone, Debug)]
+pub struct Summary {
+    pub text: String,
+    /// Token usage reported by the provider, if any
+    pub usage: Option<Usage>,
+}
+
+/// Fold trimmed messages into the previous summary using the summarizer model
+pub async fn summarize(
+    client: &dyn ChatClient,
+    config: &SummarizerConfig,
+    previous: Option<&str>,
+    evicted: &[ChatCompletionRequestMessage],
+) -> Result<Summary> {
+    let mut transcript = String::new();
+    if let Some(previous) = previous {
+        transcript.push_str(&format!("Previous summary:\n{}\n\n", previous));
+    }
+    transcript.push_str("Conversation:\n");
+    for message in evicted {
+        transcript.push_str(&format!("{}: {}\n", role(message), message_text(message)));
+    }
+
+    let request: CreateChatCompletionRequest = serde_json::from_value(json!({
+        "model": config.model,
+        "messages": [
+            { "role": "system", "content": config.prompt },
+            { "role": "user", "content": transcript },
+        ],
+        "max_tokens": config.max_tokens,
+        "temperature": 0.2,
+    }))?;
+
+    let response = client.complete(request).await?;
+    let summary = response
+        .choices
+        .first()
+        .and_then(|choice| choice.message.content.clone())
+        .unwrap_or_default();
+    if summary.trim().is_empty() {
+        return Err(eyre!("Summarizer returned an empty summary"));
+    }
+    Ok(Summary {
+        text: summary.trim().to_string(),
+        usage: Usage::from_response(&response),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(value: Value) -> ChatCompletionRequestMessage {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn system() -> ChatCompletionRequestMessage {
+        message(json!({ "role": "system", "content": "You are helpful." }))
+    }
+
+    fn user(text: &str) -> ChatCompletionRequestMessage {
+        message(json!({ "role": "user", "content": text }))
+    }
+
+    fn assistant(text: &str) -> ChatCompletionRequestMessage {
+        message(json!({ "role": "assistant", "content": text }))
+    }
+
+    fn tool_call(id: &str) -> ChatCompletionRequestMessage {
+        message(json!({
+            "role": "assistant",
+            "tool_calls": [{
+                "id": id,
+                "type": "function",
+                "function": { "name": "get_weather", "arguments": "{\"city\":\"Paris\"}" },
+            }],
+        }))
+    }
+
+    fn tool_result(id: &str) -> ChatCompletionRequestMessage {
+        message(json!({ "role": "tool", "tool_call_id": id, "content": "Sunny" }))
+    }
+
+    fn roles(messages: &[ChatCompletionRequestMessage]) -> Vec<String> {
+        messages.iter().map(role).collect()
+    }
+
+    #[test]
+    fn test_heuristic_counts_cjk_per_character() {
+        let estimator = HeuristicEstimator;
+        assert_eq!(estimator.estimate(""), 0);
+        assert_eq!(estimator.estimate("abcdefgh"), 2);
+        assert_eq!(estimator.estimate("abcdefghi"), 3);
+        assert_eq!(estimator.estimate("你好世界"), 4);
+        assert_eq!(estimator.estimate("你好 world"), 2 + 2);
+        assert!(estimator.estimate_message(&tool_call("call_1")) > MESSAGE_OVERHEAD);
+    }
+
+    #[test]
+    fn test_turns_keep_tool_results_with_their_call() {
+        let messages = vec![
+            user("weather?"),
+            tool_call("call_1"),
+            tool_result("call_1"),
+            assistant("Sunny in Paris"),
+        ];
+        assert_eq!(turns(&messages), vec![0..1, 1..3, 3..4]);
+    }
+
+    #[test]
+    fn test_trim_by_message_count() {
+        let mut messages = vec![
+            system(),
+            user("1"),
+            assistant("1"),
+            user("2"),
+            assistant("2"),
+            user("3"),
+        ];
+        let limits = HistoryLimits {
+            max_messages: 3,
+            token_budget: None,
+        };
+        let removed = trim(&mut messages, 1, limits, &HeuristicEstimator);
+        assert_eq!(roles(&removed), vec!["user", "assistant"]);
+        assert_eq!(
+            roles(&messages),
+            vec!["system", "user", "assistant", "user"]
+        );
+    }
+
+    #[test]
+    fn test_trim_never_orphans_tool_results() {
+        let mut messages = vec![
+            system(),
+            user("weather?"),
+            tool_call("call_1"),
+            tool_result("call_1"),
+            assistant("Sunny in Paris"),
+            user("thanks"),
+        ];
+        let limits = HistoryLimits {
+            max_messages: 3,
+            token_budget: None,
+        };
+        let removed = trim(&mut messages, 1, limits, &HeuristicEstimator);
+        assert_eq!(roles(&removed), vec!["user", "assistant", "tool"]);
+        assert_eq!(roles(&messages), vec!["system", "assistant", "user"]);
+    }
+
+    #[test]
+    fn test_trim_to_token_budget() {
+        let long = "word ".repeat(100);
+        let mut messages = vec![
+            system(),
+            user(&long),
+            assistant(&long),
+            user("short question"),
+        ];
+        let estimator = HeuristicEstimator;
+        let budget = estimator.estimate_messages(&messages[..1])
+            + estimator.estimate_messages(&messages[2..]);
+        let limits = HistoryLimits {
+            max_messages: 100,
+            token_budget: Some(budget),
+        };
+
+        let removed = trim(&mut messages, 1, limits, &estimator);
+        assert_eq!(roles(&removed), vec!["user"]);
+        assert!(estimator.estimate_messages(&messages) <= budget);
+
+        // The latest turn survives even when it alone exceeds the budget
+        let limits = HistoryLimits {
+            max_messages: 100,
+            token_budget: Some(1),
+        };
+        trim(&mut messages, 1, limits, &estimator);
+        assert_eq!(roles(&messages), vec!["system", "user"]);
+    }
+
+    #[tokio::test]
+    async fn test_summarize_folds_previous_summary() {
+        use crate::client::OpenaiClient;
+        use crate::config::OpenaiConfig;
+        use crate::mock_server::{MockResponse, MockServer};
+
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::json(
+            200,
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 0,
+                "model": "mock",
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": " Alice asked about Paris. " },
+                    "finish_reason": "stop",
+                }],
+                "usage": { "prompt_tokens": 120, "completion_tokens": 8, "total_tokens": 128 },
+            }),
+        ));
+        let client = OpenaiClient::new(&OpenaiConfig {
+            id: "summarizer".to_string(),
+            api_key: "test-key".to_string(),
+            api_url: server.url().to_string(),
+            proxy: false,
+            json_schema: true,
+        });
+        let config: SummarizerConfig =
+            serde_json::from_value(json!({ "model": "qwen-turbo" })).unwrap();
+
+        let summary = summarize(
+            &client,
+            &config,
+            Some("The user is called Alice."),
+            &[user("weather in Paris?"), assistant("Sunny")],
+        )
+        .await
+        .unwrap();
+        assert_eq!(summary.text, "Alice asked about Paris.");
+        assert_eq!(summary.usage, Some(Usage::new(120, 8)));
+
+        let body = server.requests()[0].json();
+        assert_eq!(body["model"], "qwen-turbo");
+        assert_eq!(body["max_tokens"], 512);
+        let transcript = body["messages"][1]["content"].as_str().unwrap();
+        assert!(transcript.contains("Previous summary:\nThe user is called Alice."));
+        assert!(transcript.contains("user: weather in Paris?\nassistant: Sunny"));
+    }
+}
@@ -3,7 +3,7 @@
 //! This node provides cloud AI integration for Dora applications, serving as a
 //! drop-in replacement for local LLM nodes. It features:
 //!
-//! - Multi-provider support (OpenAI, Gemini, etc.)
+//! - Multi-provider support (OpenAI, Gemini, Anthropic, Ollama, etc.)
 //! - Real-time streaming with SSE
 //! - Intelligent text segmentation for TTS
 //! - Session-based conversation management
@@ -27,6 +27,8 @@ use dora_node_api::{
     dora_core::config::DataId,
 };
 use eyre::{Context, Result};
+use mofa_control::ControlMessage;
+use mofa_stream::{StreamMeta, StreamStatus, StreamWriter};
 use outfox_openai::spec::{
     ChatCompletionMessageToolCall, ChatCompletionRequestAssistantMessage,
     ChatCompletionRequestAssistantMessageContent, ChatCompletionRequestMessage,
@@ -38,15 +40,35 @@ use serde_json::json;
 use tokio::sync::Mutex as AsyncMutex;
 use tokio_util::sync::CancellationToken;
 
+mod anthropic;
+mod builtin_tools;
 mod client;
 mod config;
+mod failover;
+mod fixture;
+mod gemini;
+mod history;
+#[cfg(test)]
+mod mock_server;
+mod ollama;
+mod reload;
 mod segmenter;
+mod session_store;
 mod streaming;
+mod structured;
 mod tool;
+mod usage;
+mod wire;
 
 use config::{Config, load_anchor_context, format_anchor_context};
+use failover::FailoverClient;
+use history::{HeuristicEstimator, HistoryLimits, TokenEstimator};
+use reload::ConfigWatcher;
 use segmenter::StreamSegmenter;
+use session_store::{SessionStore, StoredSession};
+use structured::ResponseFormat;
 use tool::ToolSet;
+use usage::{RequestMetrics, RequestTimer, Usage, UsageTotals};
 
 // Import CancellationReason from streaming module
 use crate::streaming::CancellationReason;
@@ -68,6 +90,98 @@ fn send_log(node: &mut DoraNode, level: &str, message: &str) -> Result<()> {
     Ok(())
 }
 
+// Forward pending provider retry/failover notices to the log output
+fn report_failovers(
+    node: &mut DoraNode,
+    notices: &mut tokio::sync::mpsc::UnboundedReceiver<String>,
+) -> Result<()> {
+    while let Ok(notice) = notices.try_recv() {
+        send_log(node, "WARNING", &notice)?;
+    }
+    Ok(())
+}
+
+// Send on `output` with the input's passthrough metadata plus stream metadata
+fn send_with_meta(
+    node: &mut DoraNode,
+    output: &str,
+    passthrough: &BTreeMap<String, Parameter>,
+    meta: &StreamMeta,
+    text: &str,
+) -> Result<()> {
+    let mut parameters = passthrough.clone();
+    meta.write_to(&mut parameters);
+    node.send_output(
+        DataId::from(output.to_string()),
+        parameters,
+        StringArray::from(vec![text]),
+    )
+    .with_context(|| format!("Failed to send {} output", output))?;
+    Ok(())
+}
+
+// Send a `text` chunk
+fn send_text(
+    node: &mut DoraNode,
+    passthrough: &BTreeMap<String, Parameter>,
+    meta: &StreamMeta,
+    text: &str,
+) -> Result<()> {
+    send_with_meta(node, "text", passthrough, meta, text)
+}
+
+// Start the stream answering an input, tagged with the input's question_id
+fn stream_writer(
+    session_id: &str,
+    request_id: &str,
+    passthrough: &BTreeMap<String, Parameter>,
+) -> StreamWriter {
+    let question_id = match passthrough.get("question_id") {
+        Some(Parameter::String(s)) => Some(s.clone()),
+        Some(Parameter::Integer(i)) => Some(i.to_string()),
+        _ => None,
+    };
+    StreamWriter::new(session_id, request_id).with_question_id(question_id)
+}
+
+// Classify a failed request: "cancelled", "timeout" or "error"
+fn error_type(error_msg: &str) -> &'static str {
+    if error_msg.contains("cancelled") {
+        "cancelled"
+    } else if error_msg.contains("timed out") {
+        "timeout"
+    } else {
+        "error"
+    }
+}
+
+// Close a stream with an error chunk (error_type/error_message are added)
+fn send_error_text(
+    node: &mut DoraNode,
+    passthrough: &BTreeMap<String, Parameter>,
+    writer: &mut StreamWriter,
+    error_msg: &str,
+    text: &str,
+) -> Result<()> {
+    let error_type = error_type(error_msg);
+    let status = if error_type == "cancelled" {
+        StreamStatus::Cancelled
+    } else {
+        StreamStatus::Error
+    };
+
+    let mut error_metadata = passthrough.clone();
+    error_metadata.insert(
+        "error_type".to_string(),
+        Parameter::String(error_type.to_string()),
+    );
+    error_metadata.insert(
+        "error_message".to_string(),
+        Parameter::String(error_msg.to_string()),
+    );
+    send_text(node, &error_metadata, &writer.finish(status, Some(error_type)), text)
+}
+
 /// Manages active request cancellation tokens
 struct RequestCancellationManager {
     /// Active tokens by request_id
@@ -167,10 +281,37 @@ struct ChatSession {
     messages: Vec<ChatCompletionRequestMessage>,
     total_tokens: usize,
     tool_set: Option<Arc<Mutex<ToolSet>>>,
+    estimator: Box<dyn TokenEstimator>,
+    // Rolling summary of trimmed turns, kept as a system message after the prompt
+    summary: Option<String>,
+    // Trimmed messages waiting to be folded into the summary
+    evicted: Vec<ChatCompletionRequestMessage>,
+    keep_evicted: bool,
+    // Provider token usage and cost, kept across resets
+    usage: UsageTotals,
 }
 
 impl ChatSession {
     fn new(system_prompt: String, anchor_context: Option<String>) -> Self {
+        let system_message = Self::system_message(system_prompt, anchor_context);
+
+        let estimator = Box::new(HeuristicEstimator);
+        Self {
+            total_tokens: estimator.estimate_messages(std::slice::from_ref(&system_message)),
+            messages: vec![system_message],
+            tool_set: None, // Will be set separately
+            estimator,
+            summary: None,
+            evicted: Vec::new(),
+            keep_evicted: false,
+            usage: UsageTotals::default(),
+        }
+    }
+
+    fn system_message(
+        system_prompt: String,
+        anchor_context: Option<String>,
+    ) -> ChatCompletionRequestMessage {
         // Combine system prompt with anchor context if provided
         let combined_prompt = if let Some(context) = anchor_context {
             format!("{}\n\n{}", context, system_prompt)
@@ -178,17 +319,21 @@ impl ChatSession {
             system_prompt
         };
 
-        let system_message =
-            ChatCompletionRequestMessage::System(ChatCompletionRequestSystemMessage {
-                content: PartibleTextContent::Text(combined_prompt),
-                name: None,
-            });
+        ChatCompletionRequestMessage::System(ChatCompletionRequestSystemMessage {
+            content: PartibleTextContent::Text(combined_prompt),
+            name: None,
+        })
+    }
 
-        Self {
-            messages: vec![system_message],
-            total_tokens: 0,
-            tool_set: None, // Will be set separately
-        }
+    /// Replace the system prompt, keeping the conversation
+    fn set_system_prompt(&mut self, system_prompt: String, anchor_context: Option<String>) {
+        self.messages[0] = Self::system_message(system_prompt, anchor_context);
+        self.total_tokens = self.estimator.estimate_messages(&self.messages);
+    }
+
+    /// Keep trimmed turns so they can be summarized
+    fn enable_summary(&mut self) {
+        self.keep_evicted = true;
     }
 
     fn set_tool_set(&mut self, tool_set: Arc<Mutex<ToolSet>>) {
@@ -271,21 +416,168 @@ impl ChatSession {
         self.messages.push(message);
     }
 
-    fn manage_history(&mut self, max_exchanges: usize) {
-        // Keep system message + last N exchanges (N*2 messages)
-        let max_messages = 1 + (max_exchanges * 2);
-        if self.messages.len() > max_messages {
-            let excess = self.messages.len() - max_messages;
-            // Remove old messages but keep system prompt
-            self.messages.drain(1..=excess);
+    fn manage_history(&mut self, limits: HistoryLimits) {
+        // Keep system message (and summary) + whole turns within the limits
+        let pinned = 1 + usize::from(self.summary.is_some());
+        let removed = history::trim(&mut self.messages, pinned, limits, self.estimator.as_ref());
+        if self.keep_evicted {
+            self.evicted.extend(removed);
+        }
+        self.total_tokens = self.estimator.estimate_messages(&self.messages);
+    }
+
+    /// Estimated usage of a reply to the current messages
+    fn estimate_usage(&self, completion: &str) -> Usage {
+        Usage::new(
+            self.estimator.estimate_messages(&self.messages) as u64,
+            self.estimator.estimate(completion) as u64,
+        )
+    }
+
+    fn take_evicted(&mut self) -> Vec<ChatCompletionRequestMessage> {
+        std::mem::take(&mut self.evicted)
+    }
+
+    fn set_summary(&mut self, summary: String) {
+        let message =
+            ChatCompletionRequestMessage::System(ChatCompletionRequestSystemMessage {
+                content: PartibleTextContent::Text(format!(
+                    "Summary of the earlier conversation:\n{}",
+                    summary
+                )),
+                name: None,
+            });
+        if self.summary.is_some() {
+            self.messages[1] = message;
+        } else {
+            self.messages.insert(1, message);
         }
+        self.summary = Some(summary);
+        self.total_tokens = self.estimator.estimate_messages(&self.messages);
     }
 
     fn reset(&mut self) {
         // Keep only system message
         self.messages.truncate(1);
-        self.total_tokens = 0;
+        self.summary = None;
+        self.evicted.clear();
+        self.total_tokens = self.estimator.estimate_messages(&self.messages);
+    }
+
+    /// Conversation (without system prompt and summary) for the session store
+    fn to_stored(&self, session_id: &str) -> StoredSession {
+        let pinned = 1 + usize::from(self.summary.is_some());
+        StoredSession::new(
+            session_id,
+            self.summary.clone(),
+            self.messages[pinned..].to_vec(),
+        )
+    }
+
+    /// Replace the conversation with a stored one, keeping the current system prompt
+    fn restore(&mut self, stored: StoredSession) {
+        self.reset();
+        if let Some(summary) = stored.summary {
+            self.set_summary(summary);
+        }
+        self.messages.extend(stored.messages);
+        self.total_tokens = self.estimator.estimate_messages(&self.messages);
+    }
+}
+
+fn new_session(
+    config: &Config,
+    anchor_context: &Option<String>,
+    tool_set: &Option<Arc<Mutex<ToolSet>>>,
+) -> ChatSession {
+    let mut session = ChatSession::new(config.system_prompt.clone(), anchor_context.clone());
+    if let Some(ts) = tool_set {
+        session.set_tool_set(ts.clone());
+    }
+    if config.summarizer.is_some() {
+        session.enable_summary();
+    }
+    session
+}
+
+/// Persist a session after a completed exchange, if a session store is configured
+fn persist_session(
+    store: Option<&SessionStore>,
+    session_id: &str,
+    session: &ChatSession,
+    node: &mut DoraNode,
+) -> Result<()> {
+    if let Some(store) = store {
+        if let Err(e) = store.save(&session.to_stored(session_id)) {
+            send_log(node, "WARNING", &format!("Failed to save session {}: {}", session_id, e))?;
+        }
     }
+    Ok(())
+}
+
+/// Emit a request's usage, latency and cost on the `metrics` output.
+///
+/// Model and prices are those of the route of `client` that answered.
+fn send_metrics(
+    node: &mut DoraNode,
+    config: &Config,
+    client: &FailoverClient,
+    session_id: &str,
+    session: &mut ChatSession,
+    metrics: &RequestMetrics,
+) -> Result<()> {
+    let (model, pricing) = match client.served_route() {
+        Some(route) => (route.model.as_str(), route.pricing.as_ref()),
+        None => (
+            config.default_model.as_str(),
+            config.pricing(&config.default_model),
+        ),
+    };
+    let record = session.usage.record(session_id, model, metrics, pricing);
+    node.send_output(
+        DataId::from("metrics".to_string()),
+        Default::default(),
+        StringArray::from(vec![record.to_string().as_str()]),
+    )
+    .context("Failed to send metrics output")?;
+    Ok(())
+}
+
+/// Fold turns trimmed from a session into its rolling summary
+async fn update_summary(
+    node: &mut DoraNode,
+    session_id: &str,
+    session: &mut ChatSession,
+    config: &Config,
+    routers: &HashMap<String, Arc<FailoverClient>>,
+) -> Result<()> {
+    let Some(summarizer) = &config.summarizer else {
+        return Ok(());
+    };
+    let evicted = session.take_evicted();
+    if evicted.is_empty() {
+        return Ok(());
+    }
+
+    let client = routers.get(&summarizer.model).ok_or_else(|| {
+        eyre::eyre!("No client found for summarizer model: {}", summarizer.model)
+    })?;
+    let timer = RequestTimer::start();
+    let summary =
+        history::summarize(client.as_ref(), summarizer, session.summary.as_deref(), &evicted)
+            .await?;
+    let metrics = timer.finish(summary.usage, || {
+        Usage::new(
+            session.estimator.estimate_messages(&evicted) as u64,
+            session.estimator.estimate(&summary.text) as u64,
+        )
+    });
+    send_metrics(node, config, client, session_id, session, &metrics)?;
+
+    session.set_summary(summary.text);
+    // The summary itself takes up budget
+    session.manage_history(config.history_limits());
+    Ok(())
 }
 
 /// Load and format anchor context for a given configuration
@@ -308,6 +600,160 @@ fn load_anchor_context_for_session(config: &Config) -> Option<String> {
     }
 }
 
+// Re-read the configuration file: rebuild provider clients and routers and
+// update the system prompt of existing sessions, keeping their history. An
+// invalid file is reported on the log output and the current configuration
+// stays active. MCP and built-in tools are not re-initialized.
+fn reload_config(
+    node: &mut DoraNode,
+    config: &mut Config,
+    anchor_context: &mut Option<String>,
+    routers: &mut HashMap<String, Arc<FailoverClient>>,
+    failover_tx: &tokio::sync::mpsc::UnboundedSender<String>,
+    sessions: &mut HashMap<String, ChatSession>,
+) -> Result<bool> {
+    let path = Config::path();
+    let new_config = match Config::load_from(&path).and_then(|c| c.validate().map(|_| c)) {
+        Ok(new_config) => new_config,
+        Err(e) => {
+            send_log(
+                node,
+                "ERROR",
+                &format!("Config reload from {} rejected: {}", path.display(), e),
+            )?;
+            return Ok(false);
+        }
+    };
+
+    let clients = new_config.create_clients();
+    *routers = new_config.create_routers(&clients, failover_tx);
+    *anchor_context = load_anchor_context_for_session(&new_config);
+
+    for session in sessions.values_mut() {
+        session.set_system_prompt(new_config.system_prompt.clone(), anchor_context.clone());
+        if new_config.summarizer.is_some() {
+            session.enable_summary();
+        }
+        session.manage_history(new_config.history_limits());
+    }
+
+    if new_config.enable_tools != config.enable_tools || new_config.mcp.is_some() != config.mcp.is_some() {
+        send_log(node, "WARNING", "Tool settings changed; restart the node to apply them")?;
+    }
+    send_log(
+        node,
+        "INFO",
+        &format!(
+            "Reloaded config from {}: model {}, {} provider(s), {} session(s) updated",
+            path.display(),
+            new_config.default_model,
+            new_config.providers.len(),
+            sessions.len()
+        ),
+    )?;
+    *config = new_config;
+    Ok(true)
+}
+
+// Answer with a reply matching `format`: the validated JSON goes on
+// `structured` and its `text_field` on `text`, both closed by the same
+// terminal metadata. Replies are validated whole, so they are not streamed,
+// and tools are not offered.
+#[allow(clippy::too_many_arguments)]
+async fn respond_structured(
+    node: &mut DoraNode,
+    client: &FailoverClient,
+    mut request: CreateChatCompletionRequest,
+    format: &ResponseFormat,
+    config: &Config,
+    session_id: &str,
+    session: &mut ChatSession,
+    passthrough: &BTreeMap<String, Parameter>,
+    failover_rx: &mut tokio::sync::mpsc::UnboundedReceiver<String>,
+    session_store: Option<&SessionStore>,
+) -> Result<()> {
+    request.stream = None;
+    request.tools = None;
+
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let mut writer = stream_writer(session_id, &request_id, passthrough);
+    let timer = RequestTimer::start();
+    let result = structured::complete(client, request, format).await;
+    report_failovers(node, failover_rx)?;
+
+    let reply = match result {
+        Ok(reply) => reply,
+        Err(e) => {
+            let error_msg = format!("{}", e);
+            send_log(node, "ERROR", &error_msg)?;
+            node.send_output(
+                DataId::from("status".to_string()),
+                Default::default(),
+                StringArray::from(vec![format!("{}: {}", error_type(&error_msg), e)]),
+            )
+            .context("Failed to send status output")?;
+            return send_error_text(node, passthrough, &mut writer, &error_msg, &error_msg);
+        }
+    };
+
+    for problem in &reply.rejected {
+        send_log(node, "WARNING", &format!("Rejected structured reply: {}", problem))?;
+    }
+    send_log(
+        node,
+        "INFO",
+        &format!(
+            "Generated structured reply ({} chars, {} repair(s))",
+            reply.content.len(),
+            reply.repairs
+        ),
+    )?;
+
+    let metrics = timer.finish(reply.usage, || session.estimate_usage(&reply.content));
+    send_metrics(node, config, client, session_id, session, &metrics)?;
+
+    let json_text = reply.value.to_string();
+    session.add_assistant_message(json_text.clone());
+    persist_session(session_store, session_id, session, node)?;
+
+    if let Some(spoken) = format.spoken_text(&reply.value).filter(|t| !t.is_empty()) {
+        send_text(node, passthrough, &writer.chunk(), &spoken)?;
+    }
+    node.send_output(
+        DataId::from("status".to_string()),
+        Default::default(),
+        StringArray::from(vec!["complete"]),
+    )
+    .context("Failed to send status output")?;
+
+    let end = writer.finish(
+        StreamStatus::Ended,
+        Some(reply.finish_reason.as_deref().unwrap_or("stop")),
+    );
+    send_with_meta(node, "structured", passthrough, &end, &json_text)?;
+    send_text(node, passthrough, &end, "")
+}
+
+// Resolve the configured response format; an unusable one disables
+// structured replies
+fn load_response_format(config: &Config) -> Option<ResponseFormat> {
+    let format = config.response_format.as_ref()?;
+    match format.load() {
+        Ok(format) => Some(format),
+        Err(e) => {
+            eprintln!("Warning: Structured output disabled: {}", e);
+            None
+        }
+    }
+}
+
+// Watcher for the config file, if `watch_config` is enabled
+fn config_watcher(config: &Config) -> Option<ConfigWatcher> {
+    config.watch_config.then(|| {
+        ConfigWatcher::new(Config::path(), Duration::from_secs(config.watch_interval_secs))
+    })
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Check if running as dynamic node with --name argument
@@ -319,13 +765,17 @@ async fn main() -> Result<()> {
     };
 
     // Load configuration
-    let config = Config::load().context("Failed to load configuration")?;
+    let mut config = Config::load().context("Failed to load configuration")?;
+    let mut watcher = config_watcher(&config);
 
     // Log level is available for future use
     let _log_level = &config.log_level;
 
     // Load anchor context if configured
-    let anchor_context = load_anchor_context_for_session(&config);
+    let mut anchor_context = load_anchor_context_for_session(&config);
+
+    // JSON-schema constrained replies, if configured
+    let mut response_format = load_response_format(&config);
 
     // Initialize MCP tools if enabled
     let tool_set = if config.enable_tools {
@@ -344,8 +794,19 @@ async fn main() -> Result<()> {
         None
     };
 
-    // Create provider clients
+    // Create provider clients and per-model failover routers
     let clients = config.create_clients();
+    let (failover_tx, mut failover_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let mut routers = config.create_routers(&clients, &failover_tx);
+
+    // Open the session store if configured
+    let session_store = config.session_dir.as_ref().and_then(|dir| match SessionStore::open(dir) {
+        Ok(store) => Some(store),
+        Err(e) => {
+            eprintln!("Warning: Session persistence disabled: {}", e);
+            None
+        }
+    });
 
     // Initialize cancellation manager if enabled
     let cancellation_manager = Arc::new(RequestCancellationManager::new());
@@ -383,14 +844,64 @@ async fn main() -> Result<()> {
         "INFO",
         &format!("Providers: {}", config.providers.len()),
     )?;
+    if let Some(format) = &response_format {
+        send_log(
+            &mut node,
+            "INFO",
+            &format!("Structured output: schema '{}'", format.name),
+        )?;
+    }
 
     // Session storage
     let mut sessions: HashMap<String, ChatSession> = HashMap::new();
 
+    // Reload persisted sessions
+    if let Some(store) = &session_store {
+        match store.load_all() {
+            Ok(stored_sessions) => {
+                for stored in stored_sessions {
+                    match stored {
+                        Ok(stored) => {
+                            let mut session = new_session(&config, &anchor_context, &tool_set);
+                            let session_id = stored.session_id.clone();
+                            session.restore(stored);
+                            session.manage_history(config.history_limits());
+                            sessions.insert(session_id, session);
+                        }
+                        Err(e) => {
+                            send_log(&mut node, "WARNING", &format!("Skipping stored session: {}", e))?;
+                        }
+                    }
+                }
+                send_log(
+                    &mut node,
+                    "INFO",
+                    &format!(
+                        "Restored {} session(s) from {}",
+                        sessions.len(),
+                        store.dir().display()
+                    ),
+                )?;
+            }
+            Err(e) => {
+                send_log(&mut node, "WARNING", &format!("Failed to list stored sessions: {}", e))?;
+            }
+        }
+    }
+
     // Process events
     let events = futures::executor::block_on_stream(events);
 
     for event in events {
+        // Pick up config file changes before handling the event
+        if watcher.as_mut().is_some_and(ConfigWatcher::poll) {
+            send_log(&mut node, "INFO", "Config file changed, reloading")?;
+            if reload_config(&mut node, &mut config, &mut anchor_context, &mut routers, &failover_tx, &mut sessions)? {
+                watcher = config_watcher(&config);
+                response_format = load_response_format(&config);
+            }
+        }
+
         match event {
             Event::Input { id, data, metadata } => {
                 // Extract session ID from metadata
@@ -423,13 +934,7 @@ async fn main() -> Result<()> {
                         }
 
                         // Get or create session
-                        let session = sessions.entry(session_id.clone()).or_insert_with(|| {
-                            let mut session = ChatSession::new(config.system_prompt.clone(), anchor_context.clone());
-                            if let Some(ref ts) = tool_set {
-                                session.set_tool_set(ts.clone());
-                            }
-                            session
-                        });
+                        let session = sessions.entry(session_id.clone()).or_insert_with(|| new_session(&config, &anchor_context, &tool_set));
 
                         let role = metadata
                             .parameters
@@ -446,7 +951,7 @@ async fn main() -> Result<()> {
                                 &format!("Caching assistant context: {}", user_text),
                             )?;
                             session.add_assistant_message(user_text.clone());
-                            session.manage_history(config.max_history_exchanges);
+                            session.manage_history(config.history_limits());
                             continue;
                         }
 
@@ -456,7 +961,19 @@ async fn main() -> Result<()> {
                         session.add_user_message(user_text.clone());
 
                         // Manage history
-                        session.manage_history(config.max_history_exchanges);
+                        session.manage_history(config.history_limits());
+                        if let Err(e) = update_summary(&mut node, &session_id, session, &config, &routers).await {
+                            send_log(&mut node, "WARNING", &format!("Failed to summarize history: {}", e))?;
+                        }
+                        send_log(
+                            &mut node,
+                            "DEBUG",
+                            &format!(
+                                "History: {} messages, ~{} tokens",
+                                session.messages.len(),
+                                session.total_tokens
+                            ),
+                        )?;
 
                         // Process the conversation with automatic tool handling
                         // FIX: Added loop to handle tool calls without waiting for user input
@@ -521,8 +1038,8 @@ async fn main() -> Result<()> {
                             request.stream = config.enable_streaming;
                             request.temperature = Some(0.7);
 
-                            let client = clients.get(&provider_id).ok_or_else(|| {
-                                eyre::eyre!("No client found for provider: {}", provider_id)
+                            let client = routers.get(&config.default_model).ok_or_else(|| {
+                                eyre::eyre!("No client found for model: {}", config.default_model)
                             })?;
 
                             send_log(
@@ -542,19 +1059,33 @@ async fn main() -> Result<()> {
                             )
                             .context("Failed to send status output")?;
 
-                            // Make API call - use streaming if enabled
-                            if config.enable_streaming.unwrap_or(false) {
+                            // Make API call - structured, streaming or plain
+                            if let Some(format) = &response_format {
+                                respond_structured(
+                                    &mut node,
+                                    client,
+                                    request,
+                                    format,
+                                    &config,
+                                    &session_id,
+                                    session,
+                                    &metadata.parameters,
+                                    &mut failover_rx,
+                                    session_store.as_ref(),
+                                )
+                                .await?;
+                            } else if config.enable_streaming.unwrap_or(false) {
                                 // Streaming mode
                                 send_log(&mut node, "DEBUG", "Using streaming mode")?;
 
                                 let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
-                                let mut has_sent_segment = false;
-                                let mut segment_index: u32 = 0;
 
                                 // Start streaming in background with cancellation support
                                 let client_clone = client.clone();
                                 let request_clone = request.clone();
                                 let request_id = uuid::Uuid::new_v4().to_string();
+                                let mut writer =
+                                    stream_writer(&session_id, &request_id, &metadata.parameters);
                                 let session_id_clone = session_id.clone();
                                 let metadata_clone = metadata.parameters.clone();
                                 let cancellation_manager_clone = cancellation_manager.clone();
@@ -569,6 +1100,7 @@ async fn main() -> Result<()> {
                                     None
                                 };
 
+                                let mut timer = RequestTimer::start();
                                 let stream_handle = tokio::spawn(async move {
                                     let result = if let Some(token) = cancellation_token {
                                         // Use cancellation-aware streaming
@@ -601,6 +1133,7 @@ async fn main() -> Result<()> {
 
                                 while let Some(chunk) = rx.recv().await {
                                     chunk_count += 1;
+                                    timer.chunk_received();
 
                                     // Add chunk to segmenter and check if we have a segment ready
                                     if let Some(segment) = segmenter.add_chunk(&chunk) {
@@ -608,30 +1141,7 @@ async fn main() -> Result<()> {
                                         segment_count += 1;
 
                                         // Send the meaningful segment with metadata passthrough
-                                        let mut segment_metadata = metadata.parameters.clone();
-                                        segment_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String(
-                                                if !has_sent_segment {
-                                                    "started".to_string()
-                                                } else {
-                                                    "ongoing".to_string()
-                                                },
-                                            ),
-                                        );
-                                        segment_metadata.insert(
-                                            "segment_index".to_string(),
-                                            Parameter::String(segment_index.to_string()),
-                                        );
-
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            segment_metadata,
-                                            StringArray::from(vec![segment.as_str()]),
-                                        )
-                                        .context("Failed to send text segment")?;
-                                        has_sent_segment = true;
-                                        segment_index += 1;
+                                        send_text(&mut node, &metadata.parameters, &writer.chunk(), &segment)?;
                                     }
                                 }
 
@@ -640,30 +1150,7 @@ async fn main() -> Result<()> {
                                     accumulated.push_str(&final_segment);
                                     segment_count += 1;
 
-                                    let mut segment_metadata = metadata.parameters.clone();
-                                    segment_metadata.insert(
-                                        "session_status".to_string(),
-                                        Parameter::String(
-                                            if !has_sent_segment {
-                                                "started".to_string()
-                                            } else {
-                                                "ongoing".to_string()
-                                            },
-                                        ),
-                                    );
-                                    segment_metadata.insert(
-                                        "segment_index".to_string(),
-                                        Parameter::String(segment_index.to_string()),
-                                    );
-
-                                    node.send_output(
-                                        DataId::from("text".to_string()),
-                                        segment_metadata,
-                                        StringArray::from(vec![final_segment.as_str()]),
-                                    )
-                                    .context("Failed to send final segment")?;
-                                    has_sent_segment = true;
-                                    segment_index += 1;
+                                    send_text(&mut node, &metadata.parameters, &writer.chunk(), &final_segment)?;
 
                                     send_log(
                                         &mut node,
@@ -677,8 +1164,10 @@ async fn main() -> Result<()> {
                                 }
 
                                 // Wait for streaming to complete
-                                match stream_handle.await {
-                                    Ok(Ok((final_text, tool_calls))) => {
+                                let stream_result = stream_handle.await;
+                                report_failovers(&mut node, &mut failover_rx)?;
+                                match stream_result {
+                                    Ok(Ok((final_text, tool_calls, usage))) => {
                                         send_log(
                                             &mut node,
                                             "INFO",
@@ -690,6 +1179,9 @@ async fn main() -> Result<()> {
                                             ),
                                         )?;
 
+                                        let metrics = timer.finish(usage, || session.estimate_usage(&final_text));
+                                        send_metrics(&mut node, &config, client, &session_id, session, &metrics)?;
+
                                         // Send "complete" status
                                         node.send_output(
                                             DataId::from("status".to_string()),
@@ -698,6 +1190,18 @@ async fn main() -> Result<()> {
                                     )
                                     .context("Failed to send status output")?;
 
+                                    // Close the stream; a tool-call-only turn sent no text
+                                    if writer.has_started() {
+                                        let finish_reason =
+                                            if tool_calls.is_some() { "tool_calls" } else { "stop" };
+                                        send_text(
+                                            &mut node,
+                                            &metadata.parameters,
+                                            &writer.finish(StreamStatus::Ended, Some(finish_reason)),
+                                            "",
+                                        )?;
+                                    }
+
                                     // FIX: Handle tool calls from streaming response
                                     // When the LLM returns tool calls, we either execute them locally (enable_local_mcp=true)
                                     // or pass them back to the client (enable_local_mcp=false)
@@ -756,7 +1260,7 @@ async fn main() -> Result<()> {
                                                                 .unwrap_or(serde_json::Value::Null);
 
                                                             // Execute the tool
-                                                            match tool.call(args).await {
+                                                            match tool.call_in_session(&session_id, args).await {
                                                                 Ok(result) => {
                                                                     let content = if let Some(
                                                                         contents,
@@ -853,6 +1357,7 @@ async fn main() -> Result<()> {
                                         } else {
                                             // No tool calls, just add the text message
                                             session.add_assistant_message(final_text.clone());
+                                            persist_session(session_store.as_ref(), &session_id, session, &mut node)?;
                                         }
                                     }
                                     Ok(Err(e)) => {
@@ -863,54 +1368,21 @@ async fn main() -> Result<()> {
                                             &format!("Streaming error: {}", error_msg),
                                         )?;
 
-                                        // Classify error type and set appropriate session_status
-                                        let status = if error_msg.contains("cancelled") || error_msg.contains("cancelled by user") {
-                                            "cancelled"
-                                        } else if error_msg.contains("timed out") {
-                                            "timeout"
-                                        } else {
-                                            "error"
-                                        };
-
-                                        // Use same classification for session_status
-                                        let session_status = if error_msg.contains("cancelled") || error_msg.contains("cancelled by user") {
-                                            "cancelled"
-                                        } else if error_msg.contains("timed out") {
-                                            "timeout"
-                                        } else {
-                                            "error"
-                                        };
-
                                         // Send status
                                         node.send_output(
                                             DataId::from("status".to_string()),
                                             Default::default(),
-                                            StringArray::from(vec![status]),
+                                            StringArray::from(vec![error_type(&error_msg)]),
                                         )
                                         .context("Failed to send status output")?;
 
-                                        let mut error_metadata = metadata.parameters.clone();
-                                        error_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String(session_status.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_type".to_string(),
-                                            Parameter::String(status.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_message".to_string(),
-                                            Parameter::String(error_msg),
-                                        );
-
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            error_metadata,
-                                            StringArray::from(vec![
-                                                format!("Error: {}", e).as_str(),
-                                            ]),
-                                        )
-                                        .context("Failed to send error")?;
+                                        send_error_text(
+                                            &mut node,
+                                            &metadata.parameters,
+                                            &mut writer,
+                                            &error_msg,
+                                            &format!("Error: {}", e),
+                                        )?;
                                     }
                                     Err(e) => {
                                         let error_msg = format!("{}", e);
@@ -920,66 +1392,32 @@ async fn main() -> Result<()> {
                                             &format!("Task error: {}", error_msg),
                                         )?;
 
-                                        // Classify error type for task errors
-                                        let error_type = if error_msg.contains("cancelled") || error_msg.contains("cancelled by user") {
-                                            "cancelled"
-                                        } else if error_msg.contains("timed out") {
-                                            "timeout"
-                                        } else {
-                                            "error"
-                                        };
-
                                         // Send error status
                                         node.send_output(
                                             DataId::from("status".to_string()),
                                             Default::default(),
-                                            StringArray::from(vec![format!("{}: {}", error_type, e)]),
+                                            StringArray::from(vec![format!("{}: {}", error_type(&error_msg), e)]),
                                         )
                                         .context("Failed to send status output")?;
 
-                                        let mut error_metadata = metadata.parameters.clone();
-                                        error_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_type".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_message".to_string(),
-                                            Parameter::String(error_msg),
-                                        );
-
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            error_metadata,
-                                            StringArray::from(vec![format!("Error: {}", e).as_str()]),
-                                        )
-                                        .context("Failed to send error output")?;
+                                        send_error_text(
+                                            &mut node,
+                                            &metadata.parameters,
+                                            &mut writer,
+                                            &error_msg,
+                                            &format!("Error: {}", e),
+                                        )?;
                                     }
                                 }
-
-                                if has_sent_segment {
-                                    let mut end_metadata = metadata.parameters.clone();
-                                    end_metadata.insert(
-                                        "session_status".to_string(),
-                                        Parameter::String("ended".to_string()),
-                                    );
-                                    end_metadata.insert(
-                                        "segment_index".to_string(),
-                                        Parameter::String(segment_index.to_string()),
-                                    );
-                                    node.send_output(
-                                        DataId::from("text".to_string()),
-                                        end_metadata,
-                                        StringArray::from(vec![""]),
-                                    )
-                                    .context("Failed to send session end marker")?;
-                                }
                             } else {
                                 // Non-streaming mode
-                                match client.complete(request).await {
+                                let request_id = uuid::Uuid::new_v4().to_string();
+                                let mut writer =
+                                    stream_writer(&session_id, &request_id, &metadata.parameters);
+                                let timer = RequestTimer::start();
+                                let result = client.complete(request).await;
+                                report_failovers(&mut node, &mut failover_rx)?;
+                                match result {
                                     Ok(response) => {
                                         if let Some(choice) = response.choices.first() {
                                             let content = match &choice.message {
@@ -997,21 +1435,15 @@ async fn main() -> Result<()> {
                                                 ),
                                             )?;
 
+                                            let metrics = timer.finish(Usage::from_response(&response), || session.estimate_usage(&content));
+                                            send_metrics(&mut node, &config, client, &session_id, session, &metrics)?;
+
                                             // Add assistant message to session
                                             session.add_assistant_message(content.clone());
+                                            persist_session(session_store.as_ref(), &session_id, session, &mut node)?;
 
                                             // Send response with metadata passthrough
-                                            let mut reply_metadata = metadata.parameters.clone();
-                                            reply_metadata.insert(
-                                                "session_status".to_string(),
-                                                Parameter::String("started".to_string()),
-                                            );
-                                            node.send_output(
-                                                DataId::from("text".to_string()),
-                                                reply_metadata,
-                                                StringArray::from(vec![content.as_str()]),
-                                            )
-                                            .context("Failed to send text output")?;
+                                            send_text(&mut node, &metadata.parameters, &writer.chunk(), &content)?;
 
                                             // Send "complete" status
                                             node.send_output(
@@ -1021,60 +1453,40 @@ async fn main() -> Result<()> {
                                             )
                                             .context("Failed to send status output")?;
 
-                                            let mut end_metadata = metadata.parameters.clone();
-                                            end_metadata.insert(
-                                                "session_status".to_string(),
-                                                Parameter::String("ended".to_string()),
-                                            );
-                                            node.send_output(
-                                                DataId::from("text".to_string()),
-                                                end_metadata,
-                                                StringArray::from(vec![""]),
-                                            )
-                                            .context("Failed to send session end marker")?;
+                                            let finish_reason = serde_json::to_value(&choice.finish_reason)
+                                                .ok()
+                                                .and_then(|v| v.as_str().map(str::to_string));
+                                            send_text(
+                                                &mut node,
+                                                &metadata.parameters,
+                                                &writer.finish(
+                                                    StreamStatus::Ended,
+                                                    Some(finish_reason.as_deref().unwrap_or("stop")),
+                                                ),
+                                                "",
+                                            )?;
                                         }
                                     }
                                     Err(e) => {
                                         let error_msg = format!("{}", e);
                                                                 send_log(&mut node, "ERROR", &error_msg)?;
 
-                                        // Classify error type for API call errors
-                                        let error_type = if error_msg.contains("cancelled") || error_msg.contains("cancelled by user") {
-                                            "cancelled"
-                                        } else if error_msg.contains("timed out") {
-                                            "timeout"
-                                        } else {
-                                            "error"
-                                        };
-
                                         // Send error status
                                         node.send_output(
                                             DataId::from("status".to_string()),
                                             Default::default(),
-                                            StringArray::from(vec![format!("{}: {}", error_type, e)]),
+                                            StringArray::from(vec![format!("{}: {}", error_type(&error_msg), e)]),
                                         )
                                         .context("Failed to send status output")?;
 
                                         // Send error response with metadata passthrough
-                                        let mut error_metadata = metadata.parameters.clone();
-                                        error_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_type".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_message".to_string(),
-                                            Parameter::String(error_msg.clone()),
-                                        );
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            error_metadata,
-                                            StringArray::from(vec![error_msg.as_str()]),
-                                        )
-                                        .context("Failed to send error output")?;
+                                        send_error_text(
+                                            &mut node,
+                                            &metadata.parameters,
+                                            &mut writer,
+                                            &error_msg,
+                                            &error_msg,
+                                        )?;
                                     }
                                 }
                             } // Close the else block for non-streaming
@@ -1104,7 +1516,7 @@ async fn main() -> Result<()> {
                                     )?;
 
                                     // Route to appropriate provider
-                                    let (provider_id, model_name) = config
+                                    let (_, model_name) = config
                                         .route_model(&config.default_model)
                                         .ok_or_else(|| {
                                             eyre::eyre!(
@@ -1138,8 +1550,8 @@ async fn main() -> Result<()> {
                                     request.stream = config.enable_streaming;
                                     request.temperature = Some(0.7);
 
-                                    let client = clients.get(&provider_id).ok_or_else(|| {
-                                        eyre::eyre!("No client found for provider: {}", provider_id)
+                                    let client = routers.get(&config.default_model).ok_or_else(|| {
+                                        eyre::eyre!("No client found for model: {}", config.default_model)
                                     })?;
 
                                     // Send "processing" status
@@ -1151,7 +1563,13 @@ async fn main() -> Result<()> {
                                     .context("Failed to send status output")?;
 
                                     // Make API call to get final response after tool execution
-                                    match client.complete(request).await {
+                                    let request_id = uuid::Uuid::new_v4().to_string();
+                                    let mut writer =
+                                        stream_writer(&session_id, &request_id, &metadata.parameters);
+                                    let timer = RequestTimer::start();
+                                    let result = client.complete(request).await;
+                                    report_failovers(&mut node, &mut failover_rx)?;
+                                    match result {
                                         Ok(response) => {
                                             if let Some(choice) = response.choices.first() {
                                                 let content = match &choice.message {
@@ -1169,16 +1587,21 @@ async fn main() -> Result<()> {
                                                     ),
                                                 )?;
 
+                                                let metrics = timer.finish(Usage::from_response(&response), || session.estimate_usage(&content));
+                                                send_metrics(&mut node, &config, client, &session_id, session, &metrics)?;
+
                                                 // Add assistant message to session
                                                 session.add_assistant_message(content.clone());
+                                                persist_session(session_store.as_ref(), &session_id, session, &mut node)?;
 
                                                 // Send response with metadata passthrough
-                                                node.send_output(
-                                                    DataId::from("text".to_string()),
-                                                    metadata.parameters.clone(),
-                                                    StringArray::from(vec![content.as_str()]),
-                                                )
-                                                .context("Failed to send text output")?;
+                                                send_text(&mut node, &metadata.parameters, &writer.chunk(), &content)?;
+                                                send_text(
+                                                    &mut node,
+                                                    &metadata.parameters,
+                                                    &writer.finish(StreamStatus::Ended, Some("stop")),
+                                                    "",
+                                                )?;
 
                                                 // Send "complete" status
                                                 node.send_output(
@@ -1194,13 +1617,22 @@ async fn main() -> Result<()> {
                                                 format!("Error processing tool results: {}", e);
                                             send_log(&mut node, "ERROR", &error_msg)?;
 
-                                            // Send "error" status
+                                            // Send error status
                                             node.send_output(
                                                 DataId::from("status".to_string()),
                                                 Default::default(),
-                                                StringArray::from(vec![format!("error: {}", e)]),
+                                                StringArray::from(vec![format!("{}: {}", error_type(&error_msg), e)]),
                                             )
                                             .context("Failed to send status output")?;
+
+                                            // Send error response with metadata passthrough
+                                            send_error_text(
+                                                &mut node,
+                                                &metadata.parameters,
+                                                &mut writer,
+                                                &error_msg,
+                                                &error_msg,
+                                            )?;
                                         }
                                     }
                                 }
@@ -1218,111 +1650,110 @@ async fn main() -> Result<()> {
                             .collect::<Vec<String>>()
                             .join(" ");
 
-                        // ENHANCED LOGGING: Log ALL control inputs for debugging
-                        send_log(&mut node, "INFO", &format!("🔍 CONTROL INPUT RECEIVED: '{}'", control_text))?;
-                        send_log(&mut node, "INFO", &format!("  Session ID: {}", session_id))?;
-                        send_log(&mut node, "INFO", &format!("  Metadata: {:?}", metadata.parameters))?;
-
-                        // Only proceed with detailed logging if this is an unexpected command
-                        // Check if it's a known command or valid JSON with prompt
-                        let is_known_command = control_text.eq_ignore_ascii_case("reset") ||
-                                               control_text.eq_ignore_ascii_case("cancel") ||
-                                               control_text.eq_ignore_ascii_case("ready") ||
-                                               control_text.eq_ignore_ascii_case("exit");
-
-                        let is_valid_json_prompt = serde_json::from_str::<serde_json::Value>(&control_text)
-                            .ok()
-                            .and_then(|v| v.get("prompt").map(|_| true))
-                            .unwrap_or(false);
-
-                        if !is_known_command && !is_valid_json_prompt {
-                            send_log(&mut node, "WARNING", &format!("🚨 UNEXPECTED CONTROL COMMAND!"))?;
-                            send_log(&mut node, "WARNING", &format!("  This should help trace where 'resume' is coming from!"))?;
-                        }
-
-                        // Try to parse as JSON first, fall back to plain text
-                        let parsed = serde_json::from_str::<serde_json::Value>(&control_text)
-                            .ok()
-                            .and_then(|v| if v.is_object() { Some(v) } else { None });
+                        send_log(&mut node, "DEBUG", &format!("Control input for session {}: '{}'", session_id, control_text))?;
 
                         let mut should_reset = false;
                         let mut should_cancel = false;
                         let mut prompt_text: Option<String> = None;
 
-                        if let Some(json) = parsed {
-                            // Handle JSON control input
-                            send_log(&mut node, "DEBUG", &format!("Parsed JSON control: {:?}", json))?;
-
-                            if let Some(command) = json.get("command").and_then(|v| v.as_str()) {
-                                send_log(&mut node, "DEBUG", &format!("Found command: {}", command))?;
-                                if command.eq_ignore_ascii_case("reset") {
-                                    should_reset = true;
-                                } else if command.eq_ignore_ascii_case("cancel") {
-                                    should_cancel = true;
-                                } else if command.eq_ignore_ascii_case("ready") {
-                                    // Send ready status
-                                    node.send_output(
-                                        DataId::from("status".to_string()),
-                                        Default::default(),
-                                        StringArray::from(vec!["ready"]),
-                                    )
-                                    .context("Failed to send status output")?;
-                                } else if command.eq_ignore_ascii_case("exit") {
-                                    sessions.remove(&session_id);
-                                    send_log(&mut node, "INFO", &format!("Removed session: {}", session_id))?;
-                                }
+                        match ControlMessage::parse(&control_text) {
+                            Ok(ControlMessage::Reset) => should_reset = true,
+                            Ok(ControlMessage::Cancel) => should_cancel = true,
+                            Ok(ControlMessage::Ready) => {
+                                node.send_output(
+                                    DataId::from("status".to_string()),
+                                    Default::default(),
+                                    StringArray::from(vec!["ready"]),
+                                )
+                                .context("Failed to send status output")?;
                             }
-                            if let Some(prompt) = json.get("prompt").and_then(|v| v.as_str()) {
-                                send_log(&mut node, "DEBUG", &format!("Found prompt field: {}", prompt))?;
-                                if !prompt.trim().is_empty() {
-                                    prompt_text = Some(prompt.to_string());
-                                    send_log(&mut node, "INFO", &format!("Extracted prompt from control: {}", prompt))?;
+                            Ok(ControlMessage::ReloadConfig) => {
+                                let reloaded = reload_config(&mut node, &mut config, &mut anchor_context, &mut routers, &failover_tx, &mut sessions)?;
+                                if reloaded {
+                                    watcher = config_watcher(&config);
+                                    response_format = load_response_format(&config);
                                 }
-                            }
-                        } else {
-                            // Plain text command (backward compatibility)
-                            if control_text.eq_ignore_ascii_case("reset") {
-                                should_reset = true;
-                            } else if control_text.eq_ignore_ascii_case("cancel") {
-                                should_cancel = true;
-                            } else if control_text.eq_ignore_ascii_case("ready") {
                                 node.send_output(
                                     DataId::from("status".to_string()),
                                     Default::default(),
-                                    StringArray::from(vec!["ready"]),
+                                    StringArray::from(vec![if reloaded { "reloaded" } else { "reload_failed" }]),
                                 )
                                 .context("Failed to send status output")?;
-                            } else if control_text.eq_ignore_ascii_case("exit") {
+                            }
+                            Ok(ControlMessage::Exit) => {
                                 sessions.remove(&session_id);
                                 send_log(&mut node, "INFO", &format!("Removed session: {}", session_id))?;
-                            } else {
-                                // ENHANCED LOGGING: Unknown control command - show complete context
-                                send_log(&mut node, "WARNING", &format!("🚨 UNKNOWN CONTROL COMMAND DETECTED!"))?;
-                                send_log(&mut node, "WARNING", &format!("Unknown control command: {}", control_text))?;
-
-                                // Debug context information
-                                let node_id = node.id();
-                                let context_msg = format!("🔍 DEBUG CONTEXT:\n  Node ID: {:?}\n  Session ID: {}\n  Input Port: control\n  Raw Control Text: '{}'\n  Control Text Length: {}\n  Metadata Parameters: {:?}",
-                                    node_id, session_id, control_text, control_text.len(), metadata.parameters);
-                                send_log(&mut node, "WARNING", &context_msg)?;
-                                send_log(&mut node, "WARNING", &format!("  Expected Commands: reset, cancel, ready, exit"))?;
-
-                                // Log environment info for debugging
-                                if let Ok(node_name) = std::env::var("DORA_NODE_NAME") {
-                                    send_log(&mut node, "WARNING", &format!("  Environment DORA_NODE_NAME: {}", node_name))?;
+                            }
+                            Ok(ControlMessage::Prompt { prompt }) => {
+                                if !prompt.trim().is_empty() {
+                                    send_log(&mut node, "INFO", &format!("Extracted prompt from control: {}", prompt))?;
+                                    prompt_text = Some(prompt);
                                 }
-                                if let Ok(maas_config) = std::env::var("MAAS_CONFIG_PATH") {
-                                    send_log(&mut node, "WARNING", &format!("  Environment MAAS_CONFIG_PATH: {}", maas_config))?;
+                            }
+                            Ok(ControlMessage::Save) => {
+                                let result = match (&session_store, sessions.get(&session_id)) {
+                                    (Some(store), Some(session)) => store.save(&session.to_stored(&session_id)),
+                                    (None, _) => Err(eyre::eyre!("Session store not configured (set session_dir)")),
+                                    (_, None) => Err(eyre::eyre!("No session to save: {}", session_id)),
+                                };
+                                match result {
+                                    Ok(()) => {
+                                        send_log(&mut node, "INFO", &format!("💾 Saved session: {}", session_id))?;
+                                        node.send_output(
+                                            DataId::from("status".to_string()),
+                                            Default::default(),
+                                            StringArray::from(vec!["saved"]),
+                                        )
+                                        .context("Failed to send status output")?;
+                                    }
+                                    Err(e) => send_log(&mut node, "WARNING", &format!("Failed to save session: {}", e))?,
                                 }
-
-                                // Log data details
-                                let data_array = data.as_string::<i32>();
-                                send_log(&mut node, "WARNING", &format!("  Data Array Length: {}", data_array.len()))?;
-                                if let Some(first_item) = data_array.iter().next().flatten() {
-                                    send_log(&mut node, "WARNING", &format!("  First Data Item: '{}'", first_item))?;
+                            }
+                            Ok(ControlMessage::Load { session_id: stored_id }) => {
+                                let stored = match &session_store {
+                                    Some(store) => store.load(&stored_id),
+                                    None => Err(eyre::eyre!("Session store not configured (set session_dir)")),
+                                };
+                                match stored {
+                                    Ok(stored) => {
+                                        let session = sessions
+                                            .entry(session_id.clone())
+                                            .or_insert_with(|| new_session(&config, &anchor_context, &tool_set));
+                                        session.restore(stored);
+                                        session.manage_history(config.history_limits());
+                                        send_log(&mut node, "INFO", &format!("📂 Loaded session {} into {} ({} messages)", stored_id, session_id, session.messages.len()))?;
+                                        node.send_output(
+                                            DataId::from("status".to_string()),
+                                            Default::default(),
+                                            StringArray::from(vec!["loaded"]),
+                                        )
+                                        .context("Failed to send status output")?;
+                                    }
+                                    Err(e) => send_log(&mut node, "WARNING", &format!("Failed to load session {}: {}", stored_id, e))?,
                                 }
-
-                                send_log(&mut node, "WARNING", &format!("  This suggests LLM1 is incorrectly receiving control commands!"))?;
+                            }
+                            Ok(ControlMessage::List) => {
+                                let ids = match &session_store {
+                                    Some(store) => store.list(),
+                                    None => Err(eyre::eyre!("Session store not configured (set session_dir)")),
+                                };
+                                match ids {
+                                    Ok(ids) => {
+                                        node.send_output(
+                                            DataId::from("status".to_string()),
+                                            Default::default(),
+                                            StringArray::from(vec![json!({ "sessions": ids }).to_string()]),
+                                        )
+                                        .context("Failed to send status output")?;
+                                    }
+                                    Err(e) => send_log(&mut node, "WARNING", &format!("Failed to list sessions: {}", e))?,
+                                }
+                            }
+                            Ok(other) => {
+                                send_log(&mut node, "DEBUG", &format!("Ignoring control command: {}", other))?;
+                            }
+                            Err(e) => {
+                                send_log(&mut node, "WARNING", &format!("{} (session: {}, metadata: {:?})", e, session_id, metadata.parameters))?;
                             }
                         }
 
@@ -1338,21 +1769,16 @@ async fn main() -> Result<()> {
                             if cancelled_count > 0 {
                                 send_log(&mut node, "INFO", &format!("🛑 Cancelled {} active streaming request(s) for session: {} (history preserved)", cancelled_count, session_id))?;
 
-                                // Send session_status: "cancelled" to signal cancellation
+                                // Send a one-chunk "cancelled" stream to signal cancellation
                                 let mut end_metadata = BTreeMap::new();
-                                end_metadata.insert(
-                                    "session_status".to_string(),
-                                    Parameter::String("cancelled".to_string()),
-                                );
                                 end_metadata.insert(
                                     "is_complete".to_string(),
                                     Parameter::Bool(true),
                                 );
-                                node.send_output(
-                                    DataId::from("text".to_string()),
-                                    end_metadata,
-                                    StringArray::from(vec![""]),
-                                ).context("Failed to send end signal on cancel")?;
+                                let request_id = uuid::Uuid::new_v4().to_string();
+                                let end_meta = stream_writer(&session_id, &request_id, &metadata.parameters)
+                                    .finish(StreamStatus::Cancelled, Some("cancelled"));
+                                send_text(&mut node, &end_metadata, &end_meta, "")?;
                             } else {
                                 send_log(&mut node, "INFO", &format!("🛑 Cancel requested but no active streaming for session: {}", session_id))?;
                             }
@@ -1379,26 +1805,22 @@ async fn main() -> Result<()> {
                                 send_log(&mut node, "INFO", &format!("🔄 Cancelled {} active streaming request(s) for session: {}", cancelled_count, session_id))?;
                             }
 
-                            // Send session_status: "reset" to signal reset (always, even if nothing was cancelled)
+                            // Send a one-chunk "reset" stream to signal reset (always, even if nothing was cancelled)
                             let mut end_metadata = BTreeMap::new();
-                            end_metadata.insert(
-                                "session_status".to_string(),
-                                Parameter::String("reset".to_string()),
-                            );
                             end_metadata.insert(
                                 "is_complete".to_string(),
                                 Parameter::Bool(true),
                             );
-                            node.send_output(
-                                DataId::from("text".to_string()),
-                                end_metadata,
-                                StringArray::from(vec![""]),
-                            ).context("Failed to send end signal on reset")?;
+                            let request_id = uuid::Uuid::new_v4().to_string();
+                            let end_meta = stream_writer(&session_id, &request_id, &metadata.parameters)
+                                .finish(StreamStatus::Reset, Some("reset"));
+                            send_text(&mut node, &end_metadata, &end_meta, "")?;
 
                             // Clear conversation history
                             if let Some(session) = sessions.get_mut(&session_id) {
                                 session.reset();
                                 send_log(&mut node, "INFO", &format!("🔄 Reset session history: {}", session_id))?;
+                                persist_session(session_store.as_ref(), &session_id, session, &mut node)?;
                             }
                             node.send_output(
                                 DataId::from("status".to_string()),
@@ -1413,22 +1835,19 @@ async fn main() -> Result<()> {
                             send_log(&mut node, "INFO", &format!("Sending prompt from control to API: {}", user_text))?;
 
                             // Get or create session
-                            let session = sessions.entry(session_id.clone()).or_insert_with(|| {
-                                let mut session = ChatSession::new(config.system_prompt.clone(), anchor_context.clone());
-                                if let Some(ref ts) = tool_set {
-                                    session.set_tool_set(ts.clone());
-                                }
-                                session
-                            });
+                            let session = sessions.entry(session_id.clone()).or_insert_with(|| new_session(&config, &anchor_context, &tool_set));
 
                             // Add user message
                             session.add_user_message(user_text.clone());
 
                             // Manage history
-                            session.manage_history(config.max_history_exchanges);
+                            session.manage_history(config.history_limits());
+                            if let Err(e) = update_summary(&mut node, &session_id, session, &config, &routers).await {
+                                send_log(&mut node, "WARNING", &format!("Failed to summarize history: {}", e))?;
+                            }
 
                             // Route to appropriate provider
-                            let (provider_id, model_name) =
+                            let (_, model_name) =
                                 config.route_model(&config.default_model).ok_or_else(|| {
                                     eyre::eyre!(
                                         "No route found for model: {}",
@@ -1445,8 +1864,8 @@ async fn main() -> Result<()> {
                             request.stream = config.enable_streaming;
                             request.temperature = Some(0.7);
 
-                            let client = clients.get(&provider_id).ok_or_else(|| {
-                                eyre::eyre!("No client found for provider: {}", provider_id)
+                            let client = routers.get(&config.default_model).ok_or_else(|| {
+                                eyre::eyre!("No client found for model: {}", config.default_model)
                             })?;
 
                             // Send "processing" status
@@ -1457,19 +1876,33 @@ async fn main() -> Result<()> {
                             )
                             .context("Failed to send status output")?;
 
-                            // Make API call - use streaming if enabled
-                            if config.enable_streaming.unwrap_or(false) {
+                            // Make API call - structured, streaming or plain
+                            if let Some(format) = &response_format {
+                                respond_structured(
+                                    &mut node,
+                                    client,
+                                    request,
+                                    format,
+                                    &config,
+                                    &session_id,
+                                    session,
+                                    &metadata.parameters,
+                                    &mut failover_rx,
+                                    session_store.as_ref(),
+                                )
+                                .await?;
+                            } else if config.enable_streaming.unwrap_or(false) {
                                 // Streaming mode
                                 send_log(&mut node, "DEBUG", "Using streaming mode for control prompt")?;
 
                                 let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
-                                let mut has_sent_segment = false;
-                                let mut segment_index: u32 = 0;
 
                                 // Start streaming in background with cancellation support
                                 let client_clone = client.clone();
                                 let request_clone = request.clone();
                                 let request_id = uuid::Uuid::new_v4().to_string();
+                                let mut writer =
+                                    stream_writer(&session_id, &request_id, &metadata.parameters);
                                 let session_id_clone = session_id.clone();
                                 let metadata_clone = metadata.parameters.clone();
                                 let cancellation_manager_clone = cancellation_manager.clone();
@@ -1484,6 +1917,7 @@ async fn main() -> Result<()> {
                                     None
                                 };
 
+                                let mut timer = RequestTimer::start();
                                 let stream_handle = tokio::spawn(async move {
                                     let result = if let Some(token) = cancellation_token {
                                         // Use cancellation-aware streaming
@@ -1518,36 +1952,14 @@ async fn main() -> Result<()> {
 
                                 while let Some(chunk) = rx.recv().await {
                                     chunk_count += 1;
+                                    timer.chunk_received();
 
                                     if let Some(segment) = segmenter.add_chunk(&chunk) {
                                         accumulated.push_str(&segment);
                                         segment_count += 1;
 
                                         // Send segment with metadata
-                                        let mut segment_metadata = metadata.parameters.clone();
-                                        segment_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String(
-                                                if !has_sent_segment {
-                                                    "started".to_string()
-                                                } else {
-                                                    "ongoing".to_string()
-                                                },
-                                            ),
-                                        );
-                                        segment_metadata.insert(
-                                            "segment_index".to_string(),
-                                            Parameter::String(segment_index.to_string()),
-                                        );
-
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            segment_metadata,
-                                            StringArray::from(vec![segment.as_str()]),
-                                        )
-                                        .context("Failed to send text segment")?;
-                                        has_sent_segment = true;
-                                        segment_index += 1;
+                                        send_text(&mut node, &metadata.parameters, &writer.chunk(), &segment)?;
                                     }
                                 }
 
@@ -1556,33 +1968,14 @@ async fn main() -> Result<()> {
                                     accumulated.push_str(&final_segment);
                                     segment_count += 1;
 
-                                    let mut segment_metadata = metadata.parameters.clone();
-                                    segment_metadata.insert(
-                                        "session_status".to_string(),
-                                        Parameter::String(
-                                            if !has_sent_segment {
-                                                "started".to_string()
-                                            } else {
-                                                "ongoing".to_string()
-                                            },
-                                        ),
-                                    );
-                                    segment_metadata.insert(
-                                        "segment_index".to_string(),
-                                        Parameter::String(segment_index.to_string()),
-                                    );
-
-                                    node.send_output(
-                                        DataId::from("text".to_string()),
-                                        segment_metadata,
-                                        StringArray::from(vec![final_segment.as_str()]),
-                                    )
-                                    .context("Failed to send final segment")?;
+                                    send_text(&mut node, &metadata.parameters, &writer.chunk(), &final_segment)?;
                                 }
 
                                 // Wait for stream to complete
-                                match stream_handle.await {
-                                    Ok(Ok(_)) => {
+                                let stream_result = stream_handle.await;
+                                report_failovers(&mut node, &mut failover_rx)?;
+                                match stream_result {
+                                    Ok(Ok((_, _, usage))) => {
                                         // Send complete log matching openai-response-client
                                         send_log(
                                             &mut node,
@@ -1594,19 +1987,15 @@ async fn main() -> Result<()> {
                                                 chunk_count
                                             ),
                                         )?;
+
+                                        let metrics = timer.finish(usage, || session.estimate_usage(&accumulated));
+                                        send_metrics(&mut node, &config, client, &session_id, session, &metrics)?;
                                     }
                                     Ok(Err(e)) => {
                                         let error_msg = format!("{}", e);
                                         send_log(&mut node, "ERROR", &format!("Streaming error: {}", error_msg))?;
 
-                                        // Classify error type
-                                        let error_type = if error_msg.contains("cancelled") || error_msg.contains("cancelled by user") {
-                                            "cancelled"
-                                        } else if error_msg.contains("timed out") {
-                                            "timeout"
-                                        } else {
-                                            "error"
-                                        };
+                                        let error_type = error_type(&error_msg);
 
                                         // Send error status
                                         node.send_output(
@@ -1616,49 +2005,27 @@ async fn main() -> Result<()> {
                                         )
                                         .context("Failed to send error status")?;
 
-                                        // Send error text output with metadata
-                                        let mut error_metadata = metadata.parameters.clone();
-                                        error_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_type".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_message".to_string(),
-                                            Parameter::String(error_msg.clone()),
-                                        );
-
                                         // For cancellation errors, send empty text (just metadata signal)
                                         // For other errors, send the error message text
                                         let text_content = if error_type == "cancelled" {
-                                            ""  // Empty - don't contaminate downstream with error text
+                                            String::new() // Empty - don't contaminate downstream with error text
                                         } else {
-                                            &format!("Error: {}", error_msg)
+                                            format!("Error: {}", error_msg)
                                         };
-
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            error_metadata,
-                                            StringArray::from(vec![text_content]),
-                                        )
-                                        .context("Failed to send error text")?;
+                                        send_error_text(
+                                            &mut node,
+                                            &metadata.parameters,
+                                            &mut writer,
+                                            &error_msg,
+                                            &text_content,
+                                        )?;
                                         continue;
                                     }
                                     Err(e) => {
                                         let error_msg = format!("{}", e);
                                         send_log(&mut node, "ERROR", &format!("Stream task failed: {}", error_msg))?;
 
-                                        // Classify error type for stream task failures
-                                        let error_type = if error_msg.contains("cancelled") || error_msg.contains("cancelled by user") {
-                                            "cancelled"
-                                        } else if error_msg.contains("timed out") {
-                                            "timeout"
-                                        } else {
-                                            "error"
-                                        };
+                                        let error_type = error_type(&error_msg);
 
                                         // Send error status
                                         node.send_output(
@@ -1668,35 +2035,20 @@ async fn main() -> Result<()> {
                                         )
                                         .context("Failed to send error status")?;
 
-                                        // Send error text output with metadata
-                                        let mut error_metadata = metadata.parameters.clone();
-                                        error_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_type".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_message".to_string(),
-                                            Parameter::String(error_msg.clone()),
-                                        );
-
                                         // For cancellation errors, send empty text (just metadata signal)
                                         // For other errors, send the error message text
                                         let text_content = if error_type == "cancelled" {
-                                            ""  // Empty - don't contaminate downstream with error text
+                                            String::new() // Empty - don't contaminate downstream with error text
                                         } else {
-                                            &format!("Error: {}", error_msg)
+                                            format!("Error: {}", error_msg)
                                         };
-
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            error_metadata,
-                                            StringArray::from(vec![text_content]),
-                                        )
-                                        .context("Failed to send error text")?;
+                                        send_error_text(
+                                            &mut node,
+                                            &metadata.parameters,
+                                            &mut writer,
+                                            &error_msg,
+                                            &text_content,
+                                        )?;
                                         continue;
                                     }
                                 }
@@ -1704,21 +2056,17 @@ async fn main() -> Result<()> {
                                 // Add assistant message to session
                                 if !accumulated.is_empty() {
                                     session.add_assistant_message(accumulated.clone());
-                                    session.manage_history(config.max_history_exchanges);
+                                    session.manage_history(config.history_limits());
+                                    persist_session(session_store.as_ref(), &session_id, session, &mut node)?;
                                 }
 
-                                // Send final empty text message with session_status="ended" for bridge
-                                let mut final_metadata = metadata.parameters.clone();
-                                final_metadata.insert(
-                                    "session_status".to_string(),
-                                    Parameter::String("ended".to_string()),
-                                );
-                                node.send_output(
-                                    DataId::from("text".to_string()),
-                                    final_metadata.clone(),
-                                    StringArray::from(vec![""]),
-                                )
-                                .context("Failed to send final text message")?;
+                                // Send final empty text chunk with status "ended" for bridge
+                                send_text(
+                                    &mut node,
+                                    &metadata.parameters,
+                                    &writer.finish(StreamStatus::Ended, Some("stop")),
+                                    "",
+                                )?;
 
                                 // Send completion status
                                 node.send_output(
@@ -1732,36 +2080,38 @@ async fn main() -> Result<()> {
                                 // Non-streaming mode
                                 send_log(&mut node, "DEBUG", "Using non-streaming mode for control prompt")?;
 
-                                match client.complete(request.clone()).await {
+                                let request_id = uuid::Uuid::new_v4().to_string();
+                                let mut writer =
+                                    stream_writer(&session_id, &request_id, &metadata.parameters);
+                                let timer = RequestTimer::start();
+                                let result = client.complete(request.clone()).await;
+
+                                report_failovers(&mut node, &mut failover_rx)?;
+
+                                match result {
                                     Ok(response) => {
                                         let assistant_message = response.choices.first()
                                             .and_then(|choice| choice.message.content.clone())
                                             .unwrap_or_default();
 
+                                        let metrics = timer.finish(Usage::from_response(&response), || session.estimate_usage(&assistant_message));
+                                        send_metrics(&mut node, &config, client, &session_id, session, &metrics)?;
+
                                         // Send response
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            Default::default(),
-                                            StringArray::from(vec![assistant_message.as_str()]),
-                                        )
-                                        .context("Failed to send text output")?;
+                                        send_text(&mut node, &metadata.parameters, &writer.chunk(), &assistant_message)?;
 
                                         // Add to session
                                         session.add_assistant_message(assistant_message);
-                                        session.manage_history(config.max_history_exchanges);
-
-                                        // Send final empty text message with session_status="ended" for bridge
-                                        let mut final_metadata = metadata.parameters.clone();
-                                        final_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String("ended".to_string()),
-                                        );
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            final_metadata,
-                                            StringArray::from(vec![""]),
-                                        )
-                                        .context("Failed to send final text message")?;
+                                        session.manage_history(config.history_limits());
+                                        persist_session(session_store.as_ref(), &session_id, session, &mut node)?;
+
+                                        // Send final empty text chunk with status "ended" for bridge
+                                        send_text(
+                                            &mut node,
+                                            &metadata.parameters,
+                                            &writer.finish(StreamStatus::Ended, Some("stop")),
+                                            "",
+                                        )?;
 
                                         // Send complete status
                                         node.send_output(
@@ -1772,13 +2122,24 @@ async fn main() -> Result<()> {
                                         .context("Failed to send status output")?;
                                     }
                                     Err(e) => {
-                                        send_log(&mut node, "ERROR", &format!("API error: {}", e))?;
+                                        let error_msg = format!("{}", e);
+                                        send_log(&mut node, "ERROR", &format!("API error: {}", error_msg))?;
+
+                                        // Send error status
                                         node.send_output(
                                             DataId::from("status".to_string()),
                                             Default::default(),
-                                            StringArray::from(vec!["error"]),
+                                            StringArray::from(vec![error_type(&error_msg)]),
                                         )
                                         .context("Failed to send error status")?;
+
+                                        send_error_text(
+                                            &mut node,
+                                            &metadata.parameters,
+                                            &mut writer,
+                                            &error_msg,
+                                            &format!("Error: {}", error_msg),
+                                        )?;
                                     }
                                 }
                             }
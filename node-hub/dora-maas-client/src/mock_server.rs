@@ -0,0 +1,202 @@
+//! Minimal HTTP server for testing provider clients
+//!
+//! Serves queued canned responses in order and records every request, so
+//! client tests can run against `http://127.0.0.1:<port>` without a real
+//! provider. Each connection handles one request and is then closed.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Canned HTTP response
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl MockResponse {
+    /// JSON response with the given status
+    pub fn json(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    /// Server-sent events stream, one `data:` event per value
+    pub fn sse(events: &[Value]) -> Self {
+        Self::sse_raw(events.iter().map(Value::to_string))
+    }
+
+    /// Server-sent events stream with raw `data:` payloads
+    pub fn sse_raw(events: impl IntoIterator<Item = String>) -> Self {
+        let body = events
+            .into_iter()
+            .map(|data| format!("data: {}\n\n", data))
+            .collect();
+        Self {
+            status: 200,
+            content_type: "text/event-stream",
+            body,
+        }
+    }
+
+    /// Server-sent events stream with named events (`event:` and `data:` lines)
+    pub fn sse_events(events: impl IntoIterator<Item = (String, Value)>) -> Self {
+        let body = events
+            .into_iter()
+            .map(|(event, data)| format!("event: {}\ndata: {}\n\n", event, data))
+            .collect();
+        Self {
+            status: 200,
+            content_type: "text/event-stream",
+            body,
+        }
+    }
+
+    /// Newline-delimited JSON stream, one line per value
+    pub fn ndjson(lines: &[Value]) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/x-ndjson",
+            body: lines.iter().map(|line| format!("{}\n", line)).collect(),
+        }
+    }
+}
+
+/// Request received by the mock server
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Path including the query string
+    pub path: String,
+    /// Header names are lowercased
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// Parse the body as JSON
+    pub fn json(&self) -> Value {
+        serde_json::from_str(&self.body).expect("request body is JSON")
+    }
+}
+
+/// HTTP server answering with queued responses
+pub struct MockServer {
+    url: String,
+    responses: Arc<Mutex<VecDeque<MockResponse>>>,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockServer {
+    /// Bind to a free local port and start serving
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let responses = Arc::new(Mutex::new(VecDeque::new()));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let (queued, recorded) = (Arc::clone(&responses), Arc::clone(&requests));
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (queued, recorded) = (Arc::clone(&queued), Arc::clone(&recorded));
+                tokio::spawn(async move {
+                    let _ = handle_connection(stream, queued, recorded).await;
+                });
+            }
+        });
+
+        Self {
+            url,
+            responses,
+            requests,
+        }
+    }
+
+    /// Base URL, e.g. `http://127.0.0.1:4312`
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Queue a response; requests without one get a 500
+    pub fn enqueue(&self, response: MockResponse) {
+        self.responses.lock().unwrap().push_back(response);
+    }
+
+    /// Requests received so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    responses: Arc<Mutex<VecDeque<MockResponse>>>,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+) -> std::io::Result<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    // Read the request head
+    let head_end = loop {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+
+    // Read the body
+    let content_length = headers
+        .get("content-length")
+        .and_then(|len| len.parse::<usize>().ok())
+        .unwrap_or(0);
+    while buffer.len() < head_end + content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    let body = String::from_utf8_lossy(&buffer[head_end..]).to_string();
+
+    requests.lock().unwrap().push(RecordedRequest {
+        method,
+        path,
+        headers,
+        body,
+    });
+
+    let response = responses.lock().unwrap().pop_front().unwrap_or_else(|| {
+        MockResponse::json(500, serde_json::json!({"error": "no response queued"}))
+    });
+    let reply = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.content_type,
+        response.body.len(),
+        response.body
+    );
+    stream.write_all(reply.as_bytes()).await?;
+    stream.shutdown().await
+}
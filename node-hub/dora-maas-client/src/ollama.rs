@@ -0,0 +1,553 @@
+//! Native Ollama chat API support
+//!
+//! Translates OpenAI-style chat completion requests into Ollama `/api/chat`
+//! bodies and Ollama responses back into [`CreateChatCompletionResponse`],
+//! so a local Ollama server can back [`OllamaClient`] without an
+//! OpenAI-compatible shim.
+//!
+//! Messages and tools keep the OpenAI shape, except that tool call
+//! `arguments` are JSON objects rather than strings and tool results carry
+//! the `tool_name` instead of a call ID. Sampling parameters move into
+//! `options` (`max_tokens` becomes `num_predict`).
+//!
+//! Streaming responses are newline-delimited JSON: one object per line, the
+//! last one with `"done": true` and the token counts.
+//!
+//! [`OllamaClient`]: crate::client::OllamaClient
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use eyre::{Result, eyre};
+use futures::StreamExt;
+use outfox_openai::spec::{
+    ChatCompletionMessageToolCall, ChatCompletionToolType, CreateChatCompletionRequest,
+    CreateChatCompletionResponse, FunctionCall,
+};
+use serde_json::{Map, Value, json};
+use tokio_util::sync::CancellationToken;
+
+use crate::failover::ProviderError;
+use crate::usage::Usage;
+use crate::wire::{content_text, unix_timestamp};
+
+/// Ollama endpoint URL for the chat API
+pub fn endpoint(api_url: &str) -> String {
+    format!("{}/api/chat", api_url.trim_end_matches('/'))
+}
+
+/// Build an Ollama `/api/chat` body from an OpenAI-style request.
+///
+/// Returns the model name and the body, with `stream` set as given.
+pub fn to_ollama_request(
+    request: &CreateChatCompletionRequest,
+    stream: bool,
+) -> Result<(String, Value)> {
+    let request = serde_json::to_value(request)?;
+    let model = request["model"]
+        .as_str()
+        .ok_or_else(|| eyre!("Request has no model"))?
+        .to_string();
+
+    let mut messages = Vec::new();
+    let mut call_names: HashMap<String, String> = HashMap::new();
+
+    for message in request["messages"].as_array().into_iter().flatten() {
+        let text = content_text(&message["content"]);
+        match message["role"].as_str().unwrap_or("user") {
+            "assistant" => {
+                let mut converted = json!({ "role": "assistant", "content": text });
+                let calls: Vec<Value> = message["tool_calls"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|call| {
+                        let name = call["function"]["name"].as_str().unwrap_or_default();
+                        if let Some(id) = call["id"].as_str() {
+                            call_names.insert(id.to_string(), name.to_string());
+                        }
+                        let arguments = call["function"]["arguments"]
+                            .as_str()
+                            .and_then(|args| serde_json::from_str::<Value>(args).ok())
+                            .unwrap_or_else(|| json!({}));
+                        json!({ "function": { "name": name, "arguments": arguments } })
+                    })
+                    .collect();
+                if !calls.is_empty() {
+                    converted["tool_calls"] = Value::Array(calls);
+                }
+                messages.push(converted);
+            }
+            "tool" => {
+                let mut converted = json!({ "role": "tool", "content": text });
+                let call_id = message["tool_call_id"].as_str().unwrap_or_default();
+                if let Some(name) = call_names.get(call_id) {
+                    converted["tool_name"] = json!(name);
+                }
+                messages.push(converted);
+            }
+            "developer" => messages.push(json!({ "role": "system", "content": text })),
+            role => messages.push(json!({ "role": role, "content": text })),
+        }
+    }
+
+    let mut body = Map::new();
+    body.insert("model".to_string(), json!(model));
+    body.insert("messages".to_string(), Value::Array(messages));
+    body.insert("stream".to_string(), json!(stream));
+    // Ollama takes OpenAI-style function tools as they are
+    if let Some(tools) = request["tools"]
+        .as_array()
+        .filter(|tools| !tools.is_empty())
+    {
+        body.insert("tools".to_string(), json!(tools));
+    }
+    let options = options(&request);
+    if !options.is_empty() {
+        body.insert("options".to_string(), Value::Object(options));
+    }
+
+    Ok((model, Value::Object(body)))
+}
+
+/// Convert an Ollama `/api/chat` response into a chat completion response
+pub fn from_ollama_response(response: &Value, model: &str) -> Result<CreateChatCompletionResponse> {
+    if let Some(error) = response["error"].as_str() {
+        return Err(eyre!("Ollama error: {}", error));
+    }
+    let message = response
+        .get("message")
+        .ok_or_else(|| eyre!("Ollama response has no message: {}", response))?;
+
+    let text = message["content"].as_str().unwrap_or_default();
+    let tool_calls = parse_tool_calls(&message["tool_calls"]);
+    let finish_reason = if !tool_calls.is_empty() {
+        "tool_calls"
+    } else if response["done_reason"] == "length" {
+        "length"
+    } else {
+        "stop"
+    };
+
+    let content = if text.is_empty() && !tool_calls.is_empty() {
+        Value::Null
+    } else {
+        Value::String(text.to_string())
+    };
+    let mut converted = json!({ "role": "assistant", "content": content });
+    if !tool_calls.is_empty() {
+        converted["tool_calls"] = serde_json::to_value(&tool_calls)?;
+    }
+
+    let mut completion = json!({
+        "id": format!("ollama-{}", uuid::Uuid::new_v4().simple()),
+        "object": "chat.completion",
+        "created": unix_timestamp(),
+        "model": response["model"].as_str().unwrap_or(model),
+        "choices": [{
+            "index": 0,
+            "message": converted,
+            "finish_reason": finish_reason,
+        }],
+    });
+    if let Some(usage) = usage(response) {
+        completion["usage"] = serde_json::to_value(usage)?;
+    }
+
+    serde_json::from_value(completion)
+        .map_err(|e| eyre!("Failed to convert Ollama response: {}", e))
+}
+
+/// Stream an `/api/chat` request (NDJSON), calling `on_chunk` for each text delta.
+///
+/// Tool calls arrive whole in a message chunk and are returned with the
+/// accumulated text once the `done` line arrives. `timeout` bounds the wait
+/// for each chunk, which matters for local models that load slowly.
+pub async fn stream_chat<F>(
+    client: &reqwest::Client,
+    url: String,
+    request_body: Value,
+    cancellation_token: Option<CancellationToken>,
+    timeout: Option<Duration>,
+    mut on_chunk: F,
+) -> Result<(
+    String,
+    Option<Vec<ChatCompletionMessageToolCall>>,
+    Option<Usage>,
+)>
+where
+    F: FnMut(String) -> Result<()>,
+{
+    let cancellation_token = cancellation_token.unwrap_or_default();
+    let request = client.post(url).json(&request_body).send();
+    let response = tokio::select! {
+        response = request => response?,
+        _ = cancellation_token.cancelled() => return Err(ProviderError::Cancelled.into()),
+    };
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ProviderError::Status {
+            status: status.as_u16(),
+            body: response.text().await.unwrap_or_default(),
+        }
+        .into());
+    }
+
+    let mut stream = response.bytes_stream();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut accumulated_content = String::new();
+    let mut tool_calls = Vec::new();
+
+    loop {
+        let idle_timeout = async {
+            match timeout {
+                Some(duration) => tokio::time::sleep(duration).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            chunk = stream.next() => {
+                match chunk {
+                    Some(Ok(bytes)) => buffer.extend_from_slice(&bytes),
+                    Some(Err(e)) => {
+                        if cancellation_token.is_cancelled() {
+                            return Err(ProviderError::Cancelled.into());
+                        }
+                        return Err(eyre::Report::from(e).wrap_err("Ollama stream error"));
+                    }
+                    // Closed without a `done` line: the response is incomplete
+                    None => return Err(ProviderError::StreamEnded.into()),
+                }
+            }
+            _ = cancellation_token.cancelled() => {
+                return Err(ProviderError::Cancelled.into());
+            }
+            _ = idle_timeout => {
+                return Err(ProviderError::StreamTimeout(timeout.unwrap_or_default()).into());
+            }
+        }
+
+        while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            let chunk: Value = match serde_json::from_slice(&line) {
+                Ok(chunk) => chunk,
+                // Skip blank or unparseable lines, like the SSE streams do
+                Err(_) => continue,
+            };
+            if let Some(error) = chunk["error"].as_str() {
+                return Err(eyre!("Ollama error: {}", error));
+            }
+
+            let text = chunk["message"]["content"].as_str().unwrap_or_default();
+            if !text.is_empty() {
+                accumulated_content.push_str(text);
+                on_chunk(text.to_string())?;
+            }
+            tool_calls.extend(parse_tool_calls(&chunk["message"]["tool_calls"]));
+
+            if chunk["done"].as_bool() == Some(true) {
+                let tool_calls = if tool_calls.is_empty() {
+                    None
+                } else {
+                    Some(tool_calls)
+                };
+                return Ok((accumulated_content, tool_calls, usage(&chunk)));
+            }
+        }
+    }
+}
+
+/// Ollama `options` from OpenAI sampling parameters
+fn options(request: &Value) -> Map<String, Value> {
+    let mut options = Map::new();
+    if let Some(temperature) = request["temperature"].as_f64() {
+        options.insert("temperature".to_string(), json!(temperature));
+    }
+    if let Some(top_p) = request["top_p"].as_f64() {
+        options.insert("top_p".to_string(), json!(top_p));
+    }
+    if let Some(seed) = request["seed"].as_i64() {
+        options.insert("seed".to_string(), json!(seed));
+    }
+    if let Some(max_tokens) = request["max_completion_tokens"]
+        .as_u64()
+        .or_else(|| request["max_tokens"].as_u64())
+    {
+        options.insert("num_predict".to_string(), json!(max_tokens));
+    }
+    match &request["stop"] {
+        Value::String(stop) => {
+            options.insert("stop".to_string(), json!([stop]));
+        }
+        Value::Array(stops) => {
+            options.insert("stop".to_string(), json!(stops));
+        }
+        _ => {}
+    }
+    options
+}
+
+/// Tool calls of an Ollama `message.tool_calls` array
+fn parse_tool_calls(calls: &Value) -> Vec<ChatCompletionMessageToolCall> {
+    calls
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|call| {
+            // Older Ollama versions don't assign call IDs
+            let id = call["id"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple()));
+            let arguments = match &call["function"]["arguments"] {
+                Value::String(arguments) => arguments.clone(),
+                Value::Null => "{}".to_string(),
+                arguments => arguments.to_string(),
+            };
+            ChatCompletionMessageToolCall {
+                id,
+                kind: ChatCompletionToolType::Function,
+                function: FunctionCall {
+                    name: call["function"]["name"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    arguments,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Usage from the counts on a final (`done`) response
+fn usage(response: &Value) -> Option<Usage> {
+    let prompt_tokens = response["prompt_eval_count"].as_u64();
+    let completion_tokens = response["eval_count"].as_u64();
+    if prompt_tokens.is_none() && completion_tokens.is_none() {
+        return None;
+    }
+    Some(Usage::new(
+        prompt_tokens.unwrap_or(0),
+        completion_tokens.unwrap_or(0),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ChatClient, OllamaClient};
+    use crate::config::OllamaConfig;
+    use crate::mock_server::{MockResponse, MockServer};
+    use tokio::sync::mpsc;
+
+    fn request(body: Value) -> CreateChatCompletionRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    fn client(server: &MockServer) -> OllamaClient {
+        OllamaClient::new(&OllamaConfig {
+            id: "ollama".to_string(),
+            api_url: server.url().to_string(),
+            keep_alive: Some("10m".to_string()),
+            proxy: false,
+        })
+    }
+
+    fn chat_request() -> CreateChatCompletionRequest {
+        request(json!({
+            "model": "qwen3:8b",
+            "messages": [
+                { "role": "system", "content": "You are a tutor." },
+                { "role": "user", "content": "What's the weather in Paris?" },
+            ],
+        }))
+    }
+
+    #[test]
+    fn test_request_translation() {
+        let (model, body) = to_ollama_request(
+            &request(json!({
+                "model": "qwen3:8b",
+                "temperature": 0.4,
+                "max_tokens": 128,
+                "messages": [
+                    { "role": "system", "content": "You are a tutor." },
+                    { "role": "user", "content": "Weather in Paris?" },
+                    { "role": "assistant", "content": null, "tool_calls": [{
+                        "id": "call_1", "type": "function",
+                        "function": { "name": "get_weather", "arguments": "{\"city\":\"Paris\"}" },
+                    }]},
+                    { "role": "tool", "tool_call_id": "call_1", "content": "sunny" },
+                ],
+                "tools": [{ "type": "function", "function": {
+                    "name": "get_weather",
+                    "parameters": { "type": "object", "properties": { "city": { "type": "string" } } },
+                }}],
+            })),
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(model, "qwen3:8b");
+        assert_eq!(body["stream"], false);
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(
+            messages[0],
+            json!({ "role": "system", "content": "You are a tutor." })
+        );
+        assert_eq!(
+            messages[2]["tool_calls"][0]["function"],
+            json!({ "name": "get_weather", "arguments": { "city": "Paris" } })
+        );
+        assert_eq!(
+            messages[3],
+            json!({ "role": "tool", "content": "sunny", "tool_name": "get_weather" })
+        );
+        assert_eq!(body["tools"][0]["function"]["name"], "get_weather");
+        assert_eq!(
+            body["options"],
+            json!({ "temperature": 0.4, "num_predict": 128 })
+        );
+    }
+
+    #[test]
+    fn test_response_translation() {
+        let response = from_ollama_response(
+            &json!({
+                "model": "qwen3:8b",
+                "message": { "role": "assistant", "content": "", "tool_calls": [
+                    { "function": { "name": "get_weather", "arguments": { "city": "Paris" } } },
+                ]},
+                "done": true,
+                "done_reason": "stop",
+                "prompt_eval_count": 30,
+                "eval_count": 12,
+            }),
+            "qwen3:8b",
+        )
+        .unwrap();
+
+        let value = serde_json::to_value(&response).unwrap();
+        let choice = &value["choices"][0];
+        assert_eq!(choice["finish_reason"], "tool_calls");
+        assert!(choice["message"]["content"].is_null());
+        let call = &choice["message"]["tool_calls"][0];
+        assert!(call["id"].as_str().unwrap().starts_with("call_"));
+        assert_eq!(call["function"]["arguments"], r#"{"city":"Paris"}"#);
+        assert_eq!(Usage::from_response(&response), Some(Usage::new(30, 12)));
+    }
+
+    #[tokio::test]
+    async fn test_complete_against_mock_server() {
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::json(
+            200,
+            json!({
+                "model": "qwen3:8b",
+                "message": { "role": "assistant", "content": "Sunny, 24°C." },
+                "done": true,
+                "done_reason": "stop",
+            }),
+        ));
+
+        let response = client(&server).complete(chat_request()).await.unwrap();
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(value["choices"][0]["message"]["content"], "Sunny, 24°C.");
+        assert_eq!(value["choices"][0]["finish_reason"], "stop");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/api/chat");
+        let body = requests[0].json();
+        assert_eq!(body["stream"], false);
+        assert_eq!(body["keep_alive"], "10m");
+    }
+
+    #[tokio::test]
+    async fn test_complete_reports_missing_model() {
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::json(
+            404,
+            json!({ "error": "model \"qwen3:8b\" not found" }),
+        ));
+
+        let err = client(&server).complete(chat_request()).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProviderError>(),
+            Some(ProviderError::Status { status: 404, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_against_mock_server() {
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::ndjson(&[
+            json!({ "model": "qwen3:8b", "message": { "role": "assistant", "content": "Checking" }, "done": false }),
+            json!({ "model": "qwen3:8b", "message": { "role": "assistant", "content": " now." }, "done": false }),
+            json!({ "model": "qwen3:8b", "message": { "role": "assistant", "content": "", "tool_calls": [
+                { "function": { "name": "get_weather", "arguments": { "city": "Paris" } } },
+            ]}, "done": false }),
+            json!({ "model": "qwen3:8b", "message": { "role": "assistant", "content": "" },
+                "done": true, "done_reason": "stop", "prompt_eval_count": 26, "eval_count": 8 }),
+        ]));
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (text, tool_calls, usage) = client(&server)
+            .complete_streaming_with_cancellation(
+                chat_request(),
+                tx,
+                CancellationToken::new(),
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap();
+
+        let mut chunks = Vec::new();
+        while let Ok(chunk) = rx.try_recv() {
+            chunks.push(chunk);
+        }
+        assert_eq!(chunks, vec!["Checking", " now."]);
+        assert_eq!(text, "Checking now.");
+        let tool_calls = tool_calls.unwrap();
+        assert_eq!(tool_calls[0].function.name, "get_weather");
+        assert_eq!(tool_calls[0].function.arguments, r#"{"city":"Paris"}"#);
+        assert_eq!(usage, Some(Usage::new(26, 8)));
+
+        assert_eq!(server.requests()[0].json()["stream"], true);
+    }
+
+    #[tokio::test]
+    async fn test_stream_error_line() {
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::ndjson(&[
+            json!({ "message": { "role": "assistant", "content": "Check" }, "done": false }),
+            json!({ "error": "out of memory" }),
+        ]));
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let err = client(&server)
+            .complete_streaming(chat_request(), tx)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("out of memory"));
+    }
+
+    #[tokio::test]
+    async fn test_truncated_stream_is_an_error() {
+        let server = MockServer::start().await;
+        server.enqueue(MockResponse::ndjson(&[json!({
+            "message": { "role": "assistant", "content": "Check" }, "done": false,
+        })]));
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let err = client(&server)
+            .complete_streaming(chat_request(), tx)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProviderError>(),
+            Some(ProviderError::StreamEnded)
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+//! Detect changes to the configuration file
+//!
+//! The node's event loop blocks on dora events, so the watcher is polled from
+//! the loop: a change is picked up with the next input after it happened.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Polls the modification time of the configuration file
+pub struct ConfigWatcher {
+    path: PathBuf,
+    interval: Duration,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl ConfigWatcher {
+    /// Watch `path`, checking at most once per `interval`
+    pub fn new(path: PathBuf, interval: Duration) -> Self {
+        let modified = modified(&path);
+        Self {
+            path,
+            interval,
+            modified,
+            last_check: Instant::now(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file changed since the last call that reported a change.
+    ///
+    /// A file that disappears (e.g. while an editor replaces it) is not
+    /// reported until it exists again.
+    pub fn poll(&mut self) -> bool {
+        if self.last_check.elapsed() < self.interval {
+            return false;
+        }
+        self.last_check = Instant::now();
+
+        match modified(&self.path) {
+            Some(current) if Some(current) != self.modified => {
+                self.modified = Some(current);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "maas-reload-{}-{}-{}",
+            name,
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ))
+    }
+
+    const VALID: &str = r#"
+default_model = "llama"
+system_prompt = "Be brief."
+max_history_exchanges = 5
+
+[[providers]]
+kind = "ollama"
+id = "local"
+
+[[models]]
+id = "llama"
+route = { provider = "local", model = "llama3.2" }
+"#;
+
+    #[test]
+    fn test_watcher_reports_each_change_once() {
+        let path = temp_path("watch.toml");
+        std::fs::write(&path, "a = 1").unwrap();
+        let mut watcher = ConfigWatcher::new(path.clone(), Duration::ZERO);
+        assert!(!watcher.poll());
+
+        // Make the new mtime distinguishable on coarse filesystems
+        let later = SystemTime::now() + Duration::from_secs(5);
+        std::fs::write(&path, "a = 2").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(!watcher.poll());
+    }
+
+    #[test]
+    fn test_watcher_respects_interval() {
+        let path = temp_path("interval.toml");
+        std::fs::write(&path, "a = 1").unwrap();
+        let mut watcher = ConfigWatcher::new(path.clone(), Duration::from_secs(3600));
+
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+        assert!(!watcher.poll());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_validate() {
+        let path = temp_path("valid.toml");
+        std::fs::write(&path, VALID).unwrap();
+        let config = Config::load_from(&path).unwrap();
+        assert!(config.validate().is_ok());
+        assert!(!config.watch_config);
+
+        let broken = VALID
+            .replace(r#"default_model = "llama""#, r#"default_model = "gpt""#)
+            .replace(r#"provider = "local""#, r#"provider = "cloud""#);
+        std::fs::write(&path, broken).unwrap();
+        let err = Config::load_from(&path)
+            .unwrap()
+            .validate()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("default_model 'gpt'"), "{}", err);
+        assert!(err.contains("unknown provider 'cloud'"), "{}", err);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(Config::load_from(&path).is_err());
+    }
+}
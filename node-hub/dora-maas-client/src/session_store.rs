@@ -0,0 +1,271 @@
+//! Persisted chat sessions
+//!
+//! With `session_dir` configured, every session is written to
+//! `<session_dir>/<session_id>.json` after each completed exchange and
+//! reloaded when the node starts. The `control` input can also `save`,
+//! `load:<id>` and `list` sessions explicitly.
+//!
+//! Files carry a schema `version`. Older versions are migrated on load:
+//!
+//! | Version | Layout |
+//! |---------|--------|
+//! | (none)  | bare array of chat messages, or `{"messages": [...]}` |
+//! | 1       | [`StoredSession`] |
+//!
+//! Only the conversation is stored; the system prompt comes from the current
+//! configuration when a session is restored.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use eyre::{Context, Result, eyre};
+use outfox_openai::spec::ChatCompletionRequestMessage;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Schema version written by [`SessionStore::save`]
+pub const SESSION_SCHEMA_VERSION: u32 = 1;
+
+/// A session as stored on disk
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredSession {
+    pub version: u32,
+    pub session_id: String,
+    /// Unix timestamp of the last save
+    #[serde(default)]
+    pub saved_at: i64,
+    /// Rolling summary of trimmed turns
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Conversation without the system prompt
+    pub messages: Vec<ChatCompletionRequestMessage>,
+}
+
+impl StoredSession {
+    pub fn new(
+        session_id: impl Into<String>,
+        summary: Option<String>,
+        messages: Vec<ChatCompletionRequestMessage>,
+    ) -> Self {
+        Self {
+            version: SESSION_SCHEMA_VERSION,
+            session_id: session_id.into(),
+            saved_at: chrono::Utc::now().timestamp(),
+            summary,
+            messages,
+        }
+    }
+
+    /// Parse a stored session, migrating older schema versions
+    pub fn from_json(session_id: &str, value: Value) -> Result<Self> {
+        let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+        let mut value = match version {
+            0 => migrate_v0(session_id, value)?,
+            v if v <= SESSION_SCHEMA_VERSION as u64 => value,
+            v => {
+                return Err(eyre!(
+                    "Unsupported session schema version {} (supported: {})",
+                    v,
+                    SESSION_SCHEMA_VERSION
+                ));
+            }
+        };
+        // Drop system messages from hand-edited or legacy files
+        if let Some(messages) = value["messages"].as_array_mut() {
+            messages.retain(|m| m["role"] != "system" && m["role"] != "developer");
+        }
+        let mut session: StoredSession = serde_json::from_value(value)
+            .map_err(|e| eyre!("Invalid session file for {}: {}", session_id, e))?;
+        session.version = SESSION_SCHEMA_VERSION;
+        Ok(session)
+    }
+}
+
+fn migrate_v0(session_id: &str, value: Value) -> Result<Value> {
+    let messages = match value {
+        Value::Array(messages) => messages,
+        Value::Object(mut map) => match map.remove("messages") {
+            Some(Value::Array(messages)) => messages,
+            _ => return Err(eyre!("Session file for {} has no messages", session_id)),
+        },
+        _ => return Err(eyre!("Session file for {} is not a session", session_id)),
+    };
+    Ok(serde_json::json!({
+        "version": SESSION_SCHEMA_VERSION,
+        "session_id": session_id,
+        "messages": messages,
+    }))
+}
+
+/// Directory of session files keyed by session ID
+pub struct SessionStore {
+    dir: PathBuf,
+}
+
+impl SessionStore {
+    /// Open (and create if needed) a session directory
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create session directory {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, session_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", encode_id(session_id)))
+    }
+
+    /// Write a session atomically (temp file + rename)
+    pub fn save(&self, session: &StoredSession) -> Result<()> {
+        let path = self.path(&session.session_id);
+        let tmp = path.with_extension("json.tmp");
+        let json = serde_json::to_string_pretty(session)?;
+        fs::write(&tmp, json).with_context(|| format!("Failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &path).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    pub fn load(&self, session_id: &str) -> Result<StoredSession> {
+        let path = self.path(session_id);
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("No stored session {} ({})", session_id, path.display()))?;
+        let value: Value = serde_json::from_str(&text)
+            .map_err(|e| eyre!("Invalid session file {}: {}", path.display(), e))?;
+        StoredSession::from_json(session_id, value)
+    }
+
+    /// IDs of all stored sessions, sorted
+    pub fn list(&self) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(id) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(decode_id)
+            {
+                ids.push(id);
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Load every stored session; unreadable files are returned as errors
+    pub fn load_all(&self) -> Result<Vec<Result<StoredSession>>> {
+        Ok(self.list()?.iter().map(|id| self.load(id)).collect())
+    }
+}
+
+/// Encode a session ID as a file name: `[A-Za-z0-9_-]` is kept, every other
+/// byte becomes `%XX`
+fn encode_id(session_id: &str) -> String {
+    let mut name = String::with_capacity(session_id.len());
+    for byte in session_id.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+            name.push(byte as char);
+        } else {
+            name.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    name
+}
+
+fn decode_id(name: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(name.len());
+    let mut iter = name.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_store(name: &str) -> SessionStore {
+        let dir = std::env::temp_dir().join(format!(
+            "maas-sessions-{}-{}",
+            name,
+            uuid::Uuid::new_v4().simple()
+        ));
+        SessionStore::open(dir).unwrap()
+    }
+
+    fn messages() -> Vec<ChatCompletionRequestMessage> {
+        serde_json::from_value(json!([
+            { "role": "user", "content": "What is dora?" },
+            { "role": "assistant", "content": "A dataflow framework." },
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_save_load_list() {
+        let store = temp_store("roundtrip");
+        let session = StoredSession::new("study/42 ü", Some("Intro".to_string()), messages());
+        store.save(&session).unwrap();
+        store
+            .save(&StoredSession::new("default", None, Vec::new()))
+            .unwrap();
+
+        assert_eq!(store.list().unwrap(), vec!["default", "study/42 ü"]);
+        let loaded = store.load("study/42 ü").unwrap();
+        assert_eq!(loaded.summary.as_deref(), Some("Intro"));
+        assert_eq!(
+            serde_json::to_value(&loaded.messages).unwrap(),
+            serde_json::to_value(messages()).unwrap()
+        );
+        assert!(store.load("missing").is_err());
+
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_loads_unversioned_files() {
+        let store = temp_store("legacy");
+        let legacy = json!([
+            { "role": "system", "content": "Old prompt" },
+            { "role": "user", "content": "What is dora?" },
+            { "role": "assistant", "content": "A dataflow framework." },
+        ]);
+        fs::write(store.dir().join("old.json"), legacy.to_string()).unwrap();
+
+        let loaded = store.load("old").unwrap();
+        assert_eq!(loaded.version, SESSION_SCHEMA_VERSION);
+        assert_eq!(loaded.session_id, "old");
+        assert_eq!(loaded.messages.len(), 2);
+
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_newer_schema() {
+        let value =
+            json!({ "version": SESSION_SCHEMA_VERSION + 1, "session_id": "x", "messages": [] });
+        assert!(StoredSession::from_json("x", value).is_err());
+    }
+
+    #[test]
+    fn test_id_encoding_round_trips() {
+        for id in ["default", "a.b", "../etc/passwd", "会话 1", "100%"] {
+            let name = encode_id(id);
+            assert!(!name.contains('/') && !name.contains('.'));
+            assert_eq!(decode_id(&name).as_deref(), Some(id));
+        }
+    }
+}
@@ -6,6 +6,9 @@ use serde::Deserialize;
 use std::time::Duration;
 use tokio_util::sync::CancellationToken;
 
+use crate::failover::ProviderError;
+use crate::usage::Usage;
+
 /// Reasons why a request was cancelled
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub enum CancellationReason {
@@ -23,6 +26,9 @@ pub struct StreamChunk {
     pub created: i64,
     pub model: String,
     pub choices: Vec<StreamChoice>,
+    /// Sent in the final chunk when `stream_options.include_usage` is set
+    #[serde(default)]
+    pub usage: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -148,7 +154,7 @@ pub async fn stream_completion<F>(
     api_key: String,
     request_body: serde_json::Value,
     mut on_chunk: F,
-) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)>
+) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)>
 where
     F: FnMut(String) -> Result<()>,
 {
@@ -163,6 +169,7 @@ where
     let mut event_source = EventSource::new(request)?;
     let mut accumulated_content = String::new();
     let mut tool_accumulator = ToolCallAccumulator::default();
+    let mut usage = None;
 
     // Track last few raw SSE events for debugging
     let mut last_raw_events: std::collections::VecDeque<String> = std::collections::VecDeque::with_capacity(5);
@@ -189,6 +196,9 @@ where
                 // Parse the JSON chunk
                 match serde_json::from_str::<StreamChunk>(&data) {
                     Ok(chunk) => {
+                        if let Some(reported) = chunk.usage.as_ref().and_then(Usage::from_value) {
+                            usage = Some(reported);
+                        }
                         if let Some(choice) = chunk.choices.first() {
                             // Handle content
                             if let Some(content) = &choice.delta.content {
@@ -214,7 +224,7 @@ where
             Err(e) => {
                 eprintln!("[SSE] Error after {} chunks: {}", chunk_count, e);
                 eprintln!("[SSE] Error details: {:?}", e);
-                return Err(eyre!("SSE error: {}", e));
+                return Err(sse_error(e).await);
             }
         }
     }
@@ -226,7 +236,26 @@ where
         None
     };
 
-    Ok((accumulated_content, tool_calls))
+    Ok((accumulated_content, tool_calls, usage))
+}
+
+/// Convert an event source error into a report the retry policy can classify
+pub async fn sse_error(e: reqwest_eventsource::Error) -> eyre::Report {
+    match e {
+        reqwest_eventsource::Error::InvalidStatusCode(status, response) => {
+            let body = response.text().await.unwrap_or_default();
+            ProviderError::Status {
+                status: status.as_u16(),
+                body,
+            }
+            .into()
+        }
+        reqwest_eventsource::Error::Transport(err) => {
+            eyre::Report::from(err).wrap_err("SSE error")
+        }
+        reqwest_eventsource::Error::StreamEnded => ProviderError::StreamEnded.into(),
+        e => eyre!("SSE error: {}", e),
+    }
 }
 
 /// Stream completion with cancellation support
@@ -238,7 +267,7 @@ pub async fn stream_completion_with_cancellation<F>(
     cancellation_token: CancellationToken,
     timeout_duration: Duration,
     mut on_chunk: F,
-) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)>
+) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<Usage>)>
 where
     F: FnMut(String) -> Result<()>,
 {
@@ -253,6 +282,7 @@ where
     let mut event_source = EventSource::new(request)?;
     let mut accumulated_content = String::new();
     let mut tool_accumulator = ToolCallAccumulator::default();
+    let mut usage = None;
 
     // Track last few raw SSE events for debugging
     let mut last_raw_events: std::collections::VecDeque<String> = std::collections::VecDeque::with_capacity(5);
@@ -283,6 +313,11 @@ where
                         // Parse the JSON chunk
                         match serde_json::from_str::<StreamChunk>(&data) {
                             Ok(chunk) => {
+                                if let Some(reported) =
+                                    chunk.usage.as_ref().and_then(Usage::from_value)
+                                {
+                                    usage = Some(reported);
+                                }
                                 if let Some(choice) = chunk.choices.first() {
                                     // Handle content
                                     if let Some(content) = &choice.delta.content {
@@ -307,10 +342,10 @@ where
                     }
                     Some(Err(e)) => {
                         if cancellation_token.is_cancelled() {
-                            return Err(eyre!("Stream cancelled by user"));
+                            return Err(ProviderError::Cancelled.into());
                         }
                         eprintln!("[SSE] Error after {} chunks: {}", chunk_count, e);
-                        return Err(eyre!("SSE error: {}", e));
+                        return Err(sse_error(e).await);
                     }
                     None => {
                         break;
@@ -319,11 +354,11 @@ where
             }
             _ = cancellation_token.cancelled() => {
                 event_source.close();
-                return Err(eyre!("Stream cancelled by user"));
+                return Err(ProviderError::Cancelled.into());
             }
             _ = timeout_future => {
                 event_source.close();
-                return Err(eyre!("Stream timed out after {:?}", timeout_duration));
+                return Err(ProviderError::StreamTimeout(timeout_duration).into());
             }
         }
     }
@@ -335,5 +370,5 @@ where
         None
     };
 
-    Ok((accumulated_content, tool_calls))
+    Ok((accumulated_content, tool_calls, usage))
 }
@@ -0,0 +1,576 @@
+//! JSON-schema constrained replies (`response_format` in the config)
+//!
+//! Providers that support it get the schema natively (OpenAI
+//! `response_format`, Ollama `format`); the others are asked for JSON in the
+//! system prompt. Either way the reply is validated here and, if it doesn't
+//! match, sent back with the problems for a bounded number of repair turns.
+
+use eyre::{Result, eyre};
+use outfox_openai::spec::{
+    ChatCompletionRequestMessage, CreateChatCompletionRequest, CreateChatCompletionResponse,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::client::ChatClient;
+use crate::usage::Usage;
+
+/// `response_format` section of the config
+#[derive(Clone, Debug, Deserialize)]
+pub struct ResponseFormatConfig {
+    /// Schema name reported to the provider
+    #[serde(default = "default_name")]
+    pub name: String,
+    /// Inline JSON schema
+    pub schema: Option<Value>,
+    /// File holding the JSON schema, used when `schema` is unset
+    pub schema_file: Option<String>,
+    /// Top-level string field sent on the `text` output (e.g. to TTS)
+    pub text_field: Option<String>,
+    /// Repair turns after a reply that doesn't match the schema
+    #[serde(default = "default_max_repairs")]
+    pub max_repairs: u32,
+    /// Ask for strict schema adherence where the provider supports it
+    #[serde(default = "default_strict")]
+    pub strict: bool,
+}
+
+fn default_name() -> String {
+    "response".to_string()
+}
+
+fn default_max_repairs() -> u32 {
+    2
+}
+
+fn default_strict() -> bool {
+    true
+}
+
+impl ResponseFormatConfig {
+    /// Resolve the schema (reading `schema_file` if needed)
+    pub fn load(&self) -> Result<ResponseFormat> {
+        let schema = match (&self.schema, &self.schema_file) {
+            (Some(schema), _) => schema.clone(),
+            (None, Some(path)) => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| eyre!("Failed to read schema file '{}': {}", path, e))?;
+                serde_json::from_str(&text)
+                    .map_err(|e| eyre!("Schema file '{}' is not JSON: {}", path, e))?
+            }
+            (None, None) => return Err(eyre!("response_format needs `schema` or `schema_file`")),
+        };
+        if !schema.is_object() {
+            return Err(eyre!("response_format schema must be a JSON object"));
+        }
+        if let Some(field) = &self.text_field {
+            if schema["properties"].get(field).is_none() {
+                return Err(eyre!("text_field '{}' is not a property of the schema", field));
+            }
+        }
+
+        Ok(ResponseFormat {
+            name: self.name.clone(),
+            schema,
+            strict: self.strict,
+            text_field: self.text_field.clone(),
+            max_repairs: self.max_repairs,
+        })
+    }
+}
+
+/// Resolved response format, passed to [`ChatClient::complete_structured`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResponseFormat {
+    pub name: String,
+    pub schema: Value,
+    pub strict: bool,
+    pub text_field: Option<String>,
+    pub max_repairs: u32,
+}
+
+impl ResponseFormat {
+    /// OpenAI `response_format` request field
+    pub fn openai_value(&self) -> Value {
+        json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": self.name,
+                "schema": self.schema,
+                "strict": self.strict,
+            }
+        })
+    }
+
+    /// System prompt addition for providers without native support
+    pub fn instructions(&self) -> String {
+        format!(
+            "Reply with a single JSON value matching this JSON schema, and nothing else \
+             (no prose, no code fences):\n{}",
+            self.schema
+        )
+    }
+
+    /// Parse and validate a reply, describing what is wrong if it doesn't match
+    pub fn check(&self, reply: &str) -> std::result::Result<Value, String> {
+        let value = extract_json(reply).ok_or_else(|| "the reply is not valid JSON".to_string())?;
+        let mut problems = Vec::new();
+        validate(&self.schema, &value, "$", &mut problems);
+        if problems.is_empty() {
+            Ok(value)
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+
+    /// Text to speak for a validated value: `text_field` if configured
+    pub fn spoken_text(&self, value: &Value) -> Option<String> {
+        let field = self.text_field.as_ref()?;
+        value[field].as_str().map(str::to_string)
+    }
+}
+
+/// Message asking the model to fix an invalid reply
+pub fn repair_message(problem: &str) -> String {
+    format!(
+        "Your reply does not match the required JSON schema: {}. \
+         Reply again with only the corrected JSON.",
+        problem
+    )
+}
+
+/// Parse JSON from a reply, tolerating code fences and surrounding prose
+pub fn extract_json(reply: &str) -> Option<Value> {
+    let trimmed = reply.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+
+    let unfenced = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.trim_end().strip_suffix("```"));
+    if let Some(inner) = unfenced {
+        if let Ok(value) = serde_json::from_str(inner.trim()) {
+            return Some(value);
+        }
+    }
+
+    let start = trimmed.find(['{', '['])?;
+    let end = trimmed.rfind(['}', ']'])?;
+    (start < end)
+        .then(|| serde_json::from_str(&trimmed[start..=end]).ok())
+        .flatten()
+}
+
+/// Check `value` against the subset of JSON schema providers use for
+/// structured output: `type`, `enum`, `const`, `properties`, `required`,
+/// `additionalProperties: false`, `items`, `anyOf` and the length and range
+/// bounds. Unknown keywords are ignored.
+pub fn validate(schema: &Value, value: &Value, path: &str, problems: &mut Vec<String>) {
+    if let Some(any_of) = schema["anyOf"].as_array() {
+        let matches = any_of.iter().any(|option| {
+            let mut inner = Vec::new();
+            validate(option, value, path, &mut inner);
+            inner.is_empty()
+        });
+        if !matches {
+            problems.push(format!("{} matches none of the allowed shapes", path));
+        }
+        return;
+    }
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            problems.push(format!("{} should be {}", path, types.join(" or ")));
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema["enum"].as_array() {
+        if !allowed.contains(value) {
+            problems.push(format!("{} should be one of {}", path, schema["enum"]));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            problems.push(format!("{} should be {}", path, expected));
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            for name in schema["required"].as_array().into_iter().flatten() {
+                if let Some(name) = name.as_str() {
+                    if !object.contains_key(name) {
+                        problems.push(format!("{} is missing '{}'", path, name));
+                    }
+                }
+            }
+            let properties = schema["properties"].as_object();
+            for (name, field) in object {
+                let field_path = format!("{}.{}", path, name);
+                match properties.and_then(|p| p.get(name)) {
+                    Some(field_schema) => validate(field_schema, field, &field_path, problems),
+                    None if schema["additionalProperties"] == json!(false) => {
+                        problems.push(format!("{} is not allowed", field_path));
+                    }
+                    None => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            check_bound(schema, "minItems", "maxItems", items.len(), "items", path, problems);
+            if schema["items"].is_object() {
+                for (index, item) in items.iter().enumerate() {
+                    validate(&schema["items"], item, &format!("{}[{}]", path, index), problems);
+                }
+            }
+        }
+        Value::String(text) => {
+            let length = text.chars().count();
+            check_bound(schema, "minLength", "maxLength", length, "characters", path, problems);
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if schema["minimum"].as_f64().is_some_and(|min| number < min) {
+                problems.push(format!("{} should be at least {}", path, schema["minimum"]));
+            }
+            if schema["maximum"].as_f64().is_some_and(|max| number > max) {
+                problems.push(format!("{} should be at most {}", path, schema["maximum"]));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64()
+                || value.is_u64()
+                || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn check_bound(
+    schema: &Value,
+    min_key: &str,
+    max_key: &str,
+    count: usize,
+    unit: &str,
+    path: &str,
+    problems: &mut Vec<String>,
+) {
+    let count = count as u64;
+    if let Some(min) = schema[min_key].as_u64().filter(|&min| count < min) {
+        problems.push(format!("{} should have at least {} {}", path, min, unit));
+    }
+    if let Some(max) = schema[max_key].as_u64().filter(|&max| count > max) {
+        problems.push(format!("{} should have at most {} {}", path, max, unit));
+    }
+}
+
+/// Validated reply of [`complete`]
+#[derive(Debug)]
+pub struct StructuredReply {
+    pub value: Value,
+    /// The reply as the model wrote it
+    pub content: String,
+    /// Repair turns it took
+    pub repairs: u32,
+    /// Problems of the rejected replies, in order
+    pub rejected: Vec<String>,
+    /// Usage summed over all turns, if the provider reported it
+    pub usage: Option<Usage>,
+    pub finish_reason: Option<String>,
+}
+
+/// Send `request` for a reply matching `format`, repairing invalid replies.
+///
+/// Prompt instructions are added to the first system message (or a new one)
+/// unless the client constrains output natively. Repair turns only extend
+/// this request; the session history is untouched.
+pub async fn complete(
+    client: &dyn ChatClient,
+    mut request: CreateChatCompletionRequest,
+    format: &ResponseFormat,
+) -> Result<StructuredReply> {
+    if !client.supports_response_format() {
+        add_instructions(&mut request, &format.instructions())?;
+    }
+
+    let mut rejected = Vec::new();
+    let mut usage: Option<Usage> = None;
+    loop {
+        let response = client.complete_structured(request.clone(), format).await?;
+        usage = match (usage, Usage::from_response(&response)) {
+            (Some(total), Some(turn)) => Some(Usage::new(
+                total.prompt_tokens + turn.prompt_tokens,
+                total.completion_tokens + turn.completion_tokens,
+            )),
+            (total, turn) => total.or(turn),
+        };
+        let (content, finish_reason) = reply_of(&response)?;
+
+        let problem = match format.check(&content) {
+            Ok(value) => {
+                return Ok(StructuredReply {
+                    value,
+                    content,
+                    repairs: rejected.len() as u32,
+                    rejected,
+                    usage,
+                    finish_reason,
+                });
+            }
+            Err(problem) => problem,
+        };
+        rejected.push(problem.clone());
+        if rejected.len() as u32 > format.max_repairs {
+            return Err(eyre!(
+                "Reply does not match the response schema after {} repair(s): {}",
+                format.max_repairs,
+                problem
+            ));
+        }
+
+        request.messages.push(message(json!({ "role": "assistant", "content": content }))?);
+        request
+            .messages
+            .push(message(json!({ "role": "user", "content": repair_message(&problem) }))?);
+    }
+}
+
+fn reply_of(response: &CreateChatCompletionResponse) -> Result<(String, Option<String>)> {
+    let choice = response
+        .choices
+        .first()
+        .ok_or_else(|| eyre!("Response has no choices"))?;
+    let content = choice.message.content.clone().unwrap_or_default();
+    let finish_reason = serde_json::to_value(&choice.finish_reason)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string));
+    Ok((content, finish_reason))
+}
+
+fn message(value: Value) -> Result<ChatCompletionRequestMessage> {
+    Ok(serde_json::from_value(value)?)
+}
+
+fn add_instructions(request: &mut CreateChatCompletionRequest, instructions: &str) -> Result<()> {
+    let mut messages = serde_json::to_value(&request.messages)?;
+    let list = messages
+        .as_array_mut()
+        .ok_or_else(|| eyre!("Request messages are not a list"))?;
+    match list.iter_mut().find(|m| m["role"] == "system") {
+        Some(system) => {
+            let prompt = system["content"].as_str().unwrap_or_default();
+            system["content"] = json!(format!("{}\n\n{}", prompt, instructions));
+        }
+        None => list.insert(0, json!({ "role": "system", "content": instructions })),
+    }
+    request.messages = serde_json::from_value(messages)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{AnthropicClient, OpenaiClient};
+    use crate::config::{AnthropicConfig, OpenaiConfig};
+    use crate::mock_server::{MockResponse, MockServer};
+
+    fn format() -> ResponseFormat {
+        let config: ResponseFormatConfig = serde_json::from_value(json!({
+            "name": "judge",
+            "text_field": "comment",
+            "max_repairs": 1,
+            "schema": {
+                "type": "object",
+                "properties": {
+                    "winner": { "type": "string", "enum": ["pro", "con"] },
+                    "score": { "type": "integer", "minimum": 0, "maximum": 10 },
+                    "comment": { "type": "string" },
+                },
+                "required": ["winner", "score", "comment"],
+                "additionalProperties": false,
+            },
+        }))
+        .unwrap();
+        config.load().unwrap()
+    }
+
+    fn request() -> CreateChatCompletionRequest {
+        serde_json::from_value(json!({
+            "model": "judge",
+            "messages": [
+                { "role": "system", "content": "You judge debates." },
+                { "role": "user", "content": "Who won?" },
+            ],
+        }))
+        .unwrap()
+    }
+
+    fn completion(content: &str) -> MockResponse {
+        MockResponse::json(
+            200,
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 0,
+                "model": "mock",
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": content },
+                    "finish_reason": "stop",
+                }],
+                "usage": { "prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15 },
+            }),
+        )
+    }
+
+    const VALID: &str = r#"{"winner":"pro","score":7,"comment":"Clear arguments."}"#;
+
+    #[test]
+    fn test_validate_reports_each_problem() {
+        let format = format();
+        assert_eq!(format.check(VALID).unwrap()["score"], 7);
+
+        let problem = format
+            .check(r#"{"winner":"both","score":11,"extra":true}"#)
+            .unwrap_err();
+        assert!(problem.contains("$ is missing 'comment'"), "{}", problem);
+        assert!(problem.contains("$.winner should be one of"), "{}", problem);
+        assert!(problem.contains("$.score should be at most 10"), "{}", problem);
+        assert!(problem.contains("$.extra is not allowed"), "{}", problem);
+
+        let problem = format.check(r#"{"winner":"pro","score":"7","comment":""}"#).unwrap_err();
+        assert_eq!(problem, "$.score should be integer");
+        assert_eq!(format.check("Pro won.").unwrap_err(), "the reply is not valid JSON");
+    }
+
+    #[test]
+    fn test_extract_json() {
+        assert_eq!(extract_json(" [1, 2] "), Some(json!([1, 2])));
+        assert_eq!(extract_json("```json\n{\"a\": 1}\n```"), Some(json!({ "a": 1 })));
+        assert_eq!(extract_json("Here you go: {\"a\": 1} Done."), Some(json!({ "a": 1 })));
+        assert_eq!(extract_json("no json here"), None);
+    }
+
+    #[test]
+    fn test_config_load() {
+        let format = format();
+        assert_eq!(format.spoken_text(&json!({ "comment": "Hi" })).as_deref(), Some("Hi"));
+        assert_eq!(format.openai_value()["json_schema"]["name"], "judge");
+
+        let missing: ResponseFormatConfig = serde_json::from_value(json!({})).unwrap();
+        assert!(missing.load().is_err());
+        let unknown_field: ResponseFormatConfig = serde_json::from_value(json!({
+            "schema": { "type": "object", "properties": {} },
+            "text_field": "comment",
+        }))
+        .unwrap();
+        assert!(unknown_field.load().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_native_format_is_sent_to_openai() {
+        let server = MockServer::start().await;
+        server.enqueue(completion(VALID));
+        let client = OpenaiClient::new(&OpenaiConfig {
+            id: "openai".to_string(),
+            api_key: "test-key".to_string(),
+            api_url: server.url().to_string(),
+            proxy: false,
+            json_schema: true,
+        });
+
+        let reply = complete(&client, request(), &format()).await.unwrap();
+        assert_eq!(reply.value["winner"], "pro");
+        assert_eq!(reply.repairs, 0);
+
+        let body = server.requests()[0].json();
+        assert_eq!(body["response_format"]["type"], "json_schema");
+        assert_eq!(body["response_format"]["json_schema"]["strict"], true);
+        assert_eq!(body["messages"][0]["content"], "You judge debates.");
+    }
+
+    #[tokio::test]
+    async fn test_prompt_fallback_repairs_invalid_reply() {
+        let server = MockServer::start().await;
+        server.enqueue(completion("The pro side won."));
+        server.enqueue(completion(&format!("```json\n{}\n```", VALID)));
+        let client = OpenaiClient::new(&OpenaiConfig {
+            id: "compatible".to_string(),
+            api_key: "test-key".to_string(),
+            api_url: server.url().to_string(),
+            proxy: false,
+            json_schema: false,
+        });
+
+        let reply = complete(&client, request(), &format()).await.unwrap();
+        assert_eq!(reply.repairs, 1);
+        assert_eq!(reply.rejected, vec!["the reply is not valid JSON"]);
+        assert_eq!(reply.usage, Some(Usage::new(20, 10)));
+
+        let requests = server.requests();
+        let first = requests[0].json();
+        assert!(first.get("response_format").is_none());
+        assert!(first["messages"][0]["content"]
+            .as_str()
+            .unwrap()
+            .contains("JSON schema"));
+        let repair = requests[1].json();
+        let messages = repair["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[2]["content"], "The pro side won.");
+        assert!(messages[3]["content"].as_str().unwrap().contains("not valid JSON"));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_repairs() {
+        let message = |text: &str| {
+            MockResponse::json(
+                200,
+                json!({
+                    "id": "msg_1",
+                    "type": "message",
+                    "role": "assistant",
+                    "model": "claude",
+                    "content": [{ "type": "text", "text": text }],
+                    "stop_reason": "end_turn",
+                    "usage": { "input_tokens": 10, "output_tokens": 5 },
+                }),
+            )
+        };
+        let server = MockServer::start().await;
+        server.enqueue(message(r#"{"winner":"pro"}"#));
+        server.enqueue(message(r#"{"winner":"pro","score":3}"#));
+        let client = AnthropicClient::new(&AnthropicConfig {
+            id: "anthropic".to_string(),
+            api_key: "test-key".to_string(),
+            api_url: server.url().to_string(),
+            max_tokens: 256,
+            proxy: false,
+        });
+
+        let err = complete(&client, request(), &format()).await.unwrap_err();
+        assert!(err.to_string().contains("after 1 repair(s)"), "{}", err);
+        assert!(err.to_string().contains("missing 'comment'"), "{}", err);
+        assert_eq!(server.requests().len(), 2);
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! This module provides the infrastructure for integrating MCP (Model Context Protocol)
 //! tools with the LLM streaming client, enabling function calling capabilities.
+//! Built-in tools (see [`crate::builtin_tools`]) implement the same [`Tool`] trait.
 
 use std::{collections::HashMap, sync::Arc};
 
@@ -27,6 +28,13 @@ pub trait Tool: Send + Sync {
 
     /// Execute the tool with the given arguments
     async fn call(&self, args: Value) -> Result<CallToolResult>;
+
+    /// Execute the tool on behalf of a session.
+    ///
+    /// Tools that keep per-session state override this; others ignore the session.
+    async fn call_in_session(&self, _session_id: &str, args: Value) -> Result<CallToolResult> {
+        self.call(args).await
+    }
 }
 
 /// Adapter that wraps an MCP tool to implement our Tool trait
@@ -0,0 +1,244 @@
+//! Token usage and cost accounting
+//!
+//! Each completed request emits a JSON record on the `metrics` output with
+//! its token counts, latencies, cost (when the model has `pricing`) and the
+//! session's cumulative totals:
+//!
+//! ```json
+//! {
+//!   "session_id": "debate",
+//!   "model": "gpt-4o",
+//!   "usage": { "prompt_tokens": 812, "completion_tokens": 96, "total_tokens": 908 },
+//!   "estimated": false,
+//!   "latency_ms": { "first_token": 420, "total": 2310 },
+//!   "cost": 0.00299, "currency": "USD",
+//!   "session_totals": { "requests": 4, "prompt_tokens": 2950, "completion_tokens": 410,
+//!                       "total_tokens": 3360, "cost": 0.0115 }
+//! }
+//! ```
+//!
+//! Counts come from the provider's `usage` block (the final stream chunk when
+//! streaming). Providers that don't report usage get a heuristic estimate and
+//! `"estimated": true`.
+
+use std::time::{Duration, Instant};
+
+use outfox_openai::spec::CreateChatCompletionResponse;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+/// Token counts of one request
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    #[serde(default)]
+    pub total_tokens: u64,
+}
+
+impl Usage {
+    pub fn new(prompt_tokens: u64, completion_tokens: u64) -> Self {
+        Self {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }
+    }
+
+    /// Parse an OpenAI `usage` object; `null` or missing gives `None`
+    pub fn from_value(value: &Value) -> Option<Self> {
+        if !value.is_object() {
+            return None;
+        }
+        let mut usage: Usage = serde_json::from_value(value.clone()).ok()?;
+        if usage.total_tokens == 0 {
+            usage.total_tokens = usage.prompt_tokens + usage.completion_tokens;
+        }
+        Some(usage)
+    }
+
+    /// Usage reported in a non-streaming response
+    pub fn from_response(response: &CreateChatCompletionResponse) -> Option<Self> {
+        let value = serde_json::to_value(response).ok()?;
+        Self::from_value(&value["usage"])
+    }
+}
+
+/// Model prices, per million tokens
+#[derive(Clone, Debug, Deserialize)]
+pub struct Pricing {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+}
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+impl Pricing {
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        (usage.prompt_tokens as f64 * self.input_per_million
+            + usage.completion_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// Measurements of one completed request
+#[derive(Clone, Debug)]
+pub struct RequestMetrics {
+    pub usage: Usage,
+    /// `usage` is a local estimate rather than provider-reported
+    pub estimated: bool,
+    /// Time to the first streamed chunk
+    pub first_token: Option<Duration>,
+    pub total: Duration,
+}
+
+/// Times a request from submission to completion
+pub struct RequestTimer {
+    started: Instant,
+    first_token: Option<Duration>,
+}
+
+impl RequestTimer {
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            first_token: None,
+        }
+    }
+
+    /// Note a streamed chunk; only the first one counts
+    pub fn chunk_received(&mut self) {
+        if self.first_token.is_none() {
+            self.first_token = Some(self.started.elapsed());
+        }
+    }
+
+    /// Finish timing, falling back to `estimate` when the provider reported no usage
+    pub fn finish(&self, usage: Option<Usage>, estimate: impl FnOnce() -> Usage) -> RequestMetrics {
+        RequestMetrics {
+            estimated: usage.is_none(),
+            usage: usage.unwrap_or_else(estimate),
+            first_token: self.first_token,
+            total: self.started.elapsed(),
+        }
+    }
+}
+
+/// Cumulative usage of a session
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub cost: f64,
+}
+
+impl UsageTotals {
+    /// Add a request to the totals and build its `metrics` record
+    pub fn record(
+        &mut self,
+        session_id: &str,
+        model: &str,
+        metrics: &RequestMetrics,
+        pricing: Option<&Pricing>,
+    ) -> Value {
+        let cost = pricing.map(|p| p.cost(&metrics.usage));
+        self.requests += 1;
+        self.prompt_tokens += metrics.usage.prompt_tokens;
+        self.completion_tokens += metrics.usage.completion_tokens;
+        self.total_tokens += metrics.usage.total_tokens;
+        self.cost += cost.unwrap_or(0.0);
+
+        json!({
+            "session_id": session_id,
+            "model": model,
+            "usage": metrics.usage,
+            "estimated": metrics.estimated,
+            "latency_ms": {
+                "first_token": metrics.first_token.map(|d| d.as_millis() as u64),
+                "total": metrics.total.as_millis() as u64,
+            },
+            "cost": cost,
+            "currency": pricing.map(|p| p.currency.as_str()),
+            "session_totals": self,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_from_value() {
+        let usage = Usage::from_value(&json!({
+            "prompt_tokens": 12,
+            "completion_tokens": 5,
+            "total_tokens": 17,
+            "prompt_tokens_details": { "cached_tokens": 0 },
+        }));
+        assert_eq!(usage, Some(Usage::new(12, 5)));
+
+        // Some providers leave out the total
+        let usage = Usage::from_value(&json!({ "prompt_tokens": 3, "completion_tokens": 4 }));
+        assert_eq!(usage.map(|u| u.total_tokens), Some(7));
+
+        assert_eq!(Usage::from_value(&Value::Null), None);
+    }
+
+    #[test]
+    fn test_timer_falls_back_to_estimate() {
+        let mut timer = RequestTimer::start();
+        timer.chunk_received();
+        let first = timer.first_token;
+        timer.chunk_received();
+        assert_eq!(timer.first_token, first);
+
+        let metrics = timer.finish(None, || Usage::new(10, 2));
+        assert!(metrics.estimated);
+        assert_eq!(metrics.usage.total_tokens, 12);
+        assert!(metrics.first_token.unwrap() <= metrics.total);
+
+        let metrics = RequestTimer::start().finish(Some(Usage::new(1, 1)), || unreachable!());
+        assert!(!metrics.estimated);
+        assert!(metrics.first_token.is_none());
+    }
+
+    #[test]
+    fn test_record_accumulates_totals() {
+        let pricing = Pricing {
+            input_per_million: 2.5,
+            output_per_million: 10.0,
+            currency: "USD".to_string(),
+        };
+        let metrics = RequestMetrics {
+            usage: Usage::new(1_000, 200),
+            estimated: false,
+            first_token: Some(Duration::from_millis(420)),
+            total: Duration::from_millis(2_310),
+        };
+
+        let mut totals = UsageTotals::default();
+        totals.record("debate", "gpt-4o", &metrics, Some(&pricing));
+        let record = totals.record("debate", "gpt-4o", &metrics, Some(&pricing));
+
+        assert_eq!(record["usage"]["total_tokens"], 1_200);
+        assert_eq!(record["latency_ms"]["first_token"], 420);
+        assert!((record["cost"].as_f64().unwrap() - 0.0045).abs() < 1e-9);
+        assert_eq!(record["session_totals"]["requests"], 2);
+        assert_eq!(record["session_totals"]["total_tokens"], 2_400);
+        assert!((totals.cost - 0.009).abs() < 1e-9);
+
+        // Without pricing only tokens are counted
+        let record = UsageTotals::default().record("debate", "gpt-4o", &metrics, None);
+        assert!(record["cost"].is_null());
+        assert!(record["currency"].is_null());
+    }
+}
@@ -0,0 +1,28 @@
+//! OpenAI wire format helpers shared by the native provider translations
+//! ([`anthropic`](crate::anthropic), [`gemini`](crate::gemini),
+//! [`ollama`](crate::ollama))
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+
+/// Text of an OpenAI message `content` (string or array of text parts)
+pub fn content_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part["text"].as_str())
+            .collect::<Vec<_>>()
+            .join(""),
+        _ => String::new(),
+    }
+}
+
+/// Seconds since the Unix epoch, for the `created` field of responses
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}